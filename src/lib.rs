@@ -0,0 +1,15338 @@
+//! Cat Shield - A cat-proof screen overlay for macOS
+//!
+//! Creates a semi-transparent overlay that:
+//! - Blocks all keyboard and mouse input
+//! - Suppresses Siri: its shortcuts, the Siri/media keys, and the Type to Siri window
+//! - Keeps the machine awake
+//! - Click and hold close button (3 seconds) to exit
+//! - Or unlock by holding a configurable keyboard shortcut (default: Cmd+Option+U for 1s)
+//! - Optional timer-based auto-exit (with a celebration if no keys were blocked)
+//! - Optional progressive dimming that tracks timer progress
+//! - Optional night mode with a dim red palette
+//! - Optional blurred backdrop that hides the desktop
+//! - Optionally dim the display while shielded (restored on exit)
+//! - Optionally turn off the keyboard backlight while shielded (restored on exit)
+//! - Optionally mute system audio while shielded (restored on exit)
+//! - Optionally lock the volume, reverting any change made while shielded
+//! - Optional emergency exit: press Caps Lock three times within two seconds
+//! - Optional gesture unlock: draw a large circle (or square) on the overlay
+//! - Optional knock unlock: click a short/long pattern on the overlay
+//! - Optional keypad code: type a numeric code on the numeric keypad
+//! - Optionally quit or hide apps launched while shielded
+//! - Optionally pass some keys through to one app (e.g. a slideshow)
+//! - Optional soft mode that lets trackpad and mouse-wheel scrolling through
+//! - Optional hard mode that captures the displays so nothing draws over the shield
+//! - Optional no-overlay mode: input blocking and sleep prevention only
+//! - Optional screen-off mode: displays sleep while the keyboard stays locked
+//! - Optional parental mode: every exit attempt requires an administrator password
+//! - Optionally accept the exit shortcut only from the built-in keyboard
+//! - Watch mode: stay in the background and arm keyboard blocking automatically
+//! - Optional keyboard backlight flash when a cat walks across the keyboard
+//! - Optional meow detection with the built-in SoundAnalysis cat classifier
+//! - Optional loudness trigger for watch mode
+//! - Optional scripting hooks (Rhai) for activation, bursts, warnings, and exit
+//! - Optional sandboxed WASM detection plugins that can arm the shield or alert
+//! - Optional health file for external monitors
+//! - Broadcasts state changes as distributed notifications for other apps
+//! - Logs lifecycle events and errors to unified logging (Console.app)
+//! - Live status line (time left, keys blocked, tap health) when run from a terminal
+//! - Lid aware: pauses the timer while the lid is closed and restores the overlay
+//!   when displays come back
+//!
+//! Usage: Run the application, and it will immediately activate the shield.
+//! Click and hold the X button in the top-right corner for 3 seconds to exit.
+//!
+//! Subcommands: Shield options can be given on their own or after `run`, and
+//! `watch` is the same as `run --watch`. `doctor` checks permissions and the
+//! config file, and `install` links cat_shield into /usr/local/bin (or --dir):
+//!   cat_shield run --timer 30m
+//!   cat_shield keys             # Keyboard only, no overlay, for 2 minutes
+//!   cat_shield doctor
+//!   cat_shield install
+//!
+//! Timer: Use --timer or -t to set auto-exit timer:
+//!   cat_shield --timer 30m      # Exit after 30 minutes
+//!   cat_shield --timer 2h       # Exit after 2 hours
+//!   cat_shield -t 45m           # Short form
+//! Durations can also be spelled out or given in ISO 8601, as other tools write them:
+//!   cat_shield --timer "1 hour 30 min"
+//!   cat_shield --timer PT1H30M
+//!
+//! Presets: List your household's standard session lengths in the config file,
+//! optionally named. They replace the menu bar's quick-start items and work with
+//! --preset in place of --timer:
+//!   presets = ["25m", "nap=1h30m", "overnight=8h"]
+//!   cat_shield --preset nap
+//!
+//! Profiles: A [profiles.<name>] table is a preset with more than a timer: its
+//! opacity and exit rules too. --preset picks a profile before a preset of the
+//! same name, and options given on the command line still win:
+//!   [profiles.movie]
+//!   timer = "2h30m"
+//!   opacity = 0.15
+//!   exit_key = "Cmd+Shift+M"
+//!   emergency_exit = true
+//!   cat_shield --preset movie
+//!
+//! Warning: A minute before auto-exit the timer turns orange and a warning is
+//! printed. Use --warn-action to also play a sound, post a notification, or
+//! flash the overlay, so it's noticed from across the room:
+//!   cat_shield --timer 2h --warn-action sound,flash
+//!
+//! Away Note: --away-note shows a message for officemates or family walking past,
+//! with the return time taken from the timer and kept current if it's extended:
+//!   cat_shield --timer 45m --away-note       # "Tyler is away — back around 3:45 PM"
+//!   cat_shield --timer 45m --away-note Sam   # Someone else's name
+//!
+//! Overlay Widgets: The config file can add a clock, battery level, blocked-key
+//! counter, now-playing song, or fixed message to the overlay, each pinned to a
+//! cell of a 3x3 grid (top-left ... bottom-right; top-right is the close button's).
+//! A timer entry moves the countdown display:
+//!   [[overlay.widgets]]
+//!   kind = "clock"          # clock, battery, blocked, now_playing, message, timer
+//!   anchor = "bottom-left"
+//!   [[overlay.widgets]]
+//!   kind = "message"
+//!   anchor = "center"
+//!   text = "Cat on keyboard, back soon"
+//! Now-playing relies on a private framework that recent macOS versions only
+//! answer for some apps, so it can stay blank.
+//!
+//! Reminders: On long sessions, --remind-every posts a quiet notification of the
+//! time left at that interval (--remind-aloud speaks it instead):
+//!   cat_shield --timer 8h --remind-every 1h
+//!
+//! Progressive Dimming: Use --dim with a timer to have the overlay darken as time
+//! passes (or lighten with --dim lighten), so remaining time is visible at a glance:
+//!   cat_shield --timer 1h --dim
+//!
+//! Night Mode: Use --night for a dim red overlay, close button, and timer display
+//! that won't light up a dark bedroom:
+//!   cat_shield --timer 8h --night
+//!
+//! Blur: Use --blur to replace the tinted background with a system-style blur that
+//! hides what's on screen:
+//!   cat_shield --timer 1h --blur
+//!
+//! Display Dimming: Use --dim-display to lower the built-in display's brightness
+//! while shielded; the original brightness comes back on exit:
+//!   cat_shield --timer 8h --dim-display 20%
+//!
+//! Keyboard Backlight: Use --backlight-off so glowing keys don't attract the cat:
+//!   cat_shield --timer 1h --backlight-off
+//!
+//! Mute: Use --mute to silence the output device while shielded:
+//!   cat_shield --timer 8h --mute
+//!
+//! Volume Lock: Use --lock-volume to revert volume or mute changes the cat
+//! makes through paths the event tap can't block (e.g. headphone controls or other apps):
+//!   cat_shield --timer 1h --lock-volume
+//!
+//! Updates: Cat Shield never touches the network on its own. To check GitHub for
+//! a newer release, or to download, verify, and install it in place (the binary
+//! or the .app bundle it runs from):
+//!   cat_shield check-update
+//!   cat_shield self-update
+//! The new version must be signed by whoever signed the installed one, so
+//! self-update only works on a signed copy; update builds from source the way
+//! they were installed.
+//!
+//! Telemetry: Off unless you opt in with a [telemetry] section in config.toml
+//! (`enabled = true` and your own https `endpoint`). Only coarse counters are sent:
+//! sessions, clean and unclean exits, and which features were used.
+//!   cat_shield telemetry status     # Show exactly what would be sent
+//!   cat_shield telemetry off        # Turn it off and discard pending counters
+//!
+//! Exit Key: Use --exit-key or -e to set custom exit shortcut:
+//!   cat_shield --exit-key "Cmd+Shift+Q"
+//!   cat_shield --exit-key "Ctrl+Option+Escape"
+//!   cat_shield -e "Cmd+Shift+X"
+//! Letters and symbols are found on the active keyboard layout, so on Dvorak or
+//! AZERTY the shortcut uses the key that types them, and follows layout switches;
+//! digits and named keys stay where they are on a US keyboard.
+//! The shortcut must stay held for a second, so a paw rolling across the keys
+//! can't trigger it. Change that with --exit-key-hold (0 for instant):
+//!   cat_shield --exit-key-hold 2
+//!
+//! Emergency Exit: Use --emergency-exit to allow a triple Caps Lock press to exit,
+//! even when the mouse is unavailable and the exit shortcut has been forgotten:
+//!   cat_shield --timer 2h --emergency-exit
+//!
+//! Gesture Unlock: Use --unlock-gesture to exit by drawing a shape with the mouse held:
+//!   cat_shield --timer 1h --unlock-gesture circle
+//!
+//! Knock Unlock: Use --knock to exit by clicking a pattern of short (.) and long (-)
+//! presses anywhere on the overlay:
+//!   cat_shield --timer 1h --knock "..-."
+//!
+//! Keypad Code: Use --keypad-code to exit by typing a numeric code on the numeric
+//! keypad followed by keypad Enter. Keypad keys never reach other apps:
+//!   cat_shield --timer 1h --keypad-code 2468
+//!
+//! Parental Mode: Use --require-admin-exit so that the close button, exit key, and
+//! other unlock methods only exit after administrator authentication:
+//!   cat_shield --timer 1h --require-admin-exit
+//!
+//! Internal Keyboard Only: Use --internal-keyboard-only to ignore the exit shortcut
+//! when it is typed on an external keyboard (the one the cat sleeps on):
+//!   cat_shield --timer 1h --internal-keyboard-only
+//!
+//! Watch Mode: Use --watch to run without an overlay and arm keyboard blocking
+//! automatically when the last external keyboard disconnects. The exit key disarms:
+//!   cat_shield --watch
+//!
+//! Daemon Mode: --daemon is watch mode that also listens on a unix socket in the
+//! temp directory, so scripts can arm and disarm blocking without starting a
+//! process each time. Each line is a command, answered with a line of JSON:
+//! "activate" or "activate 30m", "deactivate", and "status":
+//!   cat_shield --daemon
+//!   echo "activate 30m" | nc -U "$TMPDIR/catshield.sock"
+//!
+//! Cat Detection: A rapid run of presses on one key, or on keys that touch (a
+//! paw kneading or walking across the keyboard), flashes "🐾 CAT DETECTED" on the
+//! overlay for a few seconds. Each one counts as an incident in the session
+//! summary and lifetime stats.
+//!
+//! Backlight Deterrent: Use --backlight-deterrent to pulse the keyboard backlight
+//! when a burst of blocked key presses is detected (silent, unlike a sound):
+//!   cat_shield --timer 1h --backlight-deterrent
+//!
+//! Meow Detection: Use --meow-detect to listen on the microphone and log meows.
+//! Add --meow-deterrent to play a sound when the cat meows. In watch mode a meow
+//! also arms keyboard blocking:
+//!   cat_shield --watch --meow-detect --meow-deterrent
+//!
+//! Loudness Trigger: Use --loudness-threshold in watch mode to arm blocking (or,
+//! with --loudness-action notify, show a notification) when sound stays above a
+//! level for a second, e.g. when something gets knocked off the desk:
+//!   cat_shield --watch --loudness-threshold -25
+//!
+//! Lid Close: If closing the lid puts the Mac to sleep, the timer is paused and the
+//! sleep assertion released until the lid opens again. In clamshell mode (an external
+//! display keeps the Mac awake) the shield stays fully active and the overlay moves
+//! to the remaining display. Watch mode keeps watching across sleep.
+//!
+//! Sleep: Time the Mac spends asleep or with the display off doesn't count toward
+//! the timer, so a 2-hour shield still has its awake time left after waking. Use
+//! --count wall to count wall-clock time instead:
+//!   cat_shield --timer 2h --count wall
+//!
+//! Screen Saver: The overlay is brought back to the front when the screen saver
+//! stops. Use --no-screensaver to stop the screen saver as soon as it starts:
+//!   cat_shield --timer 8h --no-screensaver
+//!
+//! App Launches: Use --block-launches to quit apps that get launched while
+//! shielded (a click that slipped through before the shield was up), or
+//! --block-launches hide to hide them behind the overlay instead:
+//!   cat_shield --timer 1h --block-launches
+//!
+//! Dry Run: --dry-run checks the options, config file, and permissions, prints
+//! what the shield would do (screens covered, what is blocked, the timer, unlock
+//! methods), and exits without creating a window or event tap; the exit status
+//! is 1 if something would stop it. For verifying scripts and launch agents:
+//!   cat_shield run --timer 8h --health-file /tmp/catshield.health --dry-run
+//!
+//! Projectors: Use --on-projector to pause the shield (overlay hidden, keyboard
+//! released, timer paused) while a display that looks like a projector or capture
+//! device is connected, so a presentation room never sees a dark overlay. It is
+//! recognized by name (Epson, BenQ, Elgato, AirPlay, ...) or by mirroring another
+//! display. --on-projector notify only shows a notification:
+//!   cat_shield --timer 2h --on-projector
+//!
+//! Key Forwarding: Use --forward-to for "shielded but presentable" setups. The
+//! listed keys (arrows, Space, Page Up/Down by default, or --forward-keys) are
+//! sent straight to that app and blocked everywhere else:
+//!   cat_shield --timer 1h --forward-to Keynote
+//!   cat_shield --timer 1h --forward-to Preview --forward-keys Left,Right
+//!
+//! Universal Control: Keys typed on another Mac or iPad are blocked like any
+//! other. Use --allow-universal-control to let them through:
+//!   cat_shield --timer 1h --allow-universal-control
+//!
+//! Remote Input: Use --allow-remote-input to administer a shielded Mac over
+//! Screen Sharing or Remote Desktop while its own keyboard stays blocked:
+//!   cat_shield --timer 8h --allow-remote-input
+//!
+//! Accessibility: Use --allow-accessibility-shortcuts on a Mac shared with someone
+//! who relies on assistive features. The shortcuts for VoiceOver (Cmd+F5), the
+//! Accessibility Shortcuts panel, Zoom, and Invert Colors pass through, and so
+//! does Shift on its own, so pressing it five times still toggles Sticky Keys:
+//!   cat_shield --timer 8h --allow-accessibility-shortcuts
+//!
+//! Soft Mode: Use --allow-scroll to keep reading while shielded. Scrolling and
+//! swipes reach the app you were using; keys, clicks, pinch-zoom, rotation, and
+//! smart zoom stay blocked:
+//!   cat_shield --timer 30m --allow-scroll
+//!
+//! Hard Mode: Use --capture-display to take exclusive control of every display.
+//! The desktop is blacked out and not even notifications can appear over the shield:
+//!   cat_shield --timer 30m --capture-display
+//!
+//! No Overlay: Use --no-overlay to keep the screen fully visible (e.g. while
+//! watching a render) with keys, clicks, and scrolling blocked and sleep prevented.
+//! Exit with the exit key or the timer:
+//!   cat_shield --timer 2h --no-overlay
+//!
+//! Screen Off: Use --screen-off for overnight shields. The displays go to sleep
+//! and are put back to sleep if a blocked key wakes them; they wake on exit:
+//!   cat_shield --timer 8h --screen-off
+//!
+//! Screen Lock: Use --stand-down-when-locked to release the keyboard tap and sleep
+//! prevention while the macOS lock screen is up; the shield re-arms on unlock:
+//!   cat_shield --timer 8h --stand-down-when-locked
+//!
+//! Stats: Sessions are tallied in ~/Library/Application Support/catshield/stats.toml
+//! (incident-free streak, time shielded, keys blocked). Show them with:
+//!   cat_shield stats
+//! or after each session with --show-stats. Label sessions to compare them:
+//!   cat_shield --timer 8h --label overnight
+//!   cat_shield stats --by-label
+//! Blocked events are also counted by where they came from: each keyboard by name
+//! (once Input Monitoring is granted), mouse, trackpad, or a remote session.
+//!
+//! Config File: Persistent settings can be stored in ~/.config/catshield/config.toml:
+//!   exit_key = "Cmd+Option+U"
+//! Check it after editing (unknown keys and bad values exit non-zero, so a
+//! scheduled shield doesn't find out at 3 AM):
+//!   cat_shield config validate
+//!
+//! Cats: Define your cats in the config file to see who is responsible for blocked
+//! key presses. Presses are attributed by each cat's active hours (local time);
+//! after a session, `cat_shield blame <name>` assigns it to a cat instead:
+//!   [[cats]]
+//!   name = "Mochi"
+//!   active_hours = "22:00-06:00"
+//!
+//! Frontmost Apps: In watch mode, keyboard blocking can arm whenever an app where a
+//! stray keystroke would be costly comes to the front, and disarm when it loses
+//! focus. List apps by name or bundle ID in the config file:
+//!   activate_when_frontmost = ["Final Cut Pro", "com.apple.Keynote"]
+//!
+//! Terminal: When run from a terminal, the shield takes over the terminal's
+//! alternate screen and keeps a live status line (time left, blocked key presses,
+//! event tap health) at the top, with log messages scrolling underneath. The
+//! terminal is restored on exit.
+//!
+//! State Broadcast: Other apps (status bar tools, automation) can follow the shield
+//! by observing com.taearls.catshield.state distributed notifications. The object
+//! is "active" when the shield activates (or resumes), "warning" a minute before
+//! the timer runs out, and "exit" as it shuts down.
+//!
+//! Session Control: A running session pauses, resumes, extends, or stops on a
+//! com.taearls.catshield.control distributed notification whose object is
+//! "<pid> <action>" (pause, "pause <secs>", resume, stop, or "extend <secs>").
+//! Pausing lets the keyboard and mouse through and fades the overlay out until it
+//! resumes, without ending the session or running down the timer. Stop is an ordinary
+//! unlock, so --require-admin-exit still holds, and pausing asks for the same
+//! password. The menu bar popover, opened by clicking the 🐱 icon, uses this for
+//! its Pause/+15m/Stop buttons and shows a live countdown ring and the blocked
+//! key count; right-click for the full menu.
+//! From a terminal, status, pause, resume, stop, and extend do the same for the
+//! running shield, found through the health report every session also keeps in the
+//! temp directory:
+//!   cat_shield status
+//!   cat_shield pause 30s
+//!   cat_shield resume
+//!   cat_shield extend 30m
+//!   cat_shield stop
+//! A watch or daemon session takes them too; there stop disarms blocking, as the
+//! exit key does, and leaves it watching.
+//! Only one shield runs at a time; starting a second one fails and points to these.
+//!
+//! Displays: Every connected display gets its own overlay window, with the same
+//! backdrop, dimming, warning flash and unlock gestures, and its own hold-to-exit
+//! button; all the buttons drive one hold, so exit from whichever display the
+//! pointer is on. Widgets stay on the main display. Overlay windows are added,
+//! removed and resized as displays are plugged in, unplugged, rearranged or
+//! change resolution. Settings for one display
+//! go in a [[displays]] entry keyed by its name as System Settings shows it, or
+//! its UUID: opacity, whether the overlay widgets show (main display only), and
+//! passthrough, which lets clicks and scrolling reach the apps on that display
+//! while keys stay blocked:
+//!   [[displays]]
+//!   display = "LG TV"
+//!   opacity = 0.0
+//!   passthrough = true
+//!
+//! Stage Manager: The overlay joins every app's stage, so switching stages
+//! never moves it into the strip, and it is checked after each stage or Space
+//! switch and put back over the whole screen if anything displaced it.
+//!
+//! Health File: Use --health-file to have the shield rewrite a JSON report every
+//! few seconds with its state, event tap status, and when it last blocked a key.
+//! A monitor can alert when the file goes stale or the tap isn't "enabled":
+//!   cat_shield --timer 8h --health-file /tmp/catshield.health
+//!
+//! Session Summary: --summary-json prints a one-line JSON summary when the session
+//! ends (exit_reason "unlocked", "timer_expired", or "interrupted", duration,
+//! blocked key presses, event tap incidents); --summary-file writes it to a file:
+//!   cat_shield --timer 8h --summary-file /tmp/catshield.summary
+//!
+//! Hooks: Put a Rhai script at ~/.config/catshield/hooks.rhai to customize
+//! behavior. Define any of on_activate(), on_blocked_burst(), on_warning(remaining),
+//! and on_exit(); they can call notify(message), play_sound(name), and
+//! extend_timer(seconds), and nothing else (no files, processes, or network):
+//!   fn on_warning(remaining) { notify(`Shield ends in ${remaining}s`); }
+//!   fn on_blocked_burst() { play_sound("Funk"); extend_timer(300); }
+//!
+//! Detection Plugins: Put WASM modules in ~/.config/catshield/plugins to try out
+//! other ways of spotting a cat. Each module exports sample(key_presses,
+//! distinct_keys) -> i32, called once a second with that second's keyboard
+//! activity (counts only, never which keys), and returns 0 for nothing, 1 to arm
+//! keyboard blocking (watch mode), or 2 for an alert. Modules get no imports, and
+//! each call is metered, so a plugin can't touch the system or hang the shield.
+//!
+//! UI Automation: The overlay window and its views have stable accessibility
+//! identifiers (catshield.overlay, catshield.overlay.content,
+//! catshield.overlay.closeButton, catshield.overlay.timer). AXPress on the close
+//! button starts a hold exactly like pressing the mouse on it, so it exits after
+//! 3 seconds; AXCancel releases it. The timer's accessibility value is the time left.
+//!
+//! Logging: Lifecycle events (activation, arming, warnings, exit) and errors also
+//! go to unified logging under the com.taearls.catshield subsystem, so they show
+//! up in Console.app when the shield wasn't started from a terminal:
+//!   log stream --predicate 'subsystem == "com.taearls.catshield"'
+//!
+//! Library: The shield is a library crate with a thin command-line binary on top.
+//! Other apps embed the overlay, input blocking, and sleep prevention through
+//! Shield::builder(), which takes the same settings as the command line:
+//!   Shield::builder().timer(Duration::from_secs(1800)).opacity(0.5).build()?.run()?;
+//! run() takes over the app's run loop on the main thread and returns once the
+//! shield is unlocked or its timer runs out, leaving the app running. The user's
+//! config file, hooks, plugins, and telemetry stay with the command line.
+//!
+//! Note: Keyboard shortcuts require Accessibility permissions.
+//! Go to System Preferences → Security & Privacy → Privacy → Accessibility
+//! and add this application.
+
+mod platform;
+mod ui;
+
+use block2::RcBlock;
+use clap::error::ErrorKind;
+use clap::{CommandFactory, Parser, Subcommand};
+use objc2::rc::Retained;
+use objc2::runtime::{AnyClass, AnyObject, Bool, NSObject, NSObjectProtocol, ProtocolObject};
+use objc2::{define_class, msg_send, AllocAnyThread, ClassType, MainThreadOnly};
+use objc2_app_kit::{
+    NSAccessibility, NSAnimatablePropertyContainer, NSAnimationContext, NSApplication,
+    NSApplicationActivationPolicy, NSApplicationDidChangeScreenParametersNotification,
+    NSAutoresizingMaskOptions, NSBackingStoreType, NSButton, NSColor, NSEvent, NSEventMask,
+    NSEventModifierFlags, NSEventType, NSFont, NSHapticFeedbackPattern, NSMenu, NSMenuItem,
+    NSPopover, NSPopoverBehavior, NSRunningApplication, NSScreen, NSSound, NSStatusBar,
+    NSStatusItem, NSTextAlignment, NSTextField, NSView, NSViewController, NSWindow,
+    NSWindowCollectionBehavior, NSWindowStyleMask, NSWorkspace,
+    NSWorkspaceActiveSpaceDidChangeNotification, NSWorkspaceApplicationKey,
+    NSWorkspaceDidActivateApplicationNotification, NSWorkspaceDidLaunchApplicationNotification,
+};
+use objc2_avf_audio::{AVAudioEngine, AVAudioFormat, AVAudioPCMBuffer, AVAudioTime};
+use objc2_core_foundation::{
+    kCFRunLoopDefaultMode, CFDictionary, CFMachPort, CFNumber, CFPreferencesGetAppBooleanValue,
+    CFRetained, CFRunLoop, CFRunLoopSource, CFRunLoopTimer, CFString, CFType, CGFloat, CGPoint,
+    CGRect, CGSize,
+};
+use objc2_core_graphics::{
+    CGColor, CGEvent, CGEventField, CGEventFlags, CGEventMask, CGEventTapLocation,
+    CGEventTapOptions, CGEventTapPlacement, CGEventTapProxy, CGEventType,
+};
+use objc2_foundation::{
+    ns_string, MainThreadMarker, NSArray, NSDate, NSDateFormatter, NSDateFormatterStyle,
+    NSFullUserName, NSNotification, NSNotificationCenter, NSNotificationName, NSNumber, NSRectEdge,
+    NSString, NSURL,
+};
+use objc2_quartz_core::{
+    kCAAlignmentCenter, kCAFillModeBackwards, kCAMediaTimingFunctionEaseIn,
+    kCAMediaTimingFunctionEaseOut, CABasicAnimation, CACurrentMediaTime, CAMediaTiming,
+    CAMediaTimingFunction, CATextLayer,
+};
+use objc2_sound_analysis::{
+    SNAudioStreamAnalyzer, SNClassificationResult, SNClassifierIdentifierVersion1,
+    SNClassifySoundRequest, SNRequest, SNResult, SNResultsObserving,
+};
+use serde::{Deserialize, Serialize};
+use std::cell::{Cell, RefCell};
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
+use std::ffi::{c_void, CStr, CString, OsStr};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process;
+use std::ptr::NonNull;
+use std::sync::atomic::{AtomicBool, AtomicI64, AtomicPtr, AtomicU32, AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use platform::runloop::{common_modes, current_run_loop, RunLoopSource, RunLoopTimer};
+use ui::{
+    create_blur_view, enter_status_screen, hold_haptic, leave_status_screen,
+    overlay_background_color, palette, render_status_line, warning_flash_color, AwayNoteView,
+    CatBannerView, CloseButtonView, CountdownRingView, OverlayView, TimerDisplayView, WidgetView,
+};
+
+// IOKit power management bindings
+#[link(name = "IOKit", kind = "framework")]
+extern "C" {
+    fn IOPMAssertionCreateWithName(
+        assertion_type: *const c_void,
+        level: u32,
+        reason_for_activity: *const c_void,
+        assertion_id: *mut u32,
+    ) -> i32;
+    fn IOPMAssertionRelease(assertion_id: u32) -> i32;
+    fn IOPMAssertionDeclareUserActivity(
+        assertion_name: *const c_void,
+        user_type: u32,
+        assertion_id: *mut u32,
+    ) -> i32;
+
+    // Display sleep requests (what `pmset displaysleepnow` does)
+    fn IORegistryEntryFromPath(main_port: u32, path: *const std::ffi::c_char) -> u32;
+    fn IORegistryEntrySetCFProperty(entry: u32, name: *const c_void, value: *const c_void) -> i32;
+    fn IOObjectRelease(object: u32) -> i32;
+
+    // Power sources (the overlay's battery widget)
+    fn IOPSCopyPowerSourcesInfo() -> *const c_void;
+    fn IOPSCopyPowerSourcesList(blob: *const c_void) -> *const c_void;
+    fn IOPSGetPowerSourceDescription(blob: *const c_void, source: *const c_void) -> *const c_void;
+
+    // System power and lid (clamshell) notifications
+    fn IORegisterForSystemPower(
+        refcon: *mut c_void,
+        notify_port: *mut *mut c_void,
+        callback: unsafe extern "C" fn(*mut c_void, u32, u32, *mut c_void),
+        notifier: *mut u32,
+    ) -> u32;
+    fn IOAllowPowerChange(kernel_port: u32, notification_id: isize) -> i32;
+    fn IONotificationPortCreate(main_port: u32) -> *mut c_void;
+    fn IONotificationPortGetRunLoopSource(notify: *mut c_void) -> *mut c_void;
+    fn IOServiceMatching(name: *const std::ffi::c_char) -> *mut c_void;
+    fn IOServiceGetMatchingService(main_port: u32, matching: *mut c_void) -> u32;
+    fn IOServiceAddInterestNotification(
+        notify_port: *mut c_void,
+        service: u32,
+        interest_type: *const std::ffi::c_char,
+        callback: unsafe extern "C" fn(*mut c_void, u32, u32, *mut c_void),
+        refcon: *mut c_void,
+        notification: *mut u32,
+    ) -> i32;
+}
+
+const K_IO_MAIN_PORT_DEFAULT: u32 = 0;
+const K_IOPM_USER_ACTIVE_LOCAL: u32 = 0;
+const K_IO_MESSAGE_CAN_SYSTEM_SLEEP: u32 = 0xE000_0270;
+const K_IO_MESSAGE_SYSTEM_WILL_SLEEP: u32 = 0xE000_0280;
+const K_IO_MESSAGE_SYSTEM_HAS_POWERED_ON: u32 = 0xE000_0300;
+const K_IOPM_MESSAGE_CLAMSHELL_STATE_CHANGE: u32 = 0xE003_4100;
+const CF_NOTIFICATION_SUSPENSION_BEHAVIOR_DELIVER_IMMEDIATELY: isize = 4;
+const SCREENSAVER_DID_START: &str = "com.apple.screensaver.didstart";
+const SCREENSAVER_DID_STOP: &str = "com.apple.screensaver.didstop";
+const SCREENSAVER_BUNDLE_ID: &str = "com.apple.ScreenSaver.Engine";
+const SCREEN_IS_LOCKED: &str = "com.apple.screenIsLocked";
+const SCREEN_IS_UNLOCKED: &str = "com.apple.screenIsUnlocked";
+const STATE_NOTIFICATION: &str = "com.taearls.catshield.state"; // Object is the state name
+const CONTROL_NOTIFICATION: &str = "com.taearls.catshield.control"; // Object is "<pid> <action>"
+const KEYBOARD_LAYOUT_CHANGED: &str =
+    "com.apple.Carbon.TISNotifySelectedKeyboardInputSourceChanged";
+const K_IO_MESSAGE_DEVICE_WILL_POWER_OFF: u32 = 0xE000_0210; // Display wrangler: display off
+const K_IO_MESSAGE_DEVICE_HAS_POWERED_ON: u32 = 0xE000_0230; // Display wrangler: display on
+const K_CLAMSHELL_STATE_BIT: usize = 1 << 0; // Lid is closed
+const K_CLAMSHELL_SLEEP_BIT: usize = 1 << 1; // Closing the lid will sleep the system
+
+// IOKit HID bindings for identifying which keyboard a key press came from
+#[link(name = "IOKit", kind = "framework")]
+extern "C" {
+    fn IOHIDManagerCreate(allocator: *const c_void, options: u32) -> *mut c_void;
+    fn IOHIDManagerSetDeviceMatching(manager: *mut c_void, matching: *const c_void);
+    fn IOHIDManagerRegisterInputValueCallback(
+        manager: *mut c_void,
+        callback: unsafe extern "C" fn(*mut c_void, i32, *mut c_void, *mut c_void),
+        context: *mut c_void,
+    );
+    fn IOHIDManagerScheduleWithRunLoop(
+        manager: *mut c_void,
+        run_loop: *mut c_void,
+        mode: *const c_void,
+    );
+    fn IOHIDManagerRegisterDeviceMatchingCallback(
+        manager: *mut c_void,
+        callback: unsafe extern "C" fn(*mut c_void, i32, *mut c_void, *mut c_void),
+        context: *mut c_void,
+    );
+    fn IOHIDManagerRegisterDeviceRemovalCallback(
+        manager: *mut c_void,
+        callback: unsafe extern "C" fn(*mut c_void, i32, *mut c_void, *mut c_void),
+        context: *mut c_void,
+    );
+    fn IOHIDManagerOpen(manager: *mut c_void, options: u32) -> i32;
+    fn IOHIDValueGetElement(value: *mut c_void) -> *mut c_void;
+    fn IOHIDValueGetIntegerValue(value: *mut c_void) -> isize;
+    fn IOHIDElementGetUsagePage(element: *mut c_void) -> u32;
+    fn IOHIDElementGetUsage(element: *mut c_void) -> u32;
+    fn IOHIDElementGetDevice(element: *mut c_void) -> *mut c_void;
+    fn IOHIDDeviceGetProperty(device: *mut c_void, key: *const c_void) -> *const c_void;
+    fn IOHIDCheckAccess(request_type: u32) -> u32;
+}
+
+const K_IOHID_REQUEST_TYPE_LISTEN_EVENT: u32 = 1; // Input Monitoring
+const K_IOHID_ACCESS_TYPE_GRANTED: u32 = 0;
+
+// HID usage tables (see IOKit/hid/IOHIDUsageTables.h)
+const HID_PAGE_GENERIC_DESKTOP: i32 = 0x01;
+const HID_USAGE_GD_KEYBOARD: i32 = 0x06;
+const HID_PAGE_KEYBOARD: u32 = 0x07;
+const HID_USAGE_KEYBOARD_FIRST_KEY: u32 = 0x04; // 'a'; lower usages are error codes
+const HID_USAGE_KEYBOARD_LEFT_CONTROL: u32 = 0xE0; // Modifiers start here
+const K_IO_RETURN_SUCCESS: i32 = 0;
+
+// Additional CoreGraphics functions not in objc2-core-graphics
+#[link(name = "CoreGraphics", kind = "framework")]
+extern "C" {
+    fn CGEventTapEnable(tap: *mut c_void, enable: bool);
+    fn CGEventTapIsEnabled(tap: *mut c_void) -> bool;
+    fn AXIsProcessTrusted() -> bool;
+    fn CGMainDisplayID() -> u32;
+    fn CGCaptureAllDisplays() -> i32;
+    fn CGReleaseAllDisplays() -> i32;
+    fn CGShieldingWindowLevel() -> i32;
+    fn CGDisplayMirrorsDisplay(display: u32) -> u32;
+    fn CGDisplayRegisterReconfigurationCallback(
+        callback: unsafe extern "C" fn(u32, u32, *mut c_void),
+        user_info: *mut c_void,
+    ) -> i32;
+}
+
+const K_CG_DISPLAY_BEGIN_CONFIGURATION_FLAG: u32 = 1 << 0;
+const K_CG_DISPLAY_ADD_FLAG: u32 = 1 << 4;
+const K_CG_DISPLAY_REMOVE_FLAG: u32 = 1 << 5;
+
+// ApplicationServices framework for accessibility permission prompting
+#[link(name = "ApplicationServices", kind = "framework")]
+extern "C" {
+    fn AXIsProcessTrustedWithOptions(options: *const c_void) -> bool;
+    // Stable display identity for `[[displays]]` (ColorSync, via the umbrella)
+    fn CGDisplayCreateUUIDFromDisplayID(display: u32) -> *const c_void;
+}
+
+// CoreAudio bindings for the default output device
+#[repr(C)]
+struct AudioObjectPropertyAddress {
+    selector: u32,
+    scope: u32,
+    element: u32,
+}
+
+#[link(name = "CoreAudio", kind = "framework")]
+extern "C" {
+    fn AudioObjectGetPropertyData(
+        object_id: u32,
+        address: *const AudioObjectPropertyAddress,
+        qualifier_size: u32,
+        qualifier: *const c_void,
+        data_size: *mut u32,
+        data: *mut c_void,
+    ) -> i32;
+    fn AudioObjectSetPropertyData(
+        object_id: u32,
+        address: *const AudioObjectPropertyAddress,
+        qualifier_size: u32,
+        qualifier: *const c_void,
+        data_size: u32,
+        data: *const c_void,
+    ) -> i32;
+    fn AudioObjectAddPropertyListener(
+        object_id: u32,
+        address: *const AudioObjectPropertyAddress,
+        listener: AudioObjectPropertyListenerProc,
+        client_data: *mut c_void,
+    ) -> i32;
+}
+
+type AudioObjectPropertyListenerProc =
+    unsafe extern "C" fn(u32, u32, *const AudioObjectPropertyAddress, *mut c_void) -> i32;
+
+// The virtual main volume lives in AudioToolbox's hardware service
+#[link(name = "AudioToolbox", kind = "framework")]
+extern "C" {
+    fn AudioHardwareServiceGetPropertyData(
+        object_id: u32,
+        address: *const AudioObjectPropertyAddress,
+        qualifier_size: u32,
+        qualifier: *const c_void,
+        data_size: *mut u32,
+        data: *mut c_void,
+    ) -> i32;
+    fn AudioHardwareServiceSetPropertyData(
+        object_id: u32,
+        address: *const AudioObjectPropertyAddress,
+        qualifier_size: u32,
+        qualifier: *const c_void,
+        data_size: u32,
+        data: *const c_void,
+    ) -> i32;
+    fn AudioHardwareServiceAddPropertyListener(
+        object_id: u32,
+        address: *const AudioObjectPropertyAddress,
+        listener: AudioObjectPropertyListenerProc,
+        client_data: *mut c_void,
+    ) -> i32;
+}
+
+const K_AUDIO_OBJECT_SYSTEM_OBJECT: u32 = 1;
+const K_AUDIO_HARDWARE_PROPERTY_DEFAULT_OUTPUT_DEVICE: u32 = u32::from_be_bytes(*b"dOut");
+const K_AUDIO_DEVICE_PROPERTY_MUTE: u32 = u32::from_be_bytes(*b"mute");
+const K_AUDIO_HARDWARE_SERVICE_VIRTUAL_MAIN_VOLUME: u32 = u32::from_be_bytes(*b"vmvc");
+const K_AUDIO_OBJECT_PROPERTY_SCOPE_GLOBAL: u32 = u32::from_be_bytes(*b"glob");
+const K_AUDIO_DEVICE_PROPERTY_SCOPE_OUTPUT: u32 = u32::from_be_bytes(*b"outp");
+const K_AUDIO_OBJECT_PROPERTY_ELEMENT_MAIN: u32 = 0;
+
+// CoreFoundation bindings
+#[link(name = "CoreFoundation", kind = "framework")]
+extern "C" {
+    // Distributed notifications (screen saver, screen lock, state broadcast)
+    fn CFNotificationCenterGetDistributedCenter() -> *mut c_void;
+    fn CFNotificationCenterAddObserver(
+        center: *mut c_void,
+        observer: *const c_void,
+        callback: unsafe extern "C" fn(
+            *mut c_void,
+            *mut c_void,
+            *const c_void,
+            *const c_void,
+            *const c_void,
+        ),
+        name: *const c_void,
+        object: *const c_void,
+        suspension_behavior: isize,
+    );
+    fn CFNotificationCenterPostNotification(
+        center: *mut c_void,
+        name: *const c_void,
+        object: *const c_void,
+        user_info: *const c_void,
+        deliver_immediately: bool,
+    );
+
+    // Dictionary creation for accessibility options
+    static kCFBooleanTrue: *const c_void;
+    fn CFDictionaryCreate(
+        allocator: *const c_void,
+        keys: *const *const c_void,
+        values: *const *const c_void,
+        num_values: isize,
+        key_callbacks: *const c_void,
+        value_callbacks: *const c_void,
+    ) -> *mut c_void;
+    fn CFRelease(cf: *const c_void);
+
+    fn CFUUIDCreateString(allocator: *const c_void, uuid: *const c_void) -> *const c_void;
+
+    fn CFDataGetBytePtr(data: *const c_void) -> *const u8;
+
+    fn CFArrayGetCount(array: *const c_void) -> isize;
+    fn CFArrayGetValueAtIndex(array: *const c_void, index: isize) -> *const c_void;
+}
+
+// Text Input Sources bindings, to find which key types a character on the
+// active keyboard layout
+#[link(name = "Carbon", kind = "framework")]
+extern "C" {
+    static kTISPropertyUnicodeKeyLayoutData: *const c_void;
+    fn TISCopyCurrentKeyboardLayoutInputSource() -> *mut c_void;
+    fn TISGetInputSourceProperty(source: *const c_void, key: *const c_void) -> *const c_void;
+    fn LMGetKbdType() -> u8;
+    fn UCKeyTranslate(
+        layout: *const u8,
+        virtual_key_code: u16,
+        key_action: u16,
+        modifier_key_state: u32,
+        keyboard_type: u32,
+        key_translate_options: u32,
+        dead_key_state: *mut u32,
+        max_string_length: usize,
+        actual_string_length: *mut usize,
+        unicode_string: *mut u16,
+    ) -> i32;
+}
+
+const K_UC_KEY_ACTION_DOWN: u16 = 0;
+const K_UC_KEY_TRANSLATE_NO_DEAD_KEYS_MASK: u32 = 1;
+
+// Security framework Authorization Services bindings (admin-authenticated exit)
+#[repr(C)]
+struct AuthorizationItem {
+    name: *const std::ffi::c_char,
+    value_length: usize,
+    value: *mut c_void,
+    flags: u32,
+}
+
+#[repr(C)]
+struct AuthorizationItemSet {
+    count: u32,
+    items: *mut AuthorizationItem,
+}
+
+#[link(name = "Security", kind = "framework")]
+extern "C" {
+    fn AuthorizationCreate(
+        rights: *const AuthorizationItemSet,
+        environment: *const AuthorizationItemSet,
+        flags: u32,
+        authorization: *mut *mut c_void,
+    ) -> i32;
+    fn AuthorizationCopyRights(
+        authorization: *mut c_void,
+        rights: *const AuthorizationItemSet,
+        environment: *const AuthorizationItemSet,
+        flags: u32,
+        authorized_rights: *mut *mut AuthorizationItemSet,
+    ) -> i32;
+    fn AuthorizationFree(authorization: *mut c_void, flags: u32) -> i32;
+}
+
+const K_AUTHORIZATION_FLAG_INTERACTION_ALLOWED: u32 = 1 << 0;
+const K_AUTHORIZATION_FLAG_EXTEND_RIGHTS: u32 = 1 << 1;
+const K_AUTHORIZATION_FLAG_DESTROY_RIGHTS: u32 = 1 << 3;
+const ERR_AUTHORIZATION_SUCCESS: i32 = 0;
+const ERR_AUTHORIZATION_CANCELED: i32 = -60006;
+
+// Unified logging. `os_log` is a C macro; these are what it expands to.
+extern "C" {
+    fn os_log_create(
+        subsystem: *const std::ffi::c_char,
+        category: *const std::ffi::c_char,
+    ) -> *mut c_void;
+    fn _os_log_impl(
+        dso: *const c_void,
+        log: *mut c_void,
+        log_type: u8,
+        format: *const std::ffi::c_char,
+        buf: *const u8,
+        size: u32,
+    );
+    // Mach-O header of this image; formats are looked up relative to it
+    static __dso_handle: u8;
+}
+
+const OS_LOG_TYPE_DEFAULT: u8 = 0x00;
+const OS_LOG_TYPE_ERROR: u8 = 0x10;
+
+// Accessibility options key
+#[link(name = "ApplicationServices", kind = "framework")]
+extern "C" {
+    static kAXTrustedCheckOptionPrompt: *const c_void;
+}
+
+const K_IOPM_ASSERTION_LEVEL_ON: u32 = 255;
+
+// Default exit key configuration
+const DEFAULT_EXIT_KEY: &str = "Cmd+Option+U";
+
+// Keys passed through by --forward-to unless --forward-keys says otherwise
+// (what presentation clickers send)
+const DEFAULT_FORWARD_KEYS: &str = "Left,Right,Up,Down,Space,PageUp,PageDown";
+
+// macOS virtual key codes
+// See: https://developer.apple.com/documentation/coregraphics/cgkeycode
+fn keycode_from_name(name: &str) -> Option<i64> {
+    match name.to_lowercase().as_str() {
+        // Letters
+        "a" => Some(0),
+        "s" => Some(1),
+        "d" => Some(2),
+        "f" => Some(3),
+        "h" => Some(4),
+        "g" => Some(5),
+        "z" => Some(6),
+        "x" => Some(7),
+        "c" => Some(8),
+        "v" => Some(9),
+        "b" => Some(11),
+        "q" => Some(12),
+        "w" => Some(13),
+        "e" => Some(14),
+        "r" => Some(15),
+        "y" => Some(16),
+        "t" => Some(17),
+        "1" | "!" => Some(18),
+        "2" | "@" => Some(19),
+        "3" | "#" => Some(20),
+        "4" | "$" => Some(21),
+        "6" | "^" => Some(22),
+        "5" | "%" => Some(23),
+        "=" | "+" => Some(24),
+        "9" | "(" => Some(25),
+        "7" | "&" => Some(26),
+        "-" | "_" => Some(27),
+        "8" | "*" => Some(28),
+        "0" | ")" => Some(29),
+        "]" | "}" => Some(30),
+        "o" => Some(31),
+        "u" => Some(32),
+        "[" | "{" => Some(33),
+        "i" => Some(34),
+        "p" => Some(35),
+        "l" => Some(37),
+        "j" => Some(38),
+        "'" | "\"" => Some(39),
+        "k" => Some(40),
+        ";" | ":" => Some(41),
+        "\\" | "|" => Some(42),
+        "," | "<" => Some(43),
+        "/" | "?" => Some(44),
+        "n" => Some(45),
+        "m" => Some(46),
+        "." | ">" => Some(47),
+        "`" | "~" => Some(50),
+        // Special keys
+        "return" | "enter" => Some(36),
+        "tab" => Some(48),
+        "space" => Some(49),
+        "delete" | "backspace" => Some(51),
+        "escape" | "esc" => Some(53),
+        "f1" => Some(122),
+        "f2" => Some(120),
+        "f3" => Some(99),
+        "f4" => Some(118),
+        "f5" => Some(96),
+        "f6" => Some(97),
+        "f7" => Some(98),
+        "f8" => Some(100),
+        "f9" => Some(101),
+        "f10" => Some(109),
+        "f11" => Some(103),
+        "f12" => Some(111),
+        "home" => Some(115),
+        "end" => Some(119),
+        "pageup" => Some(116),
+        "pagedown" => Some(121),
+        "left" | "leftarrow" => Some(123),
+        "right" | "rightarrow" => Some(124),
+        "down" | "downarrow" => Some(125),
+        "up" | "uparrow" => Some(126),
+        _ => None,
+    }
+}
+
+/// Represents a parsed exit key combination
+#[derive(Debug, Clone)]
+struct ExitKey {
+    /// Key position on a US keyboard
+    keycode: i64,
+    /// Character the key types, when it should be found on the active layout
+    /// rather than by position
+    character: Option<char>,
+    requires_cmd: bool,
+    requires_option: bool,
+    requires_shift: bool,
+    requires_ctrl: bool,
+    display_name: String,
+}
+
+impl Default for ExitKey {
+    fn default() -> Self {
+        // Default: Cmd+Option+U
+        ExitKey {
+            keycode: 32, // U
+            character: Some('u'),
+            requires_cmd: true,
+            requires_option: true,
+            requires_shift: false,
+            requires_ctrl: false,
+            display_name: DEFAULT_EXIT_KEY.to_string(),
+        }
+    }
+}
+
+impl ExitKey {
+    /// Parse a key combination string like "Cmd+Option+U" or "Ctrl+Shift+Escape"
+    fn parse(input: &str) -> Result<Self, String> {
+        let input = input.trim();
+        if input.is_empty() {
+            return Err("Exit key cannot be empty".to_string());
+        }
+
+        let parts: Vec<&str> = input.split('+').map(|s| s.trim()).collect();
+        if parts.is_empty() {
+            return Err("Invalid key combination format".to_string());
+        }
+
+        let mut requires_cmd = false;
+        let mut requires_option = false;
+        let mut requires_shift = false;
+        let mut requires_ctrl = false;
+        let mut key_name: Option<&str> = None;
+
+        for part in &parts {
+            let lower = part.to_lowercase();
+            match lower.as_str() {
+                "cmd" | "command" | "⌘" => requires_cmd = true,
+                "opt" | "option" | "alt" | "⌥" => requires_option = true,
+                "shift" | "⇧" => requires_shift = true,
+                "ctrl" | "control" | "⌃" => requires_ctrl = true,
+                _ => {
+                    if let Some(existing) = key_name {
+                        return Err(format!(
+                            "Multiple keys specified: '{}' and '{}'",
+                            existing, part
+                        ));
+                    }
+                    key_name = Some(part);
+                }
+            }
+        }
+
+        let key_name = key_name.ok_or("No key specified in combination")?;
+        let keycode = keycode_from_name(key_name)
+            .ok_or_else(|| format!("Unknown key: '{}'. Valid keys include: A-Z, 0-9, F1-F12, Escape, Return, Tab, Space, Delete, Arrow keys", key_name))?;
+
+        // Require at least one modifier
+        if !requires_cmd && !requires_option && !requires_shift && !requires_ctrl {
+            return Err(
+                "At least one modifier key required (Cmd, Option, Shift, or Ctrl)".to_string(),
+            );
+        }
+
+        // Digits stay on the number row: AZERTY types them shifted, but shortcuts
+        // still use the keys by position
+        let mut chars = key_name.chars();
+        let character = match (chars.next(), chars.next()) {
+            (Some(c), None) if !c.is_ascii_digit() => c.to_lowercase().next(),
+            _ => None,
+        };
+
+        Ok(ExitKey {
+            keycode,
+            character,
+            requires_cmd,
+            requires_option,
+            requires_shift,
+            requires_ctrl,
+            display_name: input.to_string(),
+        })
+    }
+}
+
+// Global storage for exit key configuration (atomic for thread safety)
+static EXIT_KEY_KEYCODE: AtomicI64 = AtomicI64::new(32); // Default: U
+static EXIT_KEY_REQUIRES_CMD: AtomicBool = AtomicBool::new(true);
+static EXIT_KEY_REQUIRES_OPTION: AtomicBool = AtomicBool::new(true);
+static EXIT_KEY_REQUIRES_SHIFT: AtomicBool = AtomicBool::new(false);
+static EXIT_KEY_REQUIRES_CTRL: AtomicBool = AtomicBool::new(false);
+
+// What the exit key types, found again on the active layout when it changes
+// ('\0' for keys matched by position), and its US position when the layout
+// has no key for it
+static EXIT_KEY_CHARACTER: AtomicU32 = AtomicU32::new('u' as u32);
+static EXIT_KEY_POSITION: AtomicI64 = AtomicI64::new(32);
+
+/// Virtual keycode of the key that types `character` unmodified, given what
+/// each key types on a layout; the lowest one if several do
+fn keycode_typing(character: char, typed: impl Fn(u16) -> Option<char>) -> Option<i64> {
+    (0..128u16)
+        .find(|&keycode| {
+            typed(keycode).is_some_and(|c| c.to_lowercase().eq(character.to_lowercase()))
+        })
+        .map(i64::from)
+}
+
+/// Keycode that types `character` on the active keyboard layout (Dvorak,
+/// AZERTY, ...), or `None` if no key does. An input method such as Japanese
+/// types through its underlying layout, which is the one looked at here.
+fn layout_keycode(character: char) -> Option<i64> {
+    unsafe {
+        let source = TISCopyCurrentKeyboardLayoutInputSource();
+        if source.is_null() {
+            return None;
+        }
+        let data = TISGetInputSourceProperty(source, kTISPropertyUnicodeKeyLayoutData);
+        let keycode = if data.is_null() {
+            None
+        } else {
+            let layout = CFDataGetBytePtr(data);
+            let keyboard_type = LMGetKbdType() as u32;
+            keycode_typing(character, |keycode| {
+                let mut dead_key_state = 0;
+                let mut typed = [0u16; 4];
+                let mut length = 0;
+                let status = UCKeyTranslate(
+                    layout,
+                    keycode,
+                    K_UC_KEY_ACTION_DOWN,
+                    0,
+                    keyboard_type,
+                    K_UC_KEY_TRANSLATE_NO_DEAD_KEYS_MASK,
+                    &mut dead_key_state,
+                    typed.len(),
+                    &mut length,
+                    typed.as_mut_ptr(),
+                );
+                if status != 0 || length != 1 {
+                    return None;
+                }
+                char::from_u32(u32::from(typed[0]))
+            })
+        };
+        CFRelease(source);
+        keycode
+    }
+}
+
+/// Find the exit key on the active keyboard layout again
+///
+/// # Returns
+/// The exit key's keycode
+fn resolve_exit_keycode() -> i64 {
+    let keycode = char::from_u32(EXIT_KEY_CHARACTER.load(Ordering::SeqCst))
+        .filter(|&c| c != '\0')
+        .and_then(layout_keycode)
+        .unwrap_or_else(|| EXIT_KEY_POSITION.load(Ordering::SeqCst));
+    EXIT_KEY_KEYCODE.store(keycode, Ordering::SeqCst);
+    keycode
+}
+
+/// Set the global exit key configuration
+fn set_exit_key(key: &ExitKey) {
+    EXIT_KEY_CHARACTER.store(key.character.map_or(0, u32::from), Ordering::SeqCst);
+    EXIT_KEY_POSITION.store(key.keycode, Ordering::SeqCst);
+    let keycode = resolve_exit_keycode();
+    if keycode != key.keycode {
+        log_event(&format!(
+            "Exit key {} is keycode {} on this keyboard layout",
+            key.display_name, keycode
+        ));
+    }
+    EXIT_KEY_REQUIRES_CMD.store(key.requires_cmd, Ordering::SeqCst);
+    EXIT_KEY_REQUIRES_OPTION.store(key.requires_option, Ordering::SeqCst);
+    EXIT_KEY_REQUIRES_SHIFT.store(key.requires_shift, Ordering::SeqCst);
+    EXIT_KEY_REQUIRES_CTRL.store(key.requires_ctrl, Ordering::SeqCst);
+}
+
+/// Check if the given key event matches the configured exit key
+fn check_exit_key(keycode: i64, flags: CGEventFlags) -> bool {
+    let expected_keycode = EXIT_KEY_KEYCODE.load(Ordering::SeqCst);
+    if keycode != expected_keycode {
+        return false;
+    }
+
+    let has_cmd = flags.contains(CGEventFlags::MaskCommand);
+    let has_option = flags.contains(CGEventFlags::MaskAlternate);
+    let has_shift = flags.contains(CGEventFlags::MaskShift);
+    let has_ctrl = flags.contains(CGEventFlags::MaskControl);
+
+    let requires_cmd = EXIT_KEY_REQUIRES_CMD.load(Ordering::SeqCst);
+    let requires_option = EXIT_KEY_REQUIRES_OPTION.load(Ordering::SeqCst);
+    let requires_shift = EXIT_KEY_REQUIRES_SHIFT.load(Ordering::SeqCst);
+    let requires_ctrl = EXIT_KEY_REQUIRES_CTRL.load(Ordering::SeqCst);
+
+    requires_cmd == has_cmd
+        && requires_option == has_option
+        && requires_shift == has_shift
+        && requires_ctrl == has_ctrl
+}
+
+/// A system accessibility shortcut that `--allow-accessibility-shortcuts` lets through
+#[derive(Debug, PartialEq)]
+struct AccessibilityShortcut {
+    name: &'static str,
+    keycode: i64,
+    /// Exactly these of Command, Option, Control, and Shift must be held
+    modifiers: CGEventFlags,
+}
+
+/// The curated list: shortcuts that turn assistive features on and off.
+/// Keys are matched by position (`8` is the key labelled 8 on a US keyboard).
+const ACCESSIBILITY_SHORTCUTS: &[AccessibilityShortcut] = &[
+    AccessibilityShortcut {
+        name: "VoiceOver",
+        keycode: F5_KEYCODE,
+        modifiers: CGEventFlags::MaskCommand,
+    },
+    AccessibilityShortcut {
+        name: "Accessibility Shortcuts",
+        keycode: F5_KEYCODE,
+        modifiers: CGEventFlags::MaskCommand.union(CGEventFlags::MaskAlternate),
+    },
+    AccessibilityShortcut {
+        name: "Zoom",
+        keycode: 28, // 8
+        modifiers: CGEventFlags::MaskCommand.union(CGEventFlags::MaskAlternate),
+    },
+    AccessibilityShortcut {
+        name: "Zoom in",
+        keycode: 24, // =
+        modifiers: CGEventFlags::MaskCommand.union(CGEventFlags::MaskAlternate),
+    },
+    AccessibilityShortcut {
+        name: "Zoom out",
+        keycode: 27, // -
+        modifiers: CGEventFlags::MaskCommand.union(CGEventFlags::MaskAlternate),
+    },
+    AccessibilityShortcut {
+        name: "Invert colors",
+        keycode: 28, // 8
+        modifiers: CGEventFlags::MaskCommand
+            .union(CGEventFlags::MaskAlternate)
+            .union(CGEventFlags::MaskControl),
+    },
+];
+
+/// The accessibility shortcut a key press makes, if any. Fn is ignored, since
+/// F5 needs it on keyboards whose top row are media keys.
+fn accessibility_shortcut(
+    keycode: i64,
+    flags: CGEventFlags,
+) -> Option<&'static AccessibilityShortcut> {
+    let held = flags.intersection(SHORTCUT_MODIFIERS);
+    ACCESSIBILITY_SHORTCUTS
+        .iter()
+        .find(|shortcut| shortcut.keycode == keycode && shortcut.modifiers == held)
+}
+
+/// Check if a key press is one of the "hold to talk to Siri" shortcuts
+/// (Cmd+Space, Option+Space, or Fn/Globe+Space)
+fn is_siri_shortcut(keycode: i64, flags: CGEventFlags) -> bool {
+    keycode == SPACE_KEYCODE
+        && (flags.contains(CGEventFlags::MaskCommand)
+            || flags.contains(CGEventFlags::MaskAlternate)
+            || flags.contains(CGEventFlags::MaskSecondaryFn))
+}
+
+/// Lifetime protection statistics, persisted between sessions
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+struct Stats {
+    /// Completed shield sessions
+    sessions: u64,
+    /// Consecutive sessions without a blocked key press
+    incident_free_streak: u64,
+    /// Longest incident-free streak so far
+    best_streak: u64,
+    /// Total time spent shielded, in seconds
+    total_shielded_secs: u64,
+    /// Total key presses blocked
+    total_blocked_events: u64,
+    /// Key presses blocked in the most recent session
+    last_session_blocked: u64,
+    /// Total "CAT DETECTED" incidents (rapid runs on the same or neighboring keys)
+    total_cat_detections: u64,
+    /// Lifetime blocked key presses per cat
+    cat_incidents: BTreeMap<String, u64>,
+    /// Lifetime blocked events by where they came from (a keyboard's name,
+    /// "Trackpad", "Universal Control", ...)
+    sources: BTreeMap<String, u64>,
+    /// How the most recent session's presses were attributed, so `blame` can redo it
+    last_session_attribution: BTreeMap<String, u64>,
+    /// Totals for sessions started with `--label`, by label
+    labels: BTreeMap<String, LabelStats>,
+}
+
+/// Blocked event counts by source, most first, each with its rounded share in percent
+fn source_breakdown(sources: &BTreeMap<String, u64>) -> Vec<(&str, u64, u64)> {
+    let total: u64 = sources.values().sum();
+    let mut breakdown: Vec<_> = sources
+        .iter()
+        .map(|(source, &count)| {
+            let percent = (count * 100 + total / 2) / total.max(1);
+            (source.as_str(), count, percent)
+        })
+        .collect();
+    breakdown.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+    breakdown
+}
+
+/// Totals for the sessions that share a label
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+struct LabelStats {
+    sessions: u64,
+    total_shielded_secs: u64,
+    total_blocked_events: u64,
+}
+
+impl Stats {
+    /// Get the path to the stats file (~/Library/Application Support/catshield/stats.toml)
+    fn stats_path() -> Option<PathBuf> {
+        dirs::data_dir().map(|p| p.join("catshield").join("stats.toml"))
+    }
+
+    /// Load statistics from the stats file, or start fresh
+    fn load() -> Self {
+        let Some(path) = Self::stats_path() else {
+            return Self::default();
+        };
+
+        if !path.exists() {
+            return Self::default();
+        }
+
+        match fs::read_to_string(&path) {
+            Ok(contents) => toml::from_str(&contents).unwrap_or_else(|e| {
+                eprintln!("  ⚠️  Warning: Failed to parse stats file: {}", e);
+                Self::default()
+            }),
+            Err(e) => {
+                eprintln!("  ⚠️  Warning: Failed to read stats file: {}", e);
+                Self::default()
+            }
+        }
+    }
+
+    /// Write statistics to the stats file
+    fn save(&self) -> Result<(), String> {
+        let path = Self::stats_path().ok_or("No data directory available")?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+        }
+        let contents = toml::to_string(self).map_err(|e| e.to_string())?;
+        fs::write(&path, contents).map_err(|e| e.to_string())
+    }
+
+    /// Add a finished session to the totals and update the streak
+    fn record_session(&mut self, duration_secs: u64, blocked_events: u64) {
+        self.sessions += 1;
+        self.total_shielded_secs += duration_secs;
+        self.total_blocked_events += blocked_events;
+        self.last_session_blocked = blocked_events;
+        self.last_session_attribution.clear();
+
+        if blocked_events == 0 {
+            self.incident_free_streak += 1;
+            self.best_streak = self.best_streak.max(self.incident_free_streak);
+        } else {
+            self.incident_free_streak = 0;
+        }
+    }
+
+    /// Add a finished session to its label's totals
+    fn record_label(&mut self, label: &str, duration_secs: u64, blocked_events: u64) {
+        let totals = self.labels.entry(label.to_string()).or_default();
+        totals.sessions += 1;
+        totals.total_shielded_secs += duration_secs;
+        totals.total_blocked_events += blocked_events;
+    }
+
+    /// Add a finished session's blocked events by source to the totals
+    fn record_sources(&mut self, sources: &BTreeMap<String, u64>) {
+        for (source, count) in sources {
+            *self.sources.entry(source.clone()).or_default() += count;
+        }
+    }
+
+    /// Attribute the most recent session's blocked presses to cats
+    fn attribute_last_session(&mut self, attribution: BTreeMap<String, u64>) {
+        for (cat, count) in &attribution {
+            *self.cat_incidents.entry(cat.clone()).or_default() += count;
+        }
+        self.last_session_attribution = attribution;
+    }
+
+    /// Replace the most recent session's attribution with a single cat
+    fn blame_last_session(&mut self, cat: &str) -> Result<u64, String> {
+        if self.last_session_blocked == 0 {
+            return Err("The last session had no blocked key presses".to_string());
+        }
+
+        for (previous, count) in std::mem::take(&mut self.last_session_attribution) {
+            if let Some(total) = self.cat_incidents.get_mut(&previous) {
+                *total = total.saturating_sub(count);
+                if *total == 0 {
+                    self.cat_incidents.remove(&previous);
+                }
+            }
+        }
+
+        let blocked = self.last_session_blocked;
+        self.attribute_last_session(BTreeMap::from([(cat.to_string(), blocked)]));
+        Ok(blocked)
+    }
+
+    /// Print the statistics summary
+    fn print(&self) {
+        println!("  Sessions:              {}", self.sessions);
+        println!(
+            "  Incident-free streak:  {} (best: {})",
+            self.incident_free_streak, self.best_streak
+        );
+        println!(
+            "  Total time shielded:   {}",
+            format_duration(self.total_shielded_secs)
+        );
+        println!("  Keys blocked:          {}", self.total_blocked_events);
+        println!("  Cats detected:         {}", self.total_cat_detections);
+
+        if !self.cat_incidents.is_empty() {
+            let mut ranking: Vec<_> = self.cat_incidents.iter().collect();
+            ranking.sort_by(|a, b| b.1.cmp(a.1).then(a.0.cmp(b.0)));
+
+            println!();
+            println!("  🐾 Keyboard criminals:");
+            for (cat, count) in ranking {
+                println!("     {:<20} {}", cat, count);
+            }
+        }
+
+        if !self.sources.is_empty() {
+            println!();
+            println!("  ⌨️  Where blocked events came from:");
+            for (source, count, percent) in source_breakdown(&self.sources) {
+                println!("     {:<28} {} ({}%)", source, count, percent);
+            }
+        }
+    }
+
+    /// Print the totals for each session label
+    fn print_by_label(&self) {
+        if self.labels.is_empty() {
+            println!("  No labeled sessions yet. Start one with --label \"movie night\"");
+            return;
+        }
+
+        for (label, totals) in &self.labels {
+            println!("  🏷️  {}", label);
+            println!("     Sessions:             {}", totals.sessions);
+            println!(
+                "     Time shielded:        {}",
+                format_duration(totals.total_shielded_secs)
+            );
+            println!("     Keys blocked:         {}", totals.total_blocked_events);
+        }
+    }
+}
+
+/// A cat defined in the config file
+#[derive(Debug, Deserialize, Clone, PartialEq)]
+struct CatProfile {
+    /// The cat's name
+    name: String,
+    /// When the cat is usually up to mischief, in local time (e.g., "22:00-06:00")
+    active_hours: Option<String>,
+}
+
+impl CatProfile {
+    /// Whether the cat is typically active during the given local hour (0-23)
+    fn is_active_at(&self, hour: u32) -> bool {
+        let Some(Ok((start, end))) = self.active_hours.as_deref().map(parse_active_hours) else {
+            return false;
+        };
+        let minute = hour * 60;
+        if start <= end {
+            (start..end).contains(&minute)
+        } else {
+            // Wraps past midnight
+            minute >= start || minute < end
+        }
+    }
+}
+
+/// Parse an active-hours range like "22:00-06:00" or "9-17" into minutes after midnight
+fn parse_active_hours(s: &str) -> Result<(u32, u32), String> {
+    let parse_time = |t: &str| -> Option<u32> {
+        let t = t.trim();
+        let (hours, minutes) = t.split_once(':').unwrap_or((t, "0"));
+        let hours: u32 = hours.parse().ok()?;
+        let minutes: u32 = minutes.parse().ok()?;
+        (hours <= 24 && minutes < 60 && hours * 60 + minutes <= 24 * 60)
+            .then_some(hours * 60 + minutes)
+    };
+
+    let invalid = || format!("Invalid active hours '{}'. Use a range like 22:00-06:00", s);
+    let (start, end) = s.split_once('-').ok_or_else(invalid)?;
+    let start = parse_time(start).ok_or_else(invalid)?;
+    let end = parse_time(end).ok_or_else(invalid)?;
+    Ok((start, end))
+}
+
+/// Attribute blocked key presses, counted per local hour, to the first cat
+/// active in each hour. Presses in hours no cat claims are left out.
+fn attribute_by_schedule(
+    cats: &[CatProfile],
+    blocked_by_hour: &[u64; 24],
+) -> BTreeMap<String, u64> {
+    let mut attribution = BTreeMap::new();
+    for (hour, &count) in blocked_by_hour.iter().enumerate() {
+        if count == 0 {
+            continue;
+        }
+        if let Some(cat) = cats.iter().find(|cat| cat.is_active_at(hour as u32)) {
+            *attribution.entry(cat.name.clone()).or_default() += count;
+        }
+    }
+    attribution
+}
+
+/// Configuration file structure for persistent settings
+#[derive(Debug, Deserialize, Default)]
+struct Config {
+    /// Custom exit key combination (e.g., "Cmd+Option+U")
+    exit_key: Option<String>,
+    /// The household's cats, for incident attribution
+    #[serde(default)]
+    cats: Vec<CatProfile>,
+    /// Opt-in anonymous usage counters
+    #[serde(default)]
+    telemetry: TelemetryConfig,
+    /// Apps (by name or bundle ID) that arm keyboard blocking in watch mode
+    /// while they are frontmost
+    #[serde(default)]
+    activate_when_frontmost: Vec<String>,
+    /// Standard session lengths for the menu bar and `--preset`, as durations
+    /// ("25m") or named durations ("nap=25m")
+    #[serde(default)]
+    presets: Vec<String>,
+    /// Extra widgets on the overlay, and where the timer goes
+    #[serde(default)]
+    overlay: OverlayConfig,
+    /// Overlay settings for particular displays
+    #[serde(default)]
+    displays: Vec<DisplayConfig>,
+    /// Named session settings for `--preset`, by name
+    #[serde(default)]
+    profiles: BTreeMap<String, Profile>,
+}
+
+/// One `[profiles.<name>]` table: a preset with its own look and exit rules
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+struct Profile {
+    /// Session length (e.g., "2h30m"); without it the session is untimed
+    timer: Option<String>,
+    /// Overlay opacity from 0.0 (invisible) to 1.0
+    opacity: Option<f64>,
+    /// Exit key combination, in place of the config file's `exit_key`
+    exit_key: Option<String>,
+    /// Also exit on three Caps Lock presses, as `--emergency-exit`
+    #[serde(default)]
+    emergency_exit: bool,
+    /// Exiting needs an administrator password, as `--require-admin-exit`
+    #[serde(default)]
+    require_admin_exit: bool,
+    /// Dim red palette, as `--night`
+    #[serde(default)]
+    night: bool,
+}
+
+impl Profile {
+    /// Fill in `args` from the profile. Options already given on the command
+    /// line are kept; switches are turned on if either turns them on.
+    fn apply(&self, args: &mut Args) -> Result<(), String> {
+        if let Some(ref timer) = self.timer {
+            args.timer = Some(parse_duration(timer).map_err(|e| format!("timer: {}", e))?);
+        }
+        if let Some(opacity) = self.opacity {
+            if !(0.0..=1.0).contains(&opacity) {
+                return Err(format!("opacity: {} is not between 0.0 and 1.0", opacity));
+            }
+            args.opacity = args.opacity.or(Some(opacity));
+        }
+        if let (None, Some(key)) = (&args.exit_key, &self.exit_key) {
+            args.exit_key = Some(ExitKey::parse(key).map_err(|e| format!("exit_key: {}", e))?);
+        }
+        args.emergency_exit |= self.emergency_exit;
+        args.require_admin_exit |= self.require_admin_exit;
+        args.night |= self.night;
+        Ok(())
+    }
+}
+
+/// One `[[displays]]` entry: how the overlay looks and behaves on one display
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+struct DisplayConfig {
+    /// The display's name as System Settings shows it ("LG TV"), or its UUID
+    display: String,
+    /// Overlay opacity from 0.0 (invisible) to 1.0
+    opacity: Option<f64>,
+    /// Show the `[[overlay.widgets]]` (default true; only the main display has them)
+    widgets: Option<bool>,
+    /// Let clicks and scrolling through to the apps on this display; keys
+    /// stay blocked
+    #[serde(default)]
+    passthrough: bool,
+}
+
+impl DisplayConfig {
+    fn matches(&self, identity: &DisplayIdentity) -> bool {
+        let key = self.display.trim();
+        identity.name.eq_ignore_ascii_case(key)
+            || identity
+                .uuid
+                .as_deref()
+                .is_some_and(|uuid| uuid.eq_ignore_ascii_case(key))
+    }
+}
+
+/// The `[[displays]]` entry for a display, if any
+fn display_settings<'a>(
+    displays: &'a [DisplayConfig],
+    identity: &DisplayIdentity,
+) -> Option<&'a DisplayConfig> {
+    displays.iter().find(|display| display.matches(identity))
+}
+
+/// `[overlay]` config section
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct OverlayConfig {
+    widgets: Vec<WidgetSpec>,
+}
+
+/// One `[[overlay.widgets]]` entry
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+struct WidgetSpec {
+    kind: WidgetKind,
+    anchor: Anchor,
+    /// What a `message` widget says
+    text: Option<String>,
+}
+
+/// What an overlay widget shows
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum WidgetKind {
+    /// Time of day
+    Clock,
+    /// Battery charge
+    Battery,
+    /// Fixed text from the config file
+    Message,
+    /// Key presses blocked this session
+    Blocked,
+    /// The current song, where macOS shares it
+    NowPlaying,
+    /// The countdown display, which is otherwise in the top-left corner
+    Timer,
+}
+
+impl WidgetKind {
+    /// snake_case name, as in the config file
+    fn name(self) -> &'static str {
+        match self {
+            WidgetKind::Clock => "clock",
+            WidgetKind::Battery => "battery",
+            WidgetKind::Message => "message",
+            WidgetKind::Blocked => "blocked",
+            WidgetKind::NowPlaying => "now_playing",
+            WidgetKind::Timer => "timer",
+        }
+    }
+}
+
+/// Cell of a 3x3 grid over the screen that an overlay element is pinned to
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+enum Anchor {
+    #[default]
+    TopLeft,
+    TopCenter,
+    /// Taken by the close button
+    TopRight,
+    CenterLeft,
+    Center,
+    CenterRight,
+    BottomLeft,
+    BottomCenter,
+    BottomRight,
+}
+
+impl Anchor {
+    /// kebab-case name, as in the config file
+    fn name(self) -> &'static str {
+        match self {
+            Anchor::TopLeft => "top-left",
+            Anchor::TopCenter => "top-center",
+            Anchor::TopRight => "top-right",
+            Anchor::CenterLeft => "center-left",
+            Anchor::Center => "center",
+            Anchor::CenterRight => "center-right",
+            Anchor::BottomLeft => "bottom-left",
+            Anchor::BottomCenter => "bottom-center",
+            Anchor::BottomRight => "bottom-right",
+        }
+    }
+
+    /// Frame of the given size in this cell of a screen, `margin` in from the edges
+    fn frame(self, size: CGSize, screen_size: CGSize, margin: CGFloat) -> CGRect {
+        use Anchor::*;
+
+        let x = match self {
+            TopLeft | CenterLeft | BottomLeft => margin,
+            TopCenter | Center | BottomCenter => (screen_size.width - size.width) / 2.0,
+            TopRight | CenterRight | BottomRight => screen_size.width - size.width - margin,
+        };
+        let y = match self {
+            TopLeft | TopCenter | TopRight => screen_size.height - size.height - margin,
+            CenterLeft | Center | CenterRight => (screen_size.height - size.height) / 2.0,
+            BottomLeft | BottomCenter | BottomRight => margin,
+        };
+        CGRect {
+            origin: CGPoint { x, y },
+            size,
+        }
+    }
+}
+
+impl Config {
+    /// Get the path to the config file (~/.config/catshield/config.toml)
+    fn config_path() -> Option<PathBuf> {
+        dirs::config_dir().map(|p| p.join("catshield").join("config.toml"))
+    }
+
+    /// Load configuration from the config file, if it exists
+    fn load() -> Self {
+        let Some(path) = Self::config_path() else {
+            return Self::default();
+        };
+
+        if !path.exists() {
+            return Self::default();
+        }
+
+        match fs::read_to_string(&path) {
+            Ok(contents) => match toml::from_str(&contents) {
+                Ok(config) => config,
+                Err(e) => {
+                    eprintln!("  ⚠️  Warning: Failed to parse config file: {}", e);
+                    Self::default()
+                }
+            },
+            Err(e) => {
+                eprintln!("  ⚠️  Warning: Failed to read config file: {}", e);
+                Self::default()
+            }
+        }
+    }
+
+    /// The valid entries of `presets`; invalid ones are reported and skipped
+    fn presets(&self) -> Vec<Preset> {
+        self.presets
+            .iter()
+            .filter_map(|entry| match Preset::parse(entry) {
+                Ok(preset) => Some(preset),
+                Err(e) => {
+                    eprintln!("  ⚠️  Ignoring preset in config file: {}", e);
+                    None
+                }
+            })
+            .collect()
+    }
+}
+
+/// A standard session length from the config file's `presets` list
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Preset {
+    /// What `--preset` matches: the given name, or the duration as written
+    name: String,
+    /// The duration as written (e.g., "25m")
+    duration: String,
+    secs: u64,
+}
+
+impl Preset {
+    /// Parse a preset entry: a duration ("25m") or a named one ("nap=25m")
+    fn parse(entry: &str) -> Result<Self, String> {
+        let (name, duration) = entry.split_once('=').unwrap_or((entry, entry));
+        let (name, duration) = (name.trim(), duration.trim());
+        if name.is_empty() {
+            return Err(format!("Preset '{}' has no name", entry));
+        }
+        let secs = parse_duration(duration).map_err(|e| format!("Preset '{}': {}", entry, e))?;
+        Ok(Preset {
+            name: name.to_string(),
+            duration: duration.to_string(),
+            secs,
+        })
+    }
+
+    /// Title for the preset's menu bar item
+    fn menu_title(&self) -> String {
+        if self.name == self.duration {
+            format!("Shield for {}", self.duration)
+        } else {
+            format!("Shield for {} ({})", self.name, self.duration)
+        }
+    }
+}
+
+/// Look up a `--preset` by name in the config file
+///
+/// # Returns
+/// The preset's duration in seconds
+fn resolve_preset(name: &str, presets: &[Preset]) -> Result<u64, String> {
+    if let Some(preset) = presets.iter().find(|preset| preset.name == name) {
+        return Ok(preset.secs);
+    }
+    if presets.is_empty() {
+        return Err(format!(
+            "unknown preset '{}': the config file has no presets",
+            name
+        ));
+    }
+    let names: Vec<&str> = presets.iter().map(|preset| preset.name.as_str()).collect();
+    Err(format!(
+        "unknown preset '{}' (available: {})",
+        name,
+        names.join(", ")
+    ))
+}
+
+/// Apply `--preset`: the config file's profile of that name, or else its preset
+fn apply_preset(name: &str, config: &Config, args: &mut Args) -> Result<(), String> {
+    if let Some(profile) = config.profiles.get(name) {
+        return profile
+            .apply(args)
+            .map_err(|e| format!("profile '{}': {}", name, e));
+    }
+    let presets = config.presets();
+    match resolve_preset(name, &presets) {
+        Ok(secs) => {
+            args.timer = Some(secs);
+            Ok(())
+        }
+        Err(e) if config.profiles.is_empty() => Err(e),
+        Err(_) => {
+            let names: Vec<&str> = config
+                .profiles
+                .keys()
+                .map(String::as_str)
+                .chain(presets.iter().map(|preset| preset.name.as_str()))
+                .collect();
+            Err(format!(
+                "unknown preset '{}' (available: {})",
+                name,
+                names.join(", ")
+            ))
+        }
+    }
+}
+
+/// `[telemetry]` config section. Nothing is sent unless `enabled = true`
+/// and an `endpoint` is configured.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct TelemetryConfig {
+    enabled: bool,
+    endpoint: Option<String>,
+}
+
+impl TelemetryConfig {
+    /// The endpoint to report to, if telemetry is switched on. Counters are
+    /// never sent in the clear, so an endpoint that isn't https counts as unset.
+    fn active_endpoint(&self) -> Option<&str> {
+        self.endpoint
+            .as_deref()
+            .filter(|endpoint| self.enabled && endpoint.starts_with("https://"))
+    }
+}
+
+/// Keys understood at the top level of the config file, in `[[cats]]`, and in
+/// `[telemetry]`. Serde silently ignores anything else, so `config validate`
+/// checks against these to catch typos.
+const CONFIG_KEYS: &[&str] = &[
+    "exit_key",
+    "cats",
+    "telemetry",
+    "activate_when_frontmost",
+    "presets",
+    "overlay",
+    "displays",
+    "profiles",
+];
+const CAT_PROFILE_KEYS: &[&str] = &["name", "active_hours"];
+const TELEMETRY_KEYS: &[&str] = &["enabled", "endpoint"];
+const OVERLAY_KEYS: &[&str] = &["widgets"];
+const WIDGET_KEYS: &[&str] = &["kind", "anchor", "text"];
+const DISPLAY_KEYS: &[&str] = &["display", "opacity", "widgets", "passthrough"];
+const PROFILE_KEYS: &[&str] = &[
+    "timer",
+    "opacity",
+    "exit_key",
+    "emergency_exit",
+    "require_admin_exit",
+    "night",
+];
+
+/// Report keys in `table` that aren't in `known`, prefixed with `section`
+fn unknown_config_keys(
+    table: &toml::Table,
+    known: &[&str],
+    section: &str,
+    problems: &mut Vec<String>,
+) {
+    for key in table.keys().filter(|key| !known.contains(&key.as_str())) {
+        problems.push(format!("{}{}: unknown key", section, key));
+    }
+}
+
+/// Check config file contents for unknown keys, values of the wrong type,
+/// and values `Config::load` would accept but the shield can't use
+///
+/// # Returns
+/// One message per problem; empty if the config is valid
+fn validate_config(contents: &str) -> Vec<String> {
+    // Report parse errors by line rather than with toml's multi-line snippet
+    let describe = |e: toml::de::Error| match e.span() {
+        Some(span) => {
+            let line = contents[..span.start].matches('\n').count() + 1;
+            format!("line {}: {}", line, e.message().trim_end())
+        }
+        None => e.message().trim_end().to_string(),
+    };
+
+    let table: toml::Table = match toml::from_str(contents) {
+        Ok(table) => table,
+        Err(e) => return vec![describe(e)],
+    };
+
+    let mut problems = Vec::new();
+    unknown_config_keys(&table, CONFIG_KEYS, "", &mut problems);
+    if let Some(telemetry) = table.get("telemetry").and_then(toml::Value::as_table) {
+        unknown_config_keys(telemetry, TELEMETRY_KEYS, "telemetry.", &mut problems);
+    }
+    if let Some(cats) = table.get("cats").and_then(toml::Value::as_array) {
+        for (i, cat) in cats.iter().enumerate() {
+            if let Some(cat) = cat.as_table() {
+                let section = format!("cats[{}].", i);
+                unknown_config_keys(cat, CAT_PROFILE_KEYS, &section, &mut problems);
+            }
+        }
+    }
+    if let Some(overlay) = table.get("overlay").and_then(toml::Value::as_table) {
+        unknown_config_keys(overlay, OVERLAY_KEYS, "overlay.", &mut problems);
+        if let Some(widgets) = overlay.get("widgets").and_then(toml::Value::as_array) {
+            for (i, widget) in widgets.iter().enumerate() {
+                if let Some(widget) = widget.as_table() {
+                    let section = format!("overlay.widgets[{}].", i);
+                    unknown_config_keys(widget, WIDGET_KEYS, &section, &mut problems);
+                }
+            }
+        }
+    }
+
+    if let Some(displays) = table.get("displays").and_then(toml::Value::as_array) {
+        for (i, display) in displays.iter().enumerate() {
+            if let Some(display) = display.as_table() {
+                let section = format!("displays[{}].", i);
+                unknown_config_keys(display, DISPLAY_KEYS, &section, &mut problems);
+            }
+        }
+    }
+
+    if let Some(profiles) = table.get("profiles").and_then(toml::Value::as_table) {
+        for (name, profile) in profiles {
+            if let Some(profile) = profile.as_table() {
+                let section = format!("profiles.{}.", name);
+                unknown_config_keys(profile, PROFILE_KEYS, &section, &mut problems);
+            }
+        }
+    }
+
+    let config: Config = match toml::from_str(contents) {
+        Ok(config) => config,
+        Err(e) => {
+            problems.push(describe(e));
+            return problems;
+        }
+    };
+
+    if let Some(Err(e)) = config.exit_key.as_deref().map(ExitKey::parse) {
+        problems.push(format!("exit_key: {}", e));
+    }
+
+    for (i, cat) in config.cats.iter().enumerate() {
+        if cat.name.trim().is_empty() {
+            problems.push(format!("cats[{}].name: empty", i));
+        } else if config.cats[..i]
+            .iter()
+            .any(|other| other.name.eq_ignore_ascii_case(&cat.name))
+        {
+            problems.push(format!("cats[{}].name: '{}' is defined twice", i, cat.name));
+        }
+        if let Some(Err(e)) = cat.active_hours.as_deref().map(parse_active_hours) {
+            problems.push(format!("cats[{}].active_hours: {}", i, e));
+        }
+    }
+
+    if config.telemetry.enabled && config.telemetry.endpoint.is_none() {
+        problems.push("telemetry: enabled, but no endpoint is set".to_string());
+    }
+    if let Some(endpoint) = &config.telemetry.endpoint {
+        if !endpoint.starts_with("https://") {
+            problems.push(format!(
+                "telemetry.endpoint: '{}' is not an https URL",
+                endpoint
+            ));
+        }
+    }
+
+    if config
+        .activate_when_frontmost
+        .iter()
+        .any(|app| app.trim().is_empty())
+    {
+        problems.push("activate_when_frontmost: contains an empty app name".to_string());
+    }
+
+    for (i, entry) in config.presets.iter().enumerate() {
+        match Preset::parse(entry) {
+            Ok(preset) => {
+                let duplicate = config.presets[..i]
+                    .iter()
+                    .filter_map(|other| Preset::parse(other).ok())
+                    .any(|other| other.name == preset.name);
+                if duplicate {
+                    problems.push(format!(
+                        "presets[{}]: '{}' is defined twice",
+                        i, preset.name
+                    ));
+                }
+            }
+            Err(e) => problems.push(format!("presets[{}]: {}", i, e)),
+        }
+    }
+
+    let widgets = &config.overlay.widgets;
+    for (i, widget) in widgets.iter().enumerate() {
+        let section = format!("overlay.widgets[{}]", i);
+        match (widget.kind, &widget.text) {
+            (WidgetKind::Message, None) => {
+                problems.push(format!("{}: a message widget needs text", section))
+            }
+            (WidgetKind::Message, Some(_)) | (_, None) => {}
+            (kind, Some(_)) => problems.push(format!(
+                "{}.text: only message widgets have text, not {}",
+                section,
+                kind.name()
+            )),
+        }
+        if widget.anchor == Anchor::TopRight {
+            problems.push(format!(
+                "{}.anchor: top-right is taken by the close button",
+                section
+            ));
+        } else if widgets[..i]
+            .iter()
+            .any(|other| other.anchor == widget.anchor)
+        {
+            problems.push(format!(
+                "{}.anchor: another widget is already at {}",
+                section,
+                widget.anchor.name()
+            ));
+        }
+    }
+
+    for (i, display) in config.displays.iter().enumerate() {
+        let key = display.display.trim();
+        if key.is_empty() {
+            problems.push(format!("displays[{}].display: empty", i));
+        } else if config.displays[..i]
+            .iter()
+            .any(|other| other.display.trim().eq_ignore_ascii_case(key))
+        {
+            problems.push(format!(
+                "displays[{}].display: '{}' is defined twice",
+                i, key
+            ));
+        }
+        if let Some(opacity) = display
+            .opacity
+            .filter(|opacity| !(0.0..=1.0).contains(opacity))
+        {
+            problems.push(format!(
+                "displays[{}].opacity: {} is not between 0.0 and 1.0",
+                i, opacity
+            ));
+        }
+    }
+
+    for (name, profile) in &config.profiles {
+        if let Err(e) = profile.apply(&mut Args::default()) {
+            problems.push(format!("profiles.{}.{}", name, e));
+        }
+        if config
+            .presets
+            .iter()
+            .filter_map(|entry| Preset::parse(entry).ok())
+            .any(|preset| &preset.name == name)
+        {
+            problems.push(format!(
+                "profiles.{}: hides the preset of the same name",
+                name
+            ));
+        }
+    }
+
+    problems
+}
+
+/// Coarse usage counters waiting to be reported, kept in the data directory
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+struct TelemetryCounters {
+    sessions: u64,
+    clean_exits: u64,
+    unclean_exits: u64,
+    /// How many sessions used each command-line feature
+    features: BTreeMap<String, u64>,
+    /// A session has started and not yet finished (never reported)
+    session_open: bool,
+}
+
+/// The anonymous report: counters and the app version, nothing else
+#[derive(Debug, Serialize)]
+struct TelemetryReport<'a> {
+    version: &'static str,
+    sessions: u64,
+    clean_exits: u64,
+    unclean_exits: u64,
+    features: &'a BTreeMap<String, u64>,
+}
+
+impl TelemetryCounters {
+    /// Get the path to the counters file (~/Library/Application Support/catshield/telemetry.toml)
+    fn counters_path() -> Option<PathBuf> {
+        dirs::data_dir().map(|p| p.join("catshield").join("telemetry.toml"))
+    }
+
+    /// Load pending counters, or start fresh
+    fn load() -> Self {
+        Self::counters_path()
+            .and_then(|path| fs::read_to_string(path).ok())
+            .and_then(|contents| toml::from_str(&contents).ok())
+            .unwrap_or_default()
+    }
+
+    /// Write pending counters to the counters file
+    fn save(&self) -> Result<(), String> {
+        let path = Self::counters_path().ok_or("No data directory available")?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+        }
+        let contents = toml::to_string(self).map_err(|e| e.to_string())?;
+        fs::write(&path, contents).map_err(|e| e.to_string())
+    }
+
+    /// Count a starting session; a previous session that never finished crashed
+    fn begin_session(&mut self, features: &[&str]) {
+        if self.session_open {
+            self.unclean_exits += 1;
+        }
+        self.session_open = true;
+        self.sessions += 1;
+        for feature in features {
+            *self.features.entry(feature.to_string()).or_insert(0) += 1;
+        }
+    }
+
+    /// Count a session that exited normally
+    fn end_session(&mut self) {
+        if std::mem::take(&mut self.session_open) {
+            self.clean_exits += 1;
+        }
+    }
+
+    fn report(&self) -> TelemetryReport<'_> {
+        TelemetryReport {
+            version: env!("CARGO_PKG_VERSION"),
+            sessions: self.sessions,
+            clean_exits: self.clean_exits,
+            unclean_exits: self.unclean_exits,
+            features: &self.features,
+        }
+    }
+
+    /// Clear everything that has been reported
+    fn reset_reported(&mut self) {
+        *self = Self {
+            session_open: self.session_open,
+            ..Self::default()
+        };
+    }
+}
+
+// Close button configuration
+const CLOSE_BUTTON_SIZE: CGFloat = 80.0; // Large, easy-to-see button
+const CLOSE_BUTTON_MARGIN: CGFloat = 30.0;
+const HOLD_DURATION_SECS: f64 = 3.0;
+const DEFAULT_EXIT_KEY_HOLD_SECS: f64 = 1.0; // So a paw rolling across the chord can't fire it
+const MAX_EXIT_KEY_HOLD_SECS: f64 = 10.0;
+const DEFAULT_FPS: u32 = 60; // Smooth animation; --fps lowers it to save battery
+const SUPPORTED_FPS: [u32; 3] = [15, 30, 60];
+const ADAPTIVE_IDLE_MAX_INTERVAL_SECS: f64 = 1.0 / 15.0; // Slowest idle rate on ProMotion displays
+
+// Window levels from NSWindow.h
+const NS_NORMAL_WINDOW_LEVEL: isize = 0;
+const NS_SCREEN_SAVER_WINDOW_LEVEL: isize = 1000;
+const OVERLAY_ALPHA: CGFloat = 0.5; // Visible but not fully blocking the view
+const NIGHT_OVERLAY_ALPHA: CGFloat = 0.85; // Mostly hide the bright screen behind in night mode
+const SHIELD_FADE_SECS: f64 = 0.4; // Overlay fades in on activation and out on exit
+
+// Timer configuration
+const MIN_TIMER_SECONDS: u64 = 60; // Minimum 1 minute
+const MAX_TIMER_SECONDS: u64 = 24 * 60 * 60; // Maximum 24 hours
+const WARNING_SECONDS: u64 = 60; // Show warning 1 minute before exit
+const DEFAULT_KEYS_DURATION: &str = "2m"; // `cat_shield keys`: long enough to wipe the keyboard
+
+// Emergency exit configuration (triple Caps Lock press)
+const CAPS_LOCK_KEYCODE: i64 = 57;
+const EMERGENCY_EXIT_PRESSES: usize = 3;
+const EMERGENCY_EXIT_WINDOW: Duration = Duration::from_secs(2);
+
+// Update check configuration
+const RELEASES_API_URL: &str = "https://api.github.com/repos/taearls/catshield/releases/latest";
+const UPDATE_CHECK_TIMEOUT_SECS: u64 = 10;
+const UPDATE_DOWNLOAD_TIMEOUT_SECS: u64 = 300;
+const BINARY_ASSET_NAME: &str = "cat_shield-macos.tar.gz"; // Contains `cat_shield`
+const APP_ASSET_NAME: &str = "CatShield.app.tar.gz"; // Contains a single `.app` bundle
+const CHECKSUM_SUFFIX: &str = ".sha256"; // `shasum -a 256` output for each asset
+const RENAME_SWAP: u32 = 0x2;
+const DEFAULT_INSTALL_DIR: &str = "/usr/local/bin"; // On the default PATH
+
+// Telemetry configuration
+const TELEMETRY_TIMEOUT_SECS: u64 = 5;
+
+// Scripting hooks configuration (hooks run on the main thread, so keep them short)
+const HOOKS_FILE_NAME: &str = "hooks.rhai"; // Next to config.toml
+const HOOK_MAX_OPERATIONS: u64 = 100_000;
+const HOOK_MAX_CALL_LEVELS: usize = 16;
+const HOOK_MAX_STRING_SIZE: usize = 64 * 1024; // Bytes
+const HOOK_MAX_COLLECTION_SIZE: usize = 10_000; // Array or map entries
+const HOOK_MAX_EXPR_DEPTH: usize = 64;
+const HOOK_MAX_FUNCTION_EXPR_DEPTH: usize = 32;
+
+// Detection plugin configuration
+const PLUGINS_DIR_NAME: &str = "plugins"; // Next to config.toml
+const PLUGIN_SAMPLE_EXPORT: &str = "sample";
+const PLUGIN_SAMPLE_INTERVAL: Duration = Duration::from_secs(1);
+const PLUGIN_FUEL_PER_CALL: u64 = 1_000_000; // Roughly one unit per instruction
+const PLUGIN_MAX_MEMORY_BYTES: usize = 16 * 1024 * 1024; // 256 WASM pages
+
+// Accessibility shortcut configuration (--allow-accessibility-shortcuts)
+const F5_KEYCODE: i64 = 96;
+const SHIFT_KEYCODES: [i64; 2] = [56, 60]; // Left and right Shift, for Sticky Keys
+const SHORTCUT_MODIFIERS: CGEventFlags = CGEventFlags::MaskCommand
+    .union(CGEventFlags::MaskAlternate)
+    .union(CGEventFlags::MaskControl)
+    .union(CGEventFlags::MaskShift);
+
+// Siri suppression configuration
+const SPACE_KEYCODE: i64 = 49;
+const NX_SYSDEFINED: u32 = 14; // Media, dictation, and Siri keys arrive as this event type
+const NX_SUBTYPE_AUX_CONTROL_BUTTONS: i16 = 8;
+const NX_GESTURE: u32 = 29; // Trackpad gestures (NSEventTypeGesture)
+                            // Every trackpad gesture event type: rotate (18), begin/end gesture (19, 20),
+                            // gesture (29), magnify (30), swipe (31), smart magnify (32), quick look (33),
+                            // and force click pressure (34)
+const GESTURE_EVENT_MASK: u64 = (1 << 18)
+    | (1 << 19)
+    | (1 << 20)
+    | (1 << NX_GESTURE)
+    | (1 << 30)
+    | (1 << 31)
+    | (1 << 32)
+    | (1 << 33)
+    | (1 << 34);
+// What a gesture event (29) carries, as an IOHIDEventType (kCGEventGestureHIDType)
+const GESTURE_HID_TYPE_FIELD: CGEventField = CGEventField(110);
+// Rotation, scale, zoom, smart zoom (zoom toggle), and dock swipe: blocked even with --allow-scroll
+const ZOOM_GESTURE_HID_TYPES: [i64; 5] = [5, 7, 8, 22, 23];
+const MOUSE_BUTTON_EVENT_MASK: u64 = (1 << CGEventType::LeftMouseDown.0)
+    | (1 << CGEventType::LeftMouseUp.0)
+    | (1 << CGEventType::LeftMouseDragged.0)
+    | (1 << CGEventType::RightMouseDown.0)
+    | (1 << CGEventType::RightMouseUp.0)
+    | (1 << CGEventType::RightMouseDragged.0)
+    | (1 << CGEventType::OtherMouseDown.0)
+    | (1 << CGEventType::OtherMouseUp.0)
+    | (1 << CGEventType::OtherMouseDragged.0);
+const SCROLL_PHASE_BEGAN: i64 = 1; // kCGScrollPhaseBegan: a trackpad scroll starting
+const SIRI_BUNDLE_ID: &str = "com.apple.Siri";
+const SIRI_CHECK_INTERVAL: Duration = Duration::from_millis(500);
+
+// Gesture unlock configuration
+const GESTURE_MIN_SIZE: CGFloat = 200.0; // Shape must span at least 200pt in both directions
+const GESTURE_MIN_POINTS: usize = 16;
+const GESTURE_MAX_POINTS: usize = 4096;
+const GESTURE_MIN_TURN_RADIANS: f64 = 1.8 * std::f64::consts::PI; // ~324°, a nearly closed loop
+const CIRCLE_MAX_RADIUS_DEVIATION: f64 = 0.15; // Mean deviation from the mean radius
+const SQUARE_MAX_EDGE_DISTANCE: f64 = 0.12; // Max distance from the nearest edge, relative to size
+const SQUARE_MAX_ASPECT_RATIO: f64 = 1.5;
+
+// Keypad code configuration
+const KEYPAD_DIGIT_KEYCODES: [i64; 10] = [82, 83, 84, 85, 86, 87, 88, 89, 91, 92]; // Keypad 0-9
+const KEYPAD_ENTER_KEYCODE: i64 = 76;
+const KEYPAD_CLEAR_KEYCODE: i64 = 71;
+const KEYPAD_CODE_TIMEOUT: Duration = Duration::from_secs(5); // Pause that starts a new code
+const KEYPAD_CODE_MIN_LENGTH: usize = 4;
+const KEYPAD_CODE_MAX_LENGTH: usize = 12;
+
+// Knock unlock configuration
+const KNOCK_LONG_PRESS: Duration = Duration::from_millis(300); // Presses this long count as '-'
+const KNOCK_MAX_PRESS: Duration = Duration::from_secs(2); // Longer presses cancel the pattern
+const KNOCK_MAX_GAP: Duration = Duration::from_millis(1500); // Pause that starts a new pattern
+const KNOCK_MIN_LENGTH: usize = 2;
+const KNOCK_MAX_LENGTH: usize = 16;
+
+// Backlight deterrent configuration
+const BURST_KEY_PRESSES: usize = 8; // Blocked key presses that count as a burst
+const BURST_WINDOW: Duration = Duration::from_secs(1);
+const BURST_COOLDOWN: Duration = Duration::from_secs(10); // Don't flash continuously
+const BACKLIGHT_FLASH_DURATION: Duration = Duration::from_secs(2);
+const BACKLIGHT_FLASH_PERIOD: Duration = Duration::from_millis(200); // One on/off cycle
+
+// Cat detection configuration (the same or neighboring keys pressed in a rapid run)
+const CAT_RUN_PRESSES: usize = 6;
+const CAT_PRESS_GAP: Duration = Duration::from_millis(250); // Longer pauses end the run
+const CAT_DETECTION_COOLDOWN: Duration = Duration::from_secs(10); // One cat visit, one incident
+const CAT_BANNER_DURATION: Duration = Duration::from_secs(3);
+const CAT_BANNER_TEXT: &str = "🐾 CAT DETECTED";
+const CAT_BANNER_HEIGHT: CGFloat = 140.0;
+const CAT_BANNER_WIDTH_FRACTION: CGFloat = 0.6;
+const CAT_BANNER_CENTER_HEIGHT_FRACTION: CGFloat = 0.4; // Below the away note
+const CAT_BANNER_TEXT_SIZE: CGFloat = 72.0;
+
+// Meow detection configuration
+const MEOW_CLASSIFICATION: &str = "cat_meow"; // Label in the built-in sound classifier
+const MEOW_MIN_CONFIDENCE: f64 = 0.7;
+const MEOW_COOLDOWN: Duration = Duration::from_secs(5); // Overlapping windows report one meow many times
+const MICROPHONE_BUFFER_FRAMES: u32 = 8192;
+const DETERRENT_SOUND_NAME: &str = "Sosumi"; // A sharp system sound from /System/Library/Sounds
+
+// Warning action configuration (--warn-action)
+const WARNING_SOUND_NAME: &str = "Glass"; // From /System/Library/Sounds
+const WARNING_FLASH_DURATION: Duration = Duration::from_secs(3);
+const WARNING_FLASH_PERIOD: Duration = Duration::from_millis(500); // One on/off cycle
+
+// Loudness trigger configuration
+const LOUDNESS_SUSTAIN: Duration = Duration::from_secs(1); // Must stay loud this long to trigger
+const SILENCE_DBFS: f64 = -160.0; // Level reported for an all-zero buffer
+
+// Celebration configuration (timer completed with no blocked key presses)
+const CELEBRATION_DURATION: Duration = Duration::from_secs(4);
+const CELEBRATION_PAW_COUNT: usize = 40;
+const CELEBRATION_MAX_DELAY_SECS: f64 = 1.5; // Paws start falling at staggered times
+const CELEBRATION_FALL_SECS: f64 = 2.0;
+const CELEBRATION_PAW_SIZE: CGFloat = 48.0;
+const CELEBRATION_MESSAGE_SIZE: CGFloat = 72.0;
+
+// Progressive dimming configuration (overlay background opacity)
+const DIM_MIN_ALPHA: CGFloat = 0.1;
+const DIM_MAX_ALPHA: CGFloat = 0.9;
+const DIM_UPDATE_STEP: CGFloat = 0.005; // Skip redundant background updates
+
+// Screen-off configuration
+const DISPLAY_RESLEEP_DELAY_SECS: f64 = 2.0; // Also the grace period to read the startup banner
+
+// Stage Manager configuration
+const STAGE_MANAGER_DOMAIN: &str = "com.apple.WindowManager";
+const STAGE_MANAGER_ENABLED_KEY: &str = "GloballyEnabled";
+const STAGE_SWITCH_SETTLE_SECS: f64 = 0.6; // Longer than the stage switch animation
+
+// Unified logging configuration
+const OS_LOG_SUBSYSTEM: &CStr = c"com.taearls.catshield";
+const OS_LOG_CATEGORY: &CStr = c"shield";
+
+// Health file configuration
+const HEALTH_FILE_INTERVAL: Duration = Duration::from_secs(5);
+
+// Menu bar quick-start presets when the config file has no `presets`
+const DEFAULT_PRESETS: &[&str] = &["30m", "1h", "2h"];
+
+// Menu bar popover configuration
+const MENU_BAR_EXTEND_SECS: u64 = 15 * 60; // What the popover's +15m button adds
+const MENU_BAR_REFRESH_INTERVAL: f64 = 1.0;
+const STOP_WAIT: Duration = Duration::from_secs(3); // How long `stop` waits to see the shield exit
+const CONTROL_CLIENT_IDLE: Duration = Duration::from_secs(30); // Silent clients are dropped
+const MENU_BAR_POPOVER_WIDTH: CGFloat = 260.0;
+const MENU_BAR_POPOVER_HEIGHT: CGFloat = 250.0;
+const MENU_BAR_COUNTDOWN_SIZE: CGFloat = 120.0;
+const MENU_BAR_COUNTDOWN_TEXT_SIZE: CGFloat = 16.0;
+
+// Accessibility identifiers (stable: UI automation looks elements up by these)
+const AX_OVERLAY_WINDOW: &str = "catshield.overlay";
+const AX_OVERLAY_VIEW: &str = "catshield.overlay.content";
+const AX_CLOSE_BUTTON: &str = "catshield.overlay.closeButton";
+const AX_TIMER_DISPLAY: &str = "catshield.overlay.timer";
+const AX_AWAY_NOTE: &str = "catshield.overlay.awayNote";
+const AX_CAT_BANNER: &str = "catshield.overlay.catBanner";
+const AX_MENU_BAR_COUNTDOWN: &str = "catshield.menuBar.countdown";
+const AX_OVERLAY_WIDGET_PREFIX: &str = "catshield.overlay.widget"; // + ".clock", ".battery", ...
+
+// Timer display configuration
+const TIMER_DISPLAY_HEIGHT: CGFloat = 60.0;
+const TIMER_DISPLAY_WIDTH: CGFloat = 200.0;
+const TIMER_DISPLAY_MARGIN: CGFloat = 30.0;
+const TIMER_TEXT_SIZE: CGFloat = 20.0;
+
+// Overlay widget configuration ([[overlay.widgets]] in the config file)
+const OVERLAY_WIDGET_HEIGHT: CGFloat = 50.0;
+const OVERLAY_WIDGET_WIDTH: CGFloat = 240.0;
+const OVERLAY_MESSAGE_WIDTH: CGFloat = 560.0; // Messages get room for a sentence
+const OVERLAY_WIDGET_MARGIN: CGFloat = 30.0;
+const OVERLAY_WIDGET_TEXT_SIZE: CGFloat = 18.0;
+const OVERLAY_WIDGET_REFRESH: Duration = Duration::from_secs(1);
+const NOW_PLAYING_REFRESH: Duration = Duration::from_secs(5);
+const MEDIA_REMOTE_PATH: &std::ffi::CStr =
+    c"/System/Library/PrivateFrameworks/MediaRemote.framework/MediaRemote";
+
+// Away note configuration (--away-note), centered in the upper part of the screen
+const AWAY_NOTE_HEIGHT: CGFloat = 90.0;
+const AWAY_NOTE_WIDTH_FRACTION: CGFloat = 0.7;
+const AWAY_NOTE_CENTER_HEIGHT_FRACTION: CGFloat = 0.65;
+const AWAY_NOTE_TEXT_SIZE: CGFloat = 36.0;
+
+/// Command line for Cat Shield: a subcommand, or shield options to start
+/// the shield (or the menu bar app) as `run` does
+#[derive(Parser, Debug)]
+#[command(name = "cat_shield")]
+#[command(author = "Tyler Earls")]
+#[command(version)]
+#[command(about = "A cat-proof screen overlay that keeps your machine awake and blocks input")]
+#[command(args_conflicts_with_subcommands = true)]
+#[command(after_help = "EXAMPLES:
+    cat_shield                          # Use default exit key (Cmd+Option+U)
+    cat_shield --exit-key \"Cmd+Shift+Q\" # Custom exit shortcut
+    cat_shield --timer 30m              # Auto-exit after 30 minutes
+    cat_shield -e \"Ctrl+Option+X\" -t 2h # Custom key + timer
+    cat_shield -t 2h --emergency-exit   # Triple Caps Lock press also exits
+    cat_shield -t 1h --unlock-gesture circle # Draw a circle to exit
+    cat_shield -t 1h --knock \"..-.\"    # Click short-short-long-short to exit
+    cat_shield -t 1h --require-admin-exit # Exiting requires an admin password
+    cat_shield -t 1h --internal-keyboard-only # Ignore exit key on external keyboards
+    cat_shield --watch                  # Arm blocking when the external keyboard disconnects
+    cat_shield run -t 30m               # Same as cat_shield -t 30m
+    cat_shield --preset nap             # Use a timer from the config file's presets
+    cat_shield --preset movie           # Use a [profiles.movie] table from the config file
+    cat_shield watch                    # Same as cat_shield --watch
+    cat_shield --daemon                 # Watch mode plus a control socket for scripts
+    cat_shield keys                     # Block just the keyboard for 2 minutes
+    cat_shield -t 1h --no-overlay --keyboard-only # Keyboard blocked, screen and mouse usable
+    cat_shield -t 1h --dim              # Overlay darkens as time runs out
+    cat_shield -t 2h --warn-action sound,notify # Announce the one-minute warning
+    cat_shield -t 8h --remind-every 1h  # Notify the time left every hour
+    cat_shield -t 45m --away-note       # Show \"<name> is away — back around ...\"
+    cat_shield -t 8h --night            # Dim red colors for an overnight shield
+    cat_shield -t 1h --blur             # Blur the desktop for privacy
+    cat_shield -t 8h --dim-display 20%  # Lower display brightness while shielded
+    cat_shield -t 1h --backlight-off    # Keyboard backlight off while shielded
+    cat_shield -t 8h --mute             # Mute audio while shielded
+    cat_shield -t 1h --lock-volume      # Revert any volume changes while shielded
+    cat_shield -t 2h --count wall       # Keep counting down while the Mac sleeps
+    cat_shield -t 8h --fps 15           # Lower animation frame rate on battery
+    cat_shield -t 8h --no-screensaver   # Don't let the screen saver cover the shield
+    cat_shield -t 8h --stand-down-when-locked # Let the lock screen take over
+    cat_shield -t 8h --summary-json     # Print a JSON summary for scripts on exit
+    cat_shield stats                    # Show streaks and lifetime totals
+    cat_shield blame Mochi              # Attribute the last session to a cat
+    cat_shield check-update             # See if a newer release is out
+    cat_shield self-update              # Install the latest release
+    cat_shield telemetry status         # Show what opt-in telemetry would send
+    cat_shield doctor                   # Check permissions and the config file
+    cat_shield config validate          # Check the config file for typos and bad values
+    cat_shield install                  # Put cat_shield on your PATH
+    cat_shield status                   # Show the running shield's time left
+    cat_shield pause 30s                # Let the keyboard through for 30 seconds
+    cat_shield resume                   # Resume a paused shield now
+    cat_shield extend 30m               # Add 30 minutes to the running shield
+    cat_shield stop                     # Unlock the running shield
+    cat_shield -t 1h --backlight-deterrent # Flash the keyboard backlight at the cat
+    cat_shield --watch --meow-detect    # Also arm blocking when the cat meows nearby
+    cat_shield --watch --loudness-threshold -25 # ...or when something loud happens nearby
+
+CONFIG FILE:
+    Settings can be persisted in ~/.config/catshield/config.toml:
+
+    exit_key = \"Cmd+Shift+Escape\"
+    activate_when_frontmost = [\"Final Cut Pro\"]  # Watch mode: arm while frontmost
+
+SUPPORTED KEYS:
+    Letters: A-Z
+    Numbers: 0-9
+    Function keys: F1-F12
+    Special: Escape, Return, Tab, Space, Delete
+    Arrow keys: Left, Right, Up, Down, Home, End, PageUp, PageDown
+
+MODIFIERS:
+    Cmd (Command), Option (Alt), Shift, Ctrl (Control)")]
+struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    #[command(flatten)]
+    shield: Args,
+}
+
+/// Options for starting the shield (`run`, `watch`, or no subcommand)
+#[derive(clap::Args, Debug, Default)]
+#[command(group(clap::ArgGroup::new("duration").args(["timer", "preset"])))]
+struct Args {
+    /// Auto-exit after specified duration (e.g., 30m, 2h, 1h30m, "1 hour 30 min",
+    /// PT1H30M)
+    #[arg(short, long, value_parser = parse_duration)]
+    timer: Option<u64>,
+
+    /// Use a profile from the config file's `[profiles]`, or auto-exit after a
+    /// duration from its `presets` list, by name (e.g., movie, nap, or 25m for an
+    /// unnamed preset)
+    #[arg(long, value_name = "NAME")]
+    preset: Option<String>,
+
+    /// Fade the overlay with timer progress: darken (default) or lighten as time runs out
+    #[arg(
+        long,
+        value_enum,
+        num_args = 0..=1,
+        default_missing_value = "darken",
+        requires = "duration"
+    )]
+    dim: Option<DimMode>,
+
+    /// How the one-minute warning before auto-exit shows up, comma-separated
+    /// (sound, notify, flash). It is always printed to the console.
+    #[arg(
+        long,
+        value_name = "ACTIONS",
+        value_enum,
+        value_delimiter = ',',
+        requires = "duration"
+    )]
+    warn_action: Vec<WarnAction>,
+
+    /// Post a quiet notification of the time left at this interval (e.g., 30m, 1h),
+    /// for long sessions
+    #[arg(long, value_name = "DURATION", value_parser = parse_duration, requires = "duration")]
+    remind_every: Option<u64>,
+
+    /// Speak the --remind-every reminders aloud instead of posting notifications
+    #[arg(long, requires = "remind_every")]
+    remind_aloud: bool,
+
+    /// What counts toward the timer: awake time only (default) or wall-clock time
+    #[arg(long, value_enum, default_value_t = CountMode::Awake, requires = "duration")]
+    count: CountMode,
+
+    /// Hide the countdown timer display
+    #[arg(long)]
+    hide_timer: bool,
+
+    /// Show a note for passers-by on the overlay, like "Tyler is away — back
+    /// around 3:45 PM", with the time taken from the timer. NAME defaults to the
+    /// first name of the logged-in account.
+    #[arg(
+        long,
+        value_name = "NAME",
+        num_args = 0..=1,
+        default_missing_value = "",
+        requires = "duration",
+        conflicts_with = "no_overlay"
+    )]
+    away_note: Option<String>,
+
+    /// Night mode: dim red overlay, close button, and timer display for dark rooms
+    #[arg(long)]
+    night: bool,
+
+    /// Blur the desktop behind the overlay (privacy) instead of tinting it
+    #[arg(long, conflicts_with = "dim")]
+    blur: bool,
+
+    /// Overlay opacity from 0.0 to 1.0 instead of the default (set by a
+    /// `--preset` profile or `Shield::builder()`)
+    #[arg(skip)]
+    opacity: Option<f64>,
+
+    /// Lower the display brightness while shielded (e.g., "20%"), restored on exit
+    #[arg(long, value_name = "PERCENT", value_parser = parse_percentage)]
+    dim_display: Option<f32>,
+
+    /// Put the displays to sleep while shielded, keeping the keyboard locked.
+    /// A blocked key that wakes them puts them back to sleep; they wake on exit.
+    #[arg(long, conflicts_with_all = ["watch", "dim_display"])]
+    screen_off: bool,
+
+    /// Stop the screen saver whenever it starts while shielded
+    #[arg(long)]
+    no_screensaver: bool,
+
+    /// Quit (or hide) apps launched while shielded, e.g. by a click that got
+    /// through before the shield was up
+    #[arg(long, value_enum, num_args = 0..=1, default_missing_value = "terminate")]
+    block_launches: Option<LaunchAction>,
+
+    /// Release keyboard blocking and sleep prevention while the screen is locked
+    #[arg(long)]
+    stand_down_when_locked: bool,
+
+    /// When a display that looks like a projector or capture device connects,
+    /// pause the shield until it disconnects, or only notify
+    #[arg(
+        long,
+        value_enum,
+        num_args = 0..=1,
+        default_missing_value = "pause",
+        conflicts_with = "watch"
+    )]
+    on_projector: Option<ProjectorAction>,
+
+    /// Turn the keyboard backlight off while shielded, restored on exit
+    #[arg(long)]
+    backlight_off: bool,
+
+    /// Mute system audio while shielded; volume and mute are restored on exit
+    #[arg(long)]
+    mute: bool,
+
+    /// Keep the volume and mute state as they were, reverting any change while shielded
+    #[arg(long, conflicts_with = "mute")]
+    lock_volume: bool,
+
+    /// Custom exit keyboard shortcut (e.g., "Cmd+Shift+Q", "Ctrl+Option+Escape")
+    /// Requires at least one modifier key (Cmd, Option, Shift, or Ctrl).
+    /// CLI argument overrides config file setting.
+    #[arg(short = 'e', long = "exit-key", value_parser = parse_exit_key)]
+    exit_key: Option<ExitKey>,
+
+    /// Seconds the exit key combination must stay held before it counts (0 for
+    /// instant), so a cat rolling across the keys can't trigger it
+    #[arg(long, value_name = "SECONDS", value_parser = parse_exit_key_hold, default_value_t = DEFAULT_EXIT_KEY_HOLD_SECS)]
+    exit_key_hold: f64,
+
+    /// Pass some keys through to one app (by name or bundle ID) while everything
+    /// else stays blocked, e.g. arrow keys to a slideshow
+    #[arg(long, value_name = "APP")]
+    forward_to: Option<String>,
+
+    /// Keys passed through to the --forward-to app, comma-separated
+    #[arg(
+        long,
+        value_name = "KEYS",
+        value_delimiter = ',',
+        value_parser = parse_key_name,
+        default_value = DEFAULT_FORWARD_KEYS,
+        requires = "forward_to"
+    )]
+    forward_keys: Vec<i64>,
+
+    /// Let keys typed on another Mac or iPad through Universal Control reach this Mac.
+    /// The overlay still catches their clicks.
+    #[arg(long)]
+    allow_universal_control: bool,
+
+    /// Let keys from Screen Sharing or Remote Desktop reach this Mac, so it can be
+    /// administered remotely while local input stays blocked
+    #[arg(long)]
+    allow_remote_input: bool,
+
+    /// Let the system accessibility shortcuts through: VoiceOver (Cmd+F5), the
+    /// Accessibility Shortcuts panel (Option+Cmd+F5), Zoom (Option+Cmd+8, =, -),
+    /// Invert Colors (Ctrl+Option+Cmd+8), and Shift on its own for Sticky Keys
+    #[arg(long)]
+    allow_accessibility_shortcuts: bool,
+
+    /// Let scrolling and swipes through to the app you were using, while keys,
+    /// clicks, and zoom or rotate gestures stay blocked
+    #[arg(long, conflicts_with = "watch")]
+    allow_scroll: bool,
+
+    /// Hard mode: capture every display so nothing but the shield can draw on screen,
+    /// not even notifications. The desktop behind the overlay is blacked out.
+    #[arg(long, conflicts_with_all = ["watch", "allow_scroll"])]
+    capture_display: bool,
+
+    /// Block input without showing the overlay, keeping the screen fully visible
+    /// (e.g. to watch a render). Clicks and scrolling are blocked by the event tap.
+    #[arg(
+        long,
+        conflicts_with_all = [
+            "watch", "capture_display", "blur", "night", "dim", "hide_timer",
+            "unlock_gesture", "knock"
+        ]
+    )]
+    no_overlay: bool,
+
+    /// With --no-overlay, block only the keyboard and leave the mouse and trackpad usable
+    #[arg(long, requires = "no_overlay")]
+    keyboard_only: bool,
+
+    /// Allow exiting by pressing Caps Lock three times within two seconds.
+    /// Useful when the mouse is unavailable and the exit shortcut was forgotten.
+    #[arg(long)]
+    emergency_exit: bool,
+
+    /// Allow exiting by drawing a large shape on the overlay while holding the mouse button
+    #[arg(long, value_enum)]
+    unlock_gesture: Option<GestureShape>,
+
+    /// Allow exiting by clicking a pattern of short (.) and long (-) presses on the overlay
+    /// (e.g., "..-."). Long presses are held for at least 300ms.
+    #[arg(long, value_parser = parse_knock_pattern)]
+    knock: Option<String>,
+
+    /// Allow exiting by typing this numeric code on the numeric keypad, then keypad Enter
+    #[arg(long, value_name = "DIGITS", value_parser = parse_keypad_code)]
+    keypad_code: Option<String>,
+
+    /// Require administrator authentication for every exit attempt (close button,
+    /// exit key, emergency exit, gesture, knock). The auto-exit timer is not affected.
+    #[arg(long)]
+    require_admin_exit: bool,
+
+    /// Only accept the exit key when it is typed on the built-in (laptop) keyboard.
+    /// Requires Input Monitoring permission to tell keyboards apart.
+    #[arg(long)]
+    internal_keyboard_only: bool,
+
+    /// Watch mode: run in the background without an overlay and arm keyboard blocking
+    /// automatically when the last external keyboard disconnects. The exit key disarms it.
+    #[arg(long, conflicts_with_all = ["duration", "hide_timer", "unlock_gesture", "knock"])]
+    watch: bool,
+
+    /// Daemon mode: watch mode that also takes `activate [DURATION]`, `deactivate`,
+    /// and `status` commands on a unix socket in the temp directory
+    #[arg(long, conflicts_with_all = ["duration", "hide_timer", "unlock_gesture", "knock"])]
+    daemon: bool,
+
+    /// Pulse the keyboard backlight when a burst of blocked key presses is detected
+    #[arg(long)]
+    backlight_deterrent: bool,
+
+    /// Listen on the microphone for meows (logs them; arms blocking in watch mode)
+    #[arg(long)]
+    meow_detect: bool,
+
+    /// Play a deterrent sound when a meow is detected
+    #[arg(long, requires = "meow_detect")]
+    meow_deterrent: bool,
+
+    /// Watch mode: react to sound on the microphone that stays above this level
+    /// (in dBFS, where 0 is the loudest possible; default threshold: -30)
+    #[arg(
+        long,
+        value_name = "DBFS",
+        num_args = 0..=1,
+        default_missing_value = "-30",
+        allow_hyphen_values = true,
+        value_parser = parse_loudness_threshold,
+        requires = "watch"
+    )]
+    loudness_threshold: Option<f64>,
+
+    /// What a sustained loud sound does: arm keyboard blocking or show a notification
+    #[arg(long, value_enum, default_value_t = LoudnessAction::Arm, requires = "loudness_threshold")]
+    loudness_action: LoudnessAction,
+
+    /// Show lifetime protection statistics when the session ends
+    #[arg(long)]
+    show_stats: bool,
+
+    /// Label this session in the statistics (e.g., "movie night"), so
+    /// `stats --by-label` can tell overnight guarding from short breaks
+    #[arg(long, value_name = "TEXT")]
+    label: Option<String>,
+
+    /// Rewrite this file every few seconds with a JSON health report (state, event
+    /// tap status, last blocked key press) so external monitors can alert on a
+    /// dead shield or a tap that stopped working
+    #[arg(long, value_name = "PATH")]
+    health_file: Option<PathBuf>,
+
+    /// When the session ends, print a JSON summary (exit reason, duration, blocked
+    /// key presses, event tap incidents) on a line of its own, for wrapper scripts
+    #[arg(long, conflicts_with = "watch")]
+    summary_json: bool,
+
+    /// Write the JSON session summary to this file instead of printing it
+    #[arg(long, value_name = "PATH", conflicts_with_all = ["watch", "summary_json"])]
+    summary_file: Option<PathBuf>,
+
+    /// Animation frame rate: 15, 30, or 60. Lower rates save battery; hold and
+    /// timer behavior is the same at any rate.
+    #[arg(long, value_parser = parse_fps, default_value_t = DEFAULT_FPS)]
+    fps: u32,
+
+    /// Check the options, config file, and permissions, print what the shield
+    /// would do (screens covered, what is blocked, timer, unlock methods), and
+    /// exit without creating any window or event tap
+    #[arg(long)]
+    dry_run: bool,
+}
+
+/// Subcommands (running without one starts the shield or the menu bar app)
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Start the shield now (the default when shield options are given)
+    Run(Args),
+    /// Stay in the background and arm blocking automatically (same as `run --watch`)
+    Watch(Args),
+    /// Block just the keyboard for a short while, with no overlay: for wiping the
+    /// keys down, or when the cat is about to land (same as `run --no-overlay
+    /// --keyboard-only --timer DURATION`)
+    Keys {
+        /// How long to block the keyboard (e.g., 2m, 5m; at least 1 minute)
+        #[arg(default_value = DEFAULT_KEYS_DURATION, value_parser = parse_duration)]
+        duration: u64,
+    },
+    /// Show lifetime protection statistics (streaks, time shielded, keys blocked)
+    Stats {
+        /// Break the totals down by session label (see --label)
+        #[arg(long)]
+        by_label: bool,
+    },
+    /// Attribute the last session's blocked key presses to a cat
+    Blame {
+        /// Cat name, as defined in the config file
+        cat: String,
+    },
+    /// Check GitHub for a newer release (the only command that uses the network)
+    CheckUpdate,
+    /// Download, verify, and install the latest release from GitHub
+    SelfUpdate,
+    /// Show or turn off opt-in anonymous usage telemetry
+    Telemetry {
+        #[command(subcommand)]
+        action: TelemetryAction,
+    },
+    /// Work with the config file
+    Config {
+        #[command(subcommand)]
+        action: ConfigAction,
+    },
+    /// Check permissions and the config file, and report anything that would
+    /// stop the shield from working
+    Doctor,
+    /// Link cat_shield into a directory on your PATH
+    Install {
+        /// Directory to put the link in
+        #[arg(long, default_value = DEFAULT_INSTALL_DIR)]
+        dir: PathBuf,
+    },
+    /// Show the running shield's state, time left, and blocked key presses
+    Status,
+    /// Unlock the running shield, as its close button would (an administrator
+    /// password is still needed with --require-admin-exit)
+    Stop,
+    /// Let the keyboard and mouse through and fade the running shield's overlay,
+    /// without ending the session; its timer stops while paused
+    Pause {
+        /// Resume on its own after this long (e.g., 30s, 5m)
+        #[arg(value_parser = parse_pause_duration)]
+        duration: Option<u64>,
+    },
+    /// Resume a paused shield
+    Resume,
+    /// Add time to the running shield's timer
+    Extend {
+        /// How much time to add (e.g., 15m, 1h)
+        #[arg(value_parser = parse_duration)]
+        duration: u64,
+    },
+}
+
+/// `cat_shield telemetry` actions
+#[derive(Subcommand, Debug, Clone, Copy, PartialEq, Eq)]
+enum TelemetryAction {
+    /// Show whether telemetry is on and exactly what the next report would contain
+    Status,
+    /// Turn telemetry off in the config file and discard pending counters
+    Off,
+}
+
+/// `cat_shield config` actions
+#[derive(Subcommand, Debug, Clone, Copy, PartialEq, Eq)]
+enum ConfigAction {
+    /// Check the config file for unknown keys and invalid values, exiting
+    /// non-zero if there are any
+    Validate,
+}
+
+/// Which time counts toward the auto-exit timer
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+enum CountMode {
+    /// Only time the system and display are awake
+    #[default]
+    Awake,
+    /// Wall-clock time, including sleep
+    Wall,
+}
+
+/// How the pre-expiry warning shows up, besides the console and the timer display
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+enum WarnAction {
+    /// Play a system sound
+    Sound,
+    /// Post a macOS notification
+    Notify,
+    /// Flash the overlay
+    Flash,
+}
+
+impl WarnAction {
+    /// Lowercase description for console output
+    fn name(self) -> &'static str {
+        match self {
+            WarnAction::Sound => "sound",
+            WarnAction::Notify => "notification",
+            WarnAction::Flash => "overlay flash",
+        }
+    }
+}
+
+/// What the loudness trigger does in watch mode
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+enum LoudnessAction {
+    /// Arm keyboard blocking
+    #[default]
+    Arm,
+    /// Show a notification and stay disarmed
+    Notify,
+}
+
+impl LoudnessAction {
+    /// Lowercase description for console output
+    fn name(self) -> &'static str {
+        match self {
+            LoudnessAction::Arm => "arm blocking",
+            LoudnessAction::Notify => "notify",
+        }
+    }
+}
+
+/// What happens when a projector or capture display connects mid-session
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+enum ProjectorAction {
+    /// Hide the overlay, release the keyboard, and pause the timer until it
+    /// disconnects
+    Pause,
+    /// Show a notification and keep shielding
+    Notify,
+}
+
+/// What happens to apps launched while the shield is active
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+enum LaunchAction {
+    /// Ask the app to quit (it may still prompt to save documents)
+    Terminate,
+    /// Hide the app behind the overlay
+    Hide,
+}
+
+impl LaunchAction {
+    /// What happens to the app, for console output
+    fn outcome(self) -> &'static str {
+        match self {
+            LaunchAction::Terminate => "quit",
+            LaunchAction::Hide => "hidden",
+        }
+    }
+}
+
+/// Direction of progressive overlay dimming
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+enum DimMode {
+    /// Start nearly transparent and darken as the timer runs out
+    Darken,
+    /// Start dark and become nearly transparent as the timer runs out
+    Lighten,
+}
+
+/// Shapes that can be drawn on the overlay to unlock it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+enum GestureShape {
+    /// A full loop with a roughly constant radius
+    Circle,
+    /// A full loop that stays close to the edges of its bounding box
+    Square,
+}
+
+impl GestureShape {
+    /// Lowercase name for console output
+    fn name(self) -> &'static str {
+        match self {
+            GestureShape::Circle => "circle",
+            GestureShape::Square => "square",
+        }
+    }
+}
+
+/// Parse exit key string into ExitKey struct (for clap value_parser)
+fn parse_exit_key(s: &str) -> Result<ExitKey, String> {
+    ExitKey::parse(s)
+}
+
+/// Parse a single key name like "Left" or "Space" into a keycode (for clap value_parser)
+fn parse_key_name(s: &str) -> Result<i64, String> {
+    keycode_from_name(s.trim()).ok_or_else(|| format!("Unknown key: '{}'", s.trim()))
+}
+
+/// Validate a knock pattern made of '.' (short click) and '-' (long click)
+fn parse_knock_pattern(s: &str) -> Result<String, String> {
+    let pattern: String = s.chars().filter(|c| !c.is_whitespace()).collect();
+
+    if let Some(c) = pattern.chars().find(|c| *c != '.' && *c != '-') {
+        return Err(format!(
+            "Invalid character in knock pattern: '{}'. Use '.' for short and '-' for long clicks",
+            c
+        ));
+    }
+
+    if pattern.len() < KNOCK_MIN_LENGTH || pattern.len() > KNOCK_MAX_LENGTH {
+        return Err(format!(
+            "Knock pattern must be {} to {} clicks long",
+            KNOCK_MIN_LENGTH, KNOCK_MAX_LENGTH
+        ));
+    }
+
+    Ok(pattern)
+}
+
+/// Validate a numeric keypad unlock code
+fn parse_keypad_code(s: &str) -> Result<String, String> {
+    let code = s.trim();
+    if !code.chars().all(|c| c.is_ascii_digit()) {
+        return Err("Keypad code must contain only digits".to_string());
+    }
+    if code.len() < KEYPAD_CODE_MIN_LENGTH || code.len() > KEYPAD_CODE_MAX_LENGTH {
+        return Err(format!(
+            "Keypad code must be {} to {} digits long",
+            KEYPAD_CODE_MIN_LENGTH, KEYPAD_CODE_MAX_LENGTH
+        ));
+    }
+    Ok(code.to_string())
+}
+
+/// Parse a percentage like "20%" or "20" into a fraction (for clap value_parser)
+fn parse_percentage(s: &str) -> Result<f32, String> {
+    let percent: f32 = s
+        .trim()
+        .trim_end_matches('%')
+        .trim()
+        .parse()
+        .map_err(|_| format!("Invalid percentage '{}'. Use a value like 20%", s))?;
+
+    if !(0.0..=100.0).contains(&percent) {
+        return Err("Percentage must be between 0% and 100%".to_string());
+    }
+
+    Ok(percent / 100.0)
+}
+
+/// Parse an animation frame rate, one of `SUPPORTED_FPS` (for clap value_parser)
+fn parse_fps(s: &str) -> Result<u32, String> {
+    let fps: u32 = s
+        .trim()
+        .parse()
+        .map_err(|_| format!("Invalid frame rate '{}'", s))?;
+
+    if !SUPPORTED_FPS.contains(&fps) {
+        return Err("Frame rate must be 15, 30, or 60".to_string());
+    }
+
+    Ok(fps)
+}
+
+/// How to use the exit key, e.g. "hold Cmd+Option+U for 1s"
+fn exit_key_instruction(display_name: &str, hold_secs: f64) -> String {
+    if hold_secs > 0.0 {
+        format!("hold {} for {}s", display_name, hold_secs)
+    } else {
+        format!("press {}", display_name)
+    }
+}
+
+/// Parse how long the exit key must be held, in seconds (for clap value_parser)
+fn parse_exit_key_hold(s: &str) -> Result<f64, String> {
+    let secs: f64 = s
+        .trim()
+        .trim_end_matches('s')
+        .parse()
+        .map_err(|_| format!("Invalid hold time '{}'", s))?;
+
+    if !(0.0..=MAX_EXIT_KEY_HOLD_SECS).contains(&secs) {
+        return Err(format!(
+            "Hold time must be between 0 and {} seconds",
+            MAX_EXIT_KEY_HOLD_SECS
+        ));
+    }
+
+    Ok(secs)
+}
+
+/// Parse a loudness threshold in dBFS (for clap value_parser)
+fn parse_loudness_threshold(s: &str) -> Result<f64, String> {
+    let threshold: f64 = s
+        .trim()
+        .trim_end_matches("dBFS")
+        .trim_end_matches("dB")
+        .trim()
+        .parse()
+        .map_err(|_| format!("Invalid loudness threshold '{}'. Use dBFS like -30", s))?;
+
+    if !(SILENCE_DBFS..=0.0).contains(&threshold) {
+        return Err(format!(
+            "Loudness threshold must be between {} and 0 dBFS",
+            SILENCE_DBFS
+        ));
+    }
+
+    Ok(threshold)
+}
+
+/// Parse duration string like "30m", "2h", "1h30m" into seconds
+fn parse_duration(s: &str) -> Result<u64, String> {
+    let total_seconds = parse_duration_span(s)?;
+
+    if total_seconds < MIN_TIMER_SECONDS {
+        return Err(format!(
+            "Duration must be at least {} seconds (1 minute)",
+            MIN_TIMER_SECONDS
+        ));
+    }
+
+    if total_seconds > MAX_TIMER_SECONDS {
+        return Err(format!(
+            "Duration must not exceed {} seconds (24 hours)",
+            MAX_TIMER_SECONDS
+        ));
+    }
+
+    Ok(total_seconds)
+}
+
+/// Parse a pause length: a duration like `parse_duration`'s, but as short as a second
+fn parse_pause_duration(s: &str) -> Result<u64, String> {
+    let total_seconds = parse_duration_span(s)?;
+
+    if total_seconds > MAX_TIMER_SECONDS {
+        return Err(format!(
+            "Duration must not exceed {} seconds (24 hours)",
+            MAX_TIMER_SECONDS
+        ));
+    }
+
+    Ok(total_seconds)
+}
+
+/// Parse a duration string into a nonzero number of seconds, without bounds.
+/// Takes "1h30m" style strings, humantime's spelled-out units ("1 hour 30 min"),
+/// and ISO 8601 durations ("PT1H30M").
+fn parse_duration_span(s: &str) -> Result<u64, String> {
+    let s = s.trim().to_lowercase();
+    if s.is_empty() {
+        return Err("Duration cannot be empty".to_string());
+    }
+
+    let total_seconds = match s.strip_prefix('p') {
+        Some(iso) => parse_iso8601_duration(iso)?,
+        None => parse_unit_duration(&s)?,
+    };
+
+    if total_seconds == 0 {
+        return Err("Duration must be greater than zero".to_string());
+    }
+
+    Ok(total_seconds)
+}
+
+/// Seconds in a duration unit, spelled any way humantime accepts
+fn duration_unit_secs(unit: &str) -> Option<u64> {
+    match unit {
+        "s" | "sec" | "secs" | "second" | "seconds" => Some(1),
+        "m" | "min" | "mins" | "minute" | "minutes" => Some(60),
+        "h" | "hr" | "hrs" | "hour" | "hours" => Some(3600),
+        "d" | "day" | "days" => Some(86_400),
+        _ => None,
+    }
+}
+
+/// Add `number` units of `unit_secs` seconds each to `total`
+fn add_duration_part(total: u64, number: &str, unit_secs: u64) -> Result<u64, String> {
+    let number: u64 = number
+        .parse()
+        .map_err(|_| format!("Invalid number: {}", number))?;
+    number
+        .checked_mul(unit_secs)
+        .and_then(|secs| total.checked_add(secs))
+        .ok_or_else(|| "Duration is too long".to_string())
+}
+
+/// Parse numbers followed by units, like "1h30m" or "1 hour 30 min"
+fn parse_unit_duration(s: &str) -> Result<u64, String> {
+    let mut total_seconds: u64 = 0;
+    let mut current_num = String::new();
+    let mut chars = s.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if c.is_ascii_digit() {
+            current_num.push(c);
+        } else if c.is_alphabetic() {
+            let mut unit = c.to_string();
+            while let Some(next) = chars.next_if(|next| next.is_alphabetic()) {
+                unit.push(next);
+            }
+            let unit_secs = duration_unit_secs(&unit)
+                .ok_or_else(|| format!("Unknown unit in duration: '{}'", unit))?;
+            if current_num.is_empty() {
+                return Err(format!("Missing number before '{}'", unit));
+            }
+            total_seconds = add_duration_part(total_seconds, &current_num, unit_secs)?;
+            current_num.clear();
+        } else if !c.is_whitespace() {
+            return Err(format!("Invalid character in duration: '{}'", c));
+        }
+    }
+
+    // If there are remaining digits without a unit, assume minutes
+    if !current_num.is_empty() {
+        total_seconds = add_duration_part(total_seconds, &current_num, 60)?;
+    }
+
+    Ok(total_seconds)
+}
+
+/// Parse what follows the 'P' of an ISO 8601 duration, like "T1H30M" or "1DT2H".
+/// Years and months have no fixed length, so they're refused.
+fn parse_iso8601_duration(s: &str) -> Result<u64, String> {
+    let mut total_seconds: u64 = 0;
+    let mut current_num = String::new();
+    let mut in_time = false;
+    let mut parts = 0;
+
+    for c in s.chars() {
+        if c.is_ascii_digit() {
+            current_num.push(c);
+            continue;
+        }
+        if c == 't' && !in_time && current_num.is_empty() {
+            in_time = true;
+            continue;
+        }
+
+        let designator = c.to_ascii_uppercase();
+        let unit_secs = match (in_time, c) {
+            (false, 'w') => 604_800,
+            (false, 'd') => 86_400,
+            (true, 'h') => 3600,
+            (true, 'm') => 60,
+            (true, 's') => 1,
+            (false, 'y' | 'm') => {
+                return Err(
+                    "ISO 8601 years and months aren't supported; use weeks, days, hours, minutes, or seconds"
+                        .to_string(),
+                )
+            }
+            _ => {
+                return Err(format!(
+                    "Invalid character in ISO 8601 duration: '{}'",
+                    designator
+                ))
+            }
+        };
+        if current_num.is_empty() {
+            return Err(format!("Missing number before '{}'", designator));
+        }
+        total_seconds = add_duration_part(total_seconds, &current_num, unit_secs)?;
+        current_num.clear();
+        parts += 1;
+    }
+
+    if !current_num.is_empty() {
+        return Err(format!(
+            "Missing unit after '{}' in ISO 8601 duration",
+            current_num
+        ));
+    }
+    if parts == 0 {
+        return Err("ISO 8601 duration has no parts (e.g., PT30M)".to_string());
+    }
+
+    Ok(total_seconds)
+}
+
+/// Calculate hold progress as a value from 0.0 to 1.0.
+///
+/// # Arguments
+/// * `elapsed_secs` - Time elapsed since mouse down in seconds
+/// * `hold_duration_secs` - Required hold duration in seconds
+///
+/// # Returns
+/// Progress value clamped to range [0.0, 1.0]
+#[inline]
+fn calculate_hold_progress(elapsed_secs: f64, hold_duration_secs: f64) -> f64 {
+    (elapsed_secs / hold_duration_secs).min(1.0)
+}
+
+/// Check if the hold duration has been met.
+///
+/// # Arguments
+/// * `elapsed_secs` - Time elapsed since mouse down in seconds
+/// * `hold_duration_secs` - Required hold duration in seconds
+///
+/// # Returns
+/// `true` if the hold duration has been met or exceeded
+#[inline]
+fn is_hold_complete(elapsed_secs: f64, hold_duration_secs: f64) -> bool {
+    elapsed_secs >= hold_duration_secs
+}
+
+/// Tracks recent Caps Lock presses for the emergency exit sequence
+#[derive(Debug, Default)]
+struct CapsLockTracker {
+    presses: Vec<Instant>,
+}
+
+impl CapsLockTracker {
+    /// Record a Caps Lock press at `now`.
+    ///
+    /// # Returns
+    /// `true` if this press completes the emergency exit sequence
+    /// (`EMERGENCY_EXIT_PRESSES` presses within `EMERGENCY_EXIT_WINDOW`)
+    fn record_press(&mut self, now: Instant) -> bool {
+        self.presses
+            .retain(|&pressed| now.duration_since(pressed) <= EMERGENCY_EXIT_WINDOW);
+        self.presses.push(now);
+
+        if self.presses.len() >= EMERGENCY_EXIT_PRESSES {
+            self.presses.clear();
+            true
+        } else {
+            false
+        }
+    }
+}
+
+/// Detects bursts of blocked input (a cat walking across the keyboard)
+#[derive(Debug, Default)]
+struct BurstDetector {
+    events: Vec<Instant>,
+    last_burst: Option<Instant>,
+}
+
+impl BurstDetector {
+    /// Record a blocked input event at `now`.
+    ///
+    /// # Returns
+    /// `true` if this event completes a burst (`BURST_KEY_PRESSES` events within
+    /// `BURST_WINDOW`) and no burst was reported within `BURST_COOLDOWN`
+    fn record(&mut self, now: Instant) -> bool {
+        self.events
+            .retain(|&event| now.duration_since(event) <= BURST_WINDOW);
+        self.events.push(now);
+
+        if self.events.len() < BURST_KEY_PRESSES {
+            return false;
+        }
+
+        self.events.clear();
+        let cooled_down = self
+            .last_burst
+            .is_none_or(|last| now.duration_since(last) >= BURST_COOLDOWN);
+        if cooled_down {
+            self.last_burst = Some(now);
+        }
+        cooled_down
+    }
+}
+
+/// Letter and number rows of the keyboard by virtual keycode (these follow
+/// physical position, whatever the layout), each with the offset of its first
+/// key in key widths
+const KEY_ROWS: [(f64, &[i64]); 4] = [
+    (0.0, &[50, 18, 19, 20, 21, 23, 22, 26, 28, 25, 29, 27, 24]), // ` 1 2 ... - =
+    (1.5, &[12, 13, 14, 15, 17, 16, 32, 34, 31, 35, 33, 30, 42]), // Q W E ... [ ] \
+    (1.75, &[0, 1, 2, 3, 5, 4, 38, 40, 37, 41, 39]),              // A S D ... ; '
+    (2.25, &[6, 7, 8, 9, 11, 45, 46, 43, 47, 44]),                // Z X C ... . /
+];
+
+/// Row and horizontal position (in key widths) of a letter or number key
+fn key_position(keycode: i64) -> Option<(usize, f64)> {
+    KEY_ROWS
+        .iter()
+        .enumerate()
+        .find_map(|(row, (offset, keys))| {
+            keys.iter()
+                .position(|&key| key == keycode)
+                .map(|column| (row, offset + column as f64))
+        })
+}
+
+/// Whether two keys are the same key or touch on the keyboard
+fn keys_adjacent(a: i64, b: i64) -> bool {
+    if a == b {
+        return true;
+    }
+    match (key_position(a), key_position(b)) {
+        (Some((row_a, x_a)), Some((row_b, x_b))) => {
+            row_a.abs_diff(row_b) <= 1 && (x_a - x_b).abs() <= 1.0
+        }
+        _ => false,
+    }
+}
+
+/// Detects a cat by its typing: a rapid run of presses on one key, or on keys
+/// that touch each other, as a paw kneads or walks across the keyboard
+#[derive(Debug, Default)]
+struct CatDetector {
+    last_press: Option<(Instant, i64)>,
+    run: usize,
+    last_detection: Option<Instant>,
+}
+
+impl CatDetector {
+    /// Record a blocked key press (not an autorepeat) at `now`.
+    ///
+    /// # Returns
+    /// `true` if this press completes a run of `CAT_RUN_PRESSES` same or
+    /// adjacent keys, each within `CAT_PRESS_GAP` of the last, and no cat was
+    /// detected within `CAT_DETECTION_COOLDOWN`
+    fn record(&mut self, keycode: i64, now: Instant) -> bool {
+        let continues = self.last_press.is_some_and(|(at, key)| {
+            now.duration_since(at) <= CAT_PRESS_GAP && keys_adjacent(key, keycode)
+        });
+        self.run = if continues { self.run + 1 } else { 1 };
+        self.last_press = Some((now, keycode));
+
+        if self.run < CAT_RUN_PRESSES {
+            return false;
+        }
+
+        self.run = 0;
+        let cooled_down = self
+            .last_detection
+            .is_none_or(|last| now.duration_since(last) >= CAT_DETECTION_COOLDOWN);
+        if cooled_down {
+            self.last_detection = Some(now);
+        }
+        cooled_down
+    }
+}
+
+/// Root-mean-square level of audio samples in dBFS (0 = full scale)
+fn rms_dbfs(samples: impl Iterator<Item = f32>) -> f64 {
+    let (sum, count) = samples.fold((0.0f64, 0usize), |(sum, count), sample| {
+        (sum + f64::from(sample) * f64::from(sample), count + 1)
+    });
+    if count == 0 || sum == 0.0 {
+        return SILENCE_DBFS;
+    }
+
+    (20.0 * (sum / count as f64).sqrt().log10()).max(SILENCE_DBFS)
+}
+
+/// Tracks how long the microphone level has stayed above the threshold
+#[derive(Debug, Default)]
+struct LoudnessTracker {
+    loud_since: Option<Instant>,
+    triggered: bool,
+}
+
+impl LoudnessTracker {
+    /// Record the level of one audio buffer at `now`.
+    ///
+    /// # Returns
+    /// `true` once per loud stretch, when the level has stayed at or above
+    /// `threshold` for `LOUDNESS_SUSTAIN`
+    fn record(&mut self, level_dbfs: f64, threshold: f64, now: Instant) -> bool {
+        if level_dbfs < threshold {
+            self.loud_since = None;
+            self.triggered = false;
+            return false;
+        }
+
+        let since = *self.loud_since.get_or_insert(now);
+        if !self.triggered && now.duration_since(since) >= LOUDNESS_SUSTAIN {
+            self.triggered = true;
+            return true;
+        }
+        false
+    }
+}
+
+/// Check if a mouse path traced on the overlay matches the given unlock shape.
+///
+/// Both shapes must be large (at least `GESTURE_MIN_SIZE` in each direction) and
+/// form a nearly closed loop around their center, which random paw drags don't.
+///
+/// # Arguments
+/// * `points` - Mouse locations sampled from mouse down to mouse up
+/// * `shape` - The shape the path must match
+fn recognize_gesture(points: &[CGPoint], shape: GestureShape) -> bool {
+    if points.len() < GESTURE_MIN_POINTS {
+        return false;
+    }
+
+    let min_x = points.iter().map(|p| p.x).fold(CGFloat::MAX, CGFloat::min);
+    let max_x = points.iter().map(|p| p.x).fold(CGFloat::MIN, CGFloat::max);
+    let min_y = points.iter().map(|p| p.y).fold(CGFloat::MAX, CGFloat::min);
+    let max_y = points.iter().map(|p| p.y).fold(CGFloat::MIN, CGFloat::max);
+    let width = max_x - min_x;
+    let height = max_y - min_y;
+
+    if width.min(height) < GESTURE_MIN_SIZE {
+        return false;
+    }
+
+    let center_x = (min_x + max_x) / 2.0;
+    let center_y = (min_y + max_y) / 2.0;
+
+    // Total angle swept around the center; a closed loop sweeps ~2π
+    let swept: f64 = points
+        .windows(2)
+        .map(|pair| {
+            let from = (pair[0].y - center_y).atan2(pair[0].x - center_x);
+            let to = (pair[1].y - center_y).atan2(pair[1].x - center_x);
+            let delta = to - from;
+            if delta > std::f64::consts::PI {
+                delta - std::f64::consts::TAU
+            } else if delta < -std::f64::consts::PI {
+                delta + std::f64::consts::TAU
+            } else {
+                delta
+            }
+        })
+        .sum();
+
+    if swept.abs() < GESTURE_MIN_TURN_RADIANS {
+        return false;
+    }
+
+    match shape {
+        GestureShape::Circle => {
+            let radii: Vec<f64> = points
+                .iter()
+                .map(|p| (p.x - center_x).hypot(p.y - center_y))
+                .collect();
+            let mean_radius = radii.iter().sum::<f64>() / radii.len() as f64;
+            let mean_deviation =
+                radii.iter().map(|r| (r - mean_radius).abs()).sum::<f64>() / radii.len() as f64;
+            mean_deviation / mean_radius <= CIRCLE_MAX_RADIUS_DEVIATION
+        }
+        GestureShape::Square => {
+            let size = width.max(height);
+            if size / width.min(height) > SQUARE_MAX_ASPECT_RATIO {
+                return false;
+            }
+            points.iter().all(|p| {
+                let edge_distance = (p.x - min_x)
+                    .min(max_x - p.x)
+                    .min(p.y - min_y)
+                    .min(max_y - p.y);
+                edge_distance / size <= SQUARE_MAX_EDGE_DISTANCE
+            })
+        }
+    }
+}
+
+/// Collects short/long clicks on the overlay and matches them against a knock pattern
+#[derive(Debug)]
+struct KnockTracker {
+    pattern: String,
+    entered: String,
+    last_release: Option<Instant>,
+}
+
+impl KnockTracker {
+    fn new(pattern: String) -> Self {
+        KnockTracker {
+            pattern,
+            entered: String::new(),
+            last_release: None,
+        }
+    }
+
+    /// Record a click that was pressed at `pressed` and released at `released`.
+    ///
+    /// # Returns
+    /// `true` if the clicks entered so far end with the configured pattern
+    fn record_click(&mut self, pressed: Instant, released: Instant) -> bool {
+        // A long pause before this click starts a fresh attempt
+        if let Some(last) = self.last_release {
+            if pressed.duration_since(last) > KNOCK_MAX_GAP {
+                self.entered.clear();
+            }
+        }
+        self.last_release = Some(released);
+
+        let held = released.duration_since(pressed);
+        if held > KNOCK_MAX_PRESS {
+            self.entered.clear();
+            return false;
+        }
+
+        self.entered
+            .push(if held >= KNOCK_LONG_PRESS { '-' } else { '.' });
+        if self.entered.len() > KNOCK_MAX_LENGTH {
+            self.entered.remove(0);
+        }
+
+        if self.entered.ends_with(&self.pattern) {
+            self.entered.clear();
+            true
+        } else {
+            false
+        }
+    }
+}
+
+/// Input that unlock methods react to
+#[derive(Debug, Clone, Copy)]
+enum UnlockInput {
+    /// A key press reached the event tap
+    KeyDown { keycode: i64, flags: CGEventFlags },
+    /// A key release reached the event tap
+    KeyUp { keycode: i64 },
+    /// A modifier or Caps Lock toggle reached the event tap; `flags` are the
+    /// modifiers held afterwards
+    FlagsChanged { keycode: i64, flags: CGEventFlags },
+    /// Mouse pressed on the overlay background (window coordinates)
+    MouseDown(CGPoint),
+    /// Mouse dragged on the overlay background (window coordinates)
+    MouseDragged(CGPoint),
+    /// Mouse released on the overlay background
+    MouseUp,
+    /// Animation timer tick
+    Tick,
+}
+
+/// A way to unlock the shield.
+///
+/// Registered methods see every `UnlockInput` and report when they are
+/// satisfied, so a new unlock mechanism doesn't need changes to the event tap
+/// callback or the views.
+trait UnlockMethod {
+    /// Feed an input received at `now`.
+    ///
+    /// # Returns
+    /// `true` if this input completes the unlock
+    fn handle(&mut self, input: UnlockInput, now: Instant) -> bool;
+
+    /// Console message printed when this method unlocks
+    fn announcement(&self) -> Option<String> {
+        None
+    }
+
+    /// Whether the key that completes this unlock reaches the focused app
+    fn passes_key_through(&self) -> bool {
+        true
+    }
+}
+
+/// The configured exit key combination, held down for `hold`
+struct ExitChord {
+    hold: Duration,
+    /// When the whole chord went down, while it is still held
+    held_since: Option<Instant>,
+}
+
+impl ExitChord {
+    fn new(hold: Duration) -> Self {
+        Self {
+            hold,
+            held_since: None,
+        }
+    }
+
+    /// Complete once the chord has been held long enough (on the built-in
+    /// keyboard, with --internal-keyboard-only)
+    fn held_long_enough(&mut self, now: Instant) -> bool {
+        match self.held_since {
+            Some(since) if now.duration_since(since) >= self.hold => {
+                if INTERNAL_KEYBOARD_ONLY.load(Ordering::SeqCst) {
+                    match held_key_source() {
+                        KeySource::BuiltIn => {}
+                        // The HID report hasn't arrived yet; the next tick looks again
+                        KeySource::Unknown => return false,
+                        KeySource::External => {
+                            self.ignore_external();
+                            return false;
+                        }
+                    }
+                }
+                self.held_since = None;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Typed on an external keyboard - treat it like any other blocked key
+    fn ignore_external(&mut self) {
+        println!("\n  ⌨️  Exit key ignored: not typed on the built-in keyboard");
+        self.held_since = None;
+    }
+}
+
+impl UnlockMethod for ExitChord {
+    fn handle(&mut self, input: UnlockInput, now: Instant) -> bool {
+        match input {
+            UnlockInput::KeyDown { keycode, flags } => {
+                if !check_exit_key(keycode, flags) {
+                    self.held_since = None;
+                    return false;
+                }
+
+                if INTERNAL_KEYBOARD_ONLY.load(Ordering::SeqCst)
+                    && held_key_source() == KeySource::External
+                {
+                    self.ignore_external();
+                    return false;
+                }
+                // Auto-repeat keeps the time of the first press
+                self.held_since.get_or_insert(now);
+                self.held_long_enough(now)
+            }
+            // Letting go of the key or any modifier starts over
+            UnlockInput::KeyUp { keycode } => {
+                if keycode == EXIT_KEY_KEYCODE.load(Ordering::SeqCst) {
+                    self.held_since = None;
+                }
+                false
+            }
+            UnlockInput::FlagsChanged { flags, .. } => {
+                if !check_exit_key(EXIT_KEY_KEYCODE.load(Ordering::SeqCst), flags) {
+                    self.held_since = None;
+                }
+                false
+            }
+            UnlockInput::Tick => self.held_long_enough(now),
+            _ => false,
+        }
+    }
+
+    fn announcement(&self) -> Option<String> {
+        Some("🔓 Exit key combination detected!".to_string())
+    }
+}
+
+/// Holding the close button for `HOLD_DURATION_SECS`. On Force Touch
+/// trackpads the hold is felt as well as drawn: a pulse when it starts (from
+/// the button), halfway through, and when it completes.
+#[derive(Default)]
+struct HoldToExit {
+    /// Whether the halfway pulse has been played for the current hold
+    halfway_felt: bool,
+}
+
+impl UnlockMethod for HoldToExit {
+    fn handle(&mut self, input: UnlockInput, _now: Instant) -> bool {
+        if !matches!(input, UnlockInput::Tick) {
+            return false;
+        }
+
+        let held_secs = MOUSE_DOWN_TIME
+            .with(|time| time.get())
+            .filter(|_| IS_MOUSE_INSIDE.with(|inside| inside.get()))
+            .map(|start| start.elapsed().as_secs_f64());
+        let Some(held_secs) = held_secs else {
+            self.halfway_felt = false;
+            return false;
+        };
+
+        if is_hold_complete(held_secs, HOLD_DURATION_SECS) {
+            // Reset the hold so it doesn't retrigger while authentication is pending
+            MOUSE_DOWN_TIME.with(|time| time.set(None));
+            self.halfway_felt = false;
+            hold_haptic(NSHapticFeedbackPattern::LevelChange);
+            return true;
+        }
+        if !self.halfway_felt && held_secs >= HOLD_DURATION_SECS / 2.0 {
+            self.halfway_felt = true;
+            hold_haptic(NSHapticFeedbackPattern::Alignment);
+        }
+        false
+    }
+}
+
+/// Pressing Caps Lock `EMERGENCY_EXIT_PRESSES` times quickly (`--emergency-exit`)
+#[derive(Default)]
+struct EmergencyExit {
+    tracker: CapsLockTracker,
+}
+
+impl UnlockMethod for EmergencyExit {
+    fn handle(&mut self, input: UnlockInput, now: Instant) -> bool {
+        // Caps Lock toggles arrive as FlagsChanged events
+        matches!(input, UnlockInput::FlagsChanged { keycode, .. } if keycode == CAPS_LOCK_KEYCODE)
+            && self.tracker.record_press(now)
+    }
+
+    fn announcement(&self) -> Option<String> {
+        Some(format!(
+            "🚨 Emergency exit triggered (Caps Lock x{})!",
+            EMERGENCY_EXIT_PRESSES
+        ))
+    }
+}
+
+/// Drawing a shape on the overlay (`--unlock-gesture`)
+struct GestureUnlock {
+    shape: GestureShape,
+    points: Vec<CGPoint>,
+}
+
+impl GestureUnlock {
+    fn new(shape: GestureShape) -> Self {
+        GestureUnlock {
+            shape,
+            points: Vec::new(),
+        }
+    }
+}
+
+impl UnlockMethod for GestureUnlock {
+    fn handle(&mut self, input: UnlockInput, _now: Instant) -> bool {
+        match input {
+            UnlockInput::MouseDown(point) => {
+                self.points.clear();
+                self.points.push(point);
+                false
+            }
+            UnlockInput::MouseDragged(point) => {
+                if self.points.len() < GESTURE_MAX_POINTS {
+                    self.points.push(point);
+                }
+                false
+            }
+            UnlockInput::MouseUp => {
+                let points = std::mem::take(&mut self.points);
+                recognize_gesture(&points, self.shape)
+            }
+            _ => false,
+        }
+    }
+
+    fn announcement(&self) -> Option<String> {
+        Some("🔓 Unlock gesture recognized!".to_string())
+    }
+}
+
+/// Clicking a knock pattern on the overlay (`--knock`)
+struct KnockUnlock {
+    tracker: KnockTracker,
+    pressed: Option<Instant>,
+}
+
+impl KnockUnlock {
+    fn new(pattern: String) -> Self {
+        KnockUnlock {
+            tracker: KnockTracker::new(pattern),
+            pressed: None,
+        }
+    }
+}
+
+impl UnlockMethod for KnockUnlock {
+    fn handle(&mut self, input: UnlockInput, now: Instant) -> bool {
+        match input {
+            UnlockInput::MouseDown(_) => {
+                self.pressed = Some(now);
+                false
+            }
+            UnlockInput::MouseUp => self
+                .pressed
+                .take()
+                .is_some_and(|pressed| self.tracker.record_click(pressed, now)),
+            _ => false,
+        }
+    }
+
+    fn announcement(&self) -> Option<String> {
+        Some("🔓 Knock pattern recognized!".to_string())
+    }
+}
+
+/// Typing a code on the numeric keypad, then keypad Enter (`--keypad-code`).
+///
+/// Keypad keys are blocked like any other key while the code is buffered here,
+/// and so is the final Enter, so no part of the code reaches the focused app.
+struct KeypadCode {
+    code: String,
+    entered: String,
+    last_key: Option<Instant>,
+}
+
+impl KeypadCode {
+    fn new(code: String) -> Self {
+        KeypadCode {
+            code,
+            entered: String::new(),
+            last_key: None,
+        }
+    }
+}
+
+impl UnlockMethod for KeypadCode {
+    fn handle(&mut self, input: UnlockInput, now: Instant) -> bool {
+        let UnlockInput::KeyDown { keycode, .. } = input else {
+            return false;
+        };
+
+        if self
+            .last_key
+            .is_some_and(|last| now.duration_since(last) > KEYPAD_CODE_TIMEOUT)
+        {
+            self.entered.clear();
+        }
+
+        if let Some(digit) = KEYPAD_DIGIT_KEYCODES.iter().position(|&k| k == keycode) {
+            if self.entered.len() < KEYPAD_CODE_MAX_LENGTH {
+                self.entered.push(char::from(b'0' + digit as u8));
+            }
+            self.last_key = Some(now);
+            return false;
+        }
+
+        match keycode {
+            KEYPAD_ENTER_KEYCODE => {
+                self.last_key = None;
+                std::mem::take(&mut self.entered) == self.code
+            }
+            KEYPAD_CLEAR_KEYCODE => {
+                self.last_key = None;
+                self.entered.clear();
+                false
+            }
+            _ => false,
+        }
+    }
+
+    fn announcement(&self) -> Option<String> {
+        Some("🔢 Keypad code accepted!".to_string())
+    }
+
+    fn passes_key_through(&self) -> bool {
+        false
+    }
+}
+
+/// Add an unlock method to the registry evaluated by `evaluate_unlock_methods`
+fn register_unlock_method(method: impl UnlockMethod + 'static) {
+    UNLOCK_METHODS.with(|methods| methods.borrow_mut().push(Box::new(method)));
+}
+
+/// Feed an input to the registered unlock methods, in registration order, and
+/// post an unlock request to the coordinator if one of them is satisfied.
+///
+/// # Returns
+/// `Some` if an unlock method was satisfied, holding whether a key that
+/// completed it should reach the focused app
+fn evaluate_unlock_methods(input: UnlockInput) -> Option<bool> {
+    let now = Instant::now();
+    let (announcement, passes_key_through) = UNLOCK_METHODS.with(|methods| {
+        methods.borrow_mut().iter_mut().find_map(|method| {
+            method
+                .handle(input, now)
+                .then(|| (method.announcement(), method.passes_key_through()))
+        })
+    })?;
+
+    if let Some(announcement) = announcement {
+        println!("\n  {}", announcement);
+    }
+    // Exit (possibly after admin authentication)
+    post_message(ShieldMessage::UnlockRequested);
+    Some(passes_key_through)
+}
+
+// Global view reference for timer callback
+static CLOSE_BUTTON_VIEW: AtomicPtr<c_void> = AtomicPtr::new(std::ptr::null_mut());
+
+// Global pointer to the event tap for re-enabling from callback
+static EVENT_TAP: AtomicPtr<c_void> = AtomicPtr::new(std::ptr::null_mut());
+
+// Whether the event tap blocks keyboard input (watch mode starts disarmed)
+static BLOCKING_ARMED: AtomicBool = AtomicBool::new(true);
+
+// Watch mode: unlocking disarms blocking instead of exiting
+static WATCH_MODE: AtomicBool = AtomicBool::new(false);
+
+// When a timed daemon `activate` disarms blocking again (`monotonic_secs`, 0 if untimed)
+static DAEMON_ARMED_UNTIL: AtomicU64 = AtomicU64::new(0);
+
+// Number of external (non-built-in) keyboards currently connected, for watch mode
+static EXTERNAL_KEYBOARD_COUNT: AtomicU64 = AtomicU64::new(0);
+
+// Whether input from Universal Control passes through (--allow-universal-control)
+static ALLOW_UNIVERSAL_CONTROL: AtomicBool = AtomicBool::new(false);
+
+// Whether input from Screen Sharing and Remote Desktop passes through (--allow-remote-input)
+static ALLOW_REMOTE_INPUT: AtomicBool = AtomicBool::new(false);
+
+// Whether the curated accessibility shortcuts pass through (--allow-accessibility-shortcuts)
+static ALLOW_ACCESSIBILITY_SHORTCUTS: AtomicBool = AtomicBool::new(false);
+
+// Whether scrolling passes through to the app under the overlay (--allow-scroll)
+static ALLOW_SCROLL: AtomicBool = AtomicBool::new(false);
+
+// Whether the displays should be captured while shielded (--capture-display),
+// and whether they currently are; capture is dropped for the lock screen and
+// the admin authentication dialog, which would otherwise be hidden
+static CAPTURE_DISPLAYS: AtomicBool = AtomicBool::new(false);
+static DISPLAYS_CAPTURED: AtomicBool = AtomicBool::new(false);
+
+// Whether the event tap blocks clicks and scrolling because there is no overlay to
+// catch them (--no-overlay without --keyboard-only)
+static TAP_BLOCKS_MOUSE: AtomicBool = AtomicBool::new(false);
+
+// Whether the exit key is only accepted from the built-in keyboard
+static INTERNAL_KEYBOARD_ONLY: AtomicBool = AtomicBool::new(false);
+
+// Global timer state for auto-exit feature
+static AUTO_EXIT_ENABLED: AtomicBool = AtomicBool::new(false);
+static AUTO_EXIT_DURATION_SECS: AtomicU64 = AtomicU64::new(0);
+
+// Root power domain connection, needed to acknowledge sleep notifications
+static ROOT_POWER_PORT: AtomicU32 = AtomicU32::new(0);
+
+// Whether the displays are kept asleep while shielded (--screen-off)
+static SCREEN_OFF: AtomicBool = AtomicBool::new(false);
+
+// Whether the lid is currently closed
+static LID_CLOSED: AtomicBool = AtomicBool::new(false);
+
+// Whether to stop the screen saver as soon as it starts (--no-screensaver)
+static STOP_SCREENSAVER: AtomicBool = AtomicBool::new(false);
+
+// Whether to release the tap and sleep assertion while the screen is locked
+static STAND_DOWN_WHEN_LOCKED: AtomicBool = AtomicBool::new(false);
+
+// Set while stood down for the lock screen; nothing may re-arm the tap meanwhile
+static STOOD_DOWN: AtomicBool = AtomicBool::new(false);
+
+// Set while paused for a projector (--on-projector pause); also sets STOOD_DOWN
+static PROJECTOR_PAUSED: AtomicBool = AtomicBool::new(false);
+
+// Set while paused on request (menu bar, `cat_shield pause`): input passes and
+// the overlay is faded out, but the session goes on
+static SHIELD_PAUSED: AtomicBool = AtomicBool::new(false);
+
+// Whether blocking comes back armed when a pause ends: as it was when the pause
+// began, or as a watch trigger or disarm left it meanwhile
+static ARMED_ON_RESUME: AtomicBool = AtomicBool::new(true);
+
+// Key presses blocked this session (the cat incidents)
+static BLOCKED_KEY_PRESSES: AtomicU64 = AtomicU64::new(0);
+
+// Rapid same-key or neighboring-key runs this session (the "CAT DETECTED" banner)
+static CAT_DETECTIONS: AtomicU64 = AtomicU64::new(0);
+
+// Times the system disabled the event tap this session (re-enabled each time)
+static TAP_INCIDENTS: AtomicU64 = AtomicU64::new(0);
+
+// Blocked key presses by local hour of day, for attributing them to cats
+static BLOCKED_BY_HOUR: [AtomicU64; 24] = [const { AtomicU64::new(0) }; 24];
+
+// Unified logging handle (os_log_t), created on first use
+static OS_LOG: AtomicPtr<c_void> = AtomicPtr::new(std::ptr::null_mut());
+
+// Format for every unified log message: one public string. `os_log` puts its
+// formats in this section, where `log` and Console.app look for them.
+#[link_section = "__TEXT,__oslogstring,cstring_literals"]
+static OS_LOG_FORMAT: [u8; 11] = *b"%{public}s\0";
+
+// When the last key press was blocked (UNIX seconds, 0 = never)
+static LAST_BLOCKED_AT: AtomicU64 = AtomicU64::new(0);
+
+// Animation timer frame rate (--fps)
+static ANIMATION_FPS: AtomicU32 = AtomicU32::new(DEFAULT_FPS);
+
+// Start of the current shield session (unix seconds), 0 when none is running
+static SESSION_START: AtomicU64 = AtomicU64::new(0);
+
+// Whether --remind-every reminders are spoken rather than posted (--remind-aloud)
+static REMIND_ALOUD: AtomicBool = AtomicBool::new(false);
+
+// Whether to print lifetime statistics when the session ends
+static SHOW_STATS_ON_EXIT: AtomicBool = AtomicBool::new(false);
+
+// Set once a shield runs inside another app (`Shield::run`): it ends by stopping
+// the event loop rather than the process, and keeps the user's config file,
+// hooks, plugins, telemetry, and cat attribution out of the host app
+static EMBEDDED: AtomicBool = AtomicBool::new(false);
+
+// Whether the dim red night palette is in use
+static NIGHT_MODE: AtomicBool = AtomicBool::new(false);
+
+// Whether a burst of blocked key presses flashes the keyboard backlight
+static BACKLIGHT_DETERRENT_ENABLED: AtomicBool = AtomicBool::new(false);
+
+// Whether a detected meow plays the deterrent sound
+static MEOW_DETERRENT_ENABLED: AtomicBool = AtomicBool::new(false);
+
+// Messages posted by callbacks (from any thread) for the coordinator, and the
+// main run loop source that delivers them
+static MESSAGE_QUEUE: Mutex<VecDeque<ShieldMessage>> = Mutex::new(VecDeque::new());
+static MESSAGE_SOURCE: Mutex<Option<RunLoopSource>> = Mutex::new(None);
+
+// Global reference to the timer display view for updates
+static TIMER_DISPLAY_VIEW: AtomicPtr<c_void> = AtomicPtr::new(std::ptr::null_mut());
+
+// The --away-note view, redrawn when the return time changes
+static AWAY_NOTE_VIEW: AtomicPtr<c_void> = AtomicPtr::new(std::ptr::null_mut());
+
+// The "CAT DETECTED" banner, hidden until a cat is detected
+static CAT_BANNER_VIEW: AtomicPtr<c_void> = AtomicPtr::new(std::ptr::null_mut());
+
+// Parental mode: exit attempts must be confirmed with an administrator password
+static REQUIRE_ADMIN_EXIT: AtomicBool = AtomicBool::new(false);
+
+// Global reference to the overlay window (lowered while the auth dialog is shown)
+static OVERLAY_WINDOW: AtomicPtr<c_void> = AtomicPtr::new(std::ptr::null_mut());
+
+// Close button and emergency exit state stored in thread-locals (main thread only)
+thread_local! {
+    static SHIELD_STATE: Cell<ShieldState> = const { Cell::new(ShieldState::Idle) };
+    static MOUSE_DOWN_TIME: Cell<Option<Instant>> = const { Cell::new(None) };
+    static AWAY_NOTE: RefCell<Option<AwayNote>> = const { RefCell::new(None) };
+    // Where the countdown display goes (a `timer` widget in the config file moves it)
+    static TIMER_ANCHOR: Cell<Anchor> = const { Cell::new(Anchor::TopLeft) };
+    static OVERLAY_WIDGETS: RefCell<Vec<(WidgetSpec, Retained<WidgetView>)>> = const { RefCell::new(Vec::new()) };
+    // `[[displays]]` entries, the overlay's opacity without one, whether it
+    // has a `--blur` backdrop, and the overlay windows on the other displays
+    // with their close buttons
+    static DISPLAY_SETTINGS: RefCell<Vec<DisplayConfig>> = const { RefCell::new(Vec::new()) };
+    static OVERLAY_BASE_ALPHA: Cell<CGFloat> = const { Cell::new(OVERLAY_ALPHA) };
+    static OVERLAY_BLUR: Cell<bool> = const { Cell::new(false) };
+    static SECONDARY_OVERLAYS: RefCell<Vec<Retained<NSWindow>>> = const { RefCell::new(Vec::new()) };
+    static SECONDARY_CLOSE_BUTTONS: RefCell<Vec<Retained<CloseButtonView>>> = const { RefCell::new(Vec::new()) };
+    static LAST_WIDGET_REFRESH: Cell<Option<Instant>> = const { Cell::new(None) };
+    // Latest "title — artist" from MediaRemote, and when it was last asked for
+    static NOW_PLAYING: RefCell<Option<String>> = const { RefCell::new(None) };
+    static LAST_NOW_PLAYING_REQUEST: Cell<Option<Instant>> = const { Cell::new(None) };
+    static IS_MOUSE_INSIDE: Cell<bool> = const { Cell::new(false) };
+    static LAST_SIRI_CHECK: Cell<Option<Instant>> = const { Cell::new(None) };
+    static CURRENT_TIMER_INTERVAL: Cell<f64> = const { Cell::new(0.0) };
+    static UNLOCK_METHODS: RefCell<Vec<Box<dyn UnlockMethod>>> = const { RefCell::new(Vec::new()) };
+    static KEY_BURST_DETECTOR: RefCell<BurstDetector> = RefCell::new(BurstDetector::default());
+    static CAT_DETECTOR: RefCell<CatDetector> = RefCell::new(CatDetector::default());
+    static CAT_BANNER_SHOWN_AT: Cell<Option<Instant>> = const { Cell::new(None) };
+    static KEYBOARD_BACKLIGHT: RefCell<Option<KeyboardBacklight>> = const { RefCell::new(None) };
+    static BACKLIGHT_FLASH: Cell<Option<(Instant, f32)>> = const { Cell::new(None) };
+    static LAST_MEOW: Cell<Option<Instant>> = const { Cell::new(None) };
+    static DIM_MODE: Cell<Option<DimMode>> = const { Cell::new(None) };
+    static DIM_APPLIED_ALPHA: Cell<CGFloat> = const { Cell::new(-1.0) };
+    static CELEBRATION_START: Cell<Option<Instant>> = const { Cell::new(None) };
+    static SAVED_BACKLIGHT_LEVEL: Cell<Option<f32>> = const { Cell::new(None) };
+    static SAVED_AUDIO_OUTPUT: Cell<Option<AudioOutput>> = const { Cell::new(None) };
+    static DISPLAY_DIM: RefCell<Option<(DisplayBrightness, f32, f32)>> = const { RefCell::new(None) };
+    static AUTO_EXIT_COUNTDOWN: Cell<Countdown> = const { Cell::new(Countdown { duration_secs: 0, accumulated_secs: 0, running_since: None }) };
+    static COUNT_MODE: Cell<CountMode> = const { Cell::new(CountMode::Awake) };
+    static LOUDNESS_ACTION: Cell<LoudnessAction> = const { Cell::new(LoudnessAction::Arm) };
+    static WARN_ACTIONS: RefCell<Vec<WarnAction>> = const { RefCell::new(Vec::new()) };
+    // Start of a running warning flash and the overlay background to restore afterwards
+    static OVERLAY_FLASH: RefCell<Option<(Instant, Retained<NSColor>)>> = const { RefCell::new(None) };
+    static SCRIPT_HOOKS: RefCell<Option<ScriptHooks>> = const { RefCell::new(None) };
+    static LAUNCH_ACTION: Cell<Option<LaunchAction>> = const { Cell::new(None) };
+    static FRONTMOST_RULES: RefCell<Vec<String>> = const { RefCell::new(Vec::new()) };
+    static ARMED_FOR_FRONTMOST: RefCell<Option<String>> = const { RefCell::new(None) };
+    // Why keyboard blocking is armed in watch mode; it disarms once none are left
+    static ARM_CAUSES: RefCell<BTreeSet<ArmCause>> = const { RefCell::new(BTreeSet::new()) };
+    static FORWARD_RULE: RefCell<Option<ForwardRule>> = const { RefCell::new(None) };
+    static HEALTH_FILE: RefCell<Vec<PathBuf>> = const { RefCell::new(Vec::new()) };
+    static LAST_HEALTH_WRITE: Cell<Option<Instant>> = const { Cell::new(None) };
+    static DETECTION_PLUGINS: RefCell<Vec<DetectionPlugin>> = const { RefCell::new(Vec::new()) };
+    static EVENT_RATES: Cell<EventRates> = const { Cell::new(EventRates { key_presses: 0, keys_seen: 0 }) };
+    static LAST_PLUGIN_SAMPLE: Cell<Option<Instant>> = const { Cell::new(None) };
+    static ANIMATION_TIMER: RefCell<Option<RunLoopTimer>> = const { RefCell::new(None) };
+    // Fires at the next auto-exit checkpoint (warning, expiry, end of celebration)
+    static AUTO_EXIT_TIMER: RefCell<Option<RunLoopTimer>> = const { RefCell::new(None) };
+    // Fires every --remind-every interval
+    static REMINDER_TIMER: RefCell<Option<RunLoopTimer>> = const { RefCell::new(None) };
+    // Finishes termination once the overlay has faded out
+    static FADE_OUT_TIMER: RefCell<Option<RunLoopTimer>> = const { RefCell::new(None) };
+    static SESSION_LABEL: RefCell<Option<String>> = const { RefCell::new(None) };
+    // Menu bar mode: the menu's action target, and the session a preset started
+    static MENU_ACTIONS: RefCell<Option<Retained<MenuActions>>> = const { RefCell::new(None) };
+    // Title and timer of each quick-start item, by tag (no timer runs until unlocked)
+    static MENU_PRESETS: RefCell<Vec<(String, Option<u64>)>> = const { RefCell::new(Vec::new()) };
+    static MENU_BAR_SESSION: RefCell<Option<process::Child>> = const { RefCell::new(None) };
+    static MENU_BAR_POPOVER: RefCell<Option<MenuBarPopover>> = const { RefCell::new(None) };
+    // Paused from the menu bar popover; waking from sleep doesn't resume it
+    static TIMER_PAUSED_BY_USER: Cell<bool> = const { Cell::new(false) };
+    static SUMMARY_OUTPUT: RefCell<Option<SummaryOutput>> = const { RefCell::new(None) };
+    static EXIT_REASON: Cell<Option<ExitReason>> = const { Cell::new(None) };
+    // Held while shielded, released while the lid is closed
+    static SLEEP_ASSERTION: RefCell<Option<SleepAssertion>> = const { RefCell::new(None) };
+    static EVENT_TAP_GUARD: RefCell<Option<EventTapGuard>> = const { RefCell::new(None) };
+    // Keycode of the accessibility shortcut let through, so its key up follows
+    static PASSED_SHORTCUT_KEY: Cell<Option<i64>> = const { Cell::new(None) };
+    // Origin of the last process seen posting events, so the tap rarely looks names up
+    static LAST_EVENT_ORIGIN: Cell<Option<(i64, InputOrigin)>> = const { Cell::new(None) };
+    // The keyboard (IOHIDDevice address and product name) of the most recent key
+    // press, as reported by the IOHIDManager callback
+    static LAST_KEY_DEVICE: RefCell<Option<(usize, String)>> = const { RefCell::new(None) };
+    // Regular (non-modifier) keys held down on built-in and external keyboards, by
+    // HID usage, as reported by the IOHIDManager callback
+    static BUILT_IN_KEYS_DOWN: RefCell<BTreeSet<u32>> = const { RefCell::new(BTreeSet::new()) };
+    static EXTERNAL_KEYS_DOWN: RefCell<BTreeSet<u32>> = const { RefCell::new(BTreeSet::new()) };
+    // Blocked events this session by where they came from
+    static BLOCKED_BY_SOURCE: RefCell<BTreeMap<String, u64>> = const { RefCell::new(BTreeMap::new()) };
+    // Puts the displays back to sleep after something woke them (--screen-off)
+    static DISPLAY_SLEEP_TIMER: RefCell<Option<RunLoopTimer>> = const { RefCell::new(None) };
+    // --on-projector, and the display a pause is waiting on to disconnect
+    static PROJECTOR_ACTION: Cell<Option<ProjectorAction>> = const { Cell::new(None) };
+    static PROJECTOR_DISPLAY: Cell<Option<u32>> = const { Cell::new(None) };
+    // Checks the overlay again once a stage switch has finished animating
+    static STAGE_SETTLE_TIMER: RefCell<Option<RunLoopTimer>> = const { RefCell::new(None) };
+    // Resumes a shield paused for a while (`cat_shield pause 30s`)
+    static SHIELD_RESUME_TIMER: RefCell<Option<RunLoopTimer>> = const { RefCell::new(None) };
+    // A pause waiting for administrator authentication (parental mode), with its length
+    static PENDING_PAUSE: Cell<Option<Option<u64>>> = const { Cell::new(None) };
+    // Disarms blocking when a timed daemon `activate` runs out
+    static DAEMON_DISARM_TIMER: RefCell<Option<RunLoopTimer>> = const { RefCell::new(None) };
+}
+
+/// A GitHub release, as returned by the releases API
+#[derive(Debug, Deserialize)]
+struct Release {
+    tag_name: String,
+    html_url: String,
+    #[serde(default)]
+    assets: Vec<ReleaseAsset>,
+}
+
+/// A downloadable file attached to a release
+#[derive(Debug, Deserialize)]
+struct ReleaseAsset {
+    name: String,
+    browser_download_url: String,
+}
+
+impl Release {
+    fn asset(&self, name: &str) -> Option<&ReleaseAsset> {
+        self.assets.iter().find(|asset| asset.name == name)
+    }
+}
+
+/// Fetch the latest published release from GitHub.
+///
+/// Uses the system `curl` so the app doesn't carry its own HTTP/TLS stack.
+fn fetch_latest_release() -> Result<Release, String> {
+    let output = process::Command::new("curl")
+        .args(["--fail", "--silent", "--show-error", "--location"])
+        .args(["--max-time", &UPDATE_CHECK_TIMEOUT_SECS.to_string()])
+        .args(["--header", "Accept: application/vnd.github+json"])
+        .arg(RELEASES_API_URL)
+        .output()
+        .map_err(|e| format!("Failed to run curl: {}", e))?;
+
+    if !output.status.success() {
+        return Err(format!(
+            "Could not reach GitHub: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+
+    serde_json::from_slice(&output.stdout).map_err(|e| format!("Unexpected response: {}", e))
+}
+
+/// Parse a "1.2.3" or "v1.2.3" version; pre-release suffixes are ignored
+fn parse_version(version: &str) -> Option<(u64, u64, u64)> {
+    let version = version.trim().trim_start_matches('v');
+    let version = version.split(['-', '+']).next()?;
+    let mut parts = version.split('.').map(|part| part.parse::<u64>().ok());
+    let major = parts.next()??;
+    let minor = parts.next().unwrap_or(Some(0))?;
+    let patch = parts.next().unwrap_or(Some(0))?;
+    if parts.next().is_some() {
+        return None;
+    }
+    Some((major, minor, patch))
+}
+
+/// Whether `latest` is a newer version than `current`
+fn is_newer_version(latest: &str, current: &str) -> bool {
+    match (parse_version(latest), parse_version(current)) {
+        (Some(latest), Some(current)) => latest > current,
+        _ => false,
+    }
+}
+
+/// `cat_shield check-update`: report whether a newer release exists
+///
+/// # Returns
+/// The process exit code
+fn run_check_update() -> i32 {
+    let current = env!("CARGO_PKG_VERSION");
+    println!("  🔎 Checking for updates (current version {})...", current);
+
+    match fetch_latest_release() {
+        Ok(release) if is_newer_version(&release.tag_name, current) => {
+            println!("  ⬆️  A newer release is available: {}", release.tag_name);
+            println!("     {}", release.html_url);
+            0
+        }
+        Ok(_) => {
+            println!("  ✓ You're on the latest version");
+            0
+        }
+        Err(e) => {
+            eprintln!("  ✗ {}", e);
+            1
+        }
+    }
+}
+
+// renamex_np(2) for atomically swapping the installed binary or bundle
+extern "C" {
+    fn renamex_np(from: *const std::ffi::c_char, to: *const std::ffi::c_char, flags: u32) -> i32;
+}
+
+/// What `self-update` replaces: the bare binary, or the `.app` bundle it lives in
+#[derive(Debug, PartialEq)]
+enum InstallTarget {
+    Binary(PathBuf),
+    AppBundle(PathBuf),
+}
+
+impl InstallTarget {
+    /// Work out what to replace from the running executable's path
+    fn for_executable(executable: &Path) -> Self {
+        let bundle = executable
+            .parent()
+            .filter(|dir| dir.file_name() == Some("MacOS".as_ref()))
+            .and_then(Path::parent)
+            .filter(|dir| dir.file_name() == Some("Contents".as_ref()))
+            .and_then(Path::parent)
+            .filter(|dir| dir.extension() == Some("app".as_ref()));
+
+        match bundle {
+            Some(bundle) => InstallTarget::AppBundle(bundle.to_path_buf()),
+            None => InstallTarget::Binary(executable.to_path_buf()),
+        }
+    }
+
+    fn path(&self) -> &Path {
+        match self {
+            InstallTarget::Binary(path) | InstallTarget::AppBundle(path) => path,
+        }
+    }
+
+    fn asset_name(&self) -> &'static str {
+        match self {
+            InstallTarget::Binary(_) => BINARY_ASSET_NAME,
+            InstallTarget::AppBundle(_) => APP_ASSET_NAME,
+        }
+    }
+}
+
+/// Extract the hex digest from a `shasum -a 256` line ("<digest>  <file>")
+fn parse_sha256_line(line: &str) -> Option<String> {
+    let digest = line.split_whitespace().next()?;
+    (digest.len() == 64 && digest.chars().all(|c| c.is_ascii_hexdigit()))
+        .then(|| digest.to_ascii_lowercase())
+}
+
+/// Run a command-line tool, returning its stdout or a readable error
+fn run_tool(program: &str, args: &[&OsStr]) -> Result<String, String> {
+    let output = process::Command::new(program)
+        .args(args)
+        .output()
+        .map_err(|e| format!("Failed to run {}: {}", program, e))?;
+    if !output.status.success() {
+        return Err(format!(
+            "{} failed: {}",
+            program,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Download a release asset to `destination`
+fn download_asset(asset: &ReleaseAsset, destination: &Path) -> Result<(), String> {
+    run_tool(
+        "curl",
+        &[
+            "--fail".as_ref(),
+            "--silent".as_ref(),
+            "--show-error".as_ref(),
+            "--location".as_ref(),
+            "--max-time".as_ref(),
+            UPDATE_DOWNLOAD_TIMEOUT_SECS.to_string().as_ref(),
+            "--output".as_ref(),
+            destination.as_os_str(),
+            asset.browser_download_url.as_ref(),
+        ],
+    )
+    .map(|_| ())
+}
+
+/// Extract the designated requirement from `codesign -d -r-` output. An ad-hoc
+/// signature's requirement is only a comment (a hash of that one build), so
+/// it gives `None` like an unsigned copy.
+fn parse_designated_requirement(output: &str) -> Option<String> {
+    output
+        .lines()
+        .find_map(|line| line.strip_prefix("designated => "))
+        .map(|requirement| requirement.trim().to_string())
+        .filter(|requirement| !requirement.is_empty())
+}
+
+/// The designated requirement of a signed copy: which signer a replacement
+/// must come from, or `None` if it isn't signed with a certificate
+fn designated_requirement(path: &Path) -> Option<String> {
+    run_tool(
+        "codesign",
+        &["-d".as_ref(), "-r-".as_ref(), path.as_os_str()],
+    )
+    .ok()
+    .and_then(|output| parse_designated_requirement(&output))
+}
+
+/// Check that a code signature is intact and meets `requirement`
+fn codesign_verify(path: &Path, requirement: &str) -> Result<(), String> {
+    run_tool(
+        "codesign",
+        &[
+            "--verify".as_ref(),
+            "--strict".as_ref(),
+            "--deep".as_ref(),
+            "-R".as_ref(),
+            format!("={}", requirement).as_ref(),
+            path.as_os_str(),
+        ],
+    )
+    .map(|_| ())
+}
+
+/// Atomically exchange two paths (files or directories) on the same volume
+fn swap_paths(a: &Path, b: &Path) -> Result<(), String> {
+    use std::os::unix::ffi::OsStrExt;
+
+    let a = std::ffi::CString::new(a.as_os_str().as_bytes()).map_err(|e| e.to_string())?;
+    let b = std::ffi::CString::new(b.as_os_str().as_bytes()).map_err(|e| e.to_string())?;
+    if unsafe { renamex_np(a.as_ptr(), b.as_ptr(), RENAME_SWAP) } != 0 {
+        return Err(format!(
+            "Could not replace the installed copy: {}",
+            std::io::Error::last_os_error()
+        ));
+    }
+    Ok(())
+}
+
+/// Download, verify, and swap in `release` for `target`, staging in `staging`.
+///
+/// The staging directory sits next to the installed copy so the final swap
+/// never crosses volumes; afterwards it holds the old version.
+fn install_release(
+    release: &Release,
+    target: &InstallTarget,
+    staging: &Path,
+) -> Result<(), String> {
+    // The checksum comes from the same release, so it only shows the download is
+    // intact. The signature, pinned to whoever signed this copy, shows who built it.
+    let requirement = designated_requirement(target.path()).ok_or(
+        "This copy isn't signed with a certificate, so a new version can't be verified. \
+         Update it the way you installed it",
+    )?;
+
+    let asset_name = target.asset_name();
+    let asset = release
+        .asset(asset_name)
+        .ok_or_else(|| format!("Release {} has no {}", release.tag_name, asset_name))?;
+    let checksum_name = format!("{}{}", asset_name, CHECKSUM_SUFFIX);
+    let checksum = release
+        .asset(&checksum_name)
+        .ok_or_else(|| format!("Release {} has no {}", release.tag_name, checksum_name))?;
+
+    let archive = staging.join(asset_name);
+    let checksum_file = staging.join(&checksum_name);
+    println!("  ⬇️  Downloading {}...", asset_name);
+    download_asset(asset, &archive)?;
+    download_asset(checksum, &checksum_file)?;
+
+    let expected = fs::read_to_string(&checksum_file)
+        .ok()
+        .and_then(|contents| parse_sha256_line(&contents))
+        .ok_or("The checksum file is malformed")?;
+    let actual = run_tool(
+        "shasum",
+        &["-a".as_ref(), "256".as_ref(), archive.as_os_str()],
+    )
+    .ok()
+    .and_then(|output| parse_sha256_line(&output))
+    .ok_or("Could not checksum the download")?;
+    if actual != expected {
+        return Err("Checksum mismatch - the download may be corrupt or tampered with".to_string());
+    }
+    println!("  ✓ Checksum verified");
+
+    let extracted = staging.join("extracted");
+    fs::create_dir(&extracted).map_err(|e| e.to_string())?;
+    run_tool(
+        "tar",
+        &[
+            "-xzf".as_ref(),
+            archive.as_os_str(),
+            "-C".as_ref(),
+            extracted.as_os_str(),
+        ],
+    )?;
+
+    let replacement = match target {
+        InstallTarget::Binary(_) => extracted.join("cat_shield"),
+        InstallTarget::AppBundle(_) => fs::read_dir(&extracted)
+            .map_err(|e| e.to_string())?
+            .filter_map(Result::ok)
+            .map(|entry| entry.path())
+            .find(|path| path.extension() == Some("app".as_ref()))
+            .ok_or("The archive contains no .app bundle")?,
+    };
+    if !replacement.exists() {
+        return Err("The archive doesn't contain the cat_shield binary".to_string());
+    }
+
+    codesign_verify(&replacement, &requirement).map_err(|e| {
+        format!(
+            "The new version isn't signed by the same developer as this copy: {}",
+            e
+        )
+    })?;
+    println!("  ✓ Code signature verified");
+
+    swap_paths(&replacement, target.path())
+}
+
+/// `cat_shield self-update`: install the latest release over this copy
+///
+/// # Returns
+/// The process exit code
+fn run_self_update() -> i32 {
+    let current = env!("CARGO_PKG_VERSION");
+    let release = match fetch_latest_release() {
+        Ok(release) => release,
+        Err(e) => {
+            eprintln!("  ✗ {}", e);
+            return 1;
+        }
+    };
+    if !is_newer_version(&release.tag_name, current) {
+        println!("  ✓ Already on the latest version ({})", current);
+        return 0;
+    }
+
+    let executable = match std::env::current_exe().and_then(fs::canonicalize) {
+        Ok(path) => path,
+        Err(e) => {
+            eprintln!("  ✗ Could not locate the running executable: {}", e);
+            return 1;
+        }
+    };
+    let target = InstallTarget::for_executable(&executable);
+    let Some(parent) = target.path().parent() else {
+        eprintln!(
+            "  ✗ Unexpected install location: {}",
+            target.path().display()
+        );
+        return 1;
+    };
+
+    let staging = parent.join(format!(".cat_shield-update-{}", process::id()));
+    if let Err(e) = fs::create_dir(&staging) {
+        eprintln!("  ✗ Cannot write to {}: {}", parent.display(), e);
+        return 1;
+    }
+
+    println!("  ⬆️  Updating {} → {}", current, release.tag_name);
+    let result = install_release(&release, &target, &staging);
+    _ = fs::remove_dir_all(&staging);
+
+    match result {
+        Ok(()) => {
+            println!("  ✓ Updated {}", target.path().display());
+            0
+        }
+        Err(e) => {
+            eprintln!("  ✗ {}", e);
+            1
+        }
+    }
+}
+
+/// Command-line features used this session, for telemetry
+fn feature_flags(args: &Args) -> Vec<&'static str> {
+    [
+        ("timer", args.timer.is_some()),
+        ("preset", args.preset.is_some()),
+        ("dim", args.dim.is_some()),
+        ("count_wall", args.count == CountMode::Wall),
+        ("hide_timer", args.hide_timer),
+        ("away_note", args.away_note.is_some()),
+        ("night", args.night),
+        ("blur", args.blur),
+        ("dim_display", args.dim_display.is_some()),
+        ("warn_action", !args.warn_action.is_empty()),
+        ("remind_every", args.remind_every.is_some()),
+        ("screen_off", args.screen_off),
+        ("no_screensaver", args.no_screensaver),
+        ("block_launches", args.block_launches.is_some()),
+        ("forward_to", args.forward_to.is_some()),
+        ("allow_scroll", args.allow_scroll),
+        ("allow_universal_control", args.allow_universal_control),
+        ("allow_remote_input", args.allow_remote_input),
+        (
+            "allow_accessibility_shortcuts",
+            args.allow_accessibility_shortcuts,
+        ),
+        ("capture_display", args.capture_display),
+        ("no_overlay", args.no_overlay),
+        ("keyboard_only", args.keyboard_only),
+        ("stand_down_when_locked", args.stand_down_when_locked),
+        ("on_projector", args.on_projector.is_some()),
+        ("backlight_off", args.backlight_off),
+        ("mute", args.mute),
+        ("lock_volume", args.lock_volume),
+        ("exit_key", args.exit_key.is_some()),
+        (
+            "exit_key_hold",
+            args.exit_key_hold != DEFAULT_EXIT_KEY_HOLD_SECS,
+        ),
+        ("emergency_exit", args.emergency_exit),
+        ("unlock_gesture", args.unlock_gesture.is_some()),
+        ("knock", args.knock.is_some()),
+        ("keypad_code", args.keypad_code.is_some()),
+        ("require_admin_exit", args.require_admin_exit),
+        ("internal_keyboard_only", args.internal_keyboard_only),
+        ("backlight_deterrent", args.backlight_deterrent),
+        ("meow_detect", args.meow_detect),
+        ("show_stats", args.show_stats),
+        ("label", args.label.is_some()),
+        ("health_file", args.health_file.is_some()),
+        (
+            "session_summary",
+            args.summary_json || args.summary_file.is_some(),
+        ),
+        ("reduced_fps", args.fps != DEFAULT_FPS),
+    ]
+    .into_iter()
+    .filter(|(_, used)| *used)
+    .map(|(name, _)| name)
+    .collect()
+}
+
+/// Count a starting session for telemetry (only when opted in)
+fn telemetry_session_started(args: &Args) {
+    if Config::load().telemetry.active_endpoint().is_none() {
+        return;
+    }
+
+    let mut counters = TelemetryCounters::load();
+    counters.begin_session(&feature_flags(args));
+    if let Err(e) = counters.save() {
+        eprintln!("  ⚠️  Failed to save telemetry counters: {}", e);
+    }
+}
+
+/// Count a clean exit and try to send the pending report (only when opted in)
+fn telemetry_session_finished() {
+    let config = Config::load();
+    let Some(endpoint) = config.telemetry.active_endpoint() else {
+        return;
+    };
+
+    let mut counters = TelemetryCounters::load();
+    counters.end_session();
+    // Unsent counters are kept and go out with the next report
+    if send_telemetry(endpoint, &counters.report()).is_ok() {
+        counters.reset_reported();
+    }
+    if let Err(e) = counters.save() {
+        eprintln!("  ⚠️  Failed to save telemetry counters: {}", e);
+    }
+}
+
+/// POST a report as JSON to the configured endpoint
+fn send_telemetry(endpoint: &str, report: &TelemetryReport) -> Result<(), String> {
+    let body = serde_json::to_string(report).map_err(|e| e.to_string())?;
+    let status = process::Command::new("curl")
+        .args(["--fail", "--silent", "--output", "/dev/null"])
+        .args(["--max-time", &TELEMETRY_TIMEOUT_SECS.to_string()])
+        .args(["--header", "Content-Type: application/json"])
+        .args(["--data-binary", &body])
+        .arg(endpoint)
+        .status()
+        .map_err(|e| e.to_string())?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(format!("curl exited with {}", status))
+    }
+}
+
+/// Set `telemetry.enabled = false` in the config file, keeping its formatting and comments
+fn disable_telemetry_in_config() -> Result<PathBuf, String> {
+    let path = Config::config_path().ok_or("No config directory available")?;
+    let contents = match fs::read_to_string(&path) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+        Err(e) => return Err(e.to_string()),
+    };
+
+    let mut document: toml_edit::DocumentMut = contents
+        .parse()
+        .map_err(|e| format!("Failed to parse config file: {}", e))?;
+    document.entry("telemetry").or_insert(toml_edit::table())["enabled"] = toml_edit::value(false);
+
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    }
+    fs::write(&path, document.to_string()).map_err(|e| e.to_string())?;
+    Ok(path)
+}
+
+/// `cat_shield telemetry status|off`
+///
+/// # Returns
+/// The process exit code
+fn run_telemetry(action: TelemetryAction) -> i32 {
+    match action {
+        TelemetryAction::Status => {
+            let config = Config::load();
+            match config.telemetry.active_endpoint() {
+                Some(endpoint) => println!("  📡 Telemetry is ON, reporting to {}", endpoint),
+                None if config.telemetry.enabled && config.telemetry.endpoint.is_some() => {
+                    println!("  📡 Telemetry is OFF (the endpoint must be an https URL)")
+                }
+                None => {
+                    println!("  📡 Telemetry is OFF (opt in with [telemetry] in the config file)")
+                }
+            }
+
+            let counters = TelemetryCounters::load();
+            if counters.sessions > 0 {
+                let report = serde_json::to_string_pretty(&counters.report()).unwrap_or_default();
+                println!();
+                println!("  Pending report (this is everything that gets sent):");
+                for line in report.lines() {
+                    println!("     {}", line);
+                }
+            }
+            0
+        }
+        TelemetryAction::Off => {
+            let path = match disable_telemetry_in_config() {
+                Ok(path) => path,
+                Err(e) => {
+                    eprintln!("  ✗ {}", e);
+                    return 1;
+                }
+            };
+            if let Some(counters) = TelemetryCounters::counters_path() {
+                _ = fs::remove_file(counters);
+            }
+            println!("  ✓ Telemetry turned off in {}", path.display());
+            println!("  ✓ Pending counters discarded");
+            0
+        }
+    }
+}
+
+/// `cat_shield config validate`
+///
+/// # Returns
+/// The process exit code: 1 if the config file has problems
+fn run_config(action: ConfigAction) -> i32 {
+    match action {
+        ConfigAction::Validate => {
+            let Some(path) = Config::config_path() else {
+                eprintln!("  ✗ No config directory available");
+                return 1;
+            };
+            if !path.exists() {
+                println!("  ✓ No config file ({} would be used)", path.display());
+                return 0;
+            }
+            let contents = match fs::read_to_string(&path) {
+                Ok(contents) => contents,
+                Err(e) => {
+                    eprintln!("  ✗ Failed to read {}: {}", path.display(), e);
+                    return 1;
+                }
+            };
+
+            let problems = validate_config(&contents);
+            if problems.is_empty() {
+                println!("  ✓ Config file OK: {}", path.display());
+                return 0;
+            }
+            eprintln!("  ✗ Config file {} is invalid:", path.display());
+            for problem in &problems {
+                eprintln!("      {}", problem);
+            }
+            1
+        }
+    }
+}
+
+/// `cat_shield doctor`: report permissions and config problems
+///
+/// # Returns
+/// The process exit code: 1 if something would stop the shield from working
+fn run_doctor() -> i32 {
+    println!();
+    println!("  🐱 CAT SHIELD DOCTOR 🩺");
+    println!("  ════════════════════════════════════════");
+    let mut healthy = true;
+
+    if check_accessibility() {
+        println!("  ✓ Accessibility permission granted");
+    } else {
+        eprintln!("  ✗ Accessibility permission missing - input can't be blocked");
+        eprintln!("      System Settings → Privacy & Security → Accessibility");
+        healthy = false;
+    }
+
+    if unsafe { IOHIDCheckAccess(K_IOHID_REQUEST_TYPE_LISTEN_EVENT) } == K_IOHID_ACCESS_TYPE_GRANTED
+    {
+        println!("  ✓ Input Monitoring permission granted");
+    } else {
+        // Only --internal-keyboard-only and per-keyboard statistics read key presses through IOKit
+        eprintln!(
+            "  ⚠️  Input Monitoring permission not granted (needed for --internal-keyboard-only"
+        );
+        eprintln!("      and to tell keyboards apart in stats)");
+    }
+
+    match Config::config_path() {
+        Some(path) if path.exists() => match fs::read_to_string(&path)
+            .map_err(|e| vec![e.to_string()])
+            .map(|contents| validate_config(&contents))
+        {
+            Ok(problems) if problems.is_empty() => {
+                println!("  ✓ Config file OK: {}", path.display())
+            }
+            Ok(problems) | Err(problems) => {
+                eprintln!("  ✗ Config file {} is invalid:", path.display());
+                for problem in &problems {
+                    eprintln!("      {}", problem);
+                }
+                healthy = false;
+            }
+        },
+        Some(path) => println!("  ✓ No config file ({} would be used)", path.display()),
+        None => eprintln!("  ⚠️  No config directory available; defaults will be used"),
+    }
+
+    warn_about_other_sessions();
+
+    println!();
+    if healthy {
+        println!("  ✓ Cat Shield is ready");
+        0
+    } else {
+        eprintln!("  ✗ Fix the problems above before starting the shield");
+        1
+    }
+}
+
+/// A connected display, for the `--dry-run` plan
+struct PlannedDisplay {
+    identity: DisplayIdentity,
+    main: bool,
+}
+
+/// What the shield would do with these options, one line per item, for
+/// `--dry-run`. `ends_at` is when the timer would run out, as a clock time;
+/// `menu_bar` is set when no session would start until a preset is picked.
+fn dry_run_plan(
+    args: &Args,
+    exit_key: &ExitKey,
+    config: &Config,
+    displays: &[PlannedDisplay],
+    ends_at: Option<&str>,
+    menu_bar: bool,
+) -> Vec<String> {
+    let mut plan = Vec::new();
+
+    plan.push(if menu_bar {
+        "Mode: menu bar app - a session like the one below starts from a 🐱 menu preset".to_string()
+    } else if args.watch {
+        "Mode: watch - no overlay; keyboard blocking arms when an external keyboard disconnects"
+            .to_string()
+    } else {
+        "Mode: shield session".to_string()
+    });
+
+    if args.watch || args.no_overlay {
+        plan.push("Screens: no overlay, every display stays visible".to_string());
+    } else {
+        let base_alpha = if args.blur || args.dim.is_some() {
+            None
+        } else if args.night {
+            Some(NIGHT_OVERLAY_ALPHA)
+        } else {
+            Some(OVERLAY_ALPHA)
+        };
+        let widgets: Vec<&str> = config
+            .overlay
+            .widgets
+            .iter()
+            .filter(|widget| widget.kind != WidgetKind::Timer)
+            .map(|widget| widget.kind.name())
+            .collect();
+        for display in displays {
+            let settings = display_settings(&config.displays, &display.identity);
+            let opacity = settings
+                .and_then(|settings| settings.opacity)
+                .or(base_alpha);
+            let mut parts = vec![if display.main {
+                "overlay".to_string()
+            } else {
+                "cover window".to_string()
+            }];
+            match opacity {
+                Some(opacity) => parts.push(format!("{:.0}% opacity", opacity * 100.0)),
+                None if args.blur => parts.push("blurred backdrop".to_string()),
+                None => parts.push("dims as time runs out".to_string()),
+            }
+            if display.main {
+                if args.timer.is_some() && !args.hide_timer {
+                    parts.push("countdown".to_string());
+                }
+                let show_widgets = settings.and_then(|settings| settings.widgets) != Some(false);
+                if show_widgets && !widgets.is_empty() {
+                    parts.push(format!("widgets: {}", widgets.join(", ")));
+                }
+                if args.away_note.is_some() {
+                    parts.push("away note".to_string());
+                }
+            }
+            if settings.is_some_and(|settings| settings.passthrough) {
+                parts.push("clicks pass through".to_string());
+            }
+            plan.push(format!(
+                "Screen \"{}\"{}: {}",
+                display.identity.name,
+                if display.main { " (main)" } else { "" },
+                parts.join(", ")
+            ));
+        }
+    }
+
+    plan.push(if args.keyboard_only {
+        "Blocking: keyboard only; the mouse and trackpad stay usable".to_string()
+    } else if args.watch {
+        "Blocking: keyboard, while armed".to_string()
+    } else {
+        "Blocking: keyboard, clicks, scrolling, and trackpad gestures".to_string()
+    });
+    let mut allowed = Vec::new();
+    if args.allow_scroll {
+        allowed.push("scrolling and swipes".to_string());
+    }
+    if args.allow_universal_control {
+        allowed.push("Universal Control".to_string());
+    }
+    if args.allow_remote_input {
+        allowed.push("remote sessions".to_string());
+    }
+    if args.allow_accessibility_shortcuts {
+        allowed.push("accessibility shortcuts".to_string());
+    }
+    if let Some(app) = &args.forward_to {
+        allowed.push(format!("{} key(s) to {}", args.forward_keys.len(), app));
+    }
+    if !allowed.is_empty() {
+        plan.push(format!("Let through: {}", allowed.join(", ")));
+    }
+
+    match args.timer {
+        Some(secs) => {
+            let count = match args.count {
+                CountMode::Awake => "awake time",
+                CountMode::Wall => "wall-clock time",
+            };
+            let mut timer = format!("Timer: {}, counting {}", format_duration(secs), count);
+            if let Some(ends_at) = ends_at {
+                timer.push_str(&format!(", ends around {}", ends_at));
+            }
+            plan.push(timer);
+            if !args.warn_action.is_empty() {
+                let actions: Vec<&str> = args.warn_action.iter().map(|a| a.name()).collect();
+                plan.push(format!(
+                    "Warning: {} before the end ({})",
+                    format_duration(WARNING_SECONDS),
+                    actions.join(", ")
+                ));
+            }
+            if let Some(every) = args.remind_every {
+                plan.push(format!("Reminders: every {}", format_duration(every)));
+            }
+        }
+        None if args.watch => {}
+        None => plan.push("Timer: none - shielded until unlocked".to_string()),
+    }
+
+    let mut unlock = vec![exit_key_instruction(
+        &exit_key.display_name,
+        args.exit_key_hold,
+    )];
+    if !args.watch && !args.no_overlay {
+        unlock.push(format!("hold the close button for {}s", HOLD_DURATION_SECS));
+    }
+    if let Some(shape) = args.unlock_gesture {
+        unlock.push(format!("draw a {}", shape.name()));
+    }
+    if let Some(pattern) = &args.knock {
+        unlock.push(format!("knock \"{}\"", pattern));
+    }
+    if args.keypad_code.is_some() {
+        unlock.push("keypad code".to_string());
+    }
+    if args.emergency_exit {
+        unlock.push(format!("Caps Lock {} times", EMERGENCY_EXIT_PRESSES));
+    }
+    plan.push(format!("Unlock: {}", unlock.join("; ")));
+    if args.require_admin_exit {
+        plan.push("Unlock: administrator authentication required".to_string());
+    }
+    if args.internal_keyboard_only {
+        plan.push("Unlock: exit key from the built-in keyboard only".to_string());
+    }
+
+    if let Some(path) = &args.health_file {
+        plan.push(format!("Health file: {}", path.display()));
+    }
+    if let Some(path) = &args.summary_file {
+        plan.push(format!("Session summary: {}", path.display()));
+    } else if args.summary_json {
+        plan.push("Session summary: printed as JSON".to_string());
+    }
+
+    plan
+}
+
+/// `--dry-run`: check everything a session needs, print the plan, and exit
+///
+/// # Returns
+/// The process exit code: 1 if the session could not start as planned
+fn run_dry_run(
+    args: &Args,
+    exit_key: &ExitKey,
+    config: &Config,
+    menu_bar: bool,
+    mtm: MainThreadMarker,
+) -> i32 {
+    println!();
+    println!("  🐱 CAT SHIELD DRY RUN 🧪");
+    println!("  ════════════════════════════════════════");
+    let mut ready = true;
+
+    if check_accessibility() {
+        println!("  ✓ Accessibility permission granted");
+    } else {
+        eprintln!("  ✗ Accessibility permission missing - input can't be blocked");
+        ready = false;
+    }
+    if args.internal_keyboard_only
+        && unsafe { IOHIDCheckAccess(K_IOHID_REQUEST_TYPE_LISTEN_EVENT) }
+            != K_IOHID_ACCESS_TYPE_GRANTED
+    {
+        eprintln!("  ✗ Input Monitoring permission missing - needed for --internal-keyboard-only");
+        ready = false;
+    }
+    if let Some(path) = Config::config_path().filter(|path| path.exists()) {
+        let problems = fs::read_to_string(&path)
+            .map(|contents| validate_config(&contents))
+            .unwrap_or_else(|e| vec![e.to_string()]);
+        if problems.is_empty() {
+            println!("  ✓ Config file OK: {}", path.display());
+        } else {
+            eprintln!("  ✗ Config file {} is invalid:", path.display());
+            for problem in &problems {
+                eprintln!("      {}", problem);
+            }
+            ready = false;
+        }
+    }
+    for path in [&args.health_file, &args.summary_file]
+        .into_iter()
+        .flatten()
+    {
+        let parent = path
+            .parent()
+            .filter(|parent| !parent.as_os_str().is_empty())
+            .unwrap_or(Path::new("."));
+        if !parent.is_dir() {
+            eprintln!("  ✗ {}: directory does not exist", path.display());
+            ready = false;
+        }
+    }
+    if !menu_bar {
+        if let Ok(report) = running_session() {
+            eprintln!(
+                "  ✗ Cat Shield is already running (pid {}) - a second one won't start",
+                report.pid
+            );
+            ready = false;
+        }
+    }
+
+    let main_frame = NSScreen::mainScreen(mtm).map(|screen| screen.frame());
+    let displays: Vec<PlannedDisplay> = NSScreen::screens(mtm)
+        .iter()
+        .map(|screen| PlannedDisplay {
+            identity: DisplayIdentity::of(&screen),
+            main: Some(screen.frame()) == main_frame,
+        })
+        .collect();
+    let ends_at = args.timer.map(|secs| {
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+        format_clock_time(now + secs)
+    });
+
+    println!();
+    for line in dry_run_plan(
+        args,
+        exit_key,
+        config,
+        &displays,
+        ends_at.as_deref(),
+        menu_bar,
+    ) {
+        println!("  {}", line);
+    }
+    println!();
+    if ready {
+        println!("  ✓ Dry run: nothing was started");
+        0
+    } else {
+        eprintln!("  ✗ Dry run: the shield would not start as planned");
+        1
+    }
+}
+
+/// `cat_shield install`: link the running executable into `dir` so it is on the PATH
+///
+/// # Returns
+/// The process exit code
+fn run_install(dir: &Path) -> i32 {
+    let executable = match std::env::current_exe().and_then(fs::canonicalize) {
+        Ok(path) => path,
+        Err(e) => {
+            eprintln!("  ✗ Could not locate the running executable: {}", e);
+            return 1;
+        }
+    };
+    let link = dir.join("cat_shield");
+
+    match fs::symlink_metadata(&link) {
+        Ok(metadata) if metadata.file_type().is_symlink() => {
+            if fs::read_link(&link).is_ok_and(|target| target == executable) {
+                println!("  ✓ Already installed: {}", link.display());
+                return 0;
+            }
+            // A link to an older copy; replace it
+            if let Err(e) = fs::remove_file(&link) {
+                eprintln!("  ✗ Could not replace {}: {}", link.display(), e);
+                return 1;
+            }
+        }
+        Ok(_) => {
+            eprintln!(
+                "  ✗ {} already exists and is not a link; remove it or use --dir",
+                link.display()
+            );
+            return 1;
+        }
+        Err(_) => {}
+    }
+
+    if let Err(e) =
+        fs::create_dir_all(dir).and_then(|()| std::os::unix::fs::symlink(&executable, &link))
+    {
+        eprintln!("  ✗ Could not create {}: {}", link.display(), e);
+        if e.kind() == std::io::ErrorKind::PermissionDenied {
+            eprintln!("      Try again with sudo, or pick a directory you own with --dir");
+        }
+        return 1;
+    }
+    println!(
+        "  ✓ Installed: {} → {}",
+        link.display(),
+        executable.display()
+    );
+
+    let on_path = std::env::var_os("PATH")
+        .is_some_and(|path| std::env::split_paths(&path).any(|entry| entry == dir));
+    if !on_path {
+        eprintln!("  ⚠️  {} is not on your PATH", dir.display());
+    }
+    0
+}
+
+/// Fade the overlay out, then ask NSApplication to terminate (or, embedded, to
+/// stop its event loop), which ends `app.run()` and runs cleanup. Only called
+/// when entering `ShieldState::Exiting`.
+fn terminate_app() {
+    // Anything printed from here on (stats, cat attribution) belongs on the normal screen
+    leave_status_screen();
+    // The last report says "exiting", so monitors can tell a clean exit from a crash
+    write_health_file();
+
+    // Let go of the keyboard first: the telemetry report sent by
+    // `finish_session` can wait on the network for a few seconds.
+    // `terminate` exits the process without returning, so nothing after
+    // `app.run()` in main runs and the guards must be dropped here.
+    release_event_tap();
+    release_sleep_assertion();
+
+    // Uncapture first so the desktop, not a black screen, fades in behind the overlay
+    release_displays();
+    if fade_out_overlay() {
+        let timer = RunLoopTimer::once(SHIELD_FADE_SECS, fade_out_timer_callback);
+        if timer.is_some() {
+            FADE_OUT_TIMER.with(|slot| slot.replace(timer));
+            return;
+        }
+    }
+    finish_termination();
+}
+
+/// Second half of `terminate_app`, once the overlay has faded out
+fn finish_termination() {
+    finish_session();
+    remove_session_file();
+    remove_control_socket();
+    restore_system_state();
+
+    if let Some(mtm) = MainThreadMarker::new() {
+        let app = NSApplication::sharedApplication(mtm);
+        if EMBEDDED.load(Ordering::SeqCst) {
+            stop_event_loop(&app);
+        } else {
+            app.terminate(None);
+        }
+    }
+}
+
+/// End `app.run()` without ending the process. `stop` only takes effect once
+/// the loop has handled another event, so post one to wake it.
+fn stop_event_loop(app: &NSApplication) {
+    app.stop(None);
+    let wake = NSEvent::otherEventWithType_location_modifierFlags_timestamp_windowNumber_context_subtype_data1_data2(
+        NSEventType::ApplicationDefined,
+        CGPoint::new(0.0, 0.0),
+        NSEventModifierFlags::empty(),
+        0.0,
+        0,
+        None,
+        0,
+        0,
+        0,
+    );
+    if let Some(wake) = wake {
+        app.postEvent_atStart(&wake, true);
+    }
+}
+
+/// Take the overlay windows down for good once `app.run()` has returned to an
+/// embedding app. The windows are released when `launch` returns, so nothing
+/// may reach them through the global pointers afterwards.
+fn close_overlay_windows() {
+    for_each_overlay_window(|window| window.orderOut(None));
+    for view in [
+        &OVERLAY_WINDOW,
+        &CLOSE_BUTTON_VIEW,
+        &TIMER_DISPLAY_VIEW,
+        &AWAY_NOTE_VIEW,
+        &CAT_BANNER_VIEW,
+    ] {
+        view.store(std::ptr::null_mut(), Ordering::SeqCst);
+    }
+    SECONDARY_CLOSE_BUTTONS.with(|buttons| buttons.borrow_mut().clear());
+    SECONDARY_OVERLAYS.with(|windows| windows.borrow_mut().clear());
+}
+
+/// Animate the window's opacity to `alpha` over `SHIELD_FADE_SECS`. Showing
+/// eases out, so the shield is up almost at once, and hiding eases in, so the
+/// start of the fade is a visible cue that the shield is going away.
+///
+/// # Returns
+/// `false` if the change was applied at once because Reduce Motion is on
+fn fade_window(window: &NSWindow, alpha: CGFloat) -> bool {
+    if NSWorkspace::sharedWorkspace().accessibilityDisplayShouldReduceMotion() {
+        window.setAlphaValue(alpha);
+        return false;
+    }
+
+    let timing = if alpha > window.alphaValue() {
+        unsafe { kCAMediaTimingFunctionEaseOut }
+    } else {
+        unsafe { kCAMediaTimingFunctionEaseIn }
+    };
+
+    NSAnimationContext::beginGrouping();
+    let context = NSAnimationContext::currentContext();
+    context.setDuration(SHIELD_FADE_SECS);
+    context.setTimingFunction(Some(&CAMediaTimingFunction::functionWithName(timing)));
+    window.animator().setAlphaValue(alpha);
+    NSAnimationContext::endGrouping();
+    true
+}
+
+/// Start fading the overlay out on exit.
+///
+/// # Returns
+/// `true` if a fade is running and termination should wait for it
+fn fade_out_overlay() -> bool {
+    let window_ptr = OVERLAY_WINDOW.load(Ordering::SeqCst);
+    if window_ptr.is_null() || MainThreadMarker::new().is_none() {
+        return false;
+    }
+    let mut fading = false;
+    for_each_overlay_window(|window| fading |= fade_window(window, 0.0));
+    fading
+}
+
+/// The exit fade finished
+unsafe extern "C-unwind" fn fade_out_timer_callback(
+    _timer: *mut CFRunLoopTimer,
+    _info: *mut c_void,
+) {
+    catch_ffi_panic(finish_termination)
+}
+
+/// `cat_shield blame <cat>`: reassign the last session's blocked presses
+///
+/// # Returns
+/// The process exit code
+fn run_blame(cat: &str) -> i32 {
+    let config = Config::load();
+    let Some(profile) = config
+        .cats
+        .iter()
+        .find(|profile| profile.name.eq_ignore_ascii_case(cat))
+    else {
+        eprintln!(
+            "  ✗ Unknown cat '{}'. Define it under [[cats]] in the config file",
+            cat
+        );
+        return 1;
+    };
+
+    let mut stats = Stats::load();
+    match stats.blame_last_session(&profile.name) {
+        Ok(blocked) => {
+            if let Err(e) = stats.save() {
+                eprintln!("  ✗ Failed to save stats: {}", e);
+                return 1;
+            }
+            println!(
+                "  😼 {} blocked key presses blamed on {}",
+                blocked, profile.name
+            );
+            0
+        }
+        Err(e) => {
+            eprintln!("  ✗ {}", e);
+            1
+        }
+    }
+}
+
+/// Undo system changes made for the session (display brightness, ...).
+/// Safe to call more than once.
+fn restore_system_state() {
+    release_displays();
+    wake_displays();
+    restore_display_brightness();
+    restore_keyboard_backlight();
+    restore_audio();
+}
+
+// Monotonic clock that includes time asleep (mach_continuous_time)
+extern "C" {
+    fn clock_gettime_nsec_np(clock_id: u32) -> u64;
+}
+
+const CLOCK_MONOTONIC: u32 = 6;
+
+// Login records, for finding other users logged in with fast user switching
+#[repr(C)]
+struct Utmpx {
+    ut_user: [u8; 256],
+    ut_id: [u8; 4],
+    ut_line: [u8; 32],
+    ut_pid: i32,
+    ut_type: i16,
+    ut_tv: [i64; 2], // struct timeval
+    ut_host: [u8; 256],
+    ut_pad: [u32; 16],
+}
+
+extern "C" {
+    fn setutxent();
+    fn getutxent() -> *const Utmpx;
+    fn endutxent();
+}
+
+const UTMPX_USER_PROCESS: i16 = 7;
+
+// Process names, for telling which process posted an input event
+extern "C" {
+    fn proc_name(pid: i32, buffer: *mut c_void, buffer_size: u32) -> i32;
+}
+
+const PROC_NAME_MAX: usize = 256;
+
+// POSIX signal handling so Ctrl+C still restores system state
+extern "C" {
+    fn signal(signum: i32, handler: extern "C" fn(i32)) -> usize;
+}
+
+const SIGINT: i32 = 2;
+const SIGTERM: i32 = 15;
+
+// Set by the signal handler, acted on by the animation timer
+static TERMINATION_REQUESTED: AtomicBool = AtomicBool::new(false);
+
+extern "C" fn handle_termination_signal(_signum: i32) {
+    // Only async-signal-safe work here
+    TERMINATION_REQUESTED.store(true, Ordering::SeqCst);
+}
+
+/// Undo what makes a panic dangerous: a process that dies with the tap enabled
+/// or the overlay up leaves the machine blocked with nothing left to unlock it.
+/// Installed as the panic hook, so it runs before unwinding or aborting.
+fn restore_after_panic() {
+    let tap = EVENT_TAP.load(Ordering::SeqCst);
+    if !tap.is_null() {
+        unsafe { CGEventTapEnable(tap, false) };
+    }
+
+    // A captured display would keep showing the (frozen) shield after we're gone
+    release_displays();
+
+    // A flash or --backlight-off would otherwise leave the keyboard lit or dark.
+    // Not `restore_keyboard_backlight`: the panic may have happened mid-borrow
+    if let Some(level) = take_backlight_restore_level() {
+        KEYBOARD_BACKLIGHT.with(|backlight| {
+            if let Ok(backlight) = backlight.try_borrow() {
+                if let Some(backlight) = backlight.as_ref() {
+                    backlight.set_brightness(level);
+                }
+            }
+        });
+    }
+
+    // Not `release_sleep_assertion`: the panic may have happened mid-borrow
+    SLEEP_ASSERTION.with(|assertion| {
+        if let Ok(mut assertion) = assertion.try_borrow_mut() {
+            assertion.take();
+        }
+    });
+
+    // AppKit can only be touched from the main thread
+    let window_ptr = OVERLAY_WINDOW.load(Ordering::SeqCst);
+    if !window_ptr.is_null() && MainThreadMarker::new().is_some() {
+        let window: &NSWindow = unsafe { &*(window_ptr as *const NSWindow) };
+        window.orderOut(None);
+    }
+
+    // Otherwise the panic message is printed to the alternate screen and lost
+    leave_status_screen();
+}
+
+/// Restore the system on panic, then report it as usual
+fn install_panic_hook() {
+    let default_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        restore_after_panic();
+        log_error(&format!("Panicked: {}", info));
+        default_hook(info);
+    }));
+}
+
+/// Run the body of a callback invoked from C. Unwinding into CoreFoundation or
+/// AppKit frames is undefined behavior, so a panic aborts here instead (the
+/// panic hook has already released the keyboard).
+fn catch_ffi_panic<R>(f: impl FnOnce() -> R) -> R {
+    std::panic::catch_unwind(std::panic::AssertUnwindSafe(f)).unwrap_or_else(|_| process::abort())
+}
+
+/// Route Ctrl+C and `kill` through `terminate_app` (via the animation timer)
+fn install_signal_handlers() {
+    unsafe {
+        signal(SIGINT, handle_termination_signal);
+        signal(SIGTERM, handle_termination_signal);
+    }
+}
+
+/// Mark the start of a shield session for statistics
+fn start_session() {
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs();
+    SESSION_START.store(now, Ordering::SeqCst);
+}
+
+/// Record the current shield session in the persistent statistics (once)
+fn finish_session() {
+    let start = SESSION_START.swap(0, Ordering::SeqCst);
+    if start == 0 {
+        return;
+    }
+
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs();
+    let blocked = BLOCKED_KEY_PRESSES.load(Ordering::SeqCst);
+    let mut stats = Stats::load();
+    stats.record_session(now.saturating_sub(start), blocked);
+    stats.total_cat_detections += CAT_DETECTIONS.load(Ordering::SeqCst);
+    let sources = BLOCKED_BY_SOURCE.with(|sources| sources.borrow().clone());
+    stats.record_sources(&sources);
+    if let Some(&(source, count, percent)) = source_breakdown(&sources).first() {
+        println!(
+            "  ⌨️  {}% of blocked events came from {} ({} of {})",
+            percent,
+            source,
+            count,
+            sources.values().sum::<u64>()
+        );
+        let by_source: Vec<String> = source_breakdown(&sources)
+            .iter()
+            .map(|(source, count, _)| format!("{} {}", source, count))
+            .collect();
+        log_event(&format!(
+            "Blocked events by source: {}",
+            by_source.join(", ")
+        ));
+    }
+    if let Some(label) = SESSION_LABEL.with(|label| label.borrow().clone()) {
+        stats.record_label(&label, now.saturating_sub(start), blocked);
+    }
+
+    // An embedding app's sessions aren't attributed to cats or reported
+    let embedded = EMBEDDED.load(Ordering::SeqCst);
+    let cats = if embedded {
+        Vec::new()
+    } else {
+        Config::load().cats
+    };
+    if blocked > 0 && !cats.is_empty() {
+        let blocked_by_hour: [u64; 24] =
+            std::array::from_fn(|hour| BLOCKED_BY_HOUR[hour].load(Ordering::SeqCst));
+        let attribution = attribute_by_schedule(&cats, &blocked_by_hour);
+        for (cat, count) in &attribution {
+            println!("  🐾 {} blocked key presses attributed to {}", count, cat);
+        }
+        println!("     Wrong cat? Run: cat_shield blame <name>");
+        stats.attribute_last_session(attribution);
+    }
+
+    if let Err(e) = stats.save() {
+        eprintln!("  ⚠️  Failed to save stats: {}", e);
+    }
+
+    if !embedded {
+        telemetry_session_finished();
+    }
+
+    if SHOW_STATS_ON_EXIT.load(Ordering::SeqCst) {
+        println!();
+        println!("  📊 Lifetime stats");
+        stats.print();
+    }
+
+    emit_session_summary(start, now);
+}
+
+/// Why a session ended, for the session summary
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ExitReason {
+    /// An unlock method was satisfied
+    Unlocked,
+    /// The auto-exit timer ran out
+    TimerExpired,
+    /// Ctrl+C or `kill`
+    Interrupted,
+}
+
+impl ExitReason {
+    /// Snake-case name for the summary
+    fn name(self) -> &'static str {
+        match self {
+            ExitReason::Unlocked => "unlocked",
+            ExitReason::TimerExpired => "timer_expired",
+            ExitReason::Interrupted => "interrupted",
+        }
+    }
+}
+
+/// Where `--summary-json` / `--summary-file` sends the session summary
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum SummaryOutput {
+    Stdout,
+    File(PathBuf),
+}
+
+/// JSON document emitted when a session ends. Times are UNIX seconds;
+/// `exit_reason` is null if the app was quit some other way.
+#[derive(Debug, Serialize)]
+struct SessionSummary {
+    exit_reason: Option<&'static str>,
+    started: u64,
+    ended: u64,
+    duration_secs: u64,
+    blocked_key_presses: u64,
+    last_blocked: Option<u64>,
+    cat_detections: u64,
+    blocked_by_source: BTreeMap<String, u64>,
+    tap_incidents: u64,
+    label: Option<String>,
+}
+
+/// Emit the session summary, if one was asked for
+fn emit_session_summary(started: u64, ended: u64) {
+    let Some(output) = SUMMARY_OUTPUT.with(|output| output.borrow_mut().take()) else {
+        return;
+    };
+
+    let summary = SessionSummary {
+        exit_reason: EXIT_REASON
+            .with(|reason| reason.get())
+            .map(ExitReason::name),
+        started,
+        ended,
+        duration_secs: ended.saturating_sub(started),
+        blocked_key_presses: BLOCKED_KEY_PRESSES.load(Ordering::SeqCst),
+        last_blocked: Some(LAST_BLOCKED_AT.load(Ordering::SeqCst)).filter(|&at| at > 0),
+        cat_detections: CAT_DETECTIONS.load(Ordering::SeqCst),
+        blocked_by_source: BLOCKED_BY_SOURCE.with(|sources| sources.borrow().clone()),
+        tap_incidents: TAP_INCIDENTS.load(Ordering::SeqCst),
+        label: SESSION_LABEL.with(|label| label.borrow().clone()),
+    };
+    let json = match serde_json::to_string(&summary) {
+        Ok(json) => json,
+        Err(e) => {
+            eprintln!("  ⚠️  Failed to build session summary: {}", e);
+            return;
+        }
+    };
+
+    match output {
+        SummaryOutput::Stdout => println!("{}", json),
+        SummaryOutput::File(path) => {
+            if let Err(e) = fs::write(&path, json + "\n") {
+                eprintln!(
+                    "  ⚠️  Could not write session summary {}: {}",
+                    path.display(),
+                    e
+                );
+            }
+        }
+    }
+}
+
+/// Lifecycle of the shield. Only `transition` changes it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ShieldState {
+    /// Nothing running (menu bar mode)
+    Idle,
+    /// Overlay, event tap, and timers are being set up
+    Arming,
+    /// Shield up and blocking
+    Active,
+    /// The auto-exit timer is about to expire
+    Warning,
+    /// An exit attempt is being confirmed (admin authentication)
+    Unlocking,
+    /// Terminating; cleanup is running
+    Exiting,
+}
+
+/// Things that move the shield between states
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ShieldEvent {
+    /// Setup started
+    Arm,
+    /// Setup finished
+    Armed,
+    /// The auto-exit timer reached `WARNING_SECONDS`
+    WarningDue,
+    /// The auto-exit timer was extended past `WARNING_SECONDS`
+    Extended,
+    /// Any unlock method fired (close button, exit key, gesture, ...)
+    UnlockRequested,
+    /// The unlock was refused, or in watch mode only disarmed blocking
+    Resumed,
+    /// Unlocked, timer expired, or interrupted
+    Exit,
+}
+
+impl ShieldState {
+    /// Lowercase name for reports
+    fn name(self) -> &'static str {
+        match self {
+            ShieldState::Idle => "idle",
+            ShieldState::Arming => "arming",
+            ShieldState::Active => "active",
+            ShieldState::Warning => "warning",
+            ShieldState::Unlocking => "unlocking",
+            ShieldState::Exiting => "exiting",
+        }
+    }
+
+    /// The state after `event`, or `None` if the event doesn't apply here
+    fn next(self, event: ShieldEvent) -> Option<ShieldState> {
+        use ShieldEvent::*;
+        use ShieldState::*;
+
+        match (self, event) {
+            (Idle, Arm) => Some(Arming),
+            (Arming, Armed) => Some(Active),
+            (Active, WarningDue) => Some(Warning),
+            (Warning, Extended) => Some(Active),
+            (Active | Warning, UnlockRequested) => Some(Unlocking),
+            (Unlocking, Resumed) => Some(Active),
+            (Arming | Active | Warning | Unlocking, Exit) => Some(Exiting),
+            _ => None,
+        }
+    }
+}
+
+/// The current shield state
+fn shield_state() -> ShieldState {
+    SHIELD_STATE.with(|state| state.get())
+}
+
+/// Apply `event` to the shield state and run the reactions to the change.
+///
+/// # Returns
+/// `false` if the event doesn't apply in the current state (e.g. a second exit
+/// attempt while the first one is being authenticated)
+fn transition(event: ShieldEvent) -> bool {
+    let from = shield_state();
+    let Some(to) = from.next(event) else {
+        return false;
+    };
+    SHIELD_STATE.with(|state| state.set(to));
+    on_shield_transition(from, to);
+    true
+}
+
+/// React to a shield state change. Reactions may trigger further transitions.
+fn on_shield_transition(from: ShieldState, to: ShieldState) {
+    log_event(&format!("Shield {} -> {}", from.name(), to.name()));
+    let require_admin = REQUIRE_ADMIN_EXIT.load(Ordering::SeqCst);
+
+    if from == ShieldState::Unlocking && require_admin {
+        raise_shield_after_authentication();
+    }
+
+    match to {
+        ShieldState::Active => {
+            broadcast_state("active");
+            if from == ShieldState::Arming {
+                run_hook("on_activate", ());
+            }
+        }
+        ShieldState::Warning => {
+            broadcast_state("warning");
+            let remaining = get_remaining_seconds();
+            println!();
+            println!("  ⚠️  Auto-exit in {} seconds!", remaining);
+            println!();
+            run_warn_actions(remaining);
+            run_hook("on_warning", (remaining as i64,));
+        }
+        // The authentication prompt is deferred to the animation timer so it
+        // never runs inside the event tap callback
+        ShieldState::Unlocking if require_admin => lower_shield_for_authentication(),
+        ShieldState::Unlocking => complete_unlock(),
+        ShieldState::Exiting => {
+            broadcast_state("exit");
+            run_hook("on_exit", ());
+            terminate_app();
+        }
+        ShieldState::Idle | ShieldState::Arming => {}
+    }
+}
+
+/// Messages from callbacks (event tap, timers, sound analysis, views) to the
+/// coordinator. Callbacks only post; `handle_message` decides what happens.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ShieldMessage {
+    /// A registered unlock method was satisfied
+    UnlockRequested,
+    /// A burst of blocked key presses (a cat walking across the keyboard)
+    BlockedBurst,
+    /// A rapid run of presses on the same or neighboring keys
+    CatDetected,
+    /// Add this many seconds to the auto-exit timer
+    ExtendRequested(u64),
+    /// Pause the shield until `ResumeRequested`, or for this many seconds
+    PauseRequested(Option<u64>),
+    /// Resume a shield paused by `PauseRequested`
+    ResumeRequested,
+    /// The sound classifier heard a meow, with its confidence
+    MeowHeard(f64),
+    /// A sustained loud sound, with its level in dBFS
+    LoudSound(f64),
+    /// The auto-exit timer reached `WARNING_SECONDS`
+    TimerWarning,
+    /// The auto-exit timer ran out
+    TimerExpired,
+    /// A `--remind-every` interval passed
+    ReminderDue,
+    /// The completion animation finished
+    CelebrationFinished,
+    /// Ctrl+C or `kill`
+    Interrupted,
+    /// An app was launched, by process ID
+    AppLaunched(i32),
+    /// An app became frontmost, by process ID
+    AppActivated(i32),
+    /// A display was connected, by CoreGraphics display ID
+    DisplayAdded(u32),
+    /// A display was disconnected, by CoreGraphics display ID
+    DisplayRemoved(u32),
+    /// The frontmost app or the Space changed; under Stage Manager this can
+    /// move or hide other apps' windows
+    StageChanged,
+    /// AppKit's screen list changed: a display was plugged in or unplugged,
+    /// rearranged, or changed resolution
+    ScreensChanged,
+    /// The daemon's control socket asked to arm blocking, for this many
+    /// seconds if given
+    DaemonActivate(Option<u64>),
+    /// The daemon's control socket asked to disarm blocking
+    DaemonDeactivate,
+}
+
+/// Post a message for the coordinator.
+///
+/// Safe to call from any thread. The message is handled on the main run loop
+/// once the current callback has returned.
+fn post_message(message: ShieldMessage) {
+    if let Ok(mut queue) = MESSAGE_QUEUE.lock() {
+        queue.push_back(message);
+    }
+
+    if let Ok(source) = MESSAGE_SOURCE.lock() {
+        if let Some(source) = source.as_ref() {
+            source.signal();
+        }
+    }
+}
+
+/// Take the oldest posted message
+fn next_message() -> Option<ShieldMessage> {
+    MESSAGE_QUEUE.lock().ok()?.pop_front()
+}
+
+/// Run loop source callback: drain the queue into the coordinator
+unsafe extern "C-unwind" fn message_source_perform(_info: *mut c_void) {
+    catch_ffi_panic(|| {
+        while let Some(message) = next_message() {
+            handle_message(message);
+        }
+    })
+}
+
+/// Install the main run loop source that delivers posted messages
+fn setup_message_bus() -> bool {
+    let Some(source) = RunLoopSource::signaled_on_main(message_source_perform) else {
+        return false;
+    };
+
+    // Deliver anything posted before the source existed
+    source.signal();
+    let Ok(mut slot) = MESSAGE_SOURCE.lock() else {
+        return false;
+    };
+    *slot = Some(source);
+
+    true
+}
+
+/// The coordinator: turns posted messages into shield transitions and actions
+fn handle_message(message: ShieldMessage) {
+    match message {
+        // Exits unless `--require-admin-exit` is set or in watch mode
+        ShieldMessage::UnlockRequested => {
+            transition(ShieldEvent::UnlockRequested);
+        }
+        ShieldMessage::BlockedBurst => {
+            if BACKLIGHT_DETERRENT_ENABLED.load(Ordering::SeqCst) {
+                println!("\n  💡 Key burst detected - flashing keyboard backlight");
+                start_backlight_flash();
+            }
+            run_hook("on_blocked_burst", ());
+        }
+        ShieldMessage::CatDetected => {
+            let incidents = CAT_DETECTIONS.fetch_add(1, Ordering::SeqCst) + 1;
+            println!("\n  🐾 CAT DETECTED (incident {} this session)", incidents);
+            show_cat_banner();
+        }
+        ShieldMessage::ExtendRequested(secs) => {
+            if shield_state() != ShieldState::Exiting && extend_auto_exit_timer(secs) {
+                println!();
+                println!(
+                    "  ⏱️  Timer extended by {} ({} remaining)",
+                    format_duration(secs),
+                    format_duration(get_remaining_seconds())
+                );
+                if get_remaining_seconds() > WARNING_SECONDS {
+                    transition(ShieldEvent::Extended);
+                }
+                write_health_file();
+            }
+        }
+        ShieldMessage::PauseRequested(secs) => request_pause(secs),
+        ShieldMessage::ResumeRequested => resume_shield(),
+        ShieldMessage::MeowHeard(confidence) => handle_meow(confidence),
+        ShieldMessage::LoudSound(level) => handle_loud_sound(level),
+        ShieldMessage::TimerWarning => {
+            transition(ShieldEvent::WarningDue);
+        }
+        ShieldMessage::TimerExpired => handle_timer_expired(),
+        ShieldMessage::ReminderDue => send_reminder(),
+        ShieldMessage::CelebrationFinished => exit_shield(ExitReason::TimerExpired),
+        ShieldMessage::Interrupted => {
+            if shield_state() != ShieldState::Exiting {
+                println!();
+                println!("  🛑 Interrupted - restoring and exiting...");
+            }
+            exit_shield(ExitReason::Interrupted);
+        }
+        ShieldMessage::AppLaunched(pid) => handle_app_launched(pid),
+        ShieldMessage::AppActivated(pid) => handle_app_activated(pid),
+        ShieldMessage::DisplayAdded(display) => handle_display_added(display),
+        ShieldMessage::DisplayRemoved(display) => handle_display_removed(display),
+        ShieldMessage::StageChanged => handle_stage_changed(),
+        ShieldMessage::ScreensChanged => handle_screens_changed(),
+        ShieldMessage::DaemonActivate(secs) => daemon_activate(secs),
+        ShieldMessage::DaemonDeactivate => daemon_deactivate(),
+    }
+}
+
+/// Exit when the auto-exit timer runs out, after the completion animation if
+/// no keys were blocked
+fn handle_timer_expired() {
+    if CELEBRATION_START
+        .with(|celebration| celebration.get())
+        .is_some()
+        || shield_state() == ShieldState::Exiting
+    {
+        return;
+    }
+
+    println!();
+    println!("  ⏰ Timer expired - auto-exiting...");
+    if BLOCKED_KEY_PRESSES.load(Ordering::SeqCst) == 0 && start_celebration() {
+        println!("  🎉 0 cat incidents!");
+        CELEBRATION_START.with(|celebration| celebration.set(Some(Instant::now())));
+        let timer = RunLoopTimer::once(
+            CELEBRATION_DURATION.as_secs_f64(),
+            celebration_timer_callback,
+        );
+        AUTO_EXIT_TIMER.with(|slot| slot.replace(timer));
+        return;
+    }
+    exit_shield(ExitReason::TimerExpired);
+}
+
+/// Exit the shield, recording why for the session summary. The first reason
+/// that actually starts the exit wins.
+fn exit_shield(reason: ExitReason) {
+    if shield_state().next(ShieldEvent::Exit).is_some() {
+        EXIT_REASON.with(|exit_reason| exit_reason.set(Some(reason)));
+    }
+    transition(ShieldEvent::Exit);
+}
+
+/// Finish a successful unlock: exit, or in watch mode just disarm blocking
+fn complete_unlock() {
+    if WATCH_MODE.load(Ordering::SeqCst) {
+        set_blocking_armed(false, "unlocked");
+        transition(ShieldEvent::Resumed);
+    } else {
+        exit_shield(ExitReason::Unlocked);
+    }
+}
+
+/// What armed keyboard blocking in watch mode
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum ArmCause {
+    /// A listed app is frontmost (`activate_when_frontmost`)
+    FrontmostApp,
+    /// A timed daemon `activate`
+    DaemonTimer,
+    /// Anything that stays armed until unlocked: a keyboard disconnect, a cat
+    /// heard nearby, a plugin, an untimed daemon `activate`
+    UntilUnlocked,
+}
+
+/// Arm keyboard blocking in the event tap because of `cause`
+fn arm_blocking(cause: ArmCause, reason: &str) {
+    ARM_CAUSES.with(|causes| causes.borrow_mut().insert(cause));
+    set_blocking_armed(true, reason);
+}
+
+/// `cause` no longer applies: disarm keyboard blocking unless something else
+/// still wants it armed
+fn release_blocking(cause: ArmCause, reason: &str) {
+    if remove_arm_cause(cause) {
+        set_blocking_armed(false, reason);
+    }
+}
+
+/// Forget `cause`, returning whether that was the last one
+fn remove_arm_cause(cause: ArmCause) -> bool {
+    ARM_CAUSES.with(|causes| {
+        let mut causes = causes.borrow_mut();
+        causes.remove(&cause);
+        causes.is_empty()
+    })
+}
+
+/// Arm or disarm keyboard blocking in the event tap. Disarming is final: it
+/// drops every cause that armed it.
+fn set_blocking_armed(armed: bool, reason: &str) {
+    if !armed {
+        ARM_CAUSES.with(|causes| causes.borrow_mut().clear());
+        // A timed daemon activation ends with any disarm
+        clear_daemon_deadline();
+    }
+    if SHIELD_PAUSED.load(Ordering::SeqCst) {
+        // Applied when the shield resumes
+        ARMED_ON_RESUME.store(armed, Ordering::SeqCst);
+        return;
+    }
+    if BLOCKING_ARMED.swap(armed, Ordering::SeqCst) == armed {
+        return;
+    }
+
+    log_event(&format!(
+        "Keyboard blocking {} ({})",
+        if armed { "armed" } else { "disarmed" },
+        reason
+    ));
+    if armed {
+        println!();
+        println!("  🛡️  Keyboard blocking ARMED ({})", reason);
+    } else {
+        println!();
+        println!(
+            "  👀 Keyboard blocking disarmed ({}) - still watching",
+            reason
+        );
+    }
+}
+
+/// Ask for administrator credentials via Authorization Services, explaining
+/// why with `prompt`.
+///
+/// Blocks until the user responds to the system dialog.
+///
+/// # Returns
+/// `true` if an administrator authenticated successfully
+fn authenticate_admin(prompt: &std::ffi::CStr) -> bool {
+    let right_name = c"system.privilege.admin";
+
+    let mut right = AuthorizationItem {
+        name: right_name.as_ptr(),
+        value_length: 0,
+        value: std::ptr::null_mut(),
+        flags: 0,
+    };
+    let rights = AuthorizationItemSet {
+        count: 1,
+        items: &mut right,
+    };
+
+    let mut prompt_item = AuthorizationItem {
+        name: c"prompt".as_ptr(),
+        value_length: prompt.to_bytes().len(),
+        value: prompt.as_ptr() as *mut c_void,
+        flags: 0,
+    };
+    let environment = AuthorizationItemSet {
+        count: 1,
+        items: &mut prompt_item,
+    };
+
+    unsafe {
+        let mut authorization: *mut c_void = std::ptr::null_mut();
+        if AuthorizationCreate(std::ptr::null(), std::ptr::null(), 0, &mut authorization)
+            != ERR_AUTHORIZATION_SUCCESS
+        {
+            eprintln!("  ✗ Failed to create authorization session");
+            return false;
+        }
+
+        let status = AuthorizationCopyRights(
+            authorization,
+            &rights,
+            &environment,
+            K_AUTHORIZATION_FLAG_INTERACTION_ALLOWED | K_AUTHORIZATION_FLAG_EXTEND_RIGHTS,
+            std::ptr::null_mut(),
+        );
+
+        // Destroy the acquired rights so the next attempt prompts again
+        AuthorizationFree(authorization, K_AUTHORIZATION_FLAG_DESTROY_RIGHTS);
+
+        match status {
+            ERR_AUTHORIZATION_SUCCESS => true,
+            ERR_AUTHORIZATION_CANCELED => false,
+            other => {
+                eprintln!("  ✗ Admin authentication failed: {}", other);
+                false
+            }
+        }
+    }
+}
+
+/// Disable the event tap and lower the overlay while the authentication dialog
+/// is up, otherwise the password could not be typed and the dialog would be hidden
+fn lower_shield_for_authentication() {
+    let action = if PENDING_PAUSE.with(|pending| pending.get()).is_some() {
+        "Pause"
+    } else {
+        "Exit"
+    };
+    println!(
+        "\n  🔐 {} requested - waiting for administrator authentication...",
+        action
+    );
+
+    let tap = EVENT_TAP.load(Ordering::SeqCst);
+    let window_ptr = OVERLAY_WINDOW.load(Ordering::SeqCst);
+
+    unsafe {
+        if !tap.is_null() {
+            CGEventTapEnable(tap, false);
+        }
+        if !window_ptr.is_null() {
+            let window: &NSWindow = &*(window_ptr as *const NSWindow);
+            window.setLevel(NS_NORMAL_WINDOW_LEVEL);
+        }
+    }
+    SECONDARY_OVERLAYS.with(|windows| {
+        for window in windows.borrow().iter() {
+            window.setLevel(NS_NORMAL_WINDOW_LEVEL);
+        }
+    });
+    release_displays();
+}
+
+/// Undo `lower_shield_for_authentication`
+fn raise_shield_after_authentication() {
+    let tap = EVENT_TAP.load(Ordering::SeqCst);
+    let window_ptr = OVERLAY_WINDOW.load(Ordering::SeqCst);
+
+    recapture_displays();
+    unsafe {
+        if !window_ptr.is_null() {
+            let window: &NSWindow = &*(window_ptr as *const NSWindow);
+            window.setLevel(shield_window_level());
+            window.orderFrontRegardless();
+        }
+        // A paused shield keeps the tap off until it resumes
+        if !tap.is_null() && !SHIELD_PAUSED.load(Ordering::SeqCst) {
+            CGEventTapEnable(tap, true);
+        }
+    }
+    SECONDARY_OVERLAYS.with(|windows| {
+        for window in windows.borrow().iter() {
+            window.setLevel(shield_window_level());
+            window.orderFrontRegardless();
+        }
+    });
+}
+
+/// Prompt for admin credentials, then exit (or pause) if they were accepted
+fn confirm_admin_unlock() {
+    let pause = PENDING_PAUSE.with(|pending| pending.take());
+    let prompt = if pause.is_some() {
+        c"Cat Shield requires an administrator password to pause."
+    } else if WATCH_MODE.load(Ordering::SeqCst) {
+        c"Cat Shield requires an administrator password to disarm."
+    } else {
+        c"Cat Shield requires an administrator password to exit."
+    };
+    if authenticate_admin(prompt) {
+        println!("  ✓ Administrator authenticated");
+        match pause {
+            Some(secs) => {
+                transition(ShieldEvent::Resumed);
+                pause_shield(secs);
+            }
+            None => complete_unlock(),
+        }
+    } else {
+        println!("  🔒 Authentication failed or canceled - shield remains active");
+        transition(ShieldEvent::Resumed);
+    }
+}
+
+/// Whether the event tap is blocking keys
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TapHealth {
+    Enabled,
+    /// Released on purpose while the screen is locked
+    StoodDown,
+    /// Turned off while the shield is paused
+    Paused,
+    /// Missing, or disabled by the system
+    Disabled,
+}
+
+/// What the terminal status line shows
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct ShieldStatus {
+    state: ShieldState,
+    armed: bool,
+    remaining_secs: Option<u64>,
+    blocked_key_presses: u64,
+    tap: TapHealth,
+}
+
+impl ShieldStatus {
+    fn current() -> Self {
+        let tap = EVENT_TAP.load(Ordering::SeqCst);
+        let tap = if tap.is_null() {
+            TapHealth::Disabled
+        } else if STOOD_DOWN.load(Ordering::SeqCst) {
+            TapHealth::StoodDown
+        } else if SHIELD_PAUSED.load(Ordering::SeqCst) {
+            TapHealth::Paused
+        } else if unsafe { CGEventTapIsEnabled(tap) } {
+            TapHealth::Enabled
+        } else {
+            TapHealth::Disabled
+        };
+
+        ShieldStatus {
+            state: shield_state(),
+            armed: BLOCKING_ARMED.load(Ordering::SeqCst),
+            remaining_secs: AUTO_EXIT_ENABLED
+                .load(Ordering::SeqCst)
+                .then(get_remaining_seconds),
+            blocked_key_presses: BLOCKED_KEY_PRESSES.load(Ordering::SeqCst),
+            tap,
+        }
+    }
+
+    fn text(&self) -> String {
+        let headline = if self.tap == TapHealth::Paused {
+            "⏸️  Paused"
+        } else if !self.armed {
+            "👀 Watching (disarmed)"
+        } else {
+            match self.state {
+                ShieldState::Warning => "⚠️  Ending soon",
+                ShieldState::Unlocking => "🔐 Unlocking",
+                ShieldState::Exiting => "👋 Exiting",
+                ShieldState::Idle | ShieldState::Arming | ShieldState::Active => {
+                    "🛡️  Shield active"
+                }
+            }
+        };
+
+        let mut parts = vec![headline.to_string()];
+        if let Some(remaining) = self.remaining_secs {
+            parts.push(format!("{} left", format_duration(remaining)));
+        }
+        parts.push(format!("blocked: {}", self.blocked_key_presses));
+        parts.push(
+            match self.tap {
+                TapHealth::Enabled => "tap ✓",
+                TapHealth::StoodDown => "tap paused (screen locked)",
+                TapHealth::Paused => "tap paused (shield paused)",
+                TapHealth::Disabled => "tap ✗",
+            }
+            .to_string(),
+        );
+        parts.join(" │ ")
+    }
+}
+
+/// Contents of the `--health-file`. Times are UNIX seconds; a monitor should
+/// alert when `updated` goes stale (the process died) or `tap` isn't "enabled".
+#[derive(Debug, Serialize)]
+struct HealthReport {
+    pid: u32,
+    state: &'static str,
+    armed: bool,
+    tap: &'static str,
+    remaining_secs: Option<u64>,
+    /// Length of the timer including extensions, when one is set
+    duration_secs: Option<u64>,
+    /// The shield or its timer is paused (by the menu bar or `cat_shield pause`,
+    /// or while asleep with `--count awake`)
+    paused: bool,
+    /// Watch or daemon mode, where unlocking disarms blocking instead of exiting
+    watching: bool,
+    blocked_key_presses: u64,
+    last_blocked: Option<u64>,
+    updated: u64,
+}
+
+impl HealthReport {
+    fn new(status: ShieldStatus, last_blocked: Option<u64>, now: u64) -> Self {
+        HealthReport {
+            pid: process::id(),
+            state: status.state.name(),
+            armed: status.armed,
+            tap: match status.tap {
+                TapHealth::Enabled => "enabled",
+                TapHealth::StoodDown => "stood_down",
+                TapHealth::Paused => "paused",
+                TapHealth::Disabled => "disabled",
+            },
+            remaining_secs: status.remaining_secs,
+            duration_secs: None,
+            paused: false,
+            watching: false,
+            blocked_key_presses: status.blocked_key_presses,
+            last_blocked,
+            updated: now,
+        }
+    }
+}
+
+/// Start writing health reports to `path`, and report it
+fn enable_health_file(path: PathBuf) {
+    println!("  ✓ Health reports: {}", path.display());
+    HEALTH_FILE.with(|files| files.borrow_mut().push(path));
+    write_health_file();
+}
+
+/// Start writing health reports to the session file, where `status`, `stop`,
+/// and `extend` find the running shield
+fn enable_session_file() {
+    HEALTH_FILE.with(|files| files.borrow_mut().push(session_file_path()));
+    write_health_file();
+}
+
+/// Replace the health files with a fresh report. Written to a temporary file
+/// and renamed, so a monitor never reads half a report. A failed write stops
+/// reporting to that file, which monitors see as a stale file.
+fn write_health_file() {
+    let paths = HEALTH_FILE.with(|files| files.borrow().clone());
+    if paths.is_empty() {
+        return;
+    }
+    LAST_HEALTH_WRITE.with(|last| last.set(Some(Instant::now())));
+
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs();
+    let last_blocked = Some(LAST_BLOCKED_AT.load(Ordering::SeqCst)).filter(|&at| at > 0);
+    let status = ShieldStatus::current();
+    let report = HealthReport {
+        duration_secs: status
+            .remaining_secs
+            .map(|_| AUTO_EXIT_DURATION_SECS.load(Ordering::SeqCst)),
+        paused: SHIELD_PAUSED.load(Ordering::SeqCst)
+            || status.remaining_secs.is_some()
+                && AUTO_EXIT_COUNTDOWN.with(|countdown| countdown.get().running_since.is_none()),
+        watching: WATCH_MODE.load(Ordering::SeqCst),
+        ..HealthReport::new(status, last_blocked, now)
+    };
+
+    let json = match serde_json::to_string_pretty(&report) {
+        Ok(json) => json + "\n",
+        Err(e) => {
+            eprintln!("  ⚠️  Could not write health report: {}", e);
+            return;
+        }
+    };
+    for path in paths {
+        let mut temp_name = path.file_name().unwrap_or_default().to_os_string();
+        temp_name.push(".tmp");
+        let temp_path = path.with_file_name(temp_name);
+        let result = fs::write(&temp_path, &json)
+            .and_then(|()| fs::rename(&temp_path, &path))
+            .map_err(|e| e.to_string());
+
+        if let Err(e) = result {
+            eprintln!(
+                "  ⚠️  Could not write health file {}: {} - health reporting stopped",
+                path.display(),
+                e
+            );
+            HEALTH_FILE.with(|files| files.borrow_mut().retain(|file| *file != path));
+        }
+    }
+}
+
+/// Health report every shield session keeps, for `status`, `stop`, and `extend`
+fn session_file_path() -> PathBuf {
+    std::env::temp_dir().join("catshield-session.json")
+}
+
+/// Remove the session file, unless another instance has taken it over since
+fn remove_session_file() {
+    let path = session_file_path();
+    let ours = fs::read_to_string(&path)
+        .ok()
+        .and_then(|json| serde_json::from_str::<SessionReport>(&json).ok())
+        .is_some_and(|report| report.pid == process::id());
+    if ours {
+        let _ = fs::remove_file(path);
+    }
+}
+
+/// Write a health report if one is due. Called from the animation timer.
+fn update_health_file() {
+    let due = LAST_HEALTH_WRITE.with(|last| {
+        last.get()
+            .is_none_or(|written| written.elapsed() >= HEALTH_FILE_INTERVAL)
+    });
+    if due {
+        write_health_file();
+    }
+}
+
+// Timer callback to update progress, check for exit condition, and trigger redraw
+unsafe extern "C-unwind" fn timer_callback(_timer: *mut CFRunLoopTimer, _info: *mut c_void) {
+    catch_ffi_panic(|| {
+        if TERMINATION_REQUESTED.swap(false, Ordering::SeqCst) {
+            post_message(ShieldMessage::Interrupted);
+            return;
+        }
+
+        adapt_timer_interval();
+        update_backlight_flash();
+        update_overlay_flash();
+        update_progressive_dimming();
+        dismiss_siri();
+        sample_detection_plugins();
+        render_status_line(&ShieldStatus::current().text());
+        update_health_file();
+        update_away_note();
+        update_overlay_widgets();
+        update_cat_banner();
+
+        // Handle a deferred exit attempt that needs admin authentication
+        if shield_state() == ShieldState::Unlocking {
+            confirm_admin_unlock();
+            return;
+        }
+
+        // Time-based unlock methods (close button hold)
+        if evaluate_unlock_methods(UnlockInput::Tick).is_some() {
+            return;
+        }
+
+        // Trigger redraw of the close buttons; they all show the same hold
+        let view_ptr = CLOSE_BUTTON_VIEW.load(Ordering::SeqCst);
+        if !view_ptr.is_null() {
+            let view: &NSView = &*(view_ptr as *const NSView);
+            view.setNeedsDisplay(true);
+        }
+        SECONDARY_CLOSE_BUTTONS.with(|buttons| {
+            for button in buttons.borrow().iter() {
+                button.setNeedsDisplay(true);
+            }
+        });
+
+        // Trigger redraw of timer display
+        let timer_view_ptr = TIMER_DISPLAY_VIEW.load(Ordering::SeqCst);
+        if !timer_view_ptr.is_null() {
+            let view: &NSView = &*(timer_view_ptr as *const NSView);
+            view.setNeedsDisplay(true);
+        }
+    })
+}
+
+/// Horizontal positions (as a fraction of the width) and start delays for the
+/// celebration paw prints.
+///
+/// Uses golden-ratio sequences so the paws are spread evenly but look random,
+/// without pulling in a random number generator.
+fn celebration_paw_layout(count: usize) -> Vec<(f64, f64)> {
+    const GOLDEN_RATIO_CONJUGATE: f64 = 0.618_033_988_749_895;
+    const SILVER_RATIO_CONJUGATE: f64 = 0.414_213_562_373_095;
+
+    (0..count)
+        .map(|i| {
+            let x = (i as f64 * GOLDEN_RATIO_CONJUGATE).fract();
+            let delay = (i as f64 * SILVER_RATIO_CONJUGATE).fract() * CELEBRATION_MAX_DELAY_SECS;
+            (x, delay)
+        })
+        .collect()
+}
+
+/// Create a centered text layer
+fn text_layer(
+    text: &str,
+    font_size: CGFloat,
+    frame: CGRect,
+    scale: CGFloat,
+) -> Retained<CATextLayer> {
+    let layer = CATextLayer::new();
+    unsafe { layer.setString(Some(&NSString::from_str(text))) };
+    layer.setFontSize(font_size);
+    layer.setAlignmentMode(unsafe { kCAAlignmentCenter });
+    layer.setContentsScale(scale);
+    layer.setFrame(frame);
+    layer
+}
+
+/// Play the one-shot completion animation on the overlay: paw prints falling
+/// from the top and a "0 cat incidents!" message. A one-shot timer exits
+/// once `CELEBRATION_DURATION` has passed.
+///
+/// # Returns
+/// `false` if there is no overlay to animate on
+fn start_celebration() -> bool {
+    let window_ptr = OVERLAY_WINDOW.load(Ordering::SeqCst);
+    if window_ptr.is_null() {
+        return false;
+    }
+    let Some(mtm) = MainThreadMarker::new() else {
+        return false;
+    };
+    let window: &NSWindow = unsafe { &*(window_ptr as *const NSWindow) };
+    let Some(content_view) = window.contentView() else {
+        return false;
+    };
+
+    let bounds = content_view.bounds();
+    let view = NSView::initWithFrame(NSView::alloc(mtm), bounds);
+    view.setWantsLayer(true);
+    content_view.addSubview(&view);
+    let Some(root) = view.layer() else {
+        return false;
+    };
+    let scale = window.backingScaleFactor();
+    let text_color = CGColor::new_srgb(
+        palette().close_button_x.0,
+        palette().close_button_x.1,
+        palette().close_button_x.2,
+        1.0,
+    );
+
+    let message = text_layer(
+        "0 cat incidents!",
+        CELEBRATION_MESSAGE_SIZE,
+        CGRect {
+            origin: CGPoint {
+                x: 0.0,
+                y: (bounds.size.height - CELEBRATION_MESSAGE_SIZE) / 2.0,
+            },
+            size: CGSize {
+                width: bounds.size.width,
+                height: CELEBRATION_MESSAGE_SIZE * 1.3,
+            },
+        },
+        scale,
+    );
+    message.setForegroundColor(Some(&text_color));
+    root.addSublayer(&message);
+
+    let now = CACurrentMediaTime();
+    let top = bounds.size.height + CELEBRATION_PAW_SIZE;
+    let bottom = -CELEBRATION_PAW_SIZE;
+    for (x, delay) in celebration_paw_layout(CELEBRATION_PAW_COUNT) {
+        let paw = text_layer(
+            "🐾",
+            CELEBRATION_PAW_SIZE,
+            CGRect {
+                origin: CGPoint {
+                    x: x * (bounds.size.width - CELEBRATION_PAW_SIZE),
+                    y: bottom,
+                },
+                size: CGSize {
+                    width: CELEBRATION_PAW_SIZE,
+                    height: CELEBRATION_PAW_SIZE,
+                },
+            },
+            scale,
+        );
+
+        // The model position is already at the bottom, so paws stay off screen
+        // once their fall finishes
+        let fall = CABasicAnimation::animationWithKeyPath(Some(ns_string!("position.y")));
+        unsafe {
+            fall.setFromValue(Some(&NSNumber::new_f64(top)));
+            fall.setToValue(Some(&NSNumber::new_f64(bottom)));
+            fall.setFillMode(kCAFillModeBackwards);
+        }
+        fall.setDuration(CELEBRATION_FALL_SECS);
+        fall.setBeginTime(now + delay);
+        fall.setTimingFunction(Some(&CAMediaTimingFunction::functionWithName(unsafe {
+            kCAMediaTimingFunctionEaseIn
+        })));
+        paw.addAnimation_forKey(&fall, None);
+        root.addSublayer(&paw);
+    }
+
+    true
+}
+
+/// Close button frame in the top-right corner of a screen of the given size
+fn close_button_frame(screen_size: CGSize) -> CGRect {
+    CGRect {
+        origin: CGPoint {
+            x: screen_size.width - CLOSE_BUTTON_SIZE - CLOSE_BUTTON_MARGIN,
+            y: screen_size.height - CLOSE_BUTTON_SIZE - CLOSE_BUTTON_MARGIN,
+        },
+        size: CGSize {
+            width: CLOSE_BUTTON_SIZE,
+            height: CLOSE_BUTTON_SIZE,
+        },
+    }
+}
+
+/// Away note frame, centered horizontally in the upper part of a screen of the given size
+fn away_note_frame(screen_size: CGSize) -> CGRect {
+    let width = screen_size.width * AWAY_NOTE_WIDTH_FRACTION;
+    CGRect {
+        origin: CGPoint {
+            x: (screen_size.width - width) / 2.0,
+            y: screen_size.height * AWAY_NOTE_CENTER_HEIGHT_FRACTION - AWAY_NOTE_HEIGHT / 2.0,
+        },
+        size: CGSize {
+            width,
+            height: AWAY_NOTE_HEIGHT,
+        },
+    }
+}
+
+/// "CAT DETECTED" banner frame, centered horizontally below the middle of a
+/// screen of the given size
+fn cat_banner_frame(screen_size: CGSize) -> CGRect {
+    let width = screen_size.width * CAT_BANNER_WIDTH_FRACTION;
+    CGRect {
+        origin: CGPoint {
+            x: (screen_size.width - width) / 2.0,
+            y: screen_size.height * CAT_BANNER_CENTER_HEIGHT_FRACTION - CAT_BANNER_HEIGHT / 2.0,
+        },
+        size: CGSize {
+            width,
+            height: CAT_BANNER_HEIGHT,
+        },
+    }
+}
+
+/// Timer display frame on a screen of the given size: in the top-left corner,
+/// unless a `timer` widget in the config file puts it elsewhere
+fn timer_display_frame(screen_size: CGSize) -> CGRect {
+    let size = CGSize {
+        width: TIMER_DISPLAY_WIDTH,
+        height: TIMER_DISPLAY_HEIGHT,
+    };
+    TIMER_ANCHOR
+        .with(|anchor| anchor.get())
+        .frame(size, screen_size, TIMER_DISPLAY_MARGIN)
+}
+
+/// Frame for an overlay widget on a screen of the given size
+fn overlay_widget_frame(widget: &WidgetSpec, screen_size: CGSize) -> CGRect {
+    let width = if widget.kind == WidgetKind::Message {
+        OVERLAY_MESSAGE_WIDTH
+    } else {
+        OVERLAY_WIDGET_WIDTH
+    };
+    let size = CGSize {
+        width: width.min(screen_size.width - OVERLAY_WIDGET_MARGIN * 2.0),
+        height: OVERLAY_WIDGET_HEIGHT,
+    };
+    widget
+        .anchor
+        .frame(size, screen_size, OVERLAY_WIDGET_MARGIN)
+}
+
+/// Animation timer interval, adapted to variable-refresh (ProMotion) displays.
+///
+/// Runs at the display's fastest rate only while something animates (the hold
+/// progress arc), and at its slowest rate otherwise. Never faster than `--fps`;
+/// fixed-refresh displays (min == max interval) always use the `--fps` rate.
+///
+/// # Arguments
+/// * `fps_interval` - Interval from `--fps`, in seconds
+/// * `refresh_range` - The display's (minimum, maximum) refresh interval
+/// * `animating` - Whether a smooth animation is currently running
+fn adaptive_timer_interval(fps_interval: f64, refresh_range: (f64, f64), animating: bool) -> f64 {
+    let (min_interval, max_interval) = refresh_range;
+    if max_interval - min_interval < 1e-6 {
+        return fps_interval;
+    }
+
+    if animating {
+        fps_interval.max(min_interval)
+    } else {
+        fps_interval.max(max_interval.min(ADAPTIVE_IDLE_MAX_INTERVAL_SECS))
+    }
+}
+
+/// The interval the animation timer should run at right now
+fn desired_timer_interval() -> f64 {
+    let refresh_range = MainThreadMarker::new()
+        .and_then(NSScreen::mainScreen)
+        .map(|screen| {
+            (
+                screen.minimumRefreshInterval(),
+                screen.maximumRefreshInterval(),
+            )
+        })
+        .unwrap_or((0.0, 0.0));
+    let animating = MOUSE_DOWN_TIME.with(|time| time.get()).is_some();
+    adaptive_timer_interval(timer_interval_secs(), refresh_range, animating)
+}
+
+/// Restart the animation timer if it should run at a different rate now
+fn adapt_timer_interval() {
+    let desired = desired_timer_interval();
+    if (desired - CURRENT_TIMER_INTERVAL.with(|interval| interval.get())).abs() > 1e-6 {
+        stop_close_button_timer();
+        start_close_button_timer();
+    }
+}
+
+/// Start the animation timer for the close button
+fn start_close_button_timer() {
+    let interval = desired_timer_interval();
+    CURRENT_TIMER_INTERVAL.with(|current| current.set(interval));
+
+    let timer = RunLoopTimer::schedule(interval, timer_callback);
+    // Replacing a running timer invalidates it
+    let previous = ANIMATION_TIMER.with(|slot| slot.replace(timer));
+    drop(previous);
+}
+
+/// Seconds between animation timer ticks at the configured frame rate
+fn timer_interval_secs() -> f64 {
+    1.0 / f64::from(ANIMATION_FPS.load(Ordering::SeqCst))
+}
+
+/// Stop the animation timer
+fn stop_close_button_timer() {
+    // Dropped outside the borrow
+    let timer = ANIMATION_TIMER.with(|slot| slot.borrow_mut().take());
+    drop(timer);
+}
+
+/// Auto-exit countdown that accumulates the time it spends running.
+///
+/// Unlike a single start timestamp this can leave any span out (sleep, display
+/// off), which is what `--count awake` needs. Times are `monotonic_secs`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+struct Countdown {
+    duration_secs: u64,
+    accumulated_secs: u64,
+    running_since: Option<u64>,
+}
+
+impl Countdown {
+    /// Start a running countdown of `duration_secs` at `now`
+    fn start(duration_secs: u64, now: u64) -> Self {
+        Self {
+            duration_secs,
+            accumulated_secs: 0,
+            running_since: Some(now),
+        }
+    }
+
+    /// Seconds counted so far
+    fn elapsed(&self, now: u64) -> u64 {
+        let running = self
+            .running_since
+            .map_or(0, |since| now.saturating_sub(since));
+        self.accumulated_secs + running
+    }
+
+    /// Seconds left, or 0 if expired
+    fn remaining(&self, now: u64) -> u64 {
+        self.duration_secs.saturating_sub(self.elapsed(now))
+    }
+
+    /// Stop counting. Returns `false` if already paused.
+    fn pause(&mut self, now: u64) -> bool {
+        match self.running_since.take() {
+            Some(since) => {
+                self.accumulated_secs += now.saturating_sub(since);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Continue counting. Returns `false` if already running.
+    fn resume(&mut self, now: u64) -> bool {
+        if self.running_since.is_some() {
+            return false;
+        }
+        self.running_since = Some(now);
+        true
+    }
+
+    /// Add time to the countdown
+    fn extend(&mut self, secs: u64) {
+        self.duration_secs = self.duration_secs.saturating_add(secs);
+    }
+
+    /// Seconds until the countdown next reaches `warning_secs` left or expires,
+    /// or `None` while paused
+    fn next_checkpoint(&self, now: u64, warning_secs: u64) -> Option<u64> {
+        self.running_since?;
+        let remaining = self.remaining(now);
+        Some(if remaining > warning_secs {
+            remaining - warning_secs
+        } else {
+            remaining
+        })
+    }
+}
+
+/// Seconds on a clock that keeps counting through sleep (so `--count wall`
+/// works) but ignores wall clock changes: NTP corrections, manual changes,
+/// and timezone jumps can't shift the countdown. `Instant` stops while the
+/// Mac sleeps, so it won't do.
+fn monotonic_secs() -> u64 {
+    unsafe { clock_gettime_nsec_np(CLOCK_MONOTONIC) / 1_000_000_000 }
+}
+
+/// Initialize the auto-exit timer with the specified duration in seconds
+fn init_auto_exit_timer(duration_secs: u64) {
+    let now = monotonic_secs();
+    AUTO_EXIT_COUNTDOWN.with(|countdown| countdown.set(Countdown::start(duration_secs, now)));
+    AUTO_EXIT_DURATION_SECS.store(duration_secs, Ordering::SeqCst);
+    AUTO_EXIT_ENABLED.store(true, Ordering::SeqCst);
+    schedule_auto_exit_checkpoint();
+}
+
+/// Arm a one-shot timer for the countdown's next checkpoint, replacing any
+/// scheduled one. Nothing is scheduled while the countdown is paused.
+fn schedule_auto_exit_checkpoint() {
+    let now = monotonic_secs();
+    let delay =
+        AUTO_EXIT_COUNTDOWN.with(|countdown| countdown.get().next_checkpoint(now, WARNING_SECONDS));
+    let timer = delay.and_then(|secs| RunLoopTimer::once(secs as f64, auto_exit_timer_callback));
+    // Dropped outside the borrow; replacing the timer cancels it
+    let previous = AUTO_EXIT_TIMER.with(|slot| slot.replace(timer));
+    drop(previous);
+}
+
+/// Auto-exit checkpoint: post the warning or expiry that is due, then wait
+/// for the next checkpoint
+unsafe extern "C-unwind" fn auto_exit_timer_callback(
+    _timer: *mut CFRunLoopTimer,
+    _info: *mut c_void,
+) {
+    catch_ffi_panic(|| {
+        let remaining = get_remaining_seconds();
+        if remaining <= WARNING_SECONDS && shield_state() == ShieldState::Active {
+            post_message(ShieldMessage::TimerWarning);
+        }
+        if remaining == 0 {
+            post_message(ShieldMessage::TimerExpired);
+            return;
+        }
+        // The countdown counts whole seconds, so a checkpoint can come up a
+        // fraction of a second early; this also covers that
+        schedule_auto_exit_checkpoint();
+    })
+}
+
+/// End of the completion animation
+unsafe extern "C-unwind" fn celebration_timer_callback(
+    _timer: *mut CFRunLoopTimer,
+    _info: *mut c_void,
+) {
+    catch_ffi_panic(|| post_message(ShieldMessage::CelebrationFinished))
+}
+
+/// Add time to the auto-exit timer.
+///
+/// # Returns
+/// `false` if no timer is set
+fn extend_auto_exit_timer(secs: u64) -> bool {
+    if !AUTO_EXIT_ENABLED.load(Ordering::SeqCst) {
+        return false;
+    }
+
+    let duration = AUTO_EXIT_COUNTDOWN.with(|countdown| {
+        let mut updated = countdown.get();
+        updated.extend(secs);
+        countdown.set(updated);
+        updated.duration_secs
+    });
+    AUTO_EXIT_DURATION_SECS.store(duration, Ordering::SeqCst);
+    schedule_auto_exit_checkpoint();
+    true
+}
+
+/// Get the remaining seconds until auto-exit, or 0 if expired
+fn get_remaining_seconds() -> u64 {
+    if !AUTO_EXIT_ENABLED.load(Ordering::SeqCst) {
+        return u64::MAX;
+    }
+
+    let now = monotonic_secs();
+    AUTO_EXIT_COUNTDOWN.with(|countdown| countdown.get().remaining(now))
+}
+
+/// Pause the auto-exit countdown (no-op if already paused or no timer is set)
+fn pause_auto_exit_timer() {
+    if !AUTO_EXIT_ENABLED.load(Ordering::SeqCst) {
+        return;
+    }
+
+    let now = monotonic_secs();
+    let paused = AUTO_EXIT_COUNTDOWN.with(|countdown| {
+        let mut updated = countdown.get();
+        let paused = updated.pause(now);
+        countdown.set(updated);
+        paused
+    });
+    if paused {
+        schedule_auto_exit_checkpoint();
+        println!(
+            "  ⏸️  Timer paused ({} remaining)",
+            format_duration(get_remaining_seconds())
+        );
+    }
+}
+
+/// Resume the auto-exit countdown after a pause, unless the user paused it
+/// from the menu bar
+fn resume_auto_exit_timer() {
+    if !AUTO_EXIT_ENABLED.load(Ordering::SeqCst) || TIMER_PAUSED_BY_USER.with(|paused| paused.get())
+    {
+        return;
+    }
+
+    let now = monotonic_secs();
+    let resumed = AUTO_EXIT_COUNTDOWN.with(|countdown| {
+        let mut updated = countdown.get();
+        let resumed = updated.resume(now);
+        countdown.set(updated);
+        resumed
+    });
+    if resumed {
+        schedule_auto_exit_checkpoint();
+        println!(
+            "  ▶️  Timer resumed ({} remaining)",
+            format_duration(get_remaining_seconds())
+        );
+    }
+}
+
+/// Pause the countdown for sleep or display-off, unless counting wall-clock time
+fn pause_auto_exit_timer_while_away() {
+    if COUNT_MODE.with(|mode| mode.get()) == CountMode::Awake {
+        pause_auto_exit_timer();
+    }
+}
+
+/// Overlay background opacity for progressive dimming.
+///
+/// # Arguments
+/// * `progress` - Fraction of the timer elapsed, from 0.0 to 1.0
+/// * `mode` - Whether the overlay darkens or lightens over time
+fn calculate_dim_alpha(progress: f64, mode: DimMode) -> CGFloat {
+    let progress = progress.clamp(0.0, 1.0);
+    let darkness = match mode {
+        DimMode::Darken => progress,
+        DimMode::Lighten => 1.0 - progress,
+    };
+    DIM_MIN_ALPHA + (DIM_MAX_ALPHA - DIM_MIN_ALPHA) * darkness
+}
+
+/// Update the overlay background for progressive dimming; called from the animation timer
+fn update_progressive_dimming() {
+    let Some(mode) = DIM_MODE.with(|dim| dim.get()) else {
+        return;
+    };
+    // The warning flash owns the background until it ends
+    if OVERLAY_FLASH.with(|flash| flash.borrow().is_some()) {
+        return;
+    }
+    let window_ptr = OVERLAY_WINDOW.load(Ordering::SeqCst);
+    if window_ptr.is_null() {
+        return;
+    }
+
+    let duration = AUTO_EXIT_DURATION_SECS.load(Ordering::SeqCst);
+    if duration == 0 {
+        return;
+    }
+    let remaining = get_remaining_seconds().min(duration);
+    let alpha = calculate_dim_alpha(1.0 - remaining as f64 / duration as f64, mode);
+
+    let applied = DIM_APPLIED_ALPHA.with(|applied| applied.get());
+    if (alpha - applied).abs() < DIM_UPDATE_STEP {
+        return;
+    }
+    DIM_APPLIED_ALPHA.with(|applied| applied.set(alpha));
+
+    let color = overlay_background_color(alpha);
+    for_each_overlay_window(|window| window.setBackgroundColor(Some(&color)));
+}
+
+/// Format seconds as a human-readable string (e.g., "1h 30m 45s")
+fn format_duration(total_secs: u64) -> String {
+    let hours = total_secs / 3600;
+    let minutes = (total_secs % 3600) / 60;
+    let secs = total_secs % 60;
+
+    if hours > 0 {
+        format!("{}h {:02}m {:02}s", hours, minutes, secs)
+    } else if minutes > 0 {
+        format!("{}m {:02}s", minutes, secs)
+    } else {
+        format!("{}s", secs)
+    }
+}
+
+/// IOKit assertion that keeps the display awake (only the system with
+/// `--screen-off`). Dropping it releases the assertion, so no exit path can
+/// leave the machine unable to sleep.
+struct SleepAssertion(u32);
+
+impl SleepAssertion {
+    /// Creates an IOKit assertion to prevent the system from sleeping
+    fn acquire() -> Option<Self> {
+        let assertion_type = CFString::from_static_str(if SCREEN_OFF.load(Ordering::SeqCst) {
+            "PreventUserIdleSystemSleep"
+        } else {
+            "PreventUserIdleDisplaySleep"
+        });
+        let reason =
+            CFString::from_static_str("Cat Shield is active - protecting your work from cats!");
+
+        let mut assertion_id: u32 = 0;
+
+        let result = unsafe {
+            IOPMAssertionCreateWithName(
+                CFRetained::as_ptr(&assertion_type).as_ptr() as *const c_void,
+                K_IOPM_ASSERTION_LEVEL_ON,
+                CFRetained::as_ptr(&reason).as_ptr() as *const c_void,
+                &mut assertion_id,
+            )
+        };
+
+        if result == 0 {
+            println!("  ✓ Sleep prevention enabled");
+            Some(SleepAssertion(assertion_id))
+        } else {
+            eprintln!("  ✗ Failed to create power assertion: {}", result);
+            None
+        }
+    }
+}
+
+impl Drop for SleepAssertion {
+    fn drop(&mut self) {
+        let result = unsafe { IOPMAssertionRelease(self.0) };
+        if result == 0 {
+            println!("  ✓ Sleep prevention disabled");
+        }
+    }
+}
+
+/// Ask the display wrangler to put the displays to sleep now
+///
+/// # Returns
+/// `false` if the request was refused
+fn sleep_displays() -> bool {
+    let key = CFString::from_static_str("IORequestIdle");
+    unsafe {
+        let wrangler = IORegistryEntryFromPath(
+            K_IO_MAIN_PORT_DEFAULT,
+            c"IOService:/IOResources/IODisplayWrangler".as_ptr(),
+        );
+        if wrangler == 0 {
+            return false;
+        }
+        let result = IORegistryEntrySetCFProperty(
+            wrangler,
+            CFRetained::as_ptr(&key).as_ptr() as *const c_void,
+            kCFBooleanTrue,
+        );
+        IOObjectRelease(wrangler);
+        result == K_IO_RETURN_SUCCESS
+    }
+}
+
+/// Put the displays back to sleep after `DISPLAY_RESLEEP_DELAY_SECS`. A blocked
+/// key press still wakes them, and the wrangler ignores a sleep request made
+/// while it is powering on.
+fn schedule_display_sleep() {
+    if !keeps_displays_asleep() {
+        return;
+    }
+    let timer = RunLoopTimer::once(DISPLAY_RESLEEP_DELAY_SECS, display_sleep_timer_callback);
+    // Dropped outside the borrow; replacing the timer cancels it
+    let previous = DISPLAY_SLEEP_TIMER.with(|slot| slot.replace(timer));
+    drop(previous);
+}
+
+unsafe extern "C-unwind" fn display_sleep_timer_callback(
+    _timer: *mut CFRunLoopTimer,
+    _info: *mut c_void,
+) {
+    catch_ffi_panic(|| {
+        if keeps_displays_asleep() {
+            sleep_displays();
+        }
+    })
+}
+
+/// Whether the shield is up and not standing down for the lock screen
+fn keeps_displays_asleep() -> bool {
+    BLOCKING_ARMED.load(Ordering::SeqCst)
+        && !STOOD_DOWN.load(Ordering::SeqCst)
+        && shield_state() != ShieldState::Exiting
+}
+
+/// Wake the displays when a `--screen-off` shield ends
+fn wake_displays() {
+    if !SCREEN_OFF.swap(false, Ordering::SeqCst) {
+        return;
+    }
+    DISPLAY_SLEEP_TIMER.with(|slot| slot.take());
+
+    let name = CFString::from_static_str("Cat Shield deactivated");
+    let mut assertion_id: u32 = 0;
+    let result = unsafe {
+        IOPMAssertionDeclareUserActivity(
+            CFRetained::as_ptr(&name).as_ptr() as *const c_void,
+            K_IOPM_USER_ACTIVE_LOCAL,
+            &mut assertion_id,
+        )
+    };
+    if result == K_IO_RETURN_SUCCESS {
+        // The display is already waking; normal idle sleep applies from here
+        unsafe { IOPMAssertionRelease(assertion_id) };
+    }
+}
+
+/// Take the shared sleep assertion if it isn't already held
+fn acquire_sleep_assertion() {
+    SLEEP_ASSERTION.with(|assertion| {
+        let mut assertion = assertion.borrow_mut();
+        if assertion.is_none() {
+            *assertion = SleepAssertion::acquire();
+        }
+    });
+}
+
+/// Release the shared sleep assertion if it is held
+fn release_sleep_assertion() {
+    // Dropped outside the borrow
+    let assertion = SLEEP_ASSERTION.with(|assertion| assertion.borrow_mut().take());
+    drop(assertion);
+}
+
+/// Move the overlay and its views onto the current main screen and bring it back
+/// to the front. Called after wake, lid open, and display reconfiguration, when
+/// macOS may have resized or hidden the window.
+fn restore_overlay() {
+    let window_ptr = OVERLAY_WINDOW.load(Ordering::SeqCst);
+    if window_ptr.is_null()
+        || PROJECTOR_PAUSED.load(Ordering::SeqCst)
+        || SHIELD_PAUSED.load(Ordering::SeqCst)
+    {
+        return;
+    }
+    let Some(mtm) = MainThreadMarker::new() else {
+        return;
+    };
+    let Some(screen) = NSScreen::mainScreen(mtm) else {
+        return;
+    };
+    let screen_frame = screen.frame();
+
+    unsafe {
+        let window: &NSWindow = &*(window_ptr as *const NSWindow);
+        window.setFrame_display(screen_frame, true);
+
+        let close_button_ptr = CLOSE_BUTTON_VIEW.load(Ordering::SeqCst);
+        if !close_button_ptr.is_null() {
+            let view: &NSView = &*(close_button_ptr as *const NSView);
+            view.setFrame(close_button_frame(screen_frame.size));
+        }
+
+        let timer_view_ptr = TIMER_DISPLAY_VIEW.load(Ordering::SeqCst);
+        if !timer_view_ptr.is_null() {
+            let view: &NSView = &*(timer_view_ptr as *const NSView);
+            view.setFrame(timer_display_frame(screen_frame.size));
+        }
+
+        let away_note_ptr = AWAY_NOTE_VIEW.load(Ordering::SeqCst);
+        if !away_note_ptr.is_null() {
+            let view: &NSView = &*(away_note_ptr as *const NSView);
+            view.setFrame(away_note_frame(screen_frame.size));
+        }
+
+        let cat_banner_ptr = CAT_BANNER_VIEW.load(Ordering::SeqCst);
+        if !cat_banner_ptr.is_null() {
+            let view: &NSView = &*(cat_banner_ptr as *const NSView);
+            view.setFrame(cat_banner_frame(screen_frame.size));
+        }
+
+        OVERLAY_WIDGETS.with(|widgets| {
+            for (widget, view) in widgets.borrow().iter() {
+                view.setFrame(overlay_widget_frame(widget, screen_frame.size));
+            }
+        });
+
+        window.setLevel(shield_window_level());
+        window.orderFrontRegardless();
+
+        // The main display may have changed, and displays come and go
+        if shield_state() != ShieldState::Exiting {
+            apply_display_settings(window, &screen);
+            setup_secondary_overlays(mtm);
+        }
+    }
+}
+
+/// Re-enable the event tap; macOS may disable taps across sleep.
+/// A tap that can't be re-enabled is replaced with a fresh one.
+///
+/// # Returns
+/// `true` if keyboard blocking is working (or no tap was ever installed)
+fn reenable_event_tap() -> bool {
+    let tap = EVENT_TAP.load(Ordering::SeqCst);
+    if tap.is_null() || STOOD_DOWN.load(Ordering::SeqCst) || SHIELD_PAUSED.load(Ordering::SeqCst) {
+        return true;
+    }
+
+    unsafe {
+        let port: &CFMachPort = &*(tap as *const CFMachPort);
+        if port.is_valid() {
+            CGEventTapEnable(tap, true);
+            if CGEventTapIsEnabled(tap) {
+                return true;
+            }
+            // Invalidating the port also removes its run loop source
+            port.invalidate();
+        }
+    }
+
+    EVENT_TAP.store(std::ptr::null_mut(), Ordering::SeqCst);
+    setup_event_tap()
+}
+
+/// Restart the animation timer if it was invalidated while asleep.
+/// The timer drives the close button, countdown, and signal handling.
+///
+/// # Returns
+/// `true` if the timer is running (or was never started)
+fn ensure_animation_timer() -> bool {
+    let running = ANIMATION_TIMER.with(|slot| slot.borrow().as_ref().map(RunLoopTimer::is_valid));
+    if running != Some(false) {
+        return true;
+    }
+
+    start_close_button_timer();
+    ANIMATION_TIMER.with(|slot| slot.borrow().is_some())
+}
+
+/// Handle lid close: pause and release the assertion only if the Mac will sleep.
+/// In clamshell mode with an external display the shield keeps running.
+fn handle_lid_closed(will_sleep: bool) {
+    println!();
+    if will_sleep {
+        println!("  💤 Lid closed - shield paused until it opens");
+        pause_auto_exit_timer_while_away();
+        release_sleep_assertion();
+    } else {
+        println!("  🖥️  Lid closed in clamshell mode - shield stays active");
+    }
+}
+
+/// Handle lid open or wake from sleep: resume the timer and restore the shield
+fn handle_lid_opened_or_woke() {
+    resume_auto_exit_timer();
+
+    // A close-button hold that started before sleep must not complete on wake
+    MOUSE_DOWN_TIME.with(|time| time.set(None));
+
+    if reenable_event_tap() && ensure_animation_timer() {
+        println!("  ✓ Shield re-armed");
+    } else {
+        eprintln!("  ✗ Shield could not be fully re-armed - keyboard input may not be blocked");
+    }
+
+    if !OVERLAY_WINDOW.load(Ordering::SeqCst).is_null() && !STOOD_DOWN.load(Ordering::SeqCst) {
+        acquire_sleep_assertion();
+        restore_overlay();
+        reapply_display_dim();
+        if SAVED_BACKLIGHT_LEVEL.with(|saved| saved.get()).is_some() {
+            turn_off_keyboard_backlight();
+        }
+    }
+}
+
+/// IOKit power callback for both system sleep/wake and root-domain lid messages
+unsafe extern "C" fn power_event_callback(
+    _refcon: *mut c_void,
+    _service: u32,
+    message_type: u32,
+    message_argument: *mut c_void,
+) {
+    catch_ffi_panic(|| {
+        match message_type {
+            K_IO_MESSAGE_CAN_SYSTEM_SLEEP => {
+                // Never veto idle sleep here; the assertion already covers that
+                IOAllowPowerChange(
+                    ROOT_POWER_PORT.load(Ordering::SeqCst),
+                    message_argument as isize,
+                );
+            }
+            K_IO_MESSAGE_SYSTEM_WILL_SLEEP => {
+                pause_auto_exit_timer_while_away();
+                IOAllowPowerChange(
+                    ROOT_POWER_PORT.load(Ordering::SeqCst),
+                    message_argument as isize,
+                );
+            }
+            K_IO_MESSAGE_SYSTEM_HAS_POWERED_ON => {
+                // If the lid is still closed we woke in clamshell mode, which is
+                // also the right time to resume
+                println!();
+                println!("  ☀️  System woke from sleep");
+                handle_lid_opened_or_woke();
+            }
+            // With --screen-off the displays sleeping is the shield at work, not the
+            // user stepping away, so the timer keeps running
+            K_IO_MESSAGE_DEVICE_WILL_POWER_OFF if SCREEN_OFF.load(Ordering::SeqCst) => {}
+            K_IO_MESSAGE_DEVICE_HAS_POWERED_ON if SCREEN_OFF.load(Ordering::SeqCst) => {
+                schedule_display_sleep();
+            }
+            K_IO_MESSAGE_DEVICE_WILL_POWER_OFF => {
+                // Idle display sleep is prevented, so this is an explicit display sleep
+                pause_auto_exit_timer_while_away();
+            }
+            K_IO_MESSAGE_DEVICE_HAS_POWERED_ON if !LID_CLOSED.load(Ordering::SeqCst) => {
+                resume_auto_exit_timer();
+            }
+            K_IOPM_MESSAGE_CLAMSHELL_STATE_CHANGE => {
+                let state = message_argument as usize;
+                let closed = state & K_CLAMSHELL_STATE_BIT != 0;
+                if LID_CLOSED.swap(closed, Ordering::SeqCst) == closed {
+                    return;
+                }
+                if closed {
+                    handle_lid_closed(state & K_CLAMSHELL_SLEEP_BIT != 0);
+                } else {
+                    println!();
+                    println!("  💻 Lid opened");
+                    handle_lid_opened_or_woke();
+                }
+            }
+            _ => {}
+        }
+    })
+}
+
+/// CoreGraphics display reconfiguration callback: re-fit the overlay once
+/// displays finish attaching or detaching. `NSScreen` can still report the old
+/// frames here; the screen parameters notification re-fits it again once
+/// AppKit has caught up.
+unsafe extern "C" fn display_reconfiguration_callback(
+    display: u32,
+    flags: u32,
+    _user_info: *mut c_void,
+) {
+    catch_ffi_panic(|| {
+        if flags & K_CG_DISPLAY_BEGIN_CONFIGURATION_FLAG == 0 {
+            restore_overlay();
+        }
+        if flags & K_CG_DISPLAY_ADD_FLAG != 0 {
+            post_message(ShieldMessage::DisplayAdded(display));
+        }
+        if flags & K_CG_DISPLAY_REMOVE_FLAG != 0 {
+            post_message(ShieldMessage::DisplayRemoved(display));
+        }
+    })
+}
+
+/// Register for sleep/wake, lid, and display change notifications on the
+/// current run loop
+fn setup_power_monitor() -> bool {
+    unsafe {
+        let mut power_port: *mut c_void = std::ptr::null_mut();
+        let mut notifier: u32 = 0;
+        let root_port = IORegisterForSystemPower(
+            std::ptr::null_mut(),
+            &mut power_port,
+            power_event_callback,
+            &mut notifier,
+        );
+        if root_port == 0 {
+            return false;
+        }
+        ROOT_POWER_PORT.store(root_port, Ordering::SeqCst);
+
+        // The notification port owns its run loop source
+        let run_loop = current_run_loop();
+        let add_port_source = |port: *mut c_void| {
+            let source = IONotificationPortGetRunLoopSource(port) as *const CFRunLoopSource;
+            run_loop.add_source(source.as_ref(), common_modes());
+        };
+        add_port_source(power_port);
+
+        // Lid changes are broadcast as general interest on the root power domain
+        let root_domain = IOServiceGetMatchingService(
+            K_IO_MAIN_PORT_DEFAULT,
+            IOServiceMatching(c"IOPMrootDomain".as_ptr()),
+        );
+        if root_domain != 0 {
+            let lid_port = IONotificationPortCreate(K_IO_MAIN_PORT_DEFAULT);
+            let mut lid_notification: u32 = 0;
+            let result = IOServiceAddInterestNotification(
+                lid_port,
+                root_domain,
+                c"IOGeneralInterest".as_ptr(),
+                power_event_callback,
+                std::ptr::null_mut(),
+                &mut lid_notification,
+            );
+            if result == K_IO_RETURN_SUCCESS {
+                add_port_source(lid_port);
+            }
+        }
+
+        // Display sleep is reported as power changes of the display wrangler
+        let display_wrangler = IOServiceGetMatchingService(
+            K_IO_MAIN_PORT_DEFAULT,
+            IOServiceMatching(c"IODisplayWrangler".as_ptr()),
+        );
+        if display_wrangler != 0 {
+            let display_port = IONotificationPortCreate(K_IO_MAIN_PORT_DEFAULT);
+            let mut display_notification: u32 = 0;
+            let result = IOServiceAddInterestNotification(
+                display_port,
+                display_wrangler,
+                c"IOGeneralInterest".as_ptr(),
+                power_event_callback,
+                std::ptr::null_mut(),
+                &mut display_notification,
+            );
+            if result == K_IO_RETURN_SUCCESS {
+                add_port_source(display_port);
+            }
+        }
+
+        CGDisplayRegisterReconfigurationCallback(
+            display_reconfiguration_callback,
+            std::ptr::null_mut(),
+        );
+    }
+
+    // The notification ports are intentionally never released so they keep
+    // reporting for the lifetime of the process.
+    true
+}
+
+/// Distributed notification callback for screen saver and screen lock changes
+unsafe extern "C" fn system_notification_callback(
+    _center: *mut c_void,
+    _observer: *mut c_void,
+    name: *const c_void,
+    object: *const c_void,
+    _user_info: *const c_void,
+) {
+    catch_ffi_panic(|| {
+        if name.is_null() {
+            return;
+        }
+        let name = (*(name as *const CFString)).to_string();
+        let object = (!object.is_null()).then(|| (*(object as *const CFString)).to_string());
+
+        match name.as_str() {
+            // Watch mode has no overlay for the screen saver to cover
+            SCREENSAVER_DID_START if !WATCH_MODE.load(Ordering::SeqCst) => {
+                if STOP_SCREENSAVER.load(Ordering::SeqCst) && stop_screensaver() {
+                    println!();
+                    println!("  🖼️  Screen saver stopped");
+                    restore_overlay();
+                } else {
+                    println!();
+                    println!("  🖼️  Screen saver started - the shield returns when it stops");
+                }
+            }
+            SCREENSAVER_DID_STOP => {
+                // The screen saver shares our window level and can leave the overlay
+                // behind it; the tap may also have been disabled meanwhile
+                restore_overlay();
+                reenable_event_tap();
+            }
+            SCREEN_IS_LOCKED if STAND_DOWN_WHEN_LOCKED.load(Ordering::SeqCst) => {
+                stand_down_for_lock_screen();
+            }
+            SCREEN_IS_UNLOCKED
+                if STOOD_DOWN.load(Ordering::SeqCst)
+                    && !PROJECTOR_PAUSED.load(Ordering::SeqCst) =>
+            {
+                rearm_after_unlock();
+            }
+            CONTROL_NOTIFICATION => {
+                if let Some(control) = object
+                    .as_deref()
+                    .and_then(|object| SessionControl::decode(object, process::id()))
+                {
+                    post_message(control.message());
+                }
+            }
+            KEYBOARD_LAYOUT_CHANGED => {
+                let before = EXIT_KEY_KEYCODE.load(Ordering::SeqCst);
+                if resolve_exit_keycode() != before {
+                    log_event("Exit key moved with the keyboard layout");
+                }
+            }
+            _ => {}
+        }
+    })
+}
+
+/// Display name keywords of projector makers and capture devices
+const PROJECTOR_NAME_HINTS: &[&str] = &[
+    "projector",
+    "epson",
+    "benq",
+    "optoma",
+    "infocus",
+    "vivitek",
+    "capture",
+    "elgato",
+    "cam link",
+    "blackmagic",
+    "ultrastudio",
+    "magewell",
+    "airplay",
+];
+
+/// Why a newly connected display looks like a projector or capture device:
+/// a projector-maker or capture-device name, or mirroring another display,
+/// which is how presentations are usually set up
+fn projector_reason(name: &str, mirrored: bool) -> Option<String> {
+    let lower = name.to_lowercase();
+    if PROJECTOR_NAME_HINTS.iter().any(|hint| lower.contains(hint)) {
+        Some(format!("\"{}\"", name))
+    } else if mirrored {
+        Some(format!("\"{}\", mirroring another display", name))
+    } else {
+        None
+    }
+}
+
+/// Name of a connected display, if AppKit knows it yet
+fn display_name(display: u32) -> String {
+    let Some(mtm) = MainThreadMarker::new() else {
+        return String::new();
+    };
+    NSScreen::screens(mtm)
+        .iter()
+        .find(|screen| screen_display_id(screen) == Some(display))
+        .map(|screen| screen.localizedName().to_string())
+        .unwrap_or_default()
+}
+
+/// A display connected: with `--on-projector`, pause or notify if it looks
+/// like a projector or capture device
+fn handle_display_added(display: u32) {
+    let Some(action) = PROJECTOR_ACTION.with(|action| action.get()) else {
+        return;
+    };
+    if !matches!(shield_state(), ShieldState::Active | ShieldState::Warning)
+        || PROJECTOR_PAUSED.load(Ordering::SeqCst)
+    {
+        return;
+    }
+    let mirrored = unsafe { CGDisplayMirrorsDisplay(display) } != 0;
+    let Some(reason) = projector_reason(&display_name(display), mirrored) else {
+        return;
+    };
+
+    println!();
+    match action {
+        ProjectorAction::Notify => {
+            println!("  📽️  Projector or capture display connected: {}", reason);
+            send_notification(
+                "Cat Shield",
+                "A projector is connected - the shield is showing on it",
+            );
+        }
+        ProjectorAction::Pause => {
+            println!(
+                "  📽️  Projector or capture display connected: {} - shield paused until it disconnects",
+                reason
+            );
+            send_notification(
+                "Cat Shield",
+                "Paused while a projector is connected - the keyboard is not blocked",
+            );
+            PROJECTOR_DISPLAY.with(|slot| slot.set(Some(display)));
+            pause_for_projector();
+        }
+    }
+    log_event(&format!("Projector connected: {}", reason));
+}
+
+/// Resume once the display a projector pause is waiting on disconnects
+fn handle_display_removed(display: u32) {
+    if PROJECTOR_DISPLAY.with(|slot| slot.get()) != Some(display) {
+        return;
+    }
+    PROJECTOR_DISPLAY.with(|slot| slot.set(None));
+    resume_after_projector();
+}
+
+/// Hide the overlay on every display, release the keyboard, and pause the timer
+fn pause_for_projector() {
+    PROJECTOR_PAUSED.store(true, Ordering::SeqCst);
+    let already_stood_down = STOOD_DOWN.swap(true, Ordering::SeqCst);
+    let tap = EVENT_TAP.load(Ordering::SeqCst);
+    if !already_stood_down && !tap.is_null() {
+        unsafe { CGEventTapEnable(tap, false) };
+    }
+    release_displays();
+
+    for_each_overlay_window(|window| window.orderOut(None));
+    pause_auto_exit_timer();
+}
+
+/// Undo `pause_for_projector`
+fn resume_after_projector() {
+    PROJECTOR_PAUSED.store(false, Ordering::SeqCst);
+    STOOD_DOWN.store(false, Ordering::SeqCst);
+    println!();
+    println!("  📽️  Projector disconnected");
+    if reenable_event_tap() {
+        println!("  ✓ Shield re-armed");
+    } else {
+        eprintln!("  ✗ Shield could not be fully re-armed - keyboard input may not be blocked");
+    }
+    recapture_displays();
+    restore_overlay();
+    resume_auto_exit_timer();
+}
+
+/// Pause as `pause_shield` does. In parental mode that needs an administrator,
+/// like an unlock, so it goes through the same confirmation first.
+fn request_pause(secs: Option<u64>) {
+    if !REQUIRE_ADMIN_EXIT.load(Ordering::SeqCst) {
+        pause_shield(secs);
+        return;
+    }
+    PENDING_PAUSE.with(|pending| pending.set(Some(secs)));
+    if !transition(ShieldEvent::UnlockRequested) {
+        PENDING_PAUSE.with(|pending| pending.set(None));
+    }
+}
+
+/// Let the keyboard and mouse through and fade the overlay out, keeping the
+/// session: the timer stops until `resume_shield`, or `secs` from now if given.
+/// The event tap is turned off rather than left running disarmed, so no event
+/// goes through it while paused; `reenable_event_tap` leaves it off until then.
+fn pause_shield(secs: Option<u64>) {
+    if shield_state() == ShieldState::Exiting {
+        return;
+    }
+    TIMER_PAUSED_BY_USER.with(|paused| paused.set(true));
+    pause_auto_exit_timer();
+    if !SHIELD_PAUSED.swap(true, Ordering::SeqCst) {
+        ARMED_ON_RESUME.store(
+            BLOCKING_ARMED.swap(false, Ordering::SeqCst),
+            Ordering::SeqCst,
+        );
+        let tap = EVENT_TAP.load(Ordering::SeqCst);
+        if !tap.is_null() {
+            unsafe { CGEventTapEnable(tap, false) };
+        }
+        release_displays();
+        for_each_overlay_window(|window| {
+            window.setIgnoresMouseEvents(true);
+            fade_window(window, 0.0);
+        });
+        log_event("Shield paused");
+        println!();
+        println!("  ⏸️  Shield paused - the keyboard and mouse work until it resumes");
+    }
+
+    let timer = secs.and_then(|secs| RunLoopTimer::once(secs as f64, resume_timer_callback));
+    if let Some(secs) = secs.filter(|_| timer.is_some()) {
+        println!("      Resuming in {}", format_duration(secs));
+    }
+    SHIELD_RESUME_TIMER.with(|slot| slot.replace(timer));
+    write_health_file();
+}
+
+/// Undo `pause_shield`
+fn resume_shield() {
+    // Dropped outside the borrow
+    let timer = SHIELD_RESUME_TIMER.with(|slot| slot.borrow_mut().take());
+    drop(timer);
+    TIMER_PAUSED_BY_USER.with(|paused| paused.set(false));
+    if SHIELD_PAUSED.swap(false, Ordering::SeqCst) && shield_state() != ShieldState::Exiting {
+        BLOCKING_ARMED.store(ARMED_ON_RESUME.load(Ordering::SeqCst), Ordering::SeqCst);
+        if !reenable_event_tap() {
+            eprintln!("  ✗ Shield could not be fully re-armed - keyboard input may not be blocked");
+        }
+        recapture_displays();
+        restore_overlay();
+        SECONDARY_OVERLAYS.with(|windows| {
+            for window in windows.borrow().iter() {
+                if let Some(screen) = window.screen() {
+                    apply_window_settings(window, &screen);
+                }
+            }
+        });
+        log_event("Shield resumed");
+        println!();
+        println!("  ▶️  Shield resumed");
+    }
+    resume_auto_exit_timer();
+    write_health_file();
+}
+
+unsafe extern "C-unwind" fn resume_timer_callback(_timer: *mut CFRunLoopTimer, _info: *mut c_void) {
+    catch_ffi_panic(resume_shield)
+}
+
+/// Release the event tap and sleep assertion while the native lock screen is up.
+/// The system lock already protects the machine, and an active tap there can
+/// interfere with password entry.
+fn stand_down_for_lock_screen() {
+    if STOOD_DOWN.swap(true, Ordering::SeqCst) {
+        return;
+    }
+
+    let tap = EVENT_TAP.load(Ordering::SeqCst);
+    if !tap.is_null() {
+        unsafe { CGEventTapEnable(tap, false) };
+    }
+    release_sleep_assertion();
+    release_displays();
+    println!();
+    println!("  🔐 Screen locked - shield standing down");
+}
+
+/// Re-arm the shield once the user unlocks the screen
+fn rearm_after_unlock() {
+    STOOD_DOWN.store(false, Ordering::SeqCst);
+    println!();
+    println!("  🔓 Screen unlocked");
+    if reenable_event_tap() {
+        println!("  ✓ Shield re-armed");
+    } else {
+        eprintln!("  ✗ Shield could not be fully re-armed - keyboard input may not be blocked");
+    }
+    // Watch mode never holds the assertion
+    if !WATCH_MODE.load(Ordering::SeqCst) {
+        acquire_sleep_assertion();
+        recapture_displays();
+        restore_overlay();
+    }
+}
+
+/// Quit the running screen saver
+///
+/// # Returns
+/// `true` if a screen saver was running and asked to quit
+fn stop_screensaver() -> bool {
+    let engines = NSRunningApplication::runningApplicationsWithBundleIdentifier(
+        &NSString::from_str(SCREENSAVER_BUNDLE_ID),
+    );
+    let mut stopped = false;
+    for engine in engines.iter() {
+        stopped |= engine.terminate();
+    }
+    stopped
+}
+
+/// Observe screen saver, screen lock, keyboard layout, and session control
+/// distributed notifications on the current run loop
+fn setup_system_notifications() {
+    unsafe {
+        let center = CFNotificationCenterGetDistributedCenter();
+        for name in [
+            SCREENSAVER_DID_START,
+            SCREENSAVER_DID_STOP,
+            SCREEN_IS_LOCKED,
+            SCREEN_IS_UNLOCKED,
+            CONTROL_NOTIFICATION,
+            KEYBOARD_LAYOUT_CHANGED,
+        ] {
+            let name = CFString::from_str(name);
+            CFNotificationCenterAddObserver(
+                center,
+                std::ptr::null(),
+                system_notification_callback,
+                CFRetained::as_ptr(&name).as_ptr() as *const c_void,
+                std::ptr::null(),
+                CF_NOTIFICATION_SUSPENSION_BEHAVIOR_DELIVER_IMMEDIATELY,
+            );
+        }
+    }
+}
+
+/// Post a `com.taearls.catshield.state` distributed notification so other apps
+/// can follow the shield. The object is the state name: "active", "warning", or "exit".
+fn broadcast_state(state: &str) {
+    let name = CFString::from_str(STATE_NOTIFICATION);
+    let object = CFString::from_str(state);
+    unsafe {
+        CFNotificationCenterPostNotification(
+            CFNotificationCenterGetDistributedCenter(),
+            CFRetained::as_ptr(&name).as_ptr() as *const c_void,
+            CFRetained::as_ptr(&object).as_ptr() as *const c_void,
+            std::ptr::null(),
+            true, // Deliver even to observers whose apps are suspended
+        );
+    }
+}
+
+/// A request to a running session, sent as a `com.taearls.catshield.control`
+/// distributed notification whose object is "<pid> <action>". The pid keeps
+/// one menu bar app from steering another's session. Stop is an ordinary
+/// unlock, so `--require-admin-exit` still applies.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SessionControl {
+    /// Pause the shield, for this many seconds if given
+    Pause(Option<u64>),
+    Resume,
+    Stop,
+    /// Add this many seconds to the timer
+    Extend(u64),
+}
+
+impl SessionControl {
+    /// The notification object addressing the session with process ID `pid`
+    fn encode(self, pid: u32) -> String {
+        match self {
+            SessionControl::Pause(None) => format!("{} pause", pid),
+            SessionControl::Pause(Some(secs)) => format!("{} pause {}", pid, secs),
+            SessionControl::Resume => format!("{} resume", pid),
+            SessionControl::Stop => format!("{} stop", pid),
+            SessionControl::Extend(secs) => format!("{} extend {}", pid, secs),
+        }
+    }
+
+    /// Parse a notification object, or `None` if it's malformed or addressed
+    /// to another process
+    fn decode(object: &str, pid: u32) -> Option<Self> {
+        let mut words = object.split_whitespace();
+        if words.next()?.parse::<u32>().ok()? != pid {
+            return None;
+        }
+        let control = match (words.next()?, words.next()) {
+            ("pause", None) => SessionControl::Pause(None),
+            ("pause", Some(secs)) => SessionControl::Pause(Some(secs.parse().ok()?)),
+            ("resume", None) => SessionControl::Resume,
+            ("stop", None) => SessionControl::Stop,
+            ("extend", Some(secs)) => SessionControl::Extend(secs.parse().ok()?),
+            _ => return None,
+        };
+        words.next().is_none().then_some(control)
+    }
+
+    fn message(self) -> ShieldMessage {
+        match self {
+            SessionControl::Pause(secs) => ShieldMessage::PauseRequested(secs),
+            SessionControl::Resume => ShieldMessage::ResumeRequested,
+            SessionControl::Stop => ShieldMessage::UnlockRequested,
+            SessionControl::Extend(secs) => ShieldMessage::ExtendRequested(secs),
+        }
+    }
+}
+
+/// Post a session control notification to the session with process ID `pid`
+fn send_session_control(control: SessionControl, pid: u32) {
+    let name = CFString::from_str(CONTROL_NOTIFICATION);
+    let object = CFString::from_str(&control.encode(pid));
+    unsafe {
+        CFNotificationCenterPostNotification(
+            CFNotificationCenterGetDistributedCenter(),
+            CFRetained::as_ptr(&name).as_ptr() as *const c_void,
+            CFRetained::as_ptr(&object).as_ptr() as *const c_void,
+            std::ptr::null(),
+            true,
+        );
+    }
+}
+
+// libc process signals, to check a session is still alive, and file locks,
+// to keep to one shield at a time
+extern "C" {
+    fn kill(pid: i32, sig: i32) -> i32;
+    fn flock(fd: i32, operation: i32) -> i32;
+}
+
+const LOCK_EX: i32 = 2;
+const LOCK_NB: i32 = 4;
+
+/// Lock taken by the one running shield. The system drops it when the process
+/// exits, however it exits, so a crash never leaves it stuck.
+fn instance_lock_path() -> PathBuf {
+    std::env::temp_dir().join("catshield.lock")
+}
+
+/// Take the exclusive lock on `path`, or `None` if another process holds it.
+/// The lock lasts as long as the returned file is open.
+fn lock_file(path: &Path) -> std::io::Result<Option<fs::File>> {
+    use std::os::fd::AsRawFd;
+    let file = fs::OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(path)?;
+    if unsafe { flock(file.as_raw_fd(), LOCK_EX | LOCK_NB) } == 0 {
+        Ok(Some(file))
+    } else {
+        Ok(None)
+    }
+}
+
+/// Make this the only running shield. Two shields would stack their overlays
+/// and fight over the event tap, so a second one refuses to start.
+///
+/// # Returns
+/// `false` if another shield is already running
+fn acquire_instance_lock() -> bool {
+    match lock_file(&instance_lock_path()) {
+        Ok(Some(file)) => {
+            // Held until the process exits
+            std::mem::forget(file);
+            true
+        }
+        Ok(None) => false,
+        Err(e) => {
+            // Better two shields than none
+            eprintln!("  ⚠️  Could not check for another running shield: {}", e);
+            true
+        }
+    }
+}
+
+/// Whether the process with ID `pid` is still running
+fn process_running(pid: u32) -> bool {
+    i32::try_from(pid).is_ok_and(|pid| unsafe { kill(pid, 0) } == 0)
+}
+
+/// The running shield's latest health report, from the session file
+fn running_session() -> Result<SessionReport, String> {
+    let report = fs::read_to_string(session_file_path())
+        .ok()
+        .and_then(|json| serde_json::from_str::<SessionReport>(&json).ok())
+        .filter(|report| process_running(report.pid));
+    report.ok_or_else(|| "Cat Shield isn't running".to_string())
+}
+
+/// What `cat_shield status` prints for a running shield's report
+fn status_lines(report: &SessionReport, now: u64) -> Vec<String> {
+    let display = session_display(Some(report), now);
+    let mode = if report.watching {
+        "Watching"
+    } else {
+        "Running"
+    };
+    let mut lines = vec![format!("{} (pid {}): {}", mode, report.pid, report.state)];
+    if report.remaining_secs.is_some() {
+        lines.push(format!("Time left: {}", display.time));
+    }
+    lines.push(display.status);
+    lines
+}
+
+/// Show the running shield's state, time left, and blocked key presses
+///
+/// # Returns
+/// Process exit code: 0 if a shield is running, 1 if not
+fn run_status() -> i32 {
+    let report = match running_session() {
+        Ok(report) => report,
+        Err(e) => {
+            println!("  {}", e);
+            return 1;
+        }
+    };
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs();
+    println!();
+    println!("  🐱 CAT SHIELD STATUS");
+    println!("  ════════════════════════════════════════");
+    for line in status_lines(&report, now) {
+        println!("  {}", line);
+    }
+    println!();
+    0
+}
+
+/// Ask the running shield to unlock, and wait a moment to see it exit
+///
+/// # Returns
+/// Process exit code: 0 if the request was sent, 1 if no shield is running
+fn run_stop() -> i32 {
+    let report = match running_session() {
+        Ok(report) => report,
+        Err(e) => {
+            eprintln!("  ✗ {}", e);
+            return 1;
+        }
+    };
+    send_session_control(SessionControl::Stop, report.pid);
+    if report.watching {
+        // Unlocking a watch session disarms it; it goes on watching
+        println!(
+            "  ✓ Asked the shield (pid {}) to disarm - it keeps watching until Ctrl+C or `kill {}`",
+            report.pid, report.pid
+        );
+        return 0;
+    }
+
+    let deadline = Instant::now() + STOP_WAIT;
+    while process_running(report.pid) && Instant::now() < deadline {
+        std::thread::sleep(Duration::from_millis(100));
+    }
+    if process_running(report.pid) {
+        println!(
+            "  ✓ Asked the shield (pid {}) to stop - it may be waiting for an administrator password",
+            report.pid
+        );
+    } else {
+        println!("  ✓ Shield stopped");
+    }
+    0
+}
+
+/// Pause the running shield, for `secs` if given
+///
+/// # Returns
+/// Process exit code: 0 if the request was sent, 1 if no shield is running
+fn run_pause(secs: Option<u64>) -> i32 {
+    let report = match running_session() {
+        Ok(report) => report,
+        Err(e) => {
+            eprintln!("  ✗ {}", e);
+            return 1;
+        }
+    };
+    send_session_control(SessionControl::Pause(secs), report.pid);
+    match secs {
+        Some(secs) => println!(
+            "  ✓ Asked the shield (pid {}) to pause for {}",
+            report.pid,
+            format_duration(secs)
+        ),
+        None => println!(
+            "  ✓ Asked the shield (pid {}) to pause - `cat_shield resume` to resume",
+            report.pid
+        ),
+    }
+    0
+}
+
+/// Resume the running shield after `run_pause`
+///
+/// # Returns
+/// Process exit code: 0 if the request was sent, 1 if no shield is running
+fn run_resume() -> i32 {
+    let report = match running_session() {
+        Ok(report) => report,
+        Err(e) => {
+            eprintln!("  ✗ {}", e);
+            return 1;
+        }
+    };
+    if !report.paused {
+        println!("  ✓ The shield (pid {}) isn't paused", report.pid);
+        return 0;
+    }
+    send_session_control(SessionControl::Resume, report.pid);
+    println!("  ✓ Asked the shield (pid {}) to resume", report.pid);
+    0
+}
+
+/// Add `secs` to the running shield's timer
+///
+/// # Returns
+/// Process exit code: 0 if the request was sent, 1 if no timed shield is running
+fn run_extend(secs: u64) -> i32 {
+    let report = match running_session() {
+        Ok(report) => report,
+        Err(e) => {
+            eprintln!("  ✗ {}", e);
+            return 1;
+        }
+    };
+    let Some(remaining) = report.remaining_secs else {
+        eprintln!("  ✗ The running shield has no timer to extend");
+        return 1;
+    };
+    send_session_control(SessionControl::Extend(secs), report.pid);
+    println!(
+        "  ✓ Asked the shield (pid {}) to add {} (about {} left)",
+        report.pid,
+        format_duration(secs),
+        format_duration(remaining + secs)
+    );
+    0
+}
+
+// libc local time, since std has no time zone support
+#[repr(C)]
+struct Tm {
+    tm_sec: i32,
+    tm_min: i32,
+    tm_hour: i32,
+    tm_mday: i32,
+    tm_mon: i32,
+    tm_year: i32,
+    tm_wday: i32,
+    tm_yday: i32,
+    tm_isdst: i32,
+    tm_gmtoff: i64,
+    tm_zone: *const std::ffi::c_char,
+}
+
+extern "C" {
+    fn time(t: *mut i64) -> i64;
+    fn localtime_r(t: *const i64, result: *mut Tm) -> *mut Tm;
+}
+
+/// `--away-note` text for the current return time
+struct AwayNote {
+    /// Who is away
+    name: String,
+    /// Return time (UNIX minutes) the text was made for
+    back_at_minute: Option<u64>,
+    text: String,
+}
+
+/// Name for `--away-note`: the one given, or the first name of the logged-in account
+fn away_note_name(given: &str) -> String {
+    let given = given.trim();
+    if !given.is_empty() {
+        return given.to_string();
+    }
+    NSFullUserName()
+        .to_string()
+        .split_whitespace()
+        .next()
+        .unwrap_or("Someone")
+        .to_string()
+}
+
+fn away_note_text(name: &str, back_at: &str) -> String {
+    format!("{} is away — back around {}", name, back_at)
+}
+
+/// Time of day in the user's locale and 12/24-hour preference (e.g., "3:45 PM")
+fn format_clock_time(unix_secs: u64) -> String {
+    let formatter = NSDateFormatter::new();
+    formatter.setDateStyle(NSDateFormatterStyle::NoStyle);
+    formatter.setTimeStyle(NSDateFormatterStyle::ShortStyle);
+    let date = NSDate::dateWithTimeIntervalSince1970(unix_secs as f64);
+    formatter.stringFromDate(&date).to_string()
+}
+
+/// Start showing `--away-note` in `window`
+///
+/// # Returns
+/// The note as first shown
+fn show_away_note(
+    mtm: MainThreadMarker,
+    window: &NSWindow,
+    name: &str,
+    screen_size: CGSize,
+) -> String {
+    AWAY_NOTE.with(|note| {
+        note.replace(Some(AwayNote {
+            name: away_note_name(name),
+            back_at_minute: None,
+            text: String::new(),
+        }))
+    });
+
+    let view = AwayNoteView::new(mtm, away_note_frame(screen_size));
+    AWAY_NOTE_VIEW.store(Retained::as_ptr(&view) as *mut c_void, Ordering::SeqCst);
+    if let Some(content_view) = window.contentView() {
+        content_view.addSubview(&view);
+    }
+    update_away_note();
+    AWAY_NOTE.with(|note| {
+        note.borrow()
+            .as_ref()
+            .map(|note| note.text.clone())
+            .unwrap_or_default()
+    })
+}
+
+/// Refresh the away note when the return time moves to another minute (the
+/// timer was extended, or paused while the Mac slept). Called from the
+/// animation timer.
+fn update_away_note() {
+    let view_ptr = AWAY_NOTE_VIEW.load(Ordering::SeqCst);
+    if view_ptr.is_null() {
+        return;
+    }
+
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs();
+    let back_at = now + get_remaining_seconds();
+    let changed = AWAY_NOTE.with(|note| {
+        let mut note = note.borrow_mut();
+        let Some(note) = note.as_mut() else {
+            return false;
+        };
+        if note.back_at_minute == Some(back_at / 60) {
+            return false;
+        }
+        note.back_at_minute = Some(back_at / 60);
+        note.text = away_note_text(&note.name, &format_clock_time(back_at));
+        true
+    });
+    if changed {
+        let view: &NSView = unsafe { &*(view_ptr as *const NSView) };
+        view.setNeedsDisplay(true);
+    }
+}
+
+/// Add the hidden "CAT DETECTED" banner to `window`
+fn setup_cat_banner(mtm: MainThreadMarker, window: &NSWindow, screen_size: CGSize) {
+    let view = CatBannerView::new(mtm, cat_banner_frame(screen_size));
+    view.setHidden(true);
+    CAT_BANNER_VIEW.store(Retained::as_ptr(&view) as *mut c_void, Ordering::SeqCst);
+    if let Some(content_view) = window.contentView() {
+        content_view.addSubview(&view);
+    }
+}
+
+/// Show the "CAT DETECTED" banner for `CAT_BANNER_DURATION`
+fn show_cat_banner() {
+    let view_ptr = CAT_BANNER_VIEW.load(Ordering::SeqCst);
+    if view_ptr.is_null() {
+        return;
+    }
+
+    let view: &NSView = unsafe { &*(view_ptr as *const NSView) };
+    view.setHidden(false);
+    CAT_BANNER_SHOWN_AT.with(|shown| shown.set(Some(Instant::now())));
+}
+
+/// Hide the "CAT DETECTED" banner once it has been up for `CAT_BANNER_DURATION`.
+/// Called from the animation timer.
+fn update_cat_banner() {
+    let expired = CAT_BANNER_SHOWN_AT.with(|shown| {
+        shown
+            .get()
+            .is_some_and(|at| at.elapsed() >= CAT_BANNER_DURATION)
+    });
+    if !expired {
+        return;
+    }
+
+    CAT_BANNER_SHOWN_AT.with(|shown| shown.set(None));
+    let view_ptr = CAT_BANNER_VIEW.load(Ordering::SeqCst);
+    if !view_ptr.is_null() {
+        let view: &NSView = unsafe { &*(view_ptr as *const NSView) };
+        view.setHidden(true);
+    }
+}
+
+/// Add the config file's overlay widgets to `window`, and move the timer if a
+/// `timer` widget says where. Widgets in the close button's corner are skipped.
+fn setup_overlay_widgets(
+    mtm: MainThreadMarker,
+    window: &NSWindow,
+    widgets: &[WidgetSpec],
+    screen_size: CGSize,
+) {
+    let Some(content_view) = window.contentView() else {
+        return;
+    };
+
+    let mut views = Vec::new();
+    for widget in widgets {
+        if widget.anchor == Anchor::TopRight {
+            eprintln!(
+                "  ⚠️  Skipping {} widget: top-right is taken by the close button",
+                widget.kind.name()
+            );
+            continue;
+        }
+        if widget.kind == WidgetKind::Timer {
+            TIMER_ANCHOR.with(|anchor| anchor.set(widget.anchor));
+            continue;
+        }
+
+        let view = WidgetView::new(
+            mtm,
+            overlay_widget_frame(widget, screen_size),
+            widget.kind.name(),
+        );
+        content_view.addSubview(&view);
+        views.push((widget.clone(), view));
+    }
+
+    if !views.is_empty() {
+        let names: Vec<&str> = views.iter().map(|(widget, _)| widget.kind.name()).collect();
+        println!("  ✓ Overlay widgets: {}", names.join(", "));
+    }
+    OVERLAY_WIDGETS.with(|slot| slot.replace(views));
+    update_overlay_widgets();
+}
+
+/// Refresh the overlay widgets' text, at most once per `OVERLAY_WIDGET_REFRESH`.
+/// Called from the animation timer.
+fn update_overlay_widgets() {
+    let due = LAST_WIDGET_REFRESH.with(|last| {
+        last.get()
+            .is_none_or(|refreshed| refreshed.elapsed() >= OVERLAY_WIDGET_REFRESH)
+    });
+    if !due || OVERLAY_WIDGETS.with(|widgets| widgets.borrow().is_empty()) {
+        return;
+    }
+    LAST_WIDGET_REFRESH.with(|last| last.set(Some(Instant::now())));
+
+    OVERLAY_WIDGETS.with(|widgets| {
+        for (widget, view) in widgets.borrow().iter() {
+            view.set_text(overlay_widget_text(widget));
+        }
+    });
+}
+
+/// What a widget shows right now; empty hides it
+fn overlay_widget_text(widget: &WidgetSpec) -> String {
+    match widget.kind {
+        WidgetKind::Clock => {
+            let now = std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap()
+                .as_secs();
+            format_clock_time(now)
+        }
+        WidgetKind::Battery => match battery_status() {
+            Some((percent, true)) => format!("⚡ {}%", percent),
+            Some((percent, false)) => format!("🔋 {}%", percent),
+            None => String::new(),
+        },
+        WidgetKind::Message => widget.text.clone().unwrap_or_default(),
+        WidgetKind::Blocked => format!("🐾 {} blocked", BLOCKED_KEY_PRESSES.load(Ordering::SeqCst)),
+        WidgetKind::NowPlaying => {
+            request_now_playing();
+            NOW_PLAYING
+                .with(|playing| playing.borrow().clone())
+                .map(|playing| format!("♪ {}", playing))
+                .unwrap_or_default()
+        }
+        WidgetKind::Timer => String::new(),
+    }
+}
+
+/// Internal battery charge in percent and whether it is charging, or `None`
+/// on a Mac without a battery
+fn battery_status() -> Option<(i64, bool)> {
+    unsafe {
+        let info = IOPSCopyPowerSourcesInfo();
+        if info.is_null() {
+            return None;
+        }
+        let list = IOPSCopyPowerSourcesList(info);
+        let mut status = None;
+        if !list.is_null() {
+            for index in 0..CFArrayGetCount(list) {
+                let description =
+                    IOPSGetPowerSourceDescription(info, CFArrayGetValueAtIndex(list, index));
+                if description.is_null() {
+                    continue;
+                }
+                let description = &*(description as *const CFDictionary<CFString, CFType>);
+                let value =
+                    |key: &'static str| description.get_unchecked(&CFString::from_static_str(key));
+                let number = |key| {
+                    value(key)
+                        .and_then(|value| value.downcast_ref::<CFNumber>())
+                        .and_then(CFNumber::as_i64)
+                };
+                let is_battery = value("Type")
+                    .and_then(|value| value.downcast_ref::<CFString>())
+                    .is_some_and(|kind| kind.to_string() == "InternalBattery");
+                if let (true, Some(current), Some(max)) = (
+                    is_battery,
+                    number("Current Capacity"),
+                    number("Max Capacity"),
+                ) {
+                    let charging = value("Is Charging").is_some_and(|value| {
+                        std::ptr::eq(value as *const CFType as *const c_void, kCFBooleanTrue)
+                    });
+                    status = Some((current * 100 / max.max(1), charging));
+                    break;
+                }
+            }
+            CFRelease(list);
+        }
+        CFRelease(info);
+        status
+    }
+}
+
+/// Ask MediaRemote for the current song, at most once per `NOW_PLAYING_REFRESH`.
+/// The answer arrives on the main queue and is shown at the next refresh.
+/// MediaRemote is private and newer macOS versions only answer some apps, so
+/// the widget may stay empty.
+fn request_now_playing() {
+    let due = LAST_NOW_PLAYING_REQUEST.with(|last| {
+        last.get()
+            .is_none_or(|requested| requested.elapsed() >= NOW_PLAYING_REFRESH)
+    });
+    if !due {
+        return;
+    }
+    LAST_NOW_PLAYING_REQUEST.with(|last| last.set(Some(Instant::now())));
+
+    type GetNowPlayingInfo =
+        unsafe extern "C" fn(*const c_void, &block2::Block<dyn Fn(*const c_void)>);
+    let get_info: GetNowPlayingInfo = unsafe {
+        let handle = dlopen(MEDIA_REMOTE_PATH.as_ptr(), RTLD_LAZY);
+        if handle.is_null() {
+            return;
+        }
+        let symbol = dlsym(handle, c"MRMediaRemoteGetNowPlayingInfo".as_ptr());
+        if symbol.is_null() {
+            return;
+        }
+        std::mem::transmute::<*mut c_void, GetNowPlayingInfo>(symbol)
+    };
+
+    let block = RcBlock::new(|info: *const c_void| {
+        let playing = (!info.is_null())
+            .then(|| {
+                let info = unsafe { &*(info as *const CFDictionary<CFString, CFType>) };
+                let text = |key: &'static str| {
+                    unsafe { info.get_unchecked(&CFString::from_static_str(key)) }
+                        .and_then(|value| value.downcast_ref::<CFString>())
+                        .map(|value| value.to_string())
+                };
+                match (
+                    text("kMRMediaRemoteNowPlayingInfoTitle"),
+                    text("kMRMediaRemoteNowPlayingInfoArtist"),
+                ) {
+                    (Some(title), Some(artist)) => Some(format!("{} — {}", title, artist)),
+                    (title, _) => title,
+                }
+            })
+            .flatten();
+        NOW_PLAYING.with(|slot| slot.replace(playing));
+    });
+    unsafe { get_info(&_dispatch_main_q as *const c_void, &block) };
+}
+
+/// Current hour of the day (0-23) in local time
+fn local_hour() -> u32 {
+    unsafe {
+        let now = time(std::ptr::null_mut());
+        let mut tm: Tm = std::mem::zeroed();
+        if localtime_r(&now, &mut tm).is_null() {
+            return 0;
+        }
+        tm.tm_hour.clamp(0, 23) as u32
+    }
+}
+
+// The main dispatch queue (`dispatch_get_main_queue()` is an inline function returning this)
+extern "C" {
+    static _dispatch_main_q: c_void;
+}
+
+// dlopen for loading private frameworks at runtime
+extern "C" {
+    fn dlopen(path: *const std::ffi::c_char, mode: i32) -> *mut c_void;
+    fn dlsym(handle: *mut c_void, symbol: *const std::ffi::c_char) -> *mut c_void;
+}
+
+const RTLD_LAZY: i32 = 0x1;
+
+/// Keyboard backlight control through the private CoreBrightness framework.
+///
+/// `KeyboardBrightnessClient` is what Control Center uses; there is no public
+/// API. It's loaded at runtime so the app still runs if the class goes away.
+struct KeyboardBacklight {
+    client: Retained<AnyObject>,
+    keyboard_id: u64,
+}
+
+impl KeyboardBacklight {
+    /// Connect to the built-in keyboard backlight, if there is one
+    fn connect() -> Option<Self> {
+        unsafe {
+            let handle = dlopen(
+                c"/System/Library/PrivateFrameworks/CoreBrightness.framework/CoreBrightness"
+                    .as_ptr(),
+                RTLD_LAZY,
+            );
+            if handle.is_null() {
+                return None;
+            }
+
+            let class = AnyClass::get(c"KeyboardBrightnessClient")?;
+            let client: Retained<AnyObject> = msg_send![class, new];
+            let ids: Option<Retained<NSArray<NSNumber>>> =
+                msg_send![&*client, copyKeyboardBacklightIDs];
+            let keyboard_id = ids?.firstObject()?.unsignedLongLongValue();
+
+            Some(Self {
+                client,
+                keyboard_id,
+            })
+        }
+    }
+
+    /// Current brightness from 0.0 to 1.0
+    fn brightness(&self) -> f32 {
+        unsafe { msg_send![&*self.client, brightnessForKeyboard: self.keyboard_id] }
+    }
+
+    fn set_brightness(&self, brightness: f32) {
+        let _: Bool = unsafe {
+            msg_send![&*self.client, setBrightness: brightness, forKeyboard: self.keyboard_id]
+        };
+    }
+}
+
+type DisplayServicesGetBrightness = unsafe extern "C" fn(u32, *mut f32) -> i32;
+type DisplayServicesSetBrightness = unsafe extern "C" fn(u32, f32) -> i32;
+
+/// Display brightness control through the private DisplayServices framework.
+///
+/// This is what the brightness keys use for built-in displays; there is no
+/// public API. External displays usually don't support it.
+struct DisplayBrightness {
+    get: DisplayServicesGetBrightness,
+    set: DisplayServicesSetBrightness,
+    display: u32,
+}
+
+impl DisplayBrightness {
+    /// Connect to the main display's brightness control, if available
+    fn connect() -> Option<Self> {
+        unsafe {
+            let handle = dlopen(
+                c"/System/Library/PrivateFrameworks/DisplayServices.framework/DisplayServices"
+                    .as_ptr(),
+                RTLD_LAZY,
+            );
+            if handle.is_null() {
+                return None;
+            }
+
+            let get = dlsym(handle, c"DisplayServicesGetBrightness".as_ptr());
+            let set = dlsym(handle, c"DisplayServicesSetBrightness".as_ptr());
+            if get.is_null() || set.is_null() {
+                return None;
+            }
+
+            Some(Self {
+                get: std::mem::transmute::<*mut c_void, DisplayServicesGetBrightness>(get),
+                set: std::mem::transmute::<*mut c_void, DisplayServicesSetBrightness>(set),
+                display: CGMainDisplayID(),
+            })
+        }
+    }
+
+    /// Current brightness from 0.0 to 1.0
+    fn brightness(&self) -> Option<f32> {
+        let mut brightness: f32 = 0.0;
+        let result = unsafe { (self.get)(self.display, &mut brightness) };
+        (result == 0).then_some(brightness)
+    }
+
+    fn set_brightness(&self, brightness: f32) -> bool {
+        unsafe { (self.set)(self.display, brightness) == 0 }
+    }
+}
+
+/// Dim the display for `--dim-display`, remembering the original brightness
+fn dim_display(target: f32) -> Result<f32, String> {
+    let display = DisplayBrightness::connect().ok_or("Display brightness control unavailable")?;
+    let original = display
+        .brightness()
+        .ok_or("This display doesn't report its brightness")?;
+
+    // Never brighten: a display already below the target stays as it is
+    let target = target.min(original);
+    if !display.set_brightness(target) {
+        return Err("Failed to set the display brightness".to_string());
+    }
+
+    DISPLAY_DIM.with(|dim| *dim.borrow_mut() = Some((display, original, target)));
+    Ok(original)
+}
+
+/// Re-apply the display dimming; macOS may reset brightness after wake
+fn reapply_display_dim() {
+    DISPLAY_DIM.with(|dim| {
+        if let Some((ref display, _, target)) = *dim.borrow() {
+            display.set_brightness(target);
+        }
+    });
+}
+
+/// Restore the display brightness saved by `dim_display`
+fn restore_display_brightness() {
+    if let Some((display, original, _)) = DISPLAY_DIM.with(|dim| dim.borrow_mut().take()) {
+        if display.set_brightness(original) {
+            println!("  ✓ Display brightness restored");
+        }
+    }
+}
+
+/// Capture all displays for `--capture-display`. Only the shield window,
+/// raised to the shielding level, draws on a captured display.
+fn capture_displays() -> Result<(), String> {
+    let result = unsafe { CGCaptureAllDisplays() };
+    if result != 0 {
+        return Err(format!("CGCaptureAllDisplays failed ({})", result));
+    }
+    DISPLAYS_CAPTURED.store(true, Ordering::SeqCst);
+    Ok(())
+}
+
+/// Capture the displays again after `release_displays`, if `--capture-display` is
+/// on and the shield isn't paused
+fn recapture_displays() {
+    if CAPTURE_DISPLAYS.load(Ordering::SeqCst)
+        && !DISPLAYS_CAPTURED.load(Ordering::SeqCst)
+        && !SHIELD_PAUSED.load(Ordering::SeqCst)
+    {
+        if let Err(e) = capture_displays() {
+            eprintln!("  ⚠️  Could not capture the displays: {}", e);
+        }
+    }
+}
+
+/// Give the displays back to the system
+fn release_displays() {
+    if DISPLAYS_CAPTURED.swap(false, Ordering::SeqCst) {
+        unsafe { CGReleaseAllDisplays() };
+    }
+}
+
+/// Window level for the overlay: above everything on a captured display,
+/// otherwise the screen saver level
+fn shield_window_level() -> isize {
+    if DISPLAYS_CAPTURED.load(Ordering::SeqCst) {
+        unsafe { CGShieldingWindowLevel() as isize }
+    } else {
+        NS_SCREEN_SAVER_WINDOW_LEVEL
+    }
+}
+
+/// Output volume and mute state of the default output device.
+///
+/// Devices without a mute control (some USB and HDMI outputs) are silenced by
+/// setting the volume to zero instead.
+#[derive(Debug, Clone, Copy)]
+struct AudioOutput {
+    device: u32,
+    volume: Option<f32>,
+    muted: Option<bool>,
+}
+
+impl AudioOutput {
+    /// Snapshot the current default output device
+    fn current() -> Option<Self> {
+        let address = AudioObjectPropertyAddress {
+            selector: K_AUDIO_HARDWARE_PROPERTY_DEFAULT_OUTPUT_DEVICE,
+            scope: K_AUDIO_OBJECT_PROPERTY_SCOPE_GLOBAL,
+            element: K_AUDIO_OBJECT_PROPERTY_ELEMENT_MAIN,
+        };
+        let mut device: u32 = 0;
+        let mut size = std::mem::size_of::<u32>() as u32;
+        let status = unsafe {
+            AudioObjectGetPropertyData(
+                K_AUDIO_OBJECT_SYSTEM_OBJECT,
+                &address,
+                0,
+                std::ptr::null(),
+                &mut size,
+                &mut device as *mut u32 as *mut c_void,
+            )
+        };
+        if status != 0 || device == 0 {
+            return None;
+        }
+
+        Some(Self {
+            device,
+            volume: Self::read_volume(device),
+            muted: Self::read_muted(device),
+        })
+    }
+
+    fn volume_address() -> AudioObjectPropertyAddress {
+        AudioObjectPropertyAddress {
+            selector: K_AUDIO_HARDWARE_SERVICE_VIRTUAL_MAIN_VOLUME,
+            scope: K_AUDIO_DEVICE_PROPERTY_SCOPE_OUTPUT,
+            element: K_AUDIO_OBJECT_PROPERTY_ELEMENT_MAIN,
+        }
+    }
+
+    fn mute_address() -> AudioObjectPropertyAddress {
+        AudioObjectPropertyAddress {
+            selector: K_AUDIO_DEVICE_PROPERTY_MUTE,
+            scope: K_AUDIO_DEVICE_PROPERTY_SCOPE_OUTPUT,
+            element: K_AUDIO_OBJECT_PROPERTY_ELEMENT_MAIN,
+        }
+    }
+
+    fn read_volume(device: u32) -> Option<f32> {
+        let mut volume: f32 = 0.0;
+        let mut size = std::mem::size_of::<f32>() as u32;
+        let status = unsafe {
+            AudioHardwareServiceGetPropertyData(
+                device,
+                &Self::volume_address(),
+                0,
+                std::ptr::null(),
+                &mut size,
+                &mut volume as *mut f32 as *mut c_void,
+            )
+        };
+        (status == 0).then_some(volume)
+    }
+
+    fn read_muted(device: u32) -> Option<bool> {
+        let mut muted: u32 = 0;
+        let mut size = std::mem::size_of::<u32>() as u32;
+        let status = unsafe {
+            AudioObjectGetPropertyData(
+                device,
+                &Self::mute_address(),
+                0,
+                std::ptr::null(),
+                &mut size,
+                &mut muted as *mut u32 as *mut c_void,
+            )
+        };
+        (status == 0).then_some(muted != 0)
+    }
+
+    fn set_volume(&self, volume: f32) -> bool {
+        let status = unsafe {
+            AudioHardwareServiceSetPropertyData(
+                self.device,
+                &Self::volume_address(),
+                0,
+                std::ptr::null(),
+                std::mem::size_of::<f32>() as u32,
+                &volume as *const f32 as *const c_void,
+            )
+        };
+        status == 0
+    }
+
+    fn set_muted(&self, muted: bool) -> bool {
+        let value: u32 = muted.into();
+        let status = unsafe {
+            AudioObjectSetPropertyData(
+                self.device,
+                &Self::mute_address(),
+                0,
+                std::ptr::null(),
+                std::mem::size_of::<u32>() as u32,
+                &value as *const u32 as *const c_void,
+            )
+        };
+        status == 0
+    }
+
+    /// Silence the device, preferring its mute control
+    fn silence(&self) -> bool {
+        (self.muted.is_some() && self.set_muted(true))
+            || (self.volume.is_some() && self.set_volume(0.0))
+    }
+
+    /// Whether the device's volume or mute state differs from this snapshot
+    fn has_changed(&self) -> bool {
+        let volume_changed = self.volume.is_some_and(|volume| {
+            Self::read_volume(self.device).is_some_and(|now| (now - volume).abs() > 0.001)
+        });
+        let mute_changed = self
+            .muted
+            .is_some_and(|muted| Self::read_muted(self.device).is_some_and(|now| now != muted));
+        volume_changed || mute_changed
+    }
+
+    /// Put the device back to the snapshotted volume and mute state
+    fn restore(&self) -> bool {
+        let volume_ok = self.volume.is_none_or(|volume| self.set_volume(volume));
+        let mute_ok = self.muted.is_none_or(|muted| self.set_muted(muted));
+        volume_ok && mute_ok
+    }
+}
+
+/// Mute the default output device for `--mute`, remembering its state
+fn mute_audio() -> Result<(), String> {
+    let output = AudioOutput::current().ok_or("No default audio output device")?;
+    if !output.silence() {
+        return Err("The output device has no volume or mute control".to_string());
+    }
+
+    SAVED_AUDIO_OUTPUT.with(|saved| saved.set(Some(output)));
+    Ok(())
+}
+
+/// Restore the volume and mute state saved by `mute_audio`
+fn restore_audio() {
+    if let Some(output) = SAVED_AUDIO_OUTPUT.with(|saved| saved.take()) {
+        if output.restore() {
+            println!("  ✓ Audio output restored");
+        } else {
+            eprintln!("  ⚠️  Could not fully restore the audio output");
+        }
+    }
+}
+
+/// Called by CoreAudio (on its own thread) when the locked device's volume or mute changes
+unsafe extern "C" fn volume_lock_listener(
+    _object_id: u32,
+    _address_count: u32,
+    _addresses: *const AudioObjectPropertyAddress,
+    client_data: *mut c_void,
+) -> i32 {
+    catch_ffi_panic(|| {
+        let locked = &*(client_data as *const AudioOutput);
+        // Our own restore triggers this listener too; only act on real changes
+        if locked.has_changed() && locked.restore() {
+            println!("  🔒 Volume change reverted");
+        }
+        0
+    })
+}
+
+/// Lock the default output device's volume and mute state for `--lock-volume`
+fn lock_volume() -> Result<(), String> {
+    let output = AudioOutput::current().ok_or("No default audio output device")?;
+    if output.volume.is_none() && output.muted.is_none() {
+        return Err("The output device has no volume or mute control".to_string());
+    }
+
+    // Lives for the rest of the process; the listeners are never removed
+    let locked = Box::into_raw(Box::new(output)) as *mut c_void;
+    let mut registered = false;
+    unsafe {
+        if output.volume.is_some() {
+            registered |= AudioHardwareServiceAddPropertyListener(
+                output.device,
+                &AudioOutput::volume_address(),
+                volume_lock_listener,
+                locked,
+            ) == 0;
+        }
+        if output.muted.is_some() {
+            registered |= AudioObjectAddPropertyListener(
+                output.device,
+                &AudioOutput::mute_address(),
+                volume_lock_listener,
+                locked,
+            ) == 0;
+        }
+    }
+
+    if registered {
+        Ok(())
+    } else {
+        Err("Could not watch the output device for changes".to_string())
+    }
+}
+
+/// Show the pre-expiry warning the ways `--warn-action` asked for
+fn run_warn_actions(remaining: u64) {
+    let actions = WARN_ACTIONS.with(|actions| actions.borrow().clone());
+    for action in actions {
+        match action {
+            WarnAction::Sound => {
+                if let Some(sound) = NSSound::soundNamed(&NSString::from_str(WARNING_SOUND_NAME)) {
+                    sound.play();
+                }
+            }
+            WarnAction::Notify => send_notification(
+                "Cat Shield",
+                &format!("The shield comes down in {} seconds", remaining),
+            ),
+            WarnAction::Flash => start_overlay_flash(),
+        }
+    }
+}
+
+/// Start the `--remind-every` reminders
+fn start_reminders(interval_secs: u64, aloud: bool) {
+    REMIND_ALOUD.store(aloud, Ordering::SeqCst);
+    let timer = RunLoopTimer::schedule(interval_secs as f64, reminder_timer_callback);
+    REMINDER_TIMER.with(|slot| slot.replace(timer));
+}
+
+unsafe extern "C-unwind" fn reminder_timer_callback(
+    _timer: *mut CFRunLoopTimer,
+    _info: *mut c_void,
+) {
+    catch_ffi_panic(|| post_message(ShieldMessage::ReminderDue))
+}
+
+/// Announce the time left. Skipped once the one-minute warning has taken
+/// over, and while unlocking or exiting.
+fn send_reminder() {
+    if shield_state() != ShieldState::Active {
+        return;
+    }
+    let remaining = get_remaining_seconds();
+    if remaining <= WARNING_SECONDS {
+        return;
+    }
+
+    let message = format!("{} of shield time left", describe_remaining(remaining));
+    println!("  ⏳ {}", message);
+    if REMIND_ALOUD.load(Ordering::SeqCst) {
+        _ = process::Command::new("say")
+            .arg(format!("Cat Shield: {}", message))
+            .spawn();
+    } else {
+        send_notification("Cat Shield", &message);
+    }
+}
+
+/// Time left in words, to the nearest minute (e.g., "1 hour 5 minutes"), for
+/// reminders that are read or spoken rather than glanced at
+fn describe_remaining(secs: u64) -> String {
+    let minutes = (secs + 30) / 60;
+    let unit =
+        |count: u64, name: &str| format!("{} {}{}", count, name, if count == 1 { "" } else { "s" });
+    match (minutes / 60, minutes % 60) {
+        (0, minutes) => unit(minutes, "minute"),
+        (hours, 0) => unit(hours, "hour"),
+        (hours, minutes) => format!("{} {}", unit(hours, "hour"), unit(minutes, "minute")),
+    }
+}
+
+/// Start flashing the overlay background (ignored if a flash is already running)
+fn start_overlay_flash() {
+    let window_ptr = OVERLAY_WINDOW.load(Ordering::SeqCst);
+    if window_ptr.is_null() || OVERLAY_FLASH.with(|flash| flash.borrow().is_some()) {
+        return;
+    }
+    let window: &NSWindow = unsafe { &*(window_ptr as *const NSWindow) };
+    let original = window.backgroundColor();
+    OVERLAY_FLASH.with(|flash| flash.replace(Some((Instant::now(), original))));
+}
+
+/// Advance a running overlay flash; called from the animation timer
+fn update_overlay_flash() {
+    let window_ptr = OVERLAY_WINDOW.load(Ordering::SeqCst);
+    if window_ptr.is_null() {
+        return;
+    }
+
+    OVERLAY_FLASH.with(|flash| {
+        let mut flash = flash.borrow_mut();
+        let Some((start, ref original)) = *flash else {
+            return;
+        };
+
+        let elapsed = start.elapsed();
+        if elapsed >= WARNING_FLASH_DURATION {
+            for_each_overlay_window(|window| window.setBackgroundColor(Some(original)));
+            *flash = None;
+            return;
+        }
+
+        let half_period = WARNING_FLASH_PERIOD.as_millis() / 2;
+        let on = (elapsed.as_millis() / half_period).is_multiple_of(2);
+        let color = if on {
+            warning_flash_color()
+        } else {
+            original.clone()
+        };
+        for_each_overlay_window(|window| window.setBackgroundColor(Some(&color)));
+    });
+}
+
+/// Start flashing the keyboard backlight (ignored if a flash is already running)
+fn start_backlight_flash() {
+    KEYBOARD_BACKLIGHT.with(|backlight| {
+        let backlight = backlight.borrow();
+        let Some(backlight) = backlight.as_ref() else {
+            return;
+        };
+        if BACKLIGHT_FLASH.with(|flash| flash.get()).is_none() {
+            let original = backlight.brightness();
+            BACKLIGHT_FLASH.with(|flash| flash.set(Some((Instant::now(), original))));
+        }
+    });
+}
+
+/// Advance a running backlight flash; called from the animation timer
+fn update_backlight_flash() {
+    let Some((start, original)) = BACKLIGHT_FLASH.with(|flash| flash.get()) else {
+        return;
+    };
+
+    KEYBOARD_BACKLIGHT.with(|backlight| {
+        let backlight = backlight.borrow();
+        let Some(backlight) = backlight.as_ref() else {
+            return;
+        };
+
+        let elapsed = start.elapsed();
+        if elapsed >= BACKLIGHT_FLASH_DURATION {
+            backlight.set_brightness(original);
+            BACKLIGHT_FLASH.with(|flash| flash.set(None));
+            return;
+        }
+
+        let half_period = BACKLIGHT_FLASH_PERIOD.as_millis() / 2;
+        let on = (elapsed.as_millis() / half_period).is_multiple_of(2);
+        backlight.set_brightness(if on { 1.0 } else { 0.0 });
+    });
+}
+
+define_class!(
+    // Receives sound classification results; called on an analysis queue, not the main thread
+    #[unsafe(super(NSObject))]
+    #[name = "MeowObserver"]
+    struct MeowObserver;
+
+    unsafe impl NSObjectProtocol for MeowObserver {}
+
+    unsafe impl SNResultsObserving for MeowObserver {
+        #[unsafe(method(request:didProduceResult:))]
+        fn request_did_produce_result(
+            &self,
+            _request: &ProtocolObject<dyn SNRequest>,
+            result: &ProtocolObject<dyn SNResult>,
+        ) {
+            let result: &AnyObject = result.as_ref();
+            let Some(result) = result.downcast_ref::<SNClassificationResult>() else {
+                return;
+            };
+            let meow = unsafe {
+                result.classificationForIdentifier(&NSString::from_str(MEOW_CLASSIFICATION))
+            };
+            if let Some(meow) = meow {
+                let confidence = unsafe { meow.confidence() };
+                if confidence >= MEOW_MIN_CONFIDENCE {
+                    post_message(ShieldMessage::MeowHeard(confidence));
+                }
+            }
+        }
+    }
+);
+
+impl MeowObserver {
+    fn new() -> Retained<Self> {
+        unsafe { msg_send![Self::class(), new] }
+    }
+}
+
+/// Start listening on the default microphone.
+///
+/// Audio buffers arrive on an audio thread in the AVAudioEngine tap, where they
+/// are fed to the meow classifier and/or measured for loudness. Results are
+/// posted to the coordinator on the main thread.
+/// Requires Microphone permission for the app (or the terminal it runs in).
+fn setup_microphone(meow_detect: bool, loudness_threshold: Option<f64>) -> Result<(), String> {
+    unsafe {
+        let engine = AVAudioEngine::new();
+        let input = engine.inputNode();
+        let format = input.outputFormatForBus(0);
+        if format.sampleRate() <= 0.0 {
+            return Err("No microphone input available".to_string());
+        }
+
+        let analyzer = if meow_detect {
+            Some(create_meow_analyzer(&format)?)
+        } else {
+            None
+        };
+
+        let tap_analyzer = analyzer.clone();
+        let loudness = RefCell::new(LoudnessTracker::default());
+        let tap = RcBlock::new(
+            move |buffer: NonNull<AVAudioPCMBuffer>, when: NonNull<AVAudioTime>| {
+                let buffer = buffer.as_ref();
+                if let Some(ref analyzer) = tap_analyzer {
+                    analyzer.analyzeAudioBuffer_atAudioFramePosition(
+                        buffer,
+                        when.as_ref().sampleTime(),
+                    );
+                }
+
+                if let Some(threshold) = loudness_threshold {
+                    let channels = buffer.floatChannelData();
+                    if channels.is_null() {
+                        return;
+                    }
+                    // First channel only; the built-in microphone is mono anyway
+                    let samples = (*channels).as_ptr();
+                    let stride = buffer.stride();
+                    let level = rms_dbfs(
+                        (0..buffer.frameLength() as usize).map(|i| *samples.add(i * stride)),
+                    );
+                    if loudness
+                        .borrow_mut()
+                        .record(level, threshold, Instant::now())
+                    {
+                        post_message(ShieldMessage::LoudSound(level));
+                    }
+                }
+            },
+        );
+        input.installTapOnBus_bufferSize_format_block(
+            0,
+            MICROPHONE_BUFFER_FRAMES,
+            Some(&format),
+            RcBlock::as_ptr(&tap),
+        );
+
+        engine
+            .startAndReturnError()
+            .map_err(|e| e.localizedDescription().to_string())?;
+
+        // The engine must keep running; keep it (and the analyzer) for the
+        // lifetime of the process.
+        std::mem::forget(engine);
+        std::mem::forget(analyzer);
+    }
+
+    Ok(())
+}
+
+/// Create a sound analyzer that reports meows to `MeowObserver`
+unsafe fn create_meow_analyzer(
+    format: &AVAudioFormat,
+) -> Result<Retained<SNAudioStreamAnalyzer>, String> {
+    let identifier = SNClassifierIdentifierVersion1
+        .ok_or("Sound classifier is not available on this macOS version")?;
+    let request = SNClassifySoundRequest::initWithClassifierIdentifier_error(
+        SNClassifySoundRequest::alloc(),
+        identifier,
+    )
+    .map_err(|e| e.localizedDescription().to_string())?;
+
+    let analyzer = SNAudioStreamAnalyzer::initWithFormat(SNAudioStreamAnalyzer::alloc(), format);
+    let observer = MeowObserver::new();
+    analyzer
+        .addRequest_withObserver_error(
+            ProtocolObject::from_ref(&*request),
+            ProtocolObject::from_ref(&*observer),
+        )
+        .map_err(|e| e.localizedDescription().to_string())?;
+
+    // The analyzer only weakly references its observer
+    std::mem::forget(observer);
+    std::mem::forget(request);
+
+    Ok(analyzer)
+}
+
+/// Play the deterrent sound
+fn play_deterrent_sound() {
+    if let Some(sound) = NSSound::soundNamed(&NSString::from_str(DETERRENT_SOUND_NAME)) {
+        sound.play();
+    }
+}
+
+/// React to a meow reported by the sound classifier
+fn handle_meow(confidence: f64) {
+    let now = Instant::now();
+    let is_new_meow = LAST_MEOW
+        .with(|last| last.get())
+        .is_none_or(|last| now.duration_since(last) >= MEOW_COOLDOWN);
+    if !is_new_meow {
+        return;
+    }
+    LAST_MEOW.with(|last| last.set(Some(now)));
+
+    println!();
+    println!("  🐈 Meow detected ({:.0}% confidence)", confidence * 100.0);
+
+    if MEOW_DETERRENT_ENABLED.load(Ordering::SeqCst) {
+        play_deterrent_sound();
+    }
+    if WATCH_MODE.load(Ordering::SeqCst) {
+        arm_blocking(ArmCause::UntilUnlocked, "cat heard nearby");
+    }
+}
+
+/// React to a sustained loud sound reported from the microphone tap
+fn handle_loud_sound(level: f64) {
+    println!();
+    println!("  🔊 Sustained loud sound detected ({:.0} dBFS)", level);
+
+    match LOUDNESS_ACTION.with(|action| action.get()) {
+        LoudnessAction::Arm => arm_blocking(ArmCause::UntilUnlocked, "loud sound nearby"),
+        LoudnessAction::Notify => send_notification(
+            "Cat Shield",
+            &format!("Loud sound near your Mac ({:.0} dBFS)", level),
+        ),
+    }
+}
+
+/// Show a macOS notification.
+///
+/// Goes through `osascript` because the notification APIs need an app bundle;
+/// failures are ignored since notifications are best-effort.
+fn send_notification(title: &str, message: &str) {
+    let escape = |s: &str| s.replace('\\', "\\\\").replace('"', "\\\"");
+    let script = format!(
+        "display notification \"{}\" with title \"{}\"",
+        escape(message),
+        escape(title)
+    );
+    _ = process::Command::new("osascript")
+        .arg("-e")
+        .arg(script)
+        .spawn();
+}
+
+/// Send `message` to unified logging, so lifecycle events and errors show up in
+/// Console.app even when the shield wasn't started from a terminal.
+/// Safe to call from any thread.
+fn os_log(log_type: u8, message: &str) {
+    let mut log = OS_LOG.load(Ordering::SeqCst);
+    if log.is_null() {
+        log = unsafe { os_log_create(OS_LOG_SUBSYSTEM.as_ptr(), OS_LOG_CATEGORY.as_ptr()) };
+        OS_LOG.store(log, Ordering::SeqCst);
+    }
+    let Ok(message) = CString::new(message) else {
+        return;
+    };
+
+    // The argument buffer `os_log` would build: a summary byte (has non-scalar
+    // arguments), the argument count, then the argument's descriptor (public
+    // string), its size, and the pointer itself
+    let mut buf = [0u8; 12];
+    buf[..4].copy_from_slice(&[0x02, 1, 0x22, 8]);
+    buf[4..].copy_from_slice(&(message.as_ptr() as u64).to_ne_bytes());
+    unsafe {
+        _os_log_impl(
+            &raw const __dso_handle as *const c_void,
+            log,
+            log_type,
+            OS_LOG_FORMAT.as_ptr().cast(),
+            buf.as_ptr(),
+            buf.len() as u32,
+        );
+    }
+}
+
+/// Log a lifecycle event to unified logging
+fn log_event(message: &str) {
+    os_log(OS_LOG_TYPE_DEFAULT, message);
+}
+
+/// Log an error to unified logging
+fn log_error(message: &str) {
+    os_log(OS_LOG_TYPE_ERROR, message);
+}
+
+/// A compiled hooks script and the engine that runs it
+struct ScriptHooks {
+    engine: rhai::Engine,
+    ast: rhai::AST,
+}
+
+impl ScriptHooks {
+    /// Path to the hooks script (~/.config/catshield/hooks.rhai)
+    fn hooks_path() -> Option<PathBuf> {
+        Config::config_path().map(|path| path.with_file_name(HOOKS_FILE_NAME))
+    }
+
+    /// Compile `source` with `engine`, capping how much work and memory a hook
+    /// may use so a runaway script can't stall the main thread (and with it the
+    /// event tap) or exhaust memory before its operation budget runs out
+    fn compile(mut engine: rhai::Engine, source: &str) -> Result<Self, String> {
+        engine.set_max_operations(HOOK_MAX_OPERATIONS);
+        engine.set_max_call_levels(HOOK_MAX_CALL_LEVELS);
+        engine.set_max_string_size(HOOK_MAX_STRING_SIZE);
+        engine.set_max_array_size(HOOK_MAX_COLLECTION_SIZE);
+        engine.set_max_map_size(HOOK_MAX_COLLECTION_SIZE);
+        engine.set_max_expr_depths(HOOK_MAX_EXPR_DEPTH, HOOK_MAX_FUNCTION_EXPR_DEPTH);
+        let ast = engine.compile(source).map_err(|e| e.to_string())?;
+        Ok(ScriptHooks { engine, ast })
+    }
+
+    /// Call `hook` if the script defines it.
+    ///
+    /// # Returns
+    /// `Ok(false)` if the script doesn't define the hook
+    fn call(&self, hook: &str, args: impl rhai::FuncArgs) -> Result<bool, String> {
+        if !self
+            .ast
+            .iter_functions()
+            .any(|function| function.name == hook)
+        {
+            return Ok(false);
+        }
+        self.engine
+            .call_fn::<rhai::Dynamic>(&mut rhai::Scope::new(), &self.ast, hook, args)
+            .map(|_| true)
+            .map_err(|e| e.to_string())
+    }
+}
+
+/// Script engine with the hook API. Rhai has no file, process, or network
+/// access of its own, so these functions are all a script can do.
+fn hook_engine() -> rhai::Engine {
+    let mut engine = rhai::Engine::new();
+    engine.register_fn("notify", |message: &str| {
+        send_notification("Cat Shield", message)
+    });
+    engine.register_fn("play_sound", |name: &str| {
+        if let Some(sound) = NSSound::soundNamed(&NSString::from_str(name)) {
+            sound.play();
+        }
+    });
+    // Deferred through the message bus, like any other extend request
+    engine.register_fn("extend_timer", |secs: i64| {
+        if secs > 0 {
+            post_message(ShieldMessage::ExtendRequested(secs as u64));
+        }
+    });
+    engine
+}
+
+/// Load the hooks script, if there is one, and report the outcome
+fn load_hooks() {
+    let Some(path) = ScriptHooks::hooks_path().filter(|path| path.exists()) else {
+        return;
+    };
+
+    let hooks = fs::read_to_string(&path)
+        .map_err(|e| e.to_string())
+        .and_then(|source| ScriptHooks::compile(hook_engine(), &source));
+    match hooks {
+        Ok(hooks) => {
+            SCRIPT_HOOKS.with(|script| *script.borrow_mut() = Some(hooks));
+            println!("  ✓ Hooks loaded from {}", path.display());
+        }
+        Err(e) => eprintln!("  ⚠️  Failed to load hooks from {}: {}", path.display(), e),
+    }
+}
+
+/// Run a hook from the hooks script, if one is loaded and defines it
+fn run_hook(hook: &str, args: impl rhai::FuncArgs) {
+    SCRIPT_HOOKS.with(|script| {
+        let Some(hooks) = script.borrow().as_ref().map(|hooks| hooks.call(hook, args)) else {
+            return;
+        };
+        if let Err(e) = hooks {
+            eprintln!("  ⚠️  Hook {} failed: {}", hook, e);
+            log_error(&format!("Hook {} failed: {}", hook, e));
+        }
+    });
+}
+
+/// Keyboard activity over one plugin sample interval.
+///
+/// Plugins only ever see the two counts, never which keys were pressed or in
+/// what order.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+struct EventRates {
+    key_presses: u32,
+    /// One bit per virtual keycode seen this interval
+    keys_seen: u128,
+}
+
+impl EventRates {
+    fn record_key(&mut self, keycode: i64) {
+        self.key_presses = self.key_presses.saturating_add(1);
+        if let Ok(bit) = u32::try_from(keycode) {
+            self.keys_seen |= 1u128.checked_shl(bit).unwrap_or(0);
+        }
+    }
+
+    fn distinct_keys(&self) -> u32 {
+        self.keys_seen.count_ones()
+    }
+}
+
+/// What a detection plugin's `sample` export asks for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum PluginSignal {
+    Nothing,
+    Arm,
+    Alert,
+}
+
+impl PluginSignal {
+    fn from_code(code: i32) -> Option<Self> {
+        match code {
+            0 => Some(PluginSignal::Nothing),
+            1 => Some(PluginSignal::Arm),
+            2 => Some(PluginSignal::Alert),
+            _ => None,
+        }
+    }
+}
+
+/// A WASM detection plugin.
+///
+/// The module is instantiated with no imports, so it has no way to reach
+/// files, the network, or the rest of the process: it sees the event rates it
+/// is handed and answers with a signal. Every call is metered with fuel so a
+/// plugin that loops forever traps instead of stalling the main thread, and
+/// its linear memory is capped so one that keeps growing traps instead of
+/// eating the machine's RAM.
+struct DetectionPlugin {
+    name: String,
+    store: wasmi::Store<wasmi::StoreLimits>,
+    sample: wasmi::TypedFunc<(i32, i32), i32>,
+}
+
+impl DetectionPlugin {
+    /// Directory plugins are loaded from (~/.config/catshield/plugins)
+    fn plugins_dir() -> Option<PathBuf> {
+        Config::config_path().map(|path| path.with_file_name(PLUGINS_DIR_NAME))
+    }
+
+    /// Instantiate `wasm` (binary or text format) and look up its `sample` export
+    fn load(name: &str, wasm: &[u8]) -> Result<Self, String> {
+        let mut config = wasmi::Config::default();
+        config.consume_fuel(true);
+        let engine = wasmi::Engine::new(&config);
+        let module = wasmi::Module::new(&engine, wasm).map_err(|e| e.to_string())?;
+
+        let limits = wasmi::StoreLimitsBuilder::new()
+            .memory_size(PLUGIN_MAX_MEMORY_BYTES)
+            .instances(1)
+            .trap_on_grow_failure(true)
+            .build();
+        let mut store = wasmi::Store::new(&engine, limits);
+        store.limiter(|limits| limits);
+        // The start function, if any, is metered too
+        store
+            .set_fuel(PLUGIN_FUEL_PER_CALL)
+            .map_err(|e| e.to_string())?;
+        let instance = wasmi::Linker::new(&engine)
+            .instantiate_and_start(&mut store, &module)
+            .map_err(|e| e.to_string())?;
+        let sample = instance
+            .get_typed_func::<(i32, i32), i32>(&store, PLUGIN_SAMPLE_EXPORT)
+            .map_err(|e| format!("{}: {}", PLUGIN_SAMPLE_EXPORT, e))?;
+
+        Ok(DetectionPlugin {
+            name: name.to_string(),
+            store,
+            sample,
+        })
+    }
+
+    /// Hand one interval's event rates to the plugin
+    fn sample(&mut self, rates: EventRates) -> Result<PluginSignal, String> {
+        self.store
+            .set_fuel(PLUGIN_FUEL_PER_CALL)
+            .map_err(|e| e.to_string())?;
+        let key_presses = i32::try_from(rates.key_presses).unwrap_or(i32::MAX);
+        let distinct_keys = rates.distinct_keys() as i32;
+        let code = self
+            .sample
+            .call(&mut self.store, (key_presses, distinct_keys))
+            .map_err(|e| e.to_string())?;
+        PluginSignal::from_code(code).ok_or_else(|| format!("unknown signal {}", code))
+    }
+}
+
+/// Load every module in the plugins directory and report the outcome
+fn load_detection_plugins() {
+    let Some(entries) = DetectionPlugin::plugins_dir().and_then(|dir| fs::read_dir(dir).ok())
+    else {
+        return;
+    };
+    let mut paths: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == "wasm"))
+        .collect();
+    paths.sort();
+
+    for path in paths {
+        let name = path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let plugin = fs::read(&path)
+            .map_err(|e| e.to_string())
+            .and_then(|wasm| DetectionPlugin::load(&name, &wasm));
+        match plugin {
+            Ok(plugin) => {
+                DETECTION_PLUGINS.with(|plugins| plugins.borrow_mut().push(plugin));
+                println!("  ✓ Detection plugin loaded: {}", name);
+            }
+            Err(e) => eprintln!("  ⚠️  Failed to load plugin {}: {}", path.display(), e),
+        }
+    }
+}
+
+/// Hand the last interval's keyboard activity to each detection plugin and act
+/// on what they signal. Throttled; called from the animation timer.
+fn sample_detection_plugins() {
+    if DETECTION_PLUGINS.with(|plugins| plugins.borrow().is_empty()) {
+        return;
+    }
+    let due = LAST_PLUGIN_SAMPLE.with(|last| {
+        let due = last
+            .get()
+            .is_none_or(|sampled| sampled.elapsed() >= PLUGIN_SAMPLE_INTERVAL);
+        if due {
+            last.set(Some(Instant::now()));
+        }
+        due
+    });
+    if !due {
+        return;
+    }
+
+    let rates = EVENT_RATES.with(Cell::take);
+    // A plugin that traps (or runs out of fuel) is unloaded rather than retried
+    let signals: Vec<(String, PluginSignal)> = DETECTION_PLUGINS.with(|plugins| {
+        let mut signals = Vec::new();
+        plugins
+            .borrow_mut()
+            .retain_mut(|plugin| match plugin.sample(rates) {
+                Ok(PluginSignal::Nothing) => true,
+                Ok(signal) => {
+                    signals.push((plugin.name.clone(), signal));
+                    true
+                }
+                Err(e) => {
+                    eprintln!(
+                        "  ⚠️  Plugin {} failed and was unloaded: {}",
+                        plugin.name, e
+                    );
+                    log_error(&format!(
+                        "Plugin {} failed and was unloaded: {}",
+                        plugin.name, e
+                    ));
+                    false
+                }
+            });
+        signals
+    });
+
+    for (name, signal) in signals {
+        match signal {
+            PluginSignal::Nothing => {}
+            PluginSignal::Arm => {
+                if WATCH_MODE.load(Ordering::SeqCst) {
+                    arm_blocking(ArmCause::UntilUnlocked, &format!("{} plugin", name));
+                }
+            }
+            PluginSignal::Alert => {
+                println!();
+                println!("  🧩 Alert from the {} plugin", name);
+                send_notification("Cat Shield", &format!("Alert from the {} plugin", name));
+            }
+        }
+    }
+}
+
+/// Start the microphone listeners requested on the command line and report the outcome
+fn enable_microphone(args: &Args) {
+    if !args.meow_detect && args.loudness_threshold.is_none() {
+        return;
+    }
+
+    match setup_microphone(args.meow_detect, args.loudness_threshold) {
+        Ok(()) => {
+            if args.meow_detect {
+                MEOW_DETERRENT_ENABLED.store(args.meow_deterrent, Ordering::SeqCst);
+                println!("  ✓ Meow detection: listening on the microphone");
+                if args.meow_deterrent {
+                    println!(
+                        "  ✓ Meow deterrent: meows play the \"{}\" sound",
+                        DETERRENT_SOUND_NAME
+                    );
+                }
+            }
+            if let Some(threshold) = args.loudness_threshold {
+                LOUDNESS_ACTION.with(|action| action.set(args.loudness_action));
+                println!(
+                    "  ✓ Loudness trigger: {} after {}s above {:.0} dBFS",
+                    args.loudness_action.name(),
+                    LOUDNESS_SUSTAIN.as_secs(),
+                    threshold
+                );
+            }
+        }
+        Err(e) => {
+            eprintln!("  ⚠️  Microphone unavailable: {}", e);
+        }
+    }
+}
+
+/// Callback for the CGEventTap - intercepts and blocks events
+unsafe extern "C-unwind" fn event_tap_callback(
+    _proxy: CGEventTapProxy,
+    event_type: CGEventType,
+    event: NonNull<CGEvent>,
+    _user_info: *mut c_void,
+) -> *mut CGEvent {
+    catch_ffi_panic(|| {
+        // Handle tap disabled event (system can disable taps if they're too slow)
+        if event_type == CGEventType::TapDisabledByTimeout
+            || event_type == CGEventType::TapDisabledByUserInput
+        {
+            eprintln!("  ⚠️  Event tap was disabled, re-enabling...");
+            TAP_INCIDENTS.fetch_add(1, Ordering::SeqCst);
+            log_error("Event tap was disabled by the system, re-enabling");
+            // Re-enable the tap using the stored pointer
+            let tap = EVENT_TAP.load(Ordering::SeqCst);
+            if !tap.is_null() {
+                CGEventTapEnable(tap, true);
+            }
+            return event.as_ptr();
+        }
+
+        // Detection plugins see keyboard activity whether or not blocking is armed
+        if event_type == CGEventType::KeyDown {
+            let keycode = CGEvent::integer_value_field(
+                Some(event.as_ref()),
+                CGEventField::KeyboardEventKeycode,
+            );
+            EVENT_RATES.with(|rates| {
+                let mut updated = rates.get();
+                updated.record_key(keycode);
+                rates.set(updated);
+            });
+        }
+
+        // Watch mode while disarmed: let everything through
+        if !BLOCKING_ARMED.load(Ordering::SeqCst) {
+            return event.as_ptr();
+        }
+
+        if origin_passes_through(event.as_ref()) {
+            return event.as_ptr();
+        }
+
+        // Keyboard unlock methods (exit key, emergency exit, keypad code); a key that
+        // unlocks is let through unless its method says otherwise
+        let unlock_input = if event_type == CGEventType::KeyDown {
+            let cg_event = event.as_ref();
+            Some(UnlockInput::KeyDown {
+                keycode: CGEvent::integer_value_field(
+                    Some(cg_event),
+                    CGEventField::KeyboardEventKeycode,
+                ),
+                flags: CGEvent::flags(Some(cg_event)),
+            })
+        } else if event_type == CGEventType::KeyUp {
+            Some(UnlockInput::KeyUp {
+                keycode: CGEvent::integer_value_field(
+                    Some(event.as_ref()),
+                    CGEventField::KeyboardEventKeycode,
+                ),
+            })
+        } else if event_type == CGEventType::FlagsChanged {
+            Some(UnlockInput::FlagsChanged {
+                keycode: CGEvent::integer_value_field(
+                    Some(event.as_ref()),
+                    CGEventField::KeyboardEventKeycode,
+                ),
+                flags: CGEvent::flags(Some(event.as_ref())),
+            })
+        } else {
+            None
+        };
+        if let Some(passes_key_through) = unlock_input.and_then(evaluate_unlock_methods) {
+            return if passes_key_through {
+                event.as_ptr()
+            } else {
+                std::ptr::null_mut()
+            };
+        }
+
+        if passes_as_accessibility_shortcut(event.as_ref(), event_type) {
+            return event.as_ptr();
+        }
+
+        // The dedicated Siri/dictation key and media keys bypass KeyDown entirely;
+        // other system-defined events (e.g. aux mouse buttons) must still pass
+        if event_type.0 == NX_SYSDEFINED {
+            let is_aux_key = NSEvent::eventWithCGEvent(event.as_ref())
+                .is_some_and(|ns_event| ns_event.subtype().0 == NX_SUBTYPE_AUX_CONTROL_BUTTONS);
+            if is_aux_key {
+                return std::ptr::null_mut();
+            }
+            return event.as_ptr();
+        }
+
+        // The overlay would swallow scrolling, so with --allow-scroll it goes straight
+        // to the app underneath instead. Pinch-zoom and rotation never do.
+        if event_type == CGEventType::ScrollWheel
+            || 1u64
+                .checked_shl(event_type.0)
+                .is_some_and(|bit| GESTURE_EVENT_MASK & bit != 0)
+        {
+            if ALLOW_SCROLL.load(Ordering::SeqCst) && is_scroll_event(event.as_ref(), event_type) {
+                forward_scroll_event(event.as_ref());
+            } else {
+                record_blocked_source(event.as_ref(), event_type);
+            }
+            return std::ptr::null_mut();
+        }
+
+        // Only tapped with --no-overlay, where there is no window to catch clicks
+        if 1u64
+            .checked_shl(event_type.0)
+            .is_some_and(|bit| MOUSE_BUTTON_EVENT_MASK & bit != 0)
+        {
+            record_blocked_source(event.as_ref(), event_type);
+            return std::ptr::null_mut();
+        }
+
+        // Pass-through keys reach the forwarding target and nothing else
+        if (event_type == CGEventType::KeyDown || event_type == CGEventType::KeyUp)
+            && forward_key_event(event.as_ref())
+        {
+            return std::ptr::null_mut();
+        }
+
+        if event_type == CGEventType::KeyDown {
+            let cg_event = event.as_ref();
+            let keycode =
+                CGEvent::integer_value_field(Some(cg_event), CGEventField::KeyboardEventKeycode);
+            let is_repeat =
+                CGEvent::integer_value_field(Some(cg_event), CGEventField::KeyboardEventAutorepeat)
+                    != 0;
+            if !is_repeat && is_siri_shortcut(keycode, CGEvent::flags(Some(cg_event))) {
+                println!("\n  🗣️  Siri shortcut blocked");
+            }
+        }
+
+        // Block keyboard events by returning NULL
+        // Mouse events are allowed through so our close button can work
+        // (our topmost window captures all mouse events anyway)
+        if event_type == CGEventType::KeyDown
+            || event_type == CGEventType::KeyUp
+            || event_type == CGEventType::FlagsChanged
+        {
+            if event_type == CGEventType::KeyDown {
+                BLOCKED_KEY_PRESSES.fetch_add(1, Ordering::SeqCst);
+                BLOCKED_BY_HOUR[local_hour() as usize].fetch_add(1, Ordering::SeqCst);
+                let now = std::time::SystemTime::now()
+                    .duration_since(std::time::UNIX_EPOCH)
+                    .unwrap()
+                    .as_secs();
+                LAST_BLOCKED_AT.store(now, Ordering::SeqCst);
+                record_blocked_source(event.as_ref(), event_type);
+            }
+
+            if event_type == CGEventType::KeyDown
+                && KEY_BURST_DETECTOR.with(|detector| detector.borrow_mut().record(Instant::now()))
+            {
+                post_message(ShieldMessage::BlockedBurst);
+            }
+
+            if event_type == CGEventType::KeyDown {
+                let cg_event = event.as_ref();
+                let keycode = CGEvent::integer_value_field(
+                    Some(cg_event),
+                    CGEventField::KeyboardEventKeycode,
+                );
+                let is_repeat = CGEvent::integer_value_field(
+                    Some(cg_event),
+                    CGEventField::KeyboardEventAutorepeat,
+                ) != 0;
+                if !is_repeat
+                    && CAT_DETECTOR
+                        .with(|detector| detector.borrow_mut().record(keycode, Instant::now()))
+                {
+                    post_message(ShieldMessage::CatDetected);
+                }
+            }
+
+            // Return NULL to block the event
+            return std::ptr::null_mut();
+        }
+
+        event.as_ptr()
+    })
+}
+
+/// Where an input event came from, judged by the process that posted it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum InputOrigin {
+    /// Hardware attached to this Mac, or a process we don't single out
+    Local,
+    /// Another Mac or iPad, through Universal Control
+    UniversalControl,
+    /// A remote session: Screen Sharing or Apple Remote Desktop
+    Remote,
+}
+
+impl InputOrigin {
+    /// Classify the process that posted an event by its name
+    fn from_process_name(name: &str) -> Self {
+        match name {
+            "UniversalControl" => InputOrigin::UniversalControl,
+            "screensharingd" | "ScreensharingAgent" | "ARDAgent" => InputOrigin::Remote,
+            _ => InputOrigin::Local,
+        }
+    }
+}
+
+/// Where `event` came from. Events straight from HID hardware carry no process ID.
+fn event_origin(event: &CGEvent) -> InputOrigin {
+    let pid = CGEvent::integer_value_field(Some(event), CGEventField::EventSourceUnixProcessID);
+    if pid <= 0 {
+        return InputOrigin::Local;
+    }
+    if let Some((cached_pid, origin)) = LAST_EVENT_ORIGIN.with(|last| last.get()) {
+        if cached_pid == pid {
+            return origin;
+        }
+    }
+
+    let mut buffer = [0u8; PROC_NAME_MAX];
+    let length = unsafe {
+        proc_name(
+            pid as i32,
+            buffer.as_mut_ptr() as *mut c_void,
+            buffer.len() as u32,
+        )
+    };
+    let origin = if length > 0 {
+        InputOrigin::from_process_name(&String::from_utf8_lossy(&buffer[..length as usize]))
+    } else {
+        InputOrigin::Local
+    };
+    LAST_EVENT_ORIGIN.with(|last| last.set(Some((pid, origin))));
+    origin
+}
+
+/// Whether `--allow-accessibility-shortcuts` lets this key event through: an
+/// accessibility shortcut, its key up, or Shift on its own (pressing it five
+/// times toggles Sticky Keys)
+fn passes_as_accessibility_shortcut(event: &CGEvent, event_type: CGEventType) -> bool {
+    if !ALLOW_ACCESSIBILITY_SHORTCUTS.load(Ordering::SeqCst) {
+        return false;
+    }
+
+    let keycode = CGEvent::integer_value_field(Some(event), CGEventField::KeyboardEventKeycode);
+    match event_type {
+        CGEventType::KeyDown => {
+            let Some(shortcut) = accessibility_shortcut(keycode, CGEvent::flags(Some(event)))
+            else {
+                return false;
+            };
+            let is_repeat =
+                CGEvent::integer_value_field(Some(event), CGEventField::KeyboardEventAutorepeat)
+                    != 0;
+            if !is_repeat {
+                println!("\n  ♿ {} shortcut passed through", shortcut.name);
+            }
+            PASSED_SHORTCUT_KEY.with(|key| key.set(Some(keycode)));
+            true
+        }
+        CGEventType::KeyUp => PASSED_SHORTCUT_KEY.with(|key| {
+            let passed = key.get() == Some(keycode);
+            if passed {
+                key.set(None);
+            }
+            passed
+        }),
+        CGEventType::FlagsChanged => SHIFT_KEYCODES.contains(&keycode),
+        _ => false,
+    }
+}
+
+/// Where a blocked event came from, for the statistics: the keyboard's product
+/// name (with Input Monitoring permission), "Mouse", "Trackpad", or the remote
+/// session. `None` for events that continue something already counted (mouse
+/// ups and drags, the rest of a trackpad scroll).
+fn blocked_event_source(event: &CGEvent, event_type: CGEventType) -> Option<String> {
+    match event_origin(event) {
+        InputOrigin::UniversalControl => return Some("Universal Control".to_string()),
+        InputOrigin::Remote => return Some("Remote session".to_string()),
+        InputOrigin::Local => {}
+    }
+
+    match event_type {
+        CGEventType::KeyDown => Some(
+            LAST_KEY_DEVICE
+                .with(|device| device.borrow().as_ref().map(|(_, name)| name.clone()))
+                .unwrap_or_else(|| "Keyboard".to_string()),
+        ),
+        // Trackpads (and Magic Mice) scroll continuously; count each scroll once
+        CGEventType::ScrollWheel => {
+            let continuous = CGEvent::integer_value_field(
+                Some(event),
+                CGEventField::ScrollWheelEventIsContinuous,
+            ) != 0;
+            let phase = CGEvent::integer_value_field(
+                Some(event),
+                CGEventField::ScrollWheelEventScrollPhase,
+            );
+            match (continuous, phase) {
+                (false, _) => Some("Mouse".to_string()),
+                (true, SCROLL_PHASE_BEGAN) => Some("Trackpad".to_string()),
+                (true, _) => None,
+            }
+        }
+        // A click looks the same from a mouse or a trackpad
+        CGEventType::LeftMouseDown | CGEventType::RightMouseDown | CGEventType::OtherMouseDown => {
+            Some("Mouse or trackpad".to_string())
+        }
+        _ => None,
+    }
+}
+
+/// Count a blocked event under its source for this session
+fn record_blocked_source(event: &CGEvent, event_type: CGEventType) {
+    if let Some(source) = blocked_event_source(event, event_type) {
+        BLOCKED_BY_SOURCE.with(|sources| *sources.borrow_mut().entry(source).or_default() += 1);
+    }
+}
+
+/// Whether `event` comes from a device or session allowed through
+/// (`--allow-universal-control`, `--allow-remote-input`)
+fn origin_passes_through(event: &CGEvent) -> bool {
+    let allow_universal_control = ALLOW_UNIVERSAL_CONTROL.load(Ordering::SeqCst);
+    let allow_remote = ALLOW_REMOTE_INPUT.load(Ordering::SeqCst);
+    if !allow_universal_control && !allow_remote {
+        return false;
+    }
+    match event_origin(event) {
+        InputOrigin::Local => false,
+        InputOrigin::UniversalControl => allow_universal_control,
+        InputOrigin::Remote => allow_remote,
+    }
+}
+
+/// Hide Siri (including the Type to Siri window) if it took focus while armed,
+/// and bring the overlay back to the front. Throttled; called from the animation timer.
+fn dismiss_siri() {
+    if !BLOCKING_ARMED.load(Ordering::SeqCst) {
+        return;
+    }
+    let due = LAST_SIRI_CHECK.with(|last| {
+        let due = last
+            .get()
+            .is_none_or(|checked| checked.elapsed() >= SIRI_CHECK_INTERVAL);
+        if due {
+            last.set(Some(Instant::now()));
+        }
+        due
+    });
+    if !due {
+        return;
+    }
+
+    let siri = NSRunningApplication::runningApplicationsWithBundleIdentifier(&NSString::from_str(
+        SIRI_BUNDLE_ID,
+    ));
+    for app in siri.iter() {
+        if app.isActive() && app.hide() {
+            println!("\n  🗣️  Siri dismissed");
+            restore_overlay();
+        }
+    }
+}
+
+/// Users logged in at the console (GUI sessions), one entry per session
+fn console_users() -> Vec<String> {
+    // NUL-padded fixed-size field
+    fn field(bytes: &[u8]) -> String {
+        let len = bytes.iter().position(|&b| b == 0).unwrap_or(bytes.len());
+        String::from_utf8_lossy(&bytes[..len]).into_owned()
+    }
+
+    let mut users = Vec::new();
+    unsafe {
+        setutxent();
+        while let Some(entry) = getutxent().as_ref() {
+            if entry.ut_type == UTMPX_USER_PROCESS && field(&entry.ut_line) == "console" {
+                users.push(field(&entry.ut_user));
+            }
+        }
+        endutxent();
+    }
+    users
+}
+
+/// Users other than `current` among the console sessions, sorted, without duplicates
+fn other_session_users(sessions: &[String], current: &str) -> Vec<String> {
+    let mut others: Vec<String> = sessions
+        .iter()
+        .filter(|user| *user != current)
+        .cloned()
+        .collect();
+    others.sort();
+    others.dedup();
+    others
+}
+
+/// Warn when other users are logged in: the shield only covers this session,
+/// and switching users from the login window gets around the overlay
+fn warn_about_other_sessions() {
+    let current = std::env::var("USER").unwrap_or_default();
+    let others = other_session_users(&console_users(), &current);
+    if others.is_empty() {
+        return;
+    }
+
+    eprintln!("  ⚠️  Also logged in: {}", others.join(", "));
+    eprintln!("      The shield only protects this session; switching users gets around it");
+    if REQUIRE_ADMIN_EXIT.load(Ordering::SeqCst) {
+        eprintln!("      Log the other users out for parental mode to hold");
+    }
+}
+
+/// Quit or hide apps launched while blocking is armed, and report it
+fn enable_launch_blocking(action: LaunchAction) {
+    setup_launch_monitor(action);
+    println!("  ✓ App launches blocked ({})", action.outcome());
+}
+
+/// Post `message` with the app's process ID whenever NSWorkspace posts the
+/// notification `name` about an app
+fn observe_workspace_apps(name: &NSNotificationName, message: fn(i32) -> ShieldMessage) {
+    let block = RcBlock::new(move |notification: NonNull<NSNotification>| {
+        let app = unsafe { notification.as_ref() }
+            .userInfo()
+            .and_then(|info| info.objectForKey(unsafe { NSWorkspaceApplicationKey }))
+            .and_then(|app| app.downcast::<NSRunningApplication>().ok());
+        if let Some(app) = app {
+            post_message(message(app.processIdentifier()));
+        }
+    });
+    let observer = unsafe {
+        NSWorkspace::sharedWorkspace()
+            .notificationCenter()
+            .addObserverForName_object_queue_usingBlock(Some(name), None, None, &block)
+    };
+    // Observed for the lifetime of the process
+    std::mem::forget(observer);
+}
+
+/// Pass the forwarded keys through to `app`, and report it
+fn enable_key_forwarding(app: &str, keycodes: &[i64]) {
+    FORWARD_RULE.with(|rule| {
+        *rule.borrow_mut() = Some(ForwardRule {
+            app: app.to_string(),
+            keycodes: keycodes.to_vec(),
+        })
+    });
+    println!("  ✓ Forwarding {} key(s) to {}", keycodes.len(), app);
+    if find_running_app(app).is_none() {
+        eprintln!(
+            "  ⚠️  {} isn't running yet - keys are forwarded once it is",
+            app
+        );
+    }
+}
+
+/// Post `message` whenever the workspace posts the notification `name`
+fn observe_workspace(name: &NSNotificationName, message: ShieldMessage) {
+    let block = RcBlock::new(move |_notification: NonNull<NSNotification>| {
+        post_message(message);
+    });
+    let observer = unsafe {
+        NSWorkspace::sharedWorkspace()
+            .notificationCenter()
+            .addObserverForName_object_queue_usingBlock(Some(name), None, None, &block)
+    };
+    // Observed for the lifetime of the process
+    std::mem::forget(observer);
+}
+
+/// Post `message` whenever the app's notification center posts `name`
+fn observe_application(name: &NSNotificationName, message: ShieldMessage) {
+    let block = RcBlock::new(move |_notification: NonNull<NSNotification>| {
+        post_message(message);
+    });
+    let observer = unsafe {
+        NSNotificationCenter::defaultCenter().addObserverForName_object_queue_usingBlock(
+            Some(name),
+            None,
+            None,
+            &block,
+        )
+    };
+    // Observed for the lifetime of the process
+    std::mem::forget(observer);
+}
+
+/// Keep an overlay window on every display, each filling its display, as
+/// displays are plugged in, unplugged, rearranged or change resolution
+fn setup_screen_watch() {
+    observe_application(
+        unsafe { NSApplicationDidChangeScreenParametersNotification },
+        ShieldMessage::ScreensChanged,
+    );
+}
+
+/// Re-fit the overlay windows to the displays as AppKit now reports them
+fn handle_screens_changed() {
+    if shield_state() == ShieldState::Exiting || OVERLAY_WINDOW.load(Ordering::SeqCst).is_null() {
+        return;
+    }
+    let before = SECONDARY_OVERLAYS.with(|windows| windows.borrow().len());
+    restore_overlay();
+    let after = SECONDARY_OVERLAYS.with(|windows| windows.borrow().len());
+    if after != before && !PROJECTOR_PAUSED.load(Ordering::SeqCst) {
+        println!();
+        println!(
+            "  🖥️  Displays changed - overlay now covers {} display(s)",
+            after + 1
+        );
+    }
+}
+
+/// Whether Stage Manager is turned on
+fn stage_manager_enabled() -> bool {
+    let key = CFString::from_str(STAGE_MANAGER_ENABLED_KEY);
+    let domain = CFString::from_str(STAGE_MANAGER_DOMAIN);
+    unsafe { CFPreferencesGetAppBooleanValue(&key, &domain, std::ptr::null_mut()) }
+}
+
+/// Keep the overlay covering the screen through Stage Manager stage switches
+/// and Space changes. Stage Manager can be turned on mid-session from Control
+/// Center, so this watches either way.
+fn setup_stage_watch() {
+    observe_workspace(
+        unsafe { NSWorkspaceDidActivateApplicationNotification },
+        ShieldMessage::StageChanged,
+    );
+    observe_workspace(
+        unsafe { NSWorkspaceActiveSpaceDidChangeNotification },
+        ShieldMessage::StageChanged,
+    );
+    if stage_manager_enabled() {
+        println!("  ✓ Stage Manager: overlay kept over every stage");
+    }
+}
+
+/// Check the overlay now and again once the switch animation has settled
+fn handle_stage_changed() {
+    ensure_overlay_coverage();
+    let timer = RunLoopTimer::once(STAGE_SWITCH_SETTLE_SECS, stage_settle_timer_callback);
+    STAGE_SETTLE_TIMER.with(|slot| slot.replace(timer));
+}
+
+unsafe extern "C-unwind" fn stage_settle_timer_callback(
+    _timer: *mut CFRunLoopTimer,
+    _info: *mut c_void,
+) {
+    catch_ffi_panic(ensure_overlay_coverage)
+}
+
+/// Restore the overlay if it no longer fills the main screen on the active
+/// Space, so no strip of the desktop is left uncovered
+fn ensure_overlay_coverage() {
+    let window_ptr = OVERLAY_WINDOW.load(Ordering::SeqCst);
+    if window_ptr.is_null()
+        || STOOD_DOWN.load(Ordering::SeqCst)
+        || shield_state() == ShieldState::Exiting
+    {
+        return;
+    }
+    let Some(mtm) = MainThreadMarker::new() else {
+        return;
+    };
+    let Some(screen) = NSScreen::mainScreen(mtm) else {
+        return;
+    };
+
+    let window: &NSWindow = unsafe { &*(window_ptr as *const NSWindow) };
+    let wanted: Vec<CGRect> = secondary_screens(mtm)
+        .iter()
+        .map(|screen| screen.frame())
+        .collect();
+    let mut covered = window.frame() == screen.frame()
+        && SECONDARY_OVERLAYS.with(|windows| {
+            windows
+                .borrow()
+                .iter()
+                .map(|window| window.frame())
+                .eq(wanted)
+        });
+    for_each_overlay_window(|window| {
+        covered &= window.isVisible() && window.isOnActiveSpace();
+    });
+    if !covered {
+        println!();
+        println!("  🪟 Overlay displaced by a stage or Space switch - restored");
+        restore_overlay();
+    }
+}
+
+/// Observe app launches so `--block-launches` can act on them
+fn setup_launch_monitor(action: LaunchAction) {
+    LAUNCH_ACTION.with(|launch_action| launch_action.set(Some(action)));
+    observe_workspace_apps(
+        unsafe { NSWorkspaceDidLaunchApplicationNotification },
+        ShieldMessage::AppLaunched,
+    );
+}
+
+/// Whether an app is the one `rule` names, by name or bundle ID (case-insensitive)
+fn app_matches(rule: &str, name: Option<&str>, bundle_id: Option<&str>) -> bool {
+    [name, bundle_id]
+        .into_iter()
+        .flatten()
+        .any(|id| id.eq_ignore_ascii_case(rule.trim()))
+}
+
+/// Whether an app matches one of the `activate_when_frontmost` rules
+fn matches_frontmost_rule(rules: &[String], name: Option<&str>, bundle_id: Option<&str>) -> bool {
+    rules.iter().any(|rule| app_matches(rule, name, bundle_id))
+}
+
+/// The running app `rule` names, if any
+fn find_running_app(rule: &str) -> Option<Retained<NSRunningApplication>> {
+    NSWorkspace::sharedWorkspace()
+        .runningApplications()
+        .iter()
+        .find(|app| {
+            let name = app.localizedName().map(|name| name.to_string());
+            let bundle_id = app.bundleIdentifier().map(|id| id.to_string());
+            app_matches(rule, name.as_deref(), bundle_id.as_deref())
+        })
+}
+
+/// Keys passed straight to one app while everything else stays blocked
+struct ForwardRule {
+    /// App name or bundle ID
+    app: String,
+    keycodes: Vec<i64>,
+}
+
+/// Post a key event directly to the `--forward-to` app if it is one of the
+/// forwarded keys. Looked up per event, so the app can be launched (or
+/// relaunched) while shielded.
+///
+/// # Returns
+/// `true` if the event was forwarded and should be blocked everywhere else
+fn forward_key_event(event: &CGEvent) -> bool {
+    let keycode = CGEvent::integer_value_field(Some(event), CGEventField::KeyboardEventKeycode);
+    let target = FORWARD_RULE.with(|rule| {
+        let rule = rule.borrow();
+        let rule = rule.as_ref()?;
+        if !rule.keycodes.contains(&keycode) {
+            return None;
+        }
+        find_running_app(&rule.app).map(|app| app.processIdentifier())
+    });
+    let Some(pid) = target else {
+        return false;
+    };
+
+    CGEvent::post_to_pid(pid, Some(event));
+    true
+}
+
+/// Whether `event` scrolls or swipes (what `--allow-scroll` lets through), rather
+/// than zooming, rotating, or force clicking
+fn is_scroll_event(event: &CGEvent, event_type: CGEventType) -> bool {
+    if event_type == CGEventType::ScrollWheel {
+        return true;
+    }
+    event_type.0 == NX_GESTURE
+        && !ZOOM_GESTURE_HID_TYPES.contains(&CGEvent::integer_value_field(
+            Some(event),
+            GESTURE_HID_TYPE_FIELD,
+        ))
+}
+
+/// Post a scroll or gesture event directly to the frontmost app, the one the
+/// overlay covers (the shield never activates itself)
+fn forward_scroll_event(event: &CGEvent) {
+    let Some(app) = NSWorkspace::sharedWorkspace().frontmostApplication() else {
+        return;
+    };
+    let pid = app.processIdentifier();
+    if pid != process::id() as i32 {
+        CGEvent::post_to_pid(pid, Some(event));
+    }
+}
+
+/// Watch mode: follow the frontmost app so `activate_when_frontmost` can arm
+/// and disarm keyboard blocking
+fn setup_frontmost_watch(rules: Vec<String>) {
+    FRONTMOST_RULES.with(|frontmost| *frontmost.borrow_mut() = rules);
+    observe_workspace_apps(
+        unsafe { NSWorkspaceDidActivateApplicationNotification },
+        ShieldMessage::AppActivated,
+    );
+
+    // An app that is already frontmost counts too
+    if let Some(app) = NSWorkspace::sharedWorkspace().frontmostApplication() {
+        handle_app_activated(app.processIdentifier());
+    }
+}
+
+/// Arm keyboard blocking when a listed app comes to the front, and disarm when
+/// it loses focus unless something else (a keyboard disconnect) armed it too.
+fn handle_app_activated(pid: i32) {
+    let Some(app) = NSRunningApplication::runningApplicationWithProcessIdentifier(pid) else {
+        return;
+    };
+    let name = app.localizedName().map(|name| name.to_string());
+    let bundle_id = app.bundleIdentifier().map(|id| id.to_string());
+    let matched = FRONTMOST_RULES.with(|rules| {
+        matches_frontmost_rule(&rules.borrow(), name.as_deref(), bundle_id.as_deref())
+    });
+    let armed_for = ARMED_FOR_FRONTMOST.with(|armed_for| armed_for.borrow_mut().take());
+
+    if matched {
+        let name = name.or(bundle_id).unwrap_or_else(|| format!("pid {}", pid));
+        arm_blocking(ArmCause::FrontmostApp, &format!("{} is frontmost", name));
+        ARMED_FOR_FRONTMOST.with(|armed_for| *armed_for.borrow_mut() = Some(name));
+    } else if let Some(previous) = armed_for {
+        release_blocking(ArmCause::FrontmostApp, &format!("{} lost focus", previous));
+    }
+}
+
+/// Quit or hide an app launched while keyboard blocking is armed
+fn handle_app_launched(pid: i32) {
+    let Some(action) = LAUNCH_ACTION.with(|action| action.get()) else {
+        return;
+    };
+    // Never while unlocking: the administrator password dialog is an app launch too
+    if !BLOCKING_ARMED.load(Ordering::SeqCst)
+        || !matches!(shield_state(), ShieldState::Active | ShieldState::Warning)
+    {
+        return;
+    }
+    let Some(app) = NSRunningApplication::runningApplicationWithProcessIdentifier(pid) else {
+        return;
+    };
+    // Leave agents and background helpers alone; only regular apps can be clicked open
+    if app.activationPolicy() != NSApplicationActivationPolicy::Regular {
+        return;
+    }
+
+    let name = app
+        .localizedName()
+        .map(|name| name.to_string())
+        .unwrap_or_else(|| format!("pid {}", pid));
+    let stopped = match action {
+        LaunchAction::Terminate => app.terminate(),
+        LaunchAction::Hide => app.hide(),
+    };
+
+    println!();
+    if stopped {
+        println!(
+            "  🚫 {} was launched while shielded - {}",
+            name,
+            action.outcome()
+        );
+    } else {
+        println!(
+            "  ⚠️  {} was launched while shielded and could not be stopped",
+            name
+        );
+    }
+    restore_overlay();
+}
+
+/// Check if we have accessibility permissions
+fn check_accessibility() -> bool {
+    unsafe { AXIsProcessTrusted() }
+}
+
+/// Check accessibility permissions and prompt user with native dialog if not granted
+fn check_accessibility_with_prompt() -> bool {
+    unsafe {
+        let keys = [kAXTrustedCheckOptionPrompt];
+        let values = [kCFBooleanTrue];
+
+        let dict = CFDictionaryCreate(
+            std::ptr::null(),
+            keys.as_ptr(),
+            values.as_ptr(),
+            1,
+            std::ptr::null(),
+            std::ptr::null(),
+        );
+
+        let result = AXIsProcessTrustedWithOptions(dict);
+
+        if !dict.is_null() {
+            CFRelease(dict);
+        }
+
+        result
+    }
+}
+
+/// Open System Settings to the Accessibility privacy pane
+fn open_accessibility_settings() -> bool {
+    let url_string =
+        ns_string!("x-apple.systempreferences:com.apple.preference.security?Privacy_Accessibility");
+
+    if let Some(url) = NSURL::URLWithString(url_string) {
+        let workspace = NSWorkspace::sharedWorkspace();
+        return workspace.openURL(&url);
+    }
+    false
+}
+
+/// Which kind of keyboard the keys currently held down are on
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum KeySource {
+    BuiltIn,
+    External,
+    /// Nothing held down as far as the IOHIDManager has reported
+    Unknown,
+}
+
+/// Where the key being pressed right now is, going by the keys the
+/// IOHIDManager reports held down rather than the order its callback and the
+/// event tap run in. A key held on the built-in keyboard wins, so a cat lying on
+/// the external one doesn't hide the exit key typed on the laptop.
+fn held_key_source() -> KeySource {
+    if BUILT_IN_KEYS_DOWN.with(|keys| !keys.borrow().is_empty()) {
+        KeySource::BuiltIn
+    } else if EXTERNAL_KEYS_DOWN.with(|keys| !keys.borrow().is_empty()) {
+        KeySource::External
+    } else {
+        KeySource::Unknown
+    }
+}
+
+/// Track a regular key going down or up on a built-in or external keyboard
+fn record_held_key(usage: u32, pressed: bool, built_in: bool) {
+    let keys = if built_in {
+        &BUILT_IN_KEYS_DOWN
+    } else {
+        &EXTERNAL_KEYS_DOWN
+    };
+    keys.with(|keys| {
+        let mut keys = keys.borrow_mut();
+        if pressed {
+            keys.insert(usage);
+        } else {
+            keys.remove(&usage);
+        }
+    });
+}
+
+/// IOHIDManager input callback: track which keys are held down on built-in and
+/// external keyboards, and remember which keyboard each key press came from
+unsafe extern "C" fn hid_keyboard_value_callback(
+    _context: *mut c_void,
+    _result: i32,
+    _sender: *mut c_void,
+    value: *mut c_void,
+) {
+    catch_ffi_panic(|| {
+        let element = IOHIDValueGetElement(value);
+        if element.is_null() || IOHIDElementGetUsagePage(element) != HID_PAGE_KEYBOARD {
+            return;
+        }
+
+        // Only regular (non-modifier) keys; the exit key is never a modifier
+        let usage = IOHIDElementGetUsage(element);
+        if !(HID_USAGE_KEYBOARD_FIRST_KEY..HID_USAGE_KEYBOARD_LEFT_CONTROL).contains(&usage) {
+            return;
+        }
+
+        let device = IOHIDElementGetDevice(element);
+        let built_in = !device.is_null() && hid_device_is_built_in(device);
+        let pressed = IOHIDValueGetIntegerValue(value) != 0;
+        record_held_key(usage, pressed, built_in);
+        if !pressed {
+            return;
+        }
+
+        // Look the name up only when the keyboard changes
+        let address = device as usize;
+        LAST_KEY_DEVICE.with(|last| {
+            let mut last = last.borrow_mut();
+            if device.is_null() || last.as_ref().is_some_and(|(last, _)| *last == address) {
+                return;
+            }
+            let name =
+                hid_device_product_name(device).unwrap_or_else(|| "Unknown keyboard".to_string());
+            *last = Some((address, name));
+        });
+    })
+}
+
+/// Check the IOHIDDevice "Built-In" property (true for the laptop's own keyboard)
+unsafe fn hid_device_is_built_in(device: *mut c_void) -> bool {
+    let key = CFString::from_static_str("Built-In");
+    let property =
+        IOHIDDeviceGetProperty(device, CFRetained::as_ptr(&key).as_ptr() as *const c_void);
+    property == kCFBooleanTrue
+}
+
+/// Read the IOHIDDevice "Product" name, if the device reports one
+unsafe fn hid_device_product_name(device: *mut c_void) -> Option<String> {
+    let key = CFString::from_static_str("Product");
+    let property =
+        IOHIDDeviceGetProperty(device, CFRetained::as_ptr(&key).as_ptr() as *const c_void);
+    if property.is_null() {
+        return None;
+    }
+    let name: &CFString = &*(property as *const CFString);
+    Some(name.to_string())
+}
+
+/// Create an IOHIDManager matching keyboards, scheduled on the current run loop.
+///
+/// The manager is intentionally never released so it keeps reporting for the
+/// lifetime of the process.
+fn create_keyboard_hid_manager() -> Option<*mut c_void> {
+    let usage_page_key = CFString::from_static_str("DeviceUsagePage");
+    let usage_key = CFString::from_static_str("DeviceUsage");
+    let usage_page = CFNumber::new_i32(HID_PAGE_GENERIC_DESKTOP);
+    let usage = CFNumber::new_i32(HID_USAGE_GD_KEYBOARD);
+    let matching = CFDictionary::<CFString, CFNumber>::from_slices(
+        &[&usage_page_key, &usage_key],
+        &[&usage_page, &usage],
+    );
+
+    unsafe {
+        let manager = IOHIDManagerCreate(std::ptr::null(), 0);
+        if manager.is_null() {
+            return None;
+        }
+
+        IOHIDManagerSetDeviceMatching(
+            manager,
+            CFRetained::as_ptr(&matching).as_ptr() as *const c_void,
+        );
+
+        let mode = common_modes().expect("kCFRunLoopCommonModes should exist");
+        IOHIDManagerScheduleWithRunLoop(
+            manager,
+            CFRetained::as_ptr(&current_run_loop()).as_ptr().cast(),
+            (mode as *const CFString).cast(),
+        );
+
+        Some(manager)
+    }
+}
+
+/// Start an IOHIDManager that tracks which keyboard each key press comes from.
+///
+/// The exit key checks the keys held down (`held_key_source`), which doesn't
+/// depend on whether a HID input value or its CGEvent is delivered first;
+/// `LAST_KEY_DEVICE` usually names the keyboard of the key press the tap is
+/// handling, which is close enough for the statistics. Requires Input
+/// Monitoring permission.
+fn setup_keyboard_source_monitor() -> bool {
+    let Some(manager) = create_keyboard_hid_manager() else {
+        return false;
+    };
+
+    unsafe {
+        IOHIDManagerRegisterInputValueCallback(
+            manager,
+            hid_keyboard_value_callback,
+            std::ptr::null_mut(),
+        );
+        IOHIDManagerOpen(manager, 0) == K_IO_RETURN_SUCCESS
+    }
+}
+
+/// IOHIDManager matching callback: count external keyboards as they appear
+unsafe extern "C" fn hid_keyboard_attached_callback(
+    _context: *mut c_void,
+    _result: i32,
+    _sender: *mut c_void,
+    device: *mut c_void,
+) {
+    catch_ffi_panic(|| {
+        if hid_device_is_built_in(device) {
+            return;
+        }
+
+        EXTERNAL_KEYBOARD_COUNT.fetch_add(1, Ordering::SeqCst);
+        let name =
+            hid_device_product_name(device).unwrap_or_else(|| "Unknown keyboard".to_string());
+        println!("  ⌨️  External keyboard connected: {}", name);
+    })
+}
+
+/// Count an external keyboard as gone and return how many are still connected
+fn external_keyboard_removed() -> u64 {
+    EXTERNAL_KEYBOARD_COUNT
+        .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |count| {
+            Some(count.saturating_sub(1))
+        })
+        .map_or(0, |count| count.saturating_sub(1))
+}
+
+/// IOHIDManager removal callback: arm blocking when the last external keyboard
+/// disappears. Unplugging one of several leaves blocking as it was, since the
+/// others are still there to type on.
+unsafe extern "C" fn hid_keyboard_removed_callback(
+    _context: *mut c_void,
+    _result: i32,
+    _sender: *mut c_void,
+    device: *mut c_void,
+) {
+    catch_ffi_panic(|| {
+        if hid_device_is_built_in(device) {
+            return;
+        }
+
+        let remaining = external_keyboard_removed();
+        let name =
+            hid_device_product_name(device).unwrap_or_else(|| "Unknown keyboard".to_string());
+        println!("  ⌨️  External keyboard disconnected: {}", name);
+        if remaining == 0 {
+            arm_blocking(ArmCause::UntilUnlocked, "external keyboard disconnected");
+        } else {
+            println!("     {} external keyboard(s) still connected", remaining);
+        }
+    })
+}
+
+/// Watch keyboard attach/detach for watch mode.
+///
+/// Device matching works without opening the devices, so this doesn't need
+/// Input Monitoring permission.
+fn setup_keyboard_watch() -> bool {
+    let Some(manager) = create_keyboard_hid_manager() else {
+        return false;
+    };
+
+    unsafe {
+        IOHIDManagerRegisterDeviceMatchingCallback(
+            manager,
+            hid_keyboard_attached_callback,
+            std::ptr::null_mut(),
+        );
+        IOHIDManagerRegisterDeviceRemovalCallback(
+            manager,
+            hid_keyboard_removed_callback,
+            std::ptr::null_mut(),
+        );
+    }
+
+    true
+}
+
+/// Owner of the keyboard event tap. Dropping it disables and invalidates the
+/// tap, so keyboard input is never left blocked by a tap nobody owns.
+/// `EVENT_TAP` holds a borrowed pointer to the same port for the callbacks.
+struct EventTapGuard {
+    port: CFRetained<CFMachPort>,
+    // Only held so it is released along with the tap
+    _source: RunLoopSource,
+}
+
+impl EventTapGuard {
+    fn as_ptr(&self) -> *mut c_void {
+        CFRetained::as_ptr(&self.port).as_ptr() as *mut c_void
+    }
+}
+
+impl Drop for EventTapGuard {
+    fn drop(&mut self) {
+        let tap = self.as_ptr();
+        // Only forget the global pointer if it wasn't already replaced
+        let _ = EVENT_TAP.compare_exchange(
+            tap,
+            std::ptr::null_mut(),
+            Ordering::SeqCst,
+            Ordering::SeqCst,
+        );
+        if self.port.is_valid() {
+            unsafe { CGEventTapEnable(tap, false) };
+            // Invalidating the port also removes its run loop source
+            self.port.invalidate();
+        }
+    }
+}
+
+/// Disable and drop the event tap, if one is installed
+fn release_event_tap() {
+    // Dropped outside the borrow
+    let guard = EVENT_TAP_GUARD.with(|guard| guard.borrow_mut().take());
+    drop(guard);
+}
+
+/// Create and enable the event tap
+fn setup_event_tap() -> bool {
+    // Define event mask for keyboard events only
+    // Mouse events are NOT blocked - our topmost fullscreen window captures them,
+    // and we need mouse events to reach our close button
+    // Scrolling and gestures are blocked too: the overlay would catch them, but momentum
+    // from a scroll started before it appeared still goes to the window underneath, and
+    // --allow-scroll needs them routed past the overlay
+    let mut event_mask: CGEventMask = (1u64 << CGEventType::KeyDown.0)
+        | (1u64 << CGEventType::KeyUp.0)
+        | (1u64 << CGEventType::FlagsChanged.0)
+        | (1u64 << NX_SYSDEFINED)
+        | (1u64 << CGEventType::ScrollWheel.0)
+        | GESTURE_EVENT_MASK;
+    // And clicks with --no-overlay, where no window catches the mouse
+    if TAP_BLOCKS_MOUSE.load(Ordering::SeqCst) {
+        event_mask |= MOUSE_BUTTON_EVENT_MASK;
+    }
+
+    unsafe {
+        // Create the event tap using CGEvent::tap_create
+        let tap_opt = CGEvent::tap_create(
+            CGEventTapLocation::HIDEventTap, // Intercept at the HID level (earliest)
+            CGEventTapPlacement::HeadInsertEventTap,
+            CGEventTapOptions::Default, // Active tap that can modify/block events
+            event_mask,
+            Some(event_tap_callback),
+            std::ptr::null_mut(),
+        );
+
+        let Some(port) = tap_opt else {
+            return false;
+        };
+
+        // Get raw pointer for storing and run loop source creation
+        let tap_ptr = CFRetained::as_ptr(&port).as_ptr() as *mut c_void;
+
+        // Deliver the tap's events on the current run loop
+        let Some(source) = RunLoopSource::for_mach_port(&port) else {
+            return false;
+        };
+        let tap = EventTapGuard {
+            port,
+            _source: source,
+        };
+
+        // Store the tap pointer globally so we can re-enable it from the callback
+        EVENT_TAP.store(tap_ptr, Ordering::SeqCst);
+
+        // Enable the tap
+        CGEventTapEnable(tap_ptr, true);
+
+        // Keep the tap alive until `release_event_tap`; replacing a previous
+        // guard (after sleep) drops it
+        let previous = EVENT_TAP_GUARD.with(|guard| guard.borrow_mut().replace(tap));
+        drop(previous);
+
+        true
+    }
+}
+
+define_class!(
+    // Target for the menu bar menu's actions
+    #[unsafe(super(NSObject))]
+    #[thread_kind = MainThreadOnly]
+    #[name = "CatShieldMenuActions"]
+    struct MenuActions;
+
+    impl MenuActions {
+        /// A quick-start preset was chosen; its tag is the index in `MENU_PRESETS`
+        #[unsafe(method(startPreset:))]
+        fn start_preset(&self, sender: &NSMenuItem) {
+            let preset = MENU_PRESETS.with(|presets| {
+                usize::try_from(sender.tag())
+                    .ok()
+                    .and_then(|index| presets.borrow().get(index).cloned())
+            });
+            if let Some((title, timer)) = preset {
+                start_preset_session(&title, timer);
+                refresh_menu_bar_popover();
+            }
+        }
+
+        /// The status item was clicked: toggle the popover, or show the full
+        /// menu on a right-click or Control-click
+        #[unsafe(method(togglePopover:))]
+        fn toggle_popover(&self, _sender: Option<&AnyObject>) {
+            let mtm = MainThreadMarker::from(self);
+            let secondary = NSApplication::sharedApplication(mtm)
+                .currentEvent()
+                .is_some_and(|event| {
+                    event.r#type() == NSEventType::RightMouseUp
+                        || event.modifierFlags().contains(NSEventModifierFlags::Control)
+                });
+            MENU_BAR_POPOVER.with(|popover| {
+                if let Some(popover) = popover.borrow().as_ref() {
+                    if secondary {
+                        popover.show_menu(mtm);
+                    } else {
+                        popover.toggle(mtm);
+                    }
+                }
+            });
+            refresh_menu_bar_popover();
+        }
+
+        /// The popover's Start button: pop up the presets under it
+        #[unsafe(method(showPresets:))]
+        fn show_presets(&self, _sender: Option<&AnyObject>) {
+            MENU_BAR_POPOVER.with(|popover| {
+                if let Some(popover) = popover.borrow().as_ref() {
+                    let button = &popover.start_button;
+                    popover.presets.popUpMenuPositioningItem_atLocation_inView(
+                        None,
+                        CGPoint {
+                            x: 0.0,
+                            y: button.bounds().size.height + 4.0,
+                        },
+                        Some(button),
+                    );
+                }
+            });
+        }
+
+        #[unsafe(method(pauseSession:))]
+        fn pause_session(&self, _sender: Option<&AnyObject>) {
+            let paused = read_menu_bar_report().is_some_and(|report| report.paused);
+            control_menu_bar_session(if paused {
+                SessionControl::Resume
+            } else {
+                SessionControl::Pause(None)
+            });
+        }
+
+        #[unsafe(method(extendSession:))]
+        fn extend_session(&self, _sender: Option<&AnyObject>) {
+            control_menu_bar_session(SessionControl::Extend(MENU_BAR_EXTEND_SECS));
+        }
+
+        #[unsafe(method(stopSession:))]
+        fn stop_session(&self, _sender: Option<&AnyObject>) {
+            control_menu_bar_session(SessionControl::Stop);
+        }
+    }
+);
+
+impl MenuActions {
+    fn new(mtm: MainThreadMarker) -> Retained<Self> {
+        unsafe { msg_send![mtm.alloc::<Self>(), init] }
+    }
+}
+
+/// Command-line arguments that start a preset's session: `run`, with the
+/// preset's timer if it has one
+fn preset_command_args(timer: Option<u64>) -> Vec<String> {
+    let mut args = vec!["run".to_string()];
+    if let Some(secs) = timer {
+        args.push("--timer".to_string());
+        args.push(format!("{}s", secs));
+    }
+    args
+}
+
+/// Process ID of the session the menu bar started, while it's running
+fn menu_bar_session_pid() -> Option<u32> {
+    MENU_BAR_SESSION.with(|session| {
+        let mut session = session.borrow_mut();
+        let child = session.as_mut()?;
+        matches!(child.try_wait(), Ok(None)).then(|| child.id())
+    })
+}
+
+/// Health file the menu bar's sessions write, which the popover reads
+fn menu_bar_health_path() -> PathBuf {
+    std::env::temp_dir().join(format!("catshield-menu-bar-{}.json", process::id()))
+}
+
+/// Start a shield session from a menu bar preset. The session is `cat_shield
+/// run`, exactly as from the command line, in a child process, so the menu
+/// bar app is still there when the session ends.
+fn start_preset_session(title: &str, timer: Option<u64>) {
+    if menu_bar_session_pid().is_some() || running_session().is_ok() {
+        eprintln!("  ⚠️  A shield session is already running");
+        return;
+    }
+
+    // A report left by the previous session would show its countdown
+    let health_path = menu_bar_health_path();
+    let _ = fs::remove_file(&health_path);
+    let child = std::env::current_exe().and_then(|exe| {
+        process::Command::new(exe)
+            .args(preset_command_args(timer))
+            .arg("--health-file")
+            .arg(&health_path)
+            .spawn()
+    });
+    match child {
+        Ok(child) => {
+            println!("  ✓ {}", title);
+            MENU_BAR_SESSION.with(|session| session.replace(Some(child)));
+        }
+        Err(e) => eprintln!("  ✗ Could not start the shield: {}", e),
+    }
+}
+
+/// The fields of a session's health report the popover and `status` show
+#[derive(Debug, Deserialize)]
+struct SessionReport {
+    pid: u32,
+    state: String,
+    remaining_secs: Option<u64>,
+    duration_secs: Option<u64>,
+    #[serde(default)]
+    paused: bool,
+    #[serde(default)]
+    armed: bool,
+    #[serde(default)]
+    watching: bool,
+    blocked_key_presses: u64,
+    updated: u64,
+}
+
+/// The popover's countdown ring and status line
+#[derive(Debug, PartialEq)]
+struct SessionDisplay {
+    /// Fraction of the timer left, for the ring
+    fraction: f64,
+    /// Text inside the ring
+    time: String,
+    /// Line under the ring
+    status: String,
+}
+
+/// What the popover shows for a session's latest report, or with no session.
+/// Reports are written every few seconds, so a running countdown is carried
+/// forward from when the report was written.
+fn session_display(report: Option<&SessionReport>, now: u64) -> SessionDisplay {
+    let Some(report) = report else {
+        return SessionDisplay {
+            fraction: 0.0,
+            time: "Ready".to_string(),
+            status: "Not shielded".to_string(),
+        };
+    };
+
+    let blocked = match report.blocked_key_presses {
+        1 => "1 key blocked".to_string(),
+        count => format!("{} keys blocked", count),
+    };
+    let Some(remaining) = report.remaining_secs else {
+        return SessionDisplay {
+            fraction: 1.0,
+            time: "∞".to_string(),
+            status: match (report.paused, report.watching, report.armed) {
+                (true, _, _) => format!("Paused · {}", blocked),
+                (false, true, true) => format!("Armed · {}", blocked),
+                (false, true, false) => format!("Disarmed · {}", blocked),
+                (false, false, _) => format!("Until unlocked · {}", blocked),
+            },
+        };
+    };
+
+    let remaining = if report.paused {
+        remaining
+    } else {
+        remaining.saturating_sub(now.saturating_sub(report.updated))
+    };
+    let duration = report.duration_secs.unwrap_or(remaining).max(1);
+    SessionDisplay {
+        fraction: (remaining as f64 / duration as f64).min(1.0),
+        time: format_duration(remaining),
+        status: if report.paused {
+            format!("Paused · {}", blocked)
+        } else {
+            blocked
+        },
+    }
+}
+
+/// Latest health report of the menu bar's running session
+fn read_menu_bar_report() -> Option<SessionReport> {
+    menu_bar_session_pid()?;
+    let json = fs::read_to_string(menu_bar_health_path()).ok()?;
+    serde_json::from_str(&json).ok()
+}
+
+/// Send a control to the menu bar's running session, if there is one
+fn control_menu_bar_session(control: SessionControl) {
+    if let Some(pid) = menu_bar_session_pid() {
+        send_session_control(control, pid);
+    }
+}
+
+/// The status item's popover: a countdown ring, the blocked count, and
+/// Start / Pause / +15m / Stop buttons. The classic menu stays one
+/// right-click away.
+struct MenuBarPopover {
+    status_item: Retained<NSStatusItem>,
+    menu: Retained<NSMenu>,
+    presets: Retained<NSMenu>,
+    popover: Retained<NSPopover>,
+    countdown: Retained<CountdownRingView>,
+    status: Retained<NSTextField>,
+    start_button: Retained<NSButton>,
+    pause_button: Retained<NSButton>,
+    extend_button: Retained<NSButton>,
+    stop_button: Retained<NSButton>,
+    _refresh_timer: Option<RunLoopTimer>,
+}
+
+impl MenuBarPopover {
+    fn toggle(&self, mtm: MainThreadMarker) {
+        if self.popover.isShown() {
+            unsafe { self.popover.performClose(None) };
+        } else if let Some(button) = self.status_item.button(mtm) {
+            self.popover.showRelativeToRect_ofView_preferredEdge(
+                button.bounds(),
+                &button,
+                NSRectEdge::MinY,
+            );
+        }
+    }
+
+    /// Open the classic menu from the status item
+    fn show_menu(&self, mtm: MainThreadMarker) {
+        if self.popover.isShown() {
+            unsafe { self.popover.performClose(None) };
+        }
+        if let Some(button) = self.status_item.button(mtm) {
+            self.status_item.setMenu(Some(&self.menu));
+            unsafe { button.performClick(None) };
+            self.status_item.setMenu(None);
+        }
+    }
+
+    /// Show the running session's state, or the idle state once it ends
+    fn refresh(&self, mtm: MainThreadMarker) {
+        let running = menu_bar_session_pid().is_some();
+        let report = read_menu_bar_report();
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+        let display = session_display(report.as_ref().filter(|_| running), now);
+        let timed = running && report.as_ref().is_some_and(|r| r.remaining_secs.is_some());
+        let paused = report.as_ref().is_some_and(|r| r.paused);
+
+        self.countdown
+            .set_countdown(display.fraction, display.time.clone());
+        self.status
+            .setStringValue(&NSString::from_str(&display.status));
+        self.start_button.setEnabled(!running);
+        self.pause_button.setEnabled(timed);
+        self.pause_button.setTitle(if paused {
+            ns_string!("Resume")
+        } else {
+            ns_string!("Pause")
+        });
+        self.extend_button.setEnabled(timed);
+        self.stop_button.setEnabled(running);
+
+        if let Some(button) = self.status_item.button(mtm) {
+            let title = if timed {
+                format!("🐱 {}", display.time)
+            } else {
+                "🐱".to_string()
+            };
+            button.setTitle(&NSString::from_str(&title));
+        }
+    }
+}
+
+/// Update the popover and status item from the session's latest report
+fn refresh_menu_bar_popover() {
+    let Some(mtm) = MainThreadMarker::new() else {
+        return;
+    };
+    MENU_BAR_POPOVER.with(|popover| {
+        if let Some(popover) = popover.borrow().as_ref() {
+            popover.refresh(mtm);
+        }
+    });
+}
+
+unsafe extern "C-unwind" fn menu_bar_refresh_callback(
+    _timer: *mut CFRunLoopTimer,
+    _info: *mut c_void,
+) {
+    catch_ffi_panic(refresh_menu_bar_popover)
+}
+
+/// One of the popover's push buttons, sending `action` to the menu actions
+fn popover_button(
+    title: &str,
+    action: objc2::runtime::Sel,
+    actions: &MenuActions,
+    frame: CGRect,
+    mtm: MainThreadMarker,
+) -> Retained<NSButton> {
+    let button = unsafe {
+        NSButton::buttonWithTitle_target_action(
+            &NSString::from_str(title),
+            Some(actions),
+            Some(action),
+            mtm,
+        )
+    };
+    button.setFrame(frame);
+    button
+}
+
+/// Build the status item's popover and start refreshing it every second
+fn setup_menu_bar_popover(
+    status_item: Retained<NSStatusItem>,
+    menu: Retained<NSMenu>,
+    presets: Retained<NSMenu>,
+    actions: &MenuActions,
+    mtm: MainThreadMarker,
+) {
+    let width = MENU_BAR_POPOVER_WIDTH;
+    let content = NSView::initWithFrame(
+        mtm.alloc(),
+        CGRect {
+            origin: CGPoint { x: 0.0, y: 0.0 },
+            size: CGSize {
+                width,
+                height: MENU_BAR_POPOVER_HEIGHT,
+            },
+        },
+    );
+    let line = |y: CGFloat| CGRect {
+        origin: CGPoint { x: 10.0, y },
+        size: CGSize {
+            width: width - 20.0,
+            height: 20.0,
+        },
+    };
+
+    let title = NSTextField::labelWithString(ns_string!("🐱 Cat Shield"), mtm);
+    title.setFrame(line(MENU_BAR_POPOVER_HEIGHT - 32.0));
+    title.setAlignment(NSTextAlignment::Center);
+    title.setFont(Some(&NSFont::boldSystemFontOfSize(14.0)));
+    content.addSubview(&title);
+
+    let countdown = CountdownRingView::new(
+        mtm,
+        CGRect {
+            origin: CGPoint {
+                x: (width - MENU_BAR_COUNTDOWN_SIZE) / 2.0,
+                y: 86.0,
+            },
+            size: CGSize {
+                width: MENU_BAR_COUNTDOWN_SIZE,
+                height: MENU_BAR_COUNTDOWN_SIZE,
+            },
+        },
+    );
+    content.addSubview(&countdown);
+
+    let status = NSTextField::labelWithString(ns_string!(""), mtm);
+    status.setFrame(line(56.0));
+    status.setAlignment(NSTextAlignment::Center);
+    content.addSubview(&status);
+
+    // Four equal buttons along the bottom
+    let button_width = (width - 20.0 - 3.0 * 6.0) / 4.0;
+    let button_frame = |index: usize| CGRect {
+        origin: CGPoint {
+            x: 10.0 + index as CGFloat * (button_width + 6.0),
+            y: 14.0,
+        },
+        size: CGSize {
+            width: button_width,
+            height: 28.0,
+        },
+    };
+    let start_button = popover_button(
+        "Start ▾",
+        objc2::sel!(showPresets:),
+        actions,
+        button_frame(0),
+        mtm,
+    );
+    let pause_button = popover_button(
+        "Pause",
+        objc2::sel!(pauseSession:),
+        actions,
+        button_frame(1),
+        mtm,
+    );
+    let extend_button = popover_button(
+        &format!("+{}m", MENU_BAR_EXTEND_SECS / 60),
+        objc2::sel!(extendSession:),
+        actions,
+        button_frame(2),
+        mtm,
+    );
+    let stop_button = popover_button(
+        "Stop",
+        objc2::sel!(stopSession:),
+        actions,
+        button_frame(3),
+        mtm,
+    );
+    for button in [&start_button, &pause_button, &extend_button, &stop_button] {
+        content.addSubview(button);
+    }
+
+    let controller = NSViewController::new(mtm);
+    controller.setView(&content);
+    let popover = NSPopover::new(mtm);
+    popover.setBehavior(NSPopoverBehavior::Transient);
+    popover.setContentSize(content.frame().size);
+    popover.setContentViewController(Some(&controller));
+
+    // The status item sends its action on either click; the action decides
+    // between the popover and the menu
+    if let Some(button) = status_item.button(mtm) {
+        unsafe {
+            button.setTarget(Some(actions));
+            button.setAction(Some(objc2::sel!(togglePopover:)));
+        }
+        button.sendActionOn(NSEventMask::LeftMouseUp | NSEventMask::RightMouseUp);
+    }
+
+    let popover = MenuBarPopover {
+        status_item,
+        menu,
+        presets,
+        popover,
+        countdown,
+        status,
+        start_button,
+        pause_button,
+        extend_button,
+        stop_button,
+        _refresh_timer: RunLoopTimer::schedule(
+            MENU_BAR_REFRESH_INTERVAL,
+            menu_bar_refresh_callback,
+        ),
+    };
+    popover.refresh(mtm);
+    MENU_BAR_POPOVER.with(|slot| slot.replace(Some(popover)));
+}
+
+/// Set up the menu bar status item with cat emoji icon
+///
+/// Creates an NSStatusItem in the system menu bar with:
+/// - Cat emoji (🐱) as the icon, followed by the time left during a session
+/// - "Cat Shield" tooltip on hover
+/// - A popover on click with a live countdown ring, the blocked key count,
+///   and Start / Pause / +15m / Stop buttons
+/// - Comprehensive dropdown menu on right-click with all application features
+///
+/// Menu Structure:
+/// - Header: "🐱 Cat Shield" (branding)
+/// - Protection: quick-start presets (the config file's `presets`, or 30m, 1h,
+///   and 2h; then until unlocked) and Stop Protection
+/// - Configuration: Settings (for Issue #16)
+/// - Information: About and Help (About for Issue #19)
+/// - Exit: Quit with Cmd+Q
+///
+/// Returns the Retained<NSStatusItem> which must be kept alive for the duration
+/// of the app to prevent the status item from being deallocated.
+fn setup_menu_bar(mtm: MainThreadMarker) -> Retained<NSStatusItem> {
+    // Get the system status bar
+    let status_bar = NSStatusBar::systemStatusBar();
+
+    // Create a status item with variable length (adjusts to content)
+    // NSVariableStatusItemLength = -1.0
+    let status_item = status_bar.statusItemWithLength(-1.0);
+
+    // Configure the button (the clickable part of the status item)
+    if let Some(button) = status_item.button(mtm) {
+        // Set the cat emoji as the title
+        button.setTitle(ns_string!("🐱"));
+
+        // Set tooltip for accessibility
+        button.setToolTip(Some(ns_string!("Cat Shield - Protect your work from curious cats")));
+    }
+
+    // Create the main dropdown menu
+    let menu = NSMenu::new(mtm);
+
+    // ============================================
+    // HEADER SECTION
+    // ============================================
+
+    // Add "Cat Shield" title (disabled, just for branding)
+    let title_item = NSMenuItem::new(mtm);
+    title_item.setTitle(ns_string!("🐱 Cat Shield"));
+    title_item.setEnabled(false);
+    menu.addItem(&title_item);
+
+    menu.addItem(&NSMenuItem::separatorItem(mtm));
+
+    // ============================================
+    // PROTECTION SECTION
+    // ============================================
+
+    // Add the quick-start presets, each starting a session as `cat_shield run` would
+    let mut presets = Config::load().presets();
+    if presets.is_empty() {
+        presets = DEFAULT_PRESETS
+            .iter()
+            .filter_map(|entry| Preset::parse(entry).ok())
+            .collect();
+    }
+    let mut items: Vec<(String, Option<u64>)> = presets
+        .iter()
+        .map(|preset| (preset.menu_title(), Some(preset.secs)))
+        .collect();
+    items.push(("Shield Until I Return".to_string(), None));
+
+    // The same presets also drop down from the popover's Start button
+    let actions = MenuActions::new(mtm);
+    let presets_menu = NSMenu::new(mtm);
+    for (index, (title, _)) in items.iter().enumerate() {
+        for target_menu in [&menu, &presets_menu] {
+            let preset_item = NSMenuItem::new(mtm);
+            preset_item.setTitle(&NSString::from_str(title));
+            preset_item.setTag(index as isize);
+            unsafe {
+                preset_item.setTarget(Some(&actions));
+                preset_item.setAction(Some(objc2::sel!(startPreset:)));
+            }
+            target_menu.addItem(&preset_item);
+        }
+    }
+    MENU_PRESETS.with(|slot| slot.replace(items));
+
+    // Add "Stop Protection" item: ends the running session like the popover's Stop
+    let stop_item = NSMenuItem::new(mtm);
+    stop_item.setTitle(ns_string!("Stop Protection"));
+    stop_item.setToolTip(Some(ns_string!("End the running shield session")));
+    unsafe {
+        stop_item.setTarget(Some(&actions));
+        stop_item.setAction(Some(objc2::sel!(stopSession:)));
+    }
+    menu.addItem(&stop_item);
+
+    menu.addItem(&NSMenuItem::separatorItem(mtm));
+
+    // ============================================
+    // CONFIGURATION SECTION
+    // ============================================
+
+    // Add "Settings..." item (will be functional in Issue #16)
+    // Opens settings window for configuring timer, opacity, exit key, etc.
+    let settings_item = NSMenuItem::new(mtm);
+    settings_item.setTitle(ns_string!("Settings..."));
+    settings_item.setToolTip(Some(ns_string!("Configure shield settings (Available in Issue #16)")));
+    settings_item.setKeyEquivalent(ns_string!(",")); // Standard Cmd+, for settings
+    settings_item.setEnabled(false); // Disabled until Issue #16 implements settings window
+    menu.addItem(&settings_item);
+
+    menu.addItem(&NSMenuItem::separatorItem(mtm));
+
+    // ============================================
+    // INFORMATION SECTION
+    // ============================================
+
+    // Add "About Cat Shield" item (will be functional in Issue #19)
+    // Shows version, credits, and app information
+    let about_item = NSMenuItem::new(mtm);
+    about_item.setTitle(ns_string!("About Cat Shield"));
+    about_item.setToolTip(Some(ns_string!("About this application (Available in Issue #19)")));
+    about_item.setEnabled(false); // Disabled until Issue #19 implements about panel
+    menu.addItem(&about_item);
+
+    // Add "Help" submenu
+    // Contains links to documentation, GitHub, and support resources
+    let help_item = NSMenuItem::new(mtm);
+    help_item.setTitle(ns_string!("Help"));
+
+    // Create Help submenu
+    let help_submenu = NSMenu::new(mtm);
+
+    // Help -> View Documentation
+    let docs_item = NSMenuItem::new(mtm);
+    docs_item.setTitle(ns_string!("View Documentation"));
+    docs_item.setToolTip(Some(ns_string!("Open README on GitHub")));
+    docs_item.setEnabled(false); // Will need custom action handler to open URL
+    help_submenu.addItem(&docs_item);
+
+    // Help -> Report Issue
+    let issue_item = NSMenuItem::new(mtm);
+    issue_item.setTitle(ns_string!("Report Issue"));
+    issue_item.setToolTip(Some(ns_string!("Report a bug on GitHub")));
+    issue_item.setEnabled(false); // Will need custom action handler to open URL
+    help_submenu.addItem(&issue_item);
+
+    // Help -> Release Notes
+    let release_item = NSMenuItem::new(mtm);
+    release_item.setTitle(ns_string!("Release Notes"));
+    release_item.setToolTip(Some(ns_string!("View ROADMAP and release notes")));
+    release_item.setEnabled(false); // Will need custom action handler to open URL
+    help_submenu.addItem(&release_item);
+
+    help_item.setSubmenu(Some(&help_submenu));
+    menu.addItem(&help_item);
+
+    menu.addItem(&NSMenuItem::separatorItem(mtm));
+
+    // ============================================
+    // EXIT SECTION
+    // ============================================
+
+    // Add "Quit Cat Shield" item
+    // Note: This uses the standard terminate: action which NSApplication handles
+    let quit_item = NSMenuItem::new(mtm);
+    quit_item.setTitle(ns_string!("Quit Cat Shield"));
+    quit_item.setToolTip(Some(ns_string!("Quit the application")));
+    unsafe {
+        quit_item.setAction(Some(objc2::sel!(terminate:)));
+    }
+    // Set keyboard shortcut Cmd+Q
+    quit_item.setKeyEquivalent(ns_string!("q"));
+    menu.addItem(&quit_item);
+
+    // A click opens the popover; the menu is attached only for a right-click
+    setup_menu_bar_popover(status_item.clone(), menu, presets_menu, &actions, mtm);
+    // Menu items and buttons only hold their target weakly
+    MENU_ACTIONS.with(|slot| slot.replace(Some(actions)));
+
+    println!("  ✓ Menu bar icon active (🐱) with live controls and dropdown menu");
+
+    status_item
+}
+
+/// Connect to the keyboard backlight and turn on the burst deterrent
+fn enable_backlight_deterrent() {
+    if connect_keyboard_backlight() {
+        BACKLIGHT_DETERRENT_ENABLED.store(true, Ordering::SeqCst);
+        println!("  ✓ Backlight deterrent: key bursts flash the keyboard backlight");
+    } else {
+        eprintln!("  ⚠️  No controllable keyboard backlight found - deterrent disabled");
+    }
+}
+
+/// Connect to the keyboard backlight once, shared by all backlight features
+///
+/// # Returns
+/// `true` if a controllable backlight is available
+fn connect_keyboard_backlight() -> bool {
+    KEYBOARD_BACKLIGHT.with(|slot| {
+        let mut slot = slot.borrow_mut();
+        if slot.is_none() {
+            *slot = KeyboardBacklight::connect();
+        }
+        slot.is_some()
+    })
+}
+
+/// Turn the keyboard backlight off for `--backlight-off`, remembering its level
+fn turn_off_keyboard_backlight() -> bool {
+    if !connect_keyboard_backlight() {
+        return false;
+    }
+
+    KEYBOARD_BACKLIGHT.with(|backlight| {
+        let backlight = backlight.borrow();
+        let Some(backlight) = backlight.as_ref() else {
+            return false;
+        };
+        if SAVED_BACKLIGHT_LEVEL.with(|saved| saved.get()).is_none() {
+            let level = backlight.brightness();
+            SAVED_BACKLIGHT_LEVEL.with(|saved| saved.set(Some(level)));
+        }
+        backlight.set_brightness(0.0);
+        true
+    })
+}
+
+/// The keyboard backlight level to put back on exit: the one saved by
+/// `turn_off_keyboard_backlight`, else the one a flash that hasn't finished
+/// started from. Taking it ends the flash.
+fn take_backlight_restore_level() -> Option<f32> {
+    let flash = BACKLIGHT_FLASH.with(Cell::take);
+    SAVED_BACKLIGHT_LEVEL
+        .with(Cell::take)
+        .or(flash.map(|(_, original)| original))
+}
+
+/// Restore the keyboard backlight level saved by `turn_off_keyboard_backlight`,
+/// or interrupted by a flash
+fn restore_keyboard_backlight() {
+    let Some(level) = take_backlight_restore_level() else {
+        return;
+    };
+
+    KEYBOARD_BACKLIGHT.with(|backlight| {
+        if let Some(backlight) = backlight.borrow().as_ref() {
+            backlight.set_brightness(level);
+            println!("  ✓ Keyboard backlight restored");
+        }
+    });
+}
+
+/// Run watch mode: no overlay, the event tap stays installed but passive, and
+/// keyboard blocking is armed automatically when a trigger fires.
+fn run_watch_mode(app: &NSApplication, args: &Args, exit_key: &ExitKey) {
+    WATCH_MODE.store(true, Ordering::SeqCst);
+    BLOCKING_ARMED.store(false, Ordering::SeqCst);
+    transition(ShieldEvent::Arm);
+
+    if !setup_message_bus() {
+        eprintln!("  ✗ Failed to set up the message bus");
+        process::exit(1);
+    }
+
+    println!();
+    println!("  🐱 CAT SHIELD 🛡️");
+    println!("  ════════════════════════════════════════");
+    println!("  Watch mode active");
+    println!();
+
+    if setup_event_tap() {
+        println!("  ✓ Event tap ready (disarmed)");
+    } else {
+        eprintln!("  ✗ Failed to create event tap");
+        log_error("Failed to create event tap");
+        process::exit(1);
+    }
+    load_hooks();
+    load_detection_plugins();
+
+    if setup_keyboard_watch() {
+        println!("  ✓ Watching for external keyboard disconnects");
+    } else {
+        eprintln!("  ✗ Failed to watch keyboard devices");
+        process::exit(1);
+    }
+
+    // Keeps the event tap alive across sleep; there is no overlay or timer to manage
+    if !setup_power_monitor() {
+        eprintln!("  ⚠️  Could not register for power notifications");
+    }
+
+    // `cat_shield stop`, `pause`, and `resume` reach the session through these,
+    // and Ctrl+C releases the tap and removes the session files on the way out
+    setup_system_notifications();
+    install_signal_handlers();
+
+    if args.backlight_deterrent {
+        enable_backlight_deterrent();
+    }
+
+    enable_microphone(args);
+
+    if let Some(action) = args.block_launches {
+        enable_launch_blocking(action);
+    }
+    if let Some(ref app) = args.forward_to {
+        enable_key_forwarding(app, &args.forward_keys);
+    }
+    if args.allow_universal_control {
+        ALLOW_UNIVERSAL_CONTROL.store(true, Ordering::SeqCst);
+        println!("  ✓ Keys from Universal Control pass through");
+    }
+    if args.allow_remote_input {
+        ALLOW_REMOTE_INPUT.store(true, Ordering::SeqCst);
+        println!("  ✓ Keys from Screen Sharing and Remote Desktop pass through");
+    }
+    if args.allow_accessibility_shortcuts {
+        ALLOW_ACCESSIBILITY_SHORTCUTS.store(true, Ordering::SeqCst);
+        println!("  ✓ Accessibility shortcuts (VoiceOver, Zoom, Sticky Keys) pass through");
+    }
+    if args.require_admin_exit {
+        REQUIRE_ADMIN_EXIT.store(true, Ordering::SeqCst);
+        println!("  ✓ Parental mode: disarming requires an administrator password");
+    }
+    warn_about_other_sessions();
+
+    let frontmost_rules = Config::load().activate_when_frontmost;
+    let arms_for_frontmost = !frontmost_rules.is_empty();
+    if arms_for_frontmost {
+        println!("  ✓ Arming while frontmost: {}", frontmost_rules.join(", "));
+        setup_frontmost_watch(frontmost_rules);
+    }
+
+    // Services deferred unlock requests (admin authentication) and backlight flashes
+    start_close_button_timer();
+    transition(ShieldEvent::Armed);
+
+    if let Some(ref path) = args.health_file {
+        enable_health_file(path.clone());
+    }
+    enable_session_file();
+
+    if args.daemon {
+        match start_control_socket() {
+            Ok(path) => println!("  ✓ Control socket: {}", path.display()),
+            Err(e) => {
+                eprintln!("  ✗ Could not open the control socket: {}", e);
+                process::exit(1);
+            }
+        }
+    }
+
+    enter_status_screen();
+    println!();
+    println!("  Blocking arms automatically when an external keyboard disconnects.");
+    if args.meow_detect {
+        println!("  Meows heard on the microphone arm it too.");
+    }
+    if args.loudness_threshold.is_some() && args.loudness_action == LoudnessAction::Arm {
+        println!("  So does a sustained loud sound.");
+    }
+    if arms_for_frontmost {
+        println!("  So does bringing a listed app to the front, until it loses focus.");
+    }
+    if args.daemon {
+        println!("  Socket commands: activate [DURATION], deactivate, status.");
+    }
+    println!(
+        "  To disarm, {}. Ctrl+C quits.",
+        exit_key_instruction(&exit_key.display_name, args.exit_key_hold)
+    );
+    println!();
+
+    app.run();
+
+    leave_status_screen();
+    stop_close_button_timer();
+}
+
+/// Control socket for `--daemon`, in the temp directory
+fn control_socket_path() -> PathBuf {
+    std::env::temp_dir().join("catshield.sock")
+}
+
+/// A command on the daemon's control socket, one per line
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum DaemonCommand {
+    /// Arm keyboard blocking, for this many seconds if given
+    Activate(Option<u64>),
+    Deactivate,
+    Status,
+}
+
+impl DaemonCommand {
+    fn parse(line: &str) -> Result<Self, String> {
+        let mut words = line.split_whitespace();
+        let command = match (words.next(), words.next()) {
+            (Some("activate"), None) => DaemonCommand::Activate(None),
+            (Some("activate"), Some(duration)) => {
+                DaemonCommand::Activate(Some(parse_duration(duration)?))
+            }
+            (Some("deactivate"), None) => DaemonCommand::Deactivate,
+            (Some("status"), None) => DaemonCommand::Status,
+            (Some(command), _) => return Err(format!("unknown command '{}'", command)),
+            (None, _) => return Err("empty command".to_string()),
+        };
+        if words.next().is_some() {
+            return Err("too many arguments".to_string());
+        }
+        Ok(command)
+    }
+}
+
+/// The daemon's state, as the `status` command reports it
+#[derive(Debug, PartialEq, Serialize)]
+struct DaemonStatus {
+    pid: u32,
+    armed: bool,
+    /// Time left of a timed `activate`
+    remaining_secs: Option<u64>,
+    blocked_key_presses: u64,
+}
+
+impl DaemonStatus {
+    fn current(now: u64) -> Self {
+        let armed = BLOCKING_ARMED.load(Ordering::SeqCst);
+        let until = DAEMON_ARMED_UNTIL.load(Ordering::SeqCst);
+        DaemonStatus {
+            pid: process::id(),
+            armed,
+            remaining_secs: (armed && until > 0).then(|| until.saturating_sub(now)),
+            blocked_key_presses: BLOCKED_KEY_PRESSES.load(Ordering::SeqCst),
+        }
+    }
+}
+
+/// The JSON line the daemon answers `line` with. Activation is handed to the
+/// main thread, so the reply only says the command was accepted.
+fn daemon_reply(line: &str, now: u64) -> String {
+    let reply = match DaemonCommand::parse(line) {
+        Ok(DaemonCommand::Status) => serde_json::to_value(DaemonStatus::current(now))
+            .unwrap_or_else(|e| serde_json::json!({ "ok": false, "error": e.to_string() })),
+        Ok(DaemonCommand::Activate(secs)) => {
+            post_message(ShieldMessage::DaemonActivate(secs));
+            serde_json::json!({ "ok": true })
+        }
+        Ok(DaemonCommand::Deactivate) => {
+            post_message(ShieldMessage::DaemonDeactivate);
+            serde_json::json!({ "ok": true })
+        }
+        Err(e) => serde_json::json!({ "ok": false, "error": e }),
+    };
+    reply.to_string()
+}
+
+/// Listen on the control socket on a background thread. Only this user can
+/// connect: the socket is in their temp directory and readable by them alone.
+///
+/// # Returns
+/// The socket's path
+fn start_control_socket() -> std::io::Result<PathBuf> {
+    use std::io::{BufRead, BufReader, Write};
+    use std::os::unix::fs::PermissionsExt;
+    use std::os::unix::net::UnixListener;
+
+    // Left behind by a daemon that was killed; the instance lock says it's gone
+    let path = control_socket_path();
+    let _ = fs::remove_file(&path);
+    let listener = UnixListener::bind(&path)?;
+    fs::set_permissions(&path, fs::Permissions::from_mode(0o600))?;
+
+    std::thread::spawn(move || {
+        for stream in listener.incoming().flatten() {
+            // Each client gets its own thread, so one that stays connected
+            // (nc without -N) can't hold up the rest; idle ones are dropped
+            let _ = stream.set_read_timeout(Some(CONTROL_CLIENT_IDLE));
+            std::thread::spawn(move || {
+                let mut reader = BufReader::new(&stream);
+                let mut line = String::new();
+                while matches!(reader.read_line(&mut line), Ok(read) if read > 0) {
+                    let reply = daemon_reply(line.trim(), monotonic_secs());
+                    if writeln!(&stream, "{}", reply).is_err() {
+                        break;
+                    }
+                    line.clear();
+                }
+            });
+        }
+    });
+    Ok(path)
+}
+
+/// Remove the control socket on exit. Only one shield runs at a time, so a
+/// socket there can only be this one's.
+fn remove_control_socket() {
+    let _ = fs::remove_file(control_socket_path());
+}
+
+/// Forget the time left of a timed daemon `activate`
+fn clear_daemon_deadline() {
+    remove_arm_cause(ArmCause::DaemonTimer);
+    DAEMON_ARMED_UNTIL.store(0, Ordering::SeqCst);
+    // Dropped outside the borrow
+    let timer = DAEMON_DISARM_TIMER.with(|slot| slot.borrow_mut().take());
+    drop(timer);
+}
+
+/// Arm blocking for the daemon's `activate`, disarming again after `secs`
+fn daemon_activate(secs: Option<u64>) {
+    // Replaces the time left of an earlier timed activation
+    clear_daemon_deadline();
+    let Some(secs) = secs else {
+        arm_blocking(ArmCause::UntilUnlocked, "daemon command");
+        return;
+    };
+    arm_blocking(ArmCause::DaemonTimer, "daemon command");
+    DAEMON_ARMED_UNTIL.store(monotonic_secs() + secs, Ordering::SeqCst);
+    let timer = RunLoopTimer::once(secs as f64, daemon_disarm_timer_callback);
+    DAEMON_DISARM_TIMER.with(|slot| slot.replace(timer));
+}
+
+/// Disarm for the daemon's `deactivate`. That's an unlock like the exit key's,
+/// so with `--require-admin-exit` it waits for an administrator password.
+fn daemon_deactivate() {
+    if BLOCKING_ARMED.load(Ordering::SeqCst) {
+        transition(ShieldEvent::UnlockRequested);
+    } else {
+        // Nothing to unlock; while paused, this keeps blocking off on resume
+        set_blocking_armed(false, "daemon command");
+    }
+}
+
+unsafe extern "C-unwind" fn daemon_disarm_timer_callback(
+    _timer: *mut CFRunLoopTimer,
+    _info: *mut c_void,
+) {
+    catch_ffi_panic(|| {
+        // Something else may keep blocking armed, but the deadline is over either way
+        release_blocking(ArmCause::DaemonTimer, "daemon activation ran out");
+        clear_daemon_deadline();
+    })
+}
+
+/// Give an overlay window its content view: an `OverlayView`, so clicks and
+/// drags on blank areas feed the unlock methods, on a blur when `--blur` is set
+fn set_overlay_content(mtm: MainThreadMarker, window: &NSWindow, size: CGSize) {
+    let content_frame = CGRect {
+        origin: CGPoint { x: 0.0, y: 0.0 },
+        size,
+    };
+    let overlay_view = OverlayView::new(mtm, content_frame);
+    if OVERLAY_BLUR.with(|blur| blur.get()) {
+        // The blur becomes the content view with the overlay view stretched on
+        // top of it, so mouse events still reach the overlay view
+        let blur_view = create_blur_view(mtm, content_frame);
+        overlay_view.setAutoresizingMask(
+            NSAutoresizingMaskOptions::ViewWidthSizable
+                | NSAutoresizingMaskOptions::ViewHeightSizable,
+        );
+        blur_view.addSubview(&overlay_view);
+        window.setContentView(Some(&blur_view));
+
+        // The blur provides the backdrop; keep it fully opaque so nothing shows through
+        window.setAlphaValue(1.0);
+        window.setBackgroundColor(Some(&NSColor::clearColor()));
+    } else {
+        window.setContentView(Some(&overlay_view));
+    }
+}
+
+/// Run `f` on the main overlay window and then each other display's overlay
+fn for_each_overlay_window(mut f: impl FnMut(&NSWindow)) {
+    let window_ptr = OVERLAY_WINDOW.load(Ordering::SeqCst);
+    if !window_ptr.is_null() {
+        f(unsafe { &*(window_ptr as *const NSWindow) });
+    }
+    SECONDARY_OVERLAYS.with(|windows| {
+        for window in windows.borrow().iter() {
+            f(window);
+        }
+    });
+}
+
+/// Create the fullscreen overlay window with its close button and show it
+fn create_overlay_window(
+    mtm: MainThreadMarker,
+    args: &Args,
+    screen_frame: CGRect,
+) -> Retained<NSWindow> {
+    // Create a fullscreen, borderless window
+    let window = unsafe {
+        let window = NSWindow::alloc(mtm);
+        NSWindow::initWithContentRect_styleMask_backing_defer(
+            window,
+            screen_frame,
+            NSWindowStyleMask::Borderless,
+            NSBackingStoreType::Buffered,
+            false,
+        )
+    };
+
+    // Configure window to be topmost
+    window.setLevel(shield_window_level());
+
+    // Set window to appear on all spaces and stay visible. Under Stage Manager
+    // it belongs to every app's stage, so switching stages never moves it into
+    // the strip, and it also covers full-screen apps.
+    window.setCollectionBehavior(
+        NSWindowCollectionBehavior::CanJoinAllSpaces
+            | NSWindowCollectionBehavior::Stationary
+            | NSWindowCollectionBehavior::IgnoresCycle
+            | NSWindowCollectionBehavior::FullScreenAuxiliary
+            | NSWindowCollectionBehavior::CanJoinAllApplications,
+    );
+
+    // Make window semi-transparent (visible but not fully blocking view)
+    NIGHT_MODE.store(args.night, Ordering::SeqCst);
+    window.setOpaque(false);
+    window.setAlphaValue(args.opacity.unwrap_or(if args.night {
+        NIGHT_OVERLAY_ALPHA
+    } else {
+        OVERLAY_ALPHA
+    }));
+
+    // Set a dark background color
+    window.setBackgroundColor(Some(&overlay_background_color(1.0)));
+
+    // Progressive dimming varies the background opacity instead, so the close
+    // button and timer stay fully visible even when the overlay is faint
+    if let Some(mode) = args.dim {
+        DIM_MODE.with(|dim| dim.set(Some(mode)));
+        window.setAlphaValue(1.0);
+    }
+
+    // Keep window visible
+    window.setHidesOnDeactivate(false);
+
+    // Accept mouse events (needed for blocking)
+    window.setIgnoresMouseEvents(false);
+
+    // Set title
+    window.setTitle(ns_string!("Cat Shield"));
+    window.setAccessibilityIdentifier(Some(&NSString::from_str(AX_OVERLAY_WINDOW)));
+
+    // Required when creating NSWindow outside a window controller
+    unsafe {
+        window.setReleasedWhenClosed(false);
+    }
+
+    // Custom content view so clicks and drags on blank areas reach us
+    OVERLAY_BLUR.with(|blur| blur.set(args.blur));
+    set_overlay_content(mtm, &window, screen_frame.size);
+
+    // Show the window, fading it in from transparent
+    let shield_alpha = window.alphaValue();
+    OVERLAY_BASE_ALPHA.with(|alpha| alpha.set(shield_alpha));
+    window.setAlphaValue(0.0);
+    window.makeKeyAndOrderFront(None);
+    fade_window(&window, shield_alpha);
+
+    // Store window reference so it can be lowered during admin authentication.
+    // Safety: the window lives until the end of main, after app.run() returns.
+    OVERLAY_WINDOW.store(Retained::as_ptr(&window) as *mut c_void, Ordering::SeqCst);
+
+    println!("  ✓ Overlay window active");
+    if args.night {
+        println!("  ✓ Night mode: dim red palette");
+    }
+    if args.blur {
+        println!("  ✓ Blurred backdrop active");
+    }
+
+    // Create and add the close button in top-right corner
+    let close_button = CloseButtonView::new(mtm, close_button_frame(screen_frame.size));
+
+    // Store view reference for timer callback.
+    // Safety: The view remains valid because contentView retains it and
+    // app.run() blocks until we're ready to exit. The timer is stopped
+    // before cleanup begins.
+    CLOSE_BUTTON_VIEW.store(
+        Retained::as_ptr(&close_button) as *mut c_void,
+        Ordering::SeqCst,
+    );
+
+    // Add close button to the window's content view
+    if let Some(content_view) = window.contentView() {
+        content_view.addSubview(&close_button);
+    }
+
+    window
+}
+
+/// What `[[displays]]` entries match a display by
+#[derive(Debug, Clone, PartialEq)]
+struct DisplayIdentity {
+    name: String,
+    uuid: Option<String>,
+}
+
+impl DisplayIdentity {
+    fn of(screen: &NSScreen) -> Self {
+        DisplayIdentity {
+            name: screen.localizedName().to_string(),
+            uuid: screen_display_id(screen).and_then(display_uuid),
+        }
+    }
+}
+
+/// CoreGraphics display ID of a screen
+fn screen_display_id(screen: &NSScreen) -> Option<u32> {
+    screen
+        .deviceDescription()
+        .objectForKey(ns_string!("NSScreenNumber"))
+        .and_then(|number| number.downcast::<NSNumber>().ok())
+        .map(|number| number.unsignedIntValue())
+}
+
+/// A display's UUID, which unlike its ID stays the same across reconnects
+fn display_uuid(display: u32) -> Option<String> {
+    unsafe {
+        let uuid = CGDisplayCreateUUIDFromDisplayID(display);
+        if uuid.is_null() {
+            return None;
+        }
+        let string = CFUUIDCreateString(std::ptr::null(), uuid);
+        CFRelease(uuid);
+        if string.is_null() {
+            return None;
+        }
+        let text = (*(string as *const CFString)).to_string();
+        CFRelease(string);
+        Some(text)
+    }
+}
+
+/// The `[[displays]]` entry for a screen, if any
+fn screen_settings(screen: &NSScreen) -> Option<DisplayConfig> {
+    let identity = DisplayIdentity::of(screen);
+    DISPLAY_SETTINGS.with(|displays| display_settings(&displays.borrow(), &identity).cloned())
+}
+
+/// Apply the main display's `[[displays]]` entry to the overlay window
+fn apply_display_settings(window: &NSWindow, screen: &NSScreen) {
+    apply_window_settings(window, screen);
+
+    let settings = screen_settings(screen);
+    let show_widgets = settings
+        .as_ref()
+        .and_then(|settings| settings.widgets)
+        .unwrap_or(true);
+    OVERLAY_WIDGETS.with(|widgets| {
+        for (_, view) in widgets.borrow().iter() {
+            view.setHidden(!show_widgets);
+        }
+    });
+}
+
+/// Fade an overlay window to its display's opacity and let clicks through on a
+/// `passthrough` display
+fn apply_window_settings(window: &NSWindow, screen: &NSScreen) {
+    let settings = screen_settings(screen);
+    let alpha = settings
+        .as_ref()
+        .and_then(|settings| settings.opacity)
+        .unwrap_or_else(|| OVERLAY_BASE_ALPHA.with(|alpha| alpha.get()));
+    fade_window(window, alpha);
+    window.setIgnoresMouseEvents(
+        settings
+            .as_ref()
+            .is_some_and(|settings| settings.passthrough),
+    );
+}
+
+/// Every display other than the main one
+fn secondary_screens(mtm: MainThreadMarker) -> Vec<Retained<NSScreen>> {
+    let main_frame = NSScreen::mainScreen(mtm).map(|screen| screen.frame());
+    NSScreen::screens(mtm)
+        .iter()
+        .filter(|screen| Some(screen.frame()) != main_frame)
+        .collect()
+}
+
+/// Give every display other than the main one its own overlay window. These
+/// share the main overlay's backdrop, dimming, warning flash, unlock gestures
+/// and close button hold; the widgets stay on the main display. The
+/// windows are rebuilt only when the displays changed; otherwise they are
+/// just brought back to the front.
+fn setup_secondary_overlays(mtm: MainThreadMarker) {
+    let screens = secondary_screens(mtm);
+
+    let unchanged = SECONDARY_OVERLAYS.with(|windows| {
+        let windows = windows.borrow();
+        let frames: Vec<CGRect> = windows.iter().map(|window| window.frame()).collect();
+        let wanted: Vec<CGRect> = screens.iter().map(|screen| screen.frame()).collect();
+        if frames == wanted {
+            for window in windows.iter() {
+                window.setLevel(shield_window_level());
+                window.orderFrontRegardless();
+            }
+        }
+        frames == wanted
+    });
+    if unchanged {
+        return;
+    }
+
+    // Match the main overlay, including any dimming already applied
+    let default_alpha = OVERLAY_BASE_ALPHA.with(|alpha| alpha.get());
+    let main_window = OVERLAY_WINDOW.load(Ordering::SeqCst);
+    let background = if main_window.is_null() {
+        overlay_background_color(1.0)
+    } else {
+        let main_window: &NSWindow = unsafe { &*(main_window as *const NSWindow) };
+        main_window.backgroundColor()
+    };
+    let mut windows = Vec::new();
+    let mut close_buttons = Vec::new();
+    for screen in screens {
+        let settings = screen_settings(&screen);
+        let passthrough = settings
+            .as_ref()
+            .is_some_and(|settings| settings.passthrough);
+        let window = unsafe {
+            NSWindow::initWithContentRect_styleMask_backing_defer(
+                NSWindow::alloc(mtm),
+                screen.frame(),
+                NSWindowStyleMask::Borderless,
+                NSBackingStoreType::Buffered,
+                false,
+            )
+        };
+        window.setLevel(shield_window_level());
+        window.setCollectionBehavior(
+            NSWindowCollectionBehavior::CanJoinAllSpaces
+                | NSWindowCollectionBehavior::Stationary
+                | NSWindowCollectionBehavior::IgnoresCycle
+                | NSWindowCollectionBehavior::FullScreenAuxiliary
+                | NSWindowCollectionBehavior::CanJoinAllApplications,
+        );
+        window.setOpaque(false);
+        window.setBackgroundColor(Some(&background));
+        set_overlay_content(mtm, &window, screen.frame().size);
+        window.setHidesOnDeactivate(false);
+        window.setIgnoresMouseEvents(passthrough);
+        unsafe { window.setReleasedWhenClosed(false) };
+
+        // A close button driving the same hold as the main one, so whichever
+        // display the pointer is on can exit. Passthrough displays can't be
+        // clicked, so they get none.
+        if !passthrough {
+            let close_button = CloseButtonView::new(mtm, close_button_frame(screen.frame().size));
+            if let Some(content_view) = window.contentView() {
+                content_view.addSubview(&close_button);
+            }
+            close_buttons.push(close_button);
+        }
+
+        window.setAlphaValue(0.0);
+        window.orderFrontRegardless();
+        fade_window(
+            &window,
+            settings
+                .as_ref()
+                .and_then(|settings| settings.opacity)
+                .unwrap_or(default_alpha),
+        );
+        windows.push(window);
+    }
+
+    SECONDARY_CLOSE_BUTTONS.with(|slot| slot.replace(close_buttons));
+    let previous = SECONDARY_OVERLAYS.with(|slot| slot.replace(windows));
+    for window in previous {
+        window.close();
+    }
+}
+
+/// Apply `[[displays]]` to the overlay and cover the other displays, and
+/// report which displays have settings
+fn setup_display_overlays(
+    mtm: MainThreadMarker,
+    window: &NSWindow,
+    screen: &NSScreen,
+    displays: Vec<DisplayConfig>,
+) {
+    DISPLAY_SETTINGS.with(|slot| slot.replace(displays));
+    apply_display_settings(window, screen);
+    setup_secondary_overlays(mtm);
+
+    let others = SECONDARY_OVERLAYS.with(|windows| windows.borrow().len());
+    if others > 0 {
+        println!("  ✓ Covering {} more display(s)", others);
+    }
+    for screen in NSScreen::screens(mtm).iter() {
+        if let Some(settings) = screen_settings(&screen) {
+            let mut notes = Vec::new();
+            if let Some(opacity) = settings.opacity {
+                notes.push(format!("opacity {}", opacity));
+            }
+            if settings.widgets == Some(false) {
+                notes.push("no widgets".to_string());
+            }
+            if settings.passthrough {
+                notes.push("clicks pass through".to_string());
+            }
+            if notes.is_empty() {
+                continue;
+            }
+            println!(
+                "  ✓ Display \"{}\": {}",
+                screen.localizedName(),
+                notes.join(", ")
+            );
+        }
+    }
+}
+
+/// First option given to `watch` that only makes sense with an overlay or timer,
+/// mirroring the `conflicts_with` list on `--watch`
+fn watch_conflict(args: &Args) -> Option<&'static str> {
+    [
+        ("--timer", args.timer.is_some()),
+        ("--preset", args.preset.is_some()),
+        ("--hide-timer", args.hide_timer),
+        ("--unlock-gesture", args.unlock_gesture.is_some()),
+        ("--knock", args.knock.is_some()),
+        ("--allow-scroll", args.allow_scroll),
+        ("--capture-display", args.capture_display),
+        ("--no-overlay", args.no_overlay),
+        ("--screen-off", args.screen_off),
+        ("--on-projector", args.on_projector.is_some()),
+        ("--summary-json", args.summary_json),
+        ("--summary-file", args.summary_file.is_some()),
+    ]
+    .into_iter()
+    .find(|&(_, given)| given)
+    .map(|(flag, _)| flag)
+}
+
+/// Shield options with clap's defaults applied, as if none were given
+fn default_shield_args() -> Args {
+    let matches = <Args as clap::Args>::augment_args(clap::Command::new("cat_shield"))
+        .get_matches_from(["cat_shield"]);
+    <Args as clap::FromArgMatches>::from_arg_matches(&matches)
+        .expect("shield options have no required arguments")
+}
+
+/// A shield for embedding in another app: the overlay, input blocking, and
+/// sleep prevention, set up in code instead of from the command line
+#[derive(Debug)]
+pub struct Shield {
+    args: Args,
+}
+
+impl Shield {
+    /// Start configuring a shield. Unconfigured, it runs like `cat_shield run`
+    /// with no options.
+    pub fn builder() -> ShieldBuilder {
+        ShieldBuilder {
+            args: default_shield_args(),
+            exit_key: None,
+        }
+    }
+
+    /// Put the shield up and wait until it is unlocked or its timer runs out.
+    ///
+    /// Runs the app's `NSApplication` event loop until then, so it must be
+    /// called on the main thread, once per process. Like the command line, it
+    /// waits for Accessibility permission. Unlike it, it reads no config file,
+    /// hooks, or plugins, sends no telemetry, and leaves the app's panic hook
+    /// and signal handlers alone.
+    pub fn run(self) -> Result<(), String> {
+        if MainThreadMarker::new().is_none() {
+            return Err("The shield must run on the main thread".to_string());
+        }
+        if EMBEDDED.swap(true, Ordering::SeqCst) {
+            return Err("A shield has already run in this process".to_string());
+        }
+        launch(self.args, Config::default(), true)
+    }
+}
+
+/// Settings for a `Shield`; see `Shield::builder()`
+#[derive(Debug)]
+pub struct ShieldBuilder {
+    args: Args,
+    exit_key: Option<String>,
+}
+
+impl ShieldBuilder {
+    /// Lower the shield on its own after `duration` (whole seconds)
+    pub fn timer(mut self, duration: Duration) -> Self {
+        self.args.timer = Some(duration.as_secs());
+        self
+    }
+
+    /// Overlay opacity from 0.0 (invisible) to 1.0
+    pub fn opacity(mut self, opacity: f64) -> Self {
+        self.args.opacity = Some(opacity);
+        self
+    }
+
+    /// Unlock shortcut, written as for `--exit-key` (e.g., "Cmd+Shift+Q")
+    pub fn exit_key(mut self, key: &str) -> Self {
+        self.exit_key = Some(key.to_string());
+        self
+    }
+
+    /// Whether to show the overlay; without it only input is blocked
+    pub fn overlay(mut self, overlay: bool) -> Self {
+        self.args.no_overlay = !overlay;
+        self
+    }
+
+    /// Without the overlay, block only the keyboard and leave the mouse usable
+    pub fn keyboard_only(mut self, keyboard_only: bool) -> Self {
+        self.args.keyboard_only = keyboard_only;
+        self
+    }
+
+    /// Dim red colors for an overnight shield
+    pub fn night(mut self, night: bool) -> Self {
+        self.args.night = night;
+        self
+    }
+
+    /// Check the settings and create the shield
+    pub fn build(self) -> Result<Shield, String> {
+        let mut args = self.args;
+        if let Some(opacity) = args
+            .opacity
+            .filter(|opacity| !(0.0..=1.0).contains(opacity))
+        {
+            return Err(format!("Opacity {} is not between 0.0 and 1.0", opacity));
+        }
+        if args.timer == Some(0) {
+            return Err("Timer must be at least one second".to_string());
+        }
+        if args.keyboard_only && !args.no_overlay {
+            return Err("Keyboard-only blocking needs the overlay turned off".to_string());
+        }
+        if let Some(key) = self.exit_key {
+            args.exit_key = Some(ExitKey::parse(&key)?);
+        }
+        Ok(Shield { args })
+    }
+}
+
+/// Check if the app was launched with arguments that should trigger immediate shield activation
+fn has_immediate_start_args(args: &Args) -> bool {
+    // If timer, preset, exit-key, or no-overlay CLI args are provided, start shield immediately
+    args.timer.is_some() || args.preset.is_some() || args.exit_key.is_some() || args.no_overlay
+}
+
+/// Run the `cat_shield` command line: parse the arguments, then run a
+/// subcommand or start the shield (or the menu bar app)
+pub fn run_cli() {
+    // Parse command line arguments
+    let cli = Cli::parse();
+
+    let (mut args, start_now) = match cli.command {
+        None => {
+            let start_now = has_immediate_start_args(&cli.shield);
+            (cli.shield, start_now)
+        }
+        Some(Command::Run(args)) => (args, true),
+        Some(Command::Watch(mut args)) => {
+            if let Some(flag) = watch_conflict(&args) {
+                Cli::command()
+                    .error(
+                        ErrorKind::ArgumentConflict,
+                        format!("the argument '{}' cannot be used with 'watch'", flag),
+                    )
+                    .exit();
+            }
+            args.watch = true;
+            (args, true)
+        }
+        Some(Command::Keys { duration }) => {
+            let args = Args {
+                timer: Some(duration),
+                no_overlay: true,
+                keyboard_only: true,
+                ..default_shield_args()
+            };
+            (args, true)
+        }
+        Some(Command::Stats { by_label }) => {
+            println!();
+            println!("  🐱 CAT SHIELD STATS 📊");
+            println!("  ════════════════════════════════════════");
+            if by_label {
+                Stats::load().print_by_label();
+            } else {
+                Stats::load().print();
+            }
+            println!();
+            return;
+        }
+        Some(Command::Blame { ref cat }) => {
+            process::exit(run_blame(cat));
+        }
+        Some(Command::CheckUpdate) => {
+            process::exit(run_check_update());
+        }
+        Some(Command::SelfUpdate) => {
+            process::exit(run_self_update());
+        }
+        Some(Command::Telemetry { action }) => {
+            process::exit(run_telemetry(action));
+        }
+        Some(Command::Config { action }) => {
+            process::exit(run_config(action));
+        }
+        Some(Command::Doctor) => {
+            process::exit(run_doctor());
+        }
+        Some(Command::Install { ref dir }) => {
+            process::exit(run_install(dir));
+        }
+        Some(Command::Status) => {
+            process::exit(run_status());
+        }
+        Some(Command::Stop) => {
+            process::exit(run_stop());
+        }
+        Some(Command::Pause { duration }) => {
+            process::exit(run_pause(duration));
+        }
+        Some(Command::Resume) => {
+            process::exit(run_resume());
+        }
+        Some(Command::Extend { duration }) => {
+            process::exit(run_extend(duration));
+        }
+    };
+
+    // A panic must never leave the keyboard blocked
+    install_panic_hook();
+
+    // Load config file
+    let config = Config::load();
+
+    if let Some(name) = args.preset.clone() {
+        if let Err(e) = apply_preset(&name, &config, &mut args) {
+            Cli::command().error(ErrorKind::InvalidValue, e).exit();
+        }
+    }
+
+    if let Err(e) = launch(args, config, start_now) {
+        eprintln!();
+        eprintln!("  ✗ {}", e);
+        eprintln!();
+        process::exit(1);
+    }
+}
+
+/// Start the shield with `args`, or the menu bar app unless `start_now` is set
+/// or it's watch mode. Returns once the shield or the menu bar app has quit.
+///
+/// # Returns
+/// An error if the shield couldn't be set up
+fn launch(mut args: Args, config: Config, start_now: bool) -> Result<(), String> {
+    // The daemon is watch mode with a control socket
+    args.watch |= args.daemon;
+    let embedded = EMBEDDED.load(Ordering::SeqCst);
+
+    ANIMATION_FPS.store(args.fps, Ordering::SeqCst);
+
+    // Determine exit key: CLI arg > config file > default
+    let exit_key = if let Some(ref key) = args.exit_key {
+        key.clone()
+    } else if let Some(ref key_str) = config.exit_key {
+        match ExitKey::parse(key_str) {
+            Ok(key) => key,
+            Err(e) => {
+                eprintln!("  ⚠️  Invalid exit_key in config file: {}", e);
+                eprintln!("      Using default: {}", DEFAULT_EXIT_KEY);
+                ExitKey::default()
+            }
+        }
+    } else {
+        ExitKey::default()
+    };
+
+    // Set the global exit key configuration
+    set_exit_key(&exit_key);
+    register_unlock_method(ExitChord::new(Duration::from_secs_f64(args.exit_key_hold)));
+
+    // Get main thread marker - required for AppKit operations
+    let mtm = MainThreadMarker::new().expect("Must run on main thread");
+
+    // Initialize the application
+    let app = NSApplication::sharedApplication(mtm);
+    app.setActivationPolicy(NSApplicationActivationPolicy::Accessory);
+
+    if args.dry_run {
+        let menu_bar = !args.watch && !start_now;
+        process::exit(run_dry_run(&args, &exit_key, &config, menu_bar, mtm));
+    }
+
+    // Check if we should enter menu bar mode (no CLI args that trigger immediate start)
+    if !args.watch && !start_now {
+        // Menu bar mode: show icon in menu bar and wait for user interaction
+        println!();
+        println!("  🐱 CAT SHIELD 🛡️");
+        println!("  ════════════════════════════════════════");
+        println!("  Menu bar mode active");
+        println!();
+
+        // Set up menu bar icon
+        let _status_item = setup_menu_bar(mtm);
+
+        println!();
+        println!("  Click the 🐱 icon in your menu bar to access Cat Shield.");
+        println!("  Pick a 'Shield for ...' preset to activate the shield.");
+        println!("  Or run with --timer or --exit-key to start immediately.");
+        println!();
+
+        // Finish launching the application (required for menu bar apps)
+        app.finishLaunching();
+
+        // Run the NSApplication event loop
+        // The status item keeps the app alive in the menu bar
+        app.run();
+
+        println!();
+        println!("  👋 Cat Shield closed. Goodbye!");
+        println!();
+        return Ok(());
+    }
+
+    // Immediate shield mode (or watch mode): CLI args provided, start protection now
+    if !acquire_instance_lock() {
+        let running = match running_session() {
+            Ok(report) => format!("Cat Shield is already running (pid {})", report.pid),
+            Err(_) => "Cat Shield is already running".to_string(),
+        };
+        return Err(format!(
+            "{}\n      Use `cat_shield status`, `extend`, or `stop` to control it",
+            running
+        ));
+    }
+
+    // Check accessibility permissions FIRST, before any UI
+    let mut has_accessibility = check_accessibility();
+
+    if !has_accessibility {
+        println!();
+        println!("  🐱 CAT SHIELD 🛡️");
+        println!("  ════════════════════════════════════════");
+        println!();
+        eprintln!("  ⚠️  ACCESSIBILITY PERMISSION REQUIRED");
+        eprintln!();
+        eprintln!("  To block keyboard/mouse input and use the exit");
+        eprintln!(
+            "  shortcut ({}), this app needs Accessibility permissions.",
+            exit_key.display_name
+        );
+        eprintln!();
+
+        // Try to prompt user with native dialog
+        println!("  Requesting accessibility permissions...");
+        has_accessibility = check_accessibility_with_prompt();
+
+        if has_accessibility {
+            println!("  ✓ Permissions granted!");
+            println!();
+        } else {
+            eprintln!();
+            eprintln!("  Opening System Settings → Accessibility...");
+
+            if open_accessibility_settings() {
+                eprintln!("  ✓ System Settings opened");
+            }
+            eprintln!();
+            eprintln!("  Please add Cat Shield to the Accessibility list.");
+            eprintln!("  Waiting for permissions...");
+            eprintln!();
+
+            // Poll for permissions every 1 second using CFRunLoop::run_in_mode
+            // This allows the run loop to process events while waiting,
+            // which is necessary for macOS to update accessibility permission state
+            const POLL_INTERVAL_SECS: f64 = 1.0;
+            loop {
+                CFRunLoop::run_in_mode(unsafe { kCFRunLoopDefaultMode }, POLL_INTERVAL_SECS, false);
+                if check_accessibility() {
+                    println!("  ✓ Permissions granted! Starting Cat Shield...");
+                    println!();
+                    break;
+                }
+            }
+        }
+    }
+
+    if args.watch {
+        run_watch_mode(&app, &args, &exit_key);
+        return Ok(());
+    }
+
+    println!();
+    println!("  🐱 CAT SHIELD 🛡️");
+    println!("  ════════════════════════════════════════");
+    println!("  Protecting your work from curious cats!");
+    println!();
+    transition(ShieldEvent::Arm);
+
+    if !setup_message_bus() {
+        return Err("Failed to set up the message bus".to_string());
+    }
+    if !embedded {
+        load_hooks();
+        load_detection_plugins();
+    }
+
+    // Get the main screen dimensions
+    let screen = NSScreen::mainScreen(mtm).ok_or("Failed to get main screen")?;
+    let screen_frame = screen.frame();
+
+    // Capture before the window exists, so it goes straight to the shielding level
+    if args.capture_display {
+        CAPTURE_DISPLAYS.store(true, Ordering::SeqCst);
+        match capture_displays() {
+            Ok(()) => println!("  ✓ Displays captured"),
+            Err(e) => eprintln!("  ⚠️  Could not capture the displays: {}", e),
+        }
+    }
+
+    let window = if args.no_overlay {
+        if args.keyboard_only {
+            println!("  ✓ No overlay: the screen and mouse stay usable, keys stay blocked");
+        } else {
+            println!("  ✓ No overlay: the screen stays visible, keys and clicks stay blocked");
+        }
+        None
+    } else {
+        let window = create_overlay_window(mtm, &args, screen_frame);
+        setup_stage_watch();
+        setup_screen_watch();
+        Some(window)
+    };
+
+    // Start the animation timer; it also services deferred unlock requests
+    start_close_button_timer();
+
+    if window.is_some() {
+        register_unlock_method(HoldToExit::default());
+        println!("  ✓ Close button active (hold 3s to exit)");
+    }
+    println!(
+        "  ✓ Exit key: {}",
+        exit_key_instruction(&exit_key.display_name, args.exit_key_hold)
+    );
+
+    if args.emergency_exit {
+        register_unlock_method(EmergencyExit::default());
+        println!(
+            "  ✓ Emergency exit: press Caps Lock {} times within {} seconds",
+            EMERGENCY_EXIT_PRESSES,
+            EMERGENCY_EXIT_WINDOW.as_secs()
+        );
+    }
+
+    if let Some(shape) = args.unlock_gesture {
+        register_unlock_method(GestureUnlock::new(shape));
+        println!(
+            "  ✓ Gesture unlock: draw a large {} on the overlay",
+            shape.name()
+        );
+    }
+
+    // Without --internal-keyboard-only, only monitor keyboards if that won't prompt
+    // for Input Monitoring; blocked presses are then credited to plain "Keyboard"
+    let input_monitoring_granted = unsafe { IOHIDCheckAccess(K_IOHID_REQUEST_TYPE_LISTEN_EVENT) }
+        == K_IOHID_ACCESS_TYPE_GRANTED;
+    let keyboards_monitored = (args.internal_keyboard_only || input_monitoring_granted)
+        && setup_keyboard_source_monitor();
+    if args.internal_keyboard_only {
+        if keyboards_monitored {
+            INTERNAL_KEYBOARD_ONLY.store(true, Ordering::SeqCst);
+            println!("  ✓ Exit key accepted from the built-in keyboard only");
+        } else {
+            eprintln!("  ⚠️  Could not monitor keyboards (Input Monitoring permission needed)");
+            eprintln!("      Exit key will be accepted from any keyboard");
+        }
+    }
+    if keyboards_monitored {
+        println!("  ✓ Blocked key presses credited to the keyboard they came from");
+    }
+
+    if args.require_admin_exit {
+        REQUIRE_ADMIN_EXIT.store(true, Ordering::SeqCst);
+        println!("  ✓ Parental mode: exiting requires an administrator password");
+    }
+
+    if args.backlight_deterrent {
+        enable_backlight_deterrent();
+    }
+
+    enable_microphone(&args);
+
+    if let Some(ref pattern) = args.knock {
+        register_unlock_method(KnockUnlock::new(pattern.clone()));
+        println!("  ✓ Knock unlock: click \"{}\" on the overlay", pattern);
+    }
+
+    if let Some(ref code) = args.keypad_code {
+        register_unlock_method(KeypadCode::new(code.clone()));
+        println!("  ✓ Keypad code: type the code on the numeric keypad, then Enter");
+    }
+
+    if let Some(ref window) = window {
+        setup_cat_banner(mtm, window, screen_frame.size);
+        setup_overlay_widgets(mtm, window, &config.overlay.widgets, screen_frame.size);
+        setup_display_overlays(mtm, window, &screen, config.displays.clone());
+    }
+
+    // Set up auto-exit timer if specified
+    if let Some(duration_secs) = args.timer {
+        init_auto_exit_timer(duration_secs);
+        COUNT_MODE.with(|mode| mode.set(args.count));
+        WARN_ACTIONS.with(|actions| actions.replace(args.warn_action.clone()));
+        println!(
+            "  ✓ Auto-exit timer set: {} ({})",
+            format_duration(duration_secs),
+            match args.count {
+                CountMode::Awake => "awake time",
+                CountMode::Wall => "wall-clock time",
+            }
+        );
+
+        // Create timer display view if not hidden
+        if let Some(window) = window.as_ref().filter(|_| !args.hide_timer) {
+            let timer_display = TimerDisplayView::new(mtm, timer_display_frame(screen_frame.size));
+
+            // Store view reference for timer callback
+            TIMER_DISPLAY_VIEW.store(
+                Retained::as_ptr(&timer_display) as *mut c_void,
+                Ordering::SeqCst,
+            );
+
+            // Add timer display to the window's content view
+            if let Some(content_view) = window.contentView() {
+                content_view.addSubview(&timer_display);
+            }
+
+            println!("  ✓ Timer display active");
+        }
+
+        if let (Some(window), Some(name)) = (window.as_ref(), args.away_note.as_deref()) {
+            let text = show_away_note(mtm, window, name, screen_frame.size);
+            println!("  ✓ Away note: \"{}\"", text);
+        }
+
+        if !args.warn_action.is_empty() {
+            let names: Vec<&str> = args
+                .warn_action
+                .iter()
+                .map(|action| action.name())
+                .collect();
+            println!("  ✓ One-minute warning: {}", names.join(", "));
+        }
+
+        if let Some(interval) = args.remind_every {
+            start_reminders(interval, args.remind_aloud);
+            println!(
+                "  ✓ Time-left reminders every {}{}",
+                format_duration(interval),
+                if args.remind_aloud { ", spoken" } else { "" }
+            );
+        }
+
+        if let Some(mode) = args.dim {
+            update_progressive_dimming();
+            println!(
+                "  ✓ Progressive dimming: overlay {} as the timer runs",
+                match mode {
+                    DimMode::Darken => "darkens",
+                    DimMode::Lighten => "lightens",
+                }
+            );
+        }
+    }
+
+    // Prevent sleep (only system sleep with --screen-off)
+    SCREEN_OFF.store(args.screen_off, Ordering::SeqCst);
+    acquire_sleep_assertion();
+
+    if let Some(target) = args.dim_display {
+        match dim_display(target) {
+            Ok(original) => println!(
+                "  ✓ Display dimmed to {:.0}% (was {:.0}%)",
+                target.min(original) * 100.0,
+                original * 100.0
+            ),
+            Err(e) => eprintln!("  ⚠️  Could not dim the display: {}", e),
+        }
+    }
+    if args.screen_off {
+        println!("  ✓ Displays sleep once the shield is up, and wake on exit");
+    }
+
+    if args.backlight_off {
+        if turn_off_keyboard_backlight() {
+            println!("  ✓ Keyboard backlight off");
+        } else {
+            eprintln!("  ⚠️  No controllable keyboard backlight found");
+        }
+    }
+
+    if args.mute {
+        match mute_audio() {
+            Ok(()) => println!("  ✓ Audio muted"),
+            Err(e) => eprintln!("  ⚠️  Could not mute audio: {}", e),
+        }
+    }
+
+    if args.lock_volume {
+        match lock_volume() {
+            Ok(()) => println!("  ✓ Volume locked"),
+            Err(e) => eprintln!("  ⚠️  Could not lock the volume: {}", e),
+        }
+    }
+
+    // Let Ctrl+C restore the display and other settings before exiting
+    if !embedded {
+        install_signal_handlers();
+    }
+
+    // Follow lid close/open, sleep/wake, and display changes
+    if setup_power_monitor() {
+        println!("  ✓ Lid and sleep monitoring active");
+    } else {
+        eprintln!("  ⚠️  Could not register for power notifications");
+    }
+
+    // Bring the shield back after the screen saver (or keep it from starting),
+    // and optionally stand down while the screen is locked
+    STOP_SCREENSAVER.store(args.no_screensaver, Ordering::SeqCst);
+    STAND_DOWN_WHEN_LOCKED.store(args.stand_down_when_locked, Ordering::SeqCst);
+    if let Some(action) = args.on_projector {
+        PROJECTOR_ACTION.with(|slot| slot.set(Some(action)));
+        match action {
+            ProjectorAction::Pause => {
+                println!("  ✓ Projector guard: the shield pauses while a projector is connected")
+            }
+            ProjectorAction::Notify => {
+                println!("  ✓ Projector guard: a notification when a projector connects")
+            }
+        }
+    }
+    setup_system_notifications();
+    if args.no_screensaver {
+        println!("  ✓ Screen saver suppressed");
+    }
+    if let Some(action) = args.block_launches {
+        enable_launch_blocking(action);
+    }
+    if let Some(ref app) = args.forward_to {
+        enable_key_forwarding(app, &args.forward_keys);
+    }
+    warn_about_other_sessions();
+    if args.stand_down_when_locked {
+        println!("  ✓ Standing down while the screen is locked");
+    }
+    if args.allow_scroll {
+        ALLOW_SCROLL.store(true, Ordering::SeqCst);
+        println!("  ✓ Scrolling and swipes pass through to the app underneath");
+    }
+    if args.allow_universal_control {
+        ALLOW_UNIVERSAL_CONTROL.store(true, Ordering::SeqCst);
+        println!("  ✓ Keys from Universal Control pass through");
+    }
+    if args.allow_remote_input {
+        ALLOW_REMOTE_INPUT.store(true, Ordering::SeqCst);
+        println!("  ✓ Keys from Screen Sharing and Remote Desktop pass through");
+    }
+    if args.allow_accessibility_shortcuts {
+        ALLOW_ACCESSIBILITY_SHORTCUTS.store(true, Ordering::SeqCst);
+        println!("  ✓ Accessibility shortcuts (VoiceOver, Zoom, Sticky Keys) pass through");
+    }
+    TAP_BLOCKS_MOUSE.store(args.no_overlay && !args.keyboard_only, Ordering::SeqCst);
+
+    // Set up event tap (we always have permissions at this point)
+    if setup_event_tap() {
+        println!("  ✓ Input blocking active");
+    } else {
+        eprintln!("  ✗ Failed to create event tap");
+        log_error("Failed to create event tap");
+    }
+
+    // Count this session in the lifetime statistics
+    SHOW_STATS_ON_EXIT.store(args.show_stats, Ordering::SeqCst);
+    SESSION_LABEL.with(|label| label.replace(args.label.clone()));
+    let summary_output = match (&args.summary_file, args.summary_json) {
+        (Some(path), _) => Some(SummaryOutput::File(path.clone())),
+        (None, true) => Some(SummaryOutput::Stdout),
+        (None, false) => None,
+    };
+    SUMMARY_OUTPUT.with(|output| output.replace(summary_output));
+    start_session();
+    if !embedded {
+        telemetry_session_started(&args);
+    }
+    transition(ShieldEvent::Armed);
+
+    if let Some(ref path) = args.health_file {
+        enable_health_file(path.clone());
+    }
+    enable_session_file();
+
+    enter_status_screen();
+    println!();
+    println!("  ═══════════════════════════════════════");
+    println!("  🛡️  CAT SHIELD IS NOW ACTIVE!");
+    println!("  ═══════════════════════════════════════");
+    println!();
+    if window.is_some() {
+        println!("  Exit: Hold X button (top-right) for 3 seconds");
+        println!(
+            "        Or {}",
+            exit_key_instruction(&exit_key.display_name, args.exit_key_hold)
+        );
+    } else {
+        println!(
+            "  Exit: {}",
+            exit_key_instruction(&exit_key.display_name, args.exit_key_hold)
+        );
+    }
+    if args.emergency_exit {
+        println!(
+            "        Or press Caps Lock {} times quickly",
+            EMERGENCY_EXIT_PRESSES
+        );
+    }
+    if let Some(shape) = args.unlock_gesture {
+        println!(
+            "        Or draw a large {} with the mouse held down",
+            shape.name()
+        );
+    }
+    if let Some(ref pattern) = args.knock {
+        println!(
+            "        Or knock \"{}\" on the overlay (. short, - long)",
+            pattern
+        );
+    }
+    if args.timer.is_some() {
+        println!(
+            "        Or wait for timer ({} remaining)",
+            format_duration(get_remaining_seconds())
+        );
+    }
+    if args.keypad_code.is_some() {
+        println!("        Or type your code on the numeric keypad, then Enter");
+    }
+    if args.require_admin_exit {
+        println!("        (all but the timer require an administrator password)");
+    }
+    println!();
+
+    if args.screen_off {
+        schedule_display_sleep();
+    }
+
+    // Run the NSApplication event loop (required for AppKit event handling)
+    app.run();
+
+    // Cleanup
+    leave_status_screen();
+    stop_close_button_timer();
+    release_event_tap();
+    release_sleep_assertion();
+    finish_session();
+    remove_session_file();
+    restore_system_state();
+    if embedded {
+        close_overlay_windows();
+    }
+
+    println!();
+    println!("  👋 Cat Shield deactivated. Goodbye!");
+    println!();
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_calculate_hold_progress_zero() {
+        assert_eq!(calculate_hold_progress(0.0, 3.0), 0.0);
+    }
+
+    #[test]
+    fn test_calculate_hold_progress_partial() {
+        let progress = calculate_hold_progress(1.5, 3.0);
+        assert!((progress - 0.5).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn test_calculate_hold_progress_complete() {
+        assert_eq!(calculate_hold_progress(3.0, 3.0), 1.0);
+    }
+
+    #[test]
+    fn test_calculate_hold_progress_exceeds() {
+        // Should clamp to 1.0 when elapsed exceeds duration
+        assert_eq!(calculate_hold_progress(5.0, 3.0), 1.0);
+    }
+
+    #[test]
+    fn test_is_hold_complete_false() {
+        assert!(!is_hold_complete(2.0, 3.0));
+        assert!(!is_hold_complete(2.999, 3.0));
+    }
+
+    #[test]
+    fn test_is_hold_complete_exact() {
+        assert!(is_hold_complete(3.0, 3.0));
+    }
+
+    #[test]
+    fn test_is_hold_complete_exceeds() {
+        assert!(is_hold_complete(5.0, 3.0));
+    }
+
+    #[test]
+    fn test_hold_completes_on_time_at_any_frame_rate() {
+        // Holds are measured in elapsed time, so at any supported rate the
+        // exit triggers on the first tick at or after the hold duration
+        for fps in SUPPORTED_FPS {
+            let interval = 1.0 / f64::from(fps);
+            let ticks = (1..)
+                .find(|&tick| is_hold_complete(f64::from(tick) * interval, HOLD_DURATION_SECS))
+                .unwrap();
+            let triggered_at = f64::from(ticks) * interval;
+            assert!(triggered_at >= HOLD_DURATION_SECS);
+            assert!(triggered_at < HOLD_DURATION_SECS + interval + 1e-9);
+        }
+    }
+
+    #[test]
+    fn test_adaptive_timer_interval_fixed_refresh() {
+        let fps = 1.0 / 60.0;
+        assert_eq!(adaptive_timer_interval(fps, (fps, fps), true), fps);
+        assert_eq!(adaptive_timer_interval(fps, (fps, fps), false), fps);
+        // No screen reports (0, 0)
+        assert_eq!(adaptive_timer_interval(fps, (0.0, 0.0), false), fps);
+    }
+
+    #[test]
+    fn test_adaptive_timer_interval_promotion() {
+        let promotion = (1.0 / 120.0, 1.0 / 24.0);
+        // Animating: as fast as --fps allows
+        assert_eq!(
+            adaptive_timer_interval(1.0 / 60.0, promotion, true),
+            1.0 / 60.0
+        );
+        // Idle: the display's slowest rate
+        assert_eq!(
+            adaptive_timer_interval(1.0 / 60.0, promotion, false),
+            1.0 / 24.0
+        );
+        // A display that can go down to 1 Hz still ticks at least 15 times a second
+        let one_hz = (1.0 / 120.0, 1.0);
+        assert_eq!(
+            adaptive_timer_interval(1.0 / 60.0, one_hz, false),
+            ADAPTIVE_IDLE_MAX_INTERVAL_SECS
+        );
+        // --fps still caps the rate while animating
+        assert_eq!(
+            adaptive_timer_interval(1.0 / 15.0, promotion, true),
+            1.0 / 15.0
+        );
+    }
+
+    #[test]
+    fn test_parse_fps() {
+        assert_eq!(parse_fps("15"), Ok(15));
+        assert_eq!(parse_fps("30"), Ok(30));
+        assert_eq!(parse_fps("60"), Ok(60));
+        assert!(parse_fps("24").is_err());
+        assert!(parse_fps("0").is_err());
+        assert!(parse_fps("fast").is_err());
+    }
+
+    #[test]
+    fn test_parse_duration_minutes() {
+        assert_eq!(parse_duration("30m").unwrap(), 30 * 60);
+        assert_eq!(parse_duration("1m").unwrap(), 60);
+        assert_eq!(parse_duration("90m").unwrap(), 90 * 60);
+    }
+
+    #[test]
+    fn test_parse_duration_hours() {
+        assert_eq!(parse_duration("1h").unwrap(), 3600);
+        assert_eq!(parse_duration("2h").unwrap(), 2 * 3600);
+        assert_eq!(parse_duration("24h").unwrap(), 24 * 3600);
+    }
+
+    #[test]
+    fn test_parse_duration_combined() {
+        assert_eq!(parse_duration("1h30m").unwrap(), 3600 + 30 * 60);
+        assert_eq!(parse_duration("2h45m").unwrap(), 2 * 3600 + 45 * 60);
+    }
+
+    #[test]
+    fn test_parse_duration_with_spaces() {
+        assert_eq!(parse_duration(" 30m ").unwrap(), 30 * 60);
+        assert_eq!(parse_duration("1h 30m").unwrap(), 3600 + 30 * 60);
+    }
+
+    #[test]
+    fn test_parse_duration_bare_number_as_minutes() {
+        // A bare number without unit is treated as minutes
+        assert_eq!(parse_duration("30").unwrap(), 30 * 60);
+        assert_eq!(parse_duration("60").unwrap(), 60 * 60);
+    }
+
+    #[test]
+    fn test_parse_duration_seconds() {
+        assert_eq!(parse_duration("90s").unwrap(), 90);
+        assert_eq!(parse_duration("1m30s").unwrap(), 90);
+    }
+
+    #[test]
+    fn test_parse_duration_errors() {
+        assert!(parse_duration("").is_err());
+        assert!(parse_duration("0m").is_err());
+        assert!(parse_duration("abc").is_err());
+        assert!(parse_duration("30x").is_err());
+        assert!(parse_duration("30s").is_err()); // Less than 1 minute
+        assert!(parse_duration("25h").is_err()); // More than 24 hours
+        assert!(parse_duration("30 fortnights").is_err());
+        assert!(parse_duration("99999999999999999999h").is_err());
+    }
+
+    #[test]
+    fn test_parse_duration_spelled_out_units() {
+        assert_eq!(parse_duration("1 hour 30 min").unwrap(), 3600 + 30 * 60);
+        assert_eq!(parse_duration("2 hours").unwrap(), 2 * 3600);
+        assert_eq!(
+            parse_duration("1hr 5mins 30secs").unwrap(),
+            3600 + 5 * 60 + 30
+        );
+        assert_eq!(parse_duration("90 Seconds").unwrap(), 90);
+        assert_eq!(parse_duration("1 day").unwrap(), 86_400);
+    }
+
+    #[test]
+    fn test_parse_duration_iso8601() {
+        assert_eq!(parse_duration("PT1H30M").unwrap(), 3600 + 30 * 60);
+        assert_eq!(parse_duration("pt45m").unwrap(), 45 * 60);
+        assert_eq!(parse_duration("PT90S").unwrap(), 90);
+        assert_eq!(parse_duration("P1D").unwrap(), 86_400);
+        assert_eq!(parse_duration("P0DT2H").unwrap(), 2 * 3600);
+        assert!(parse_duration("P1M").is_err()); // Months, not minutes
+        assert!(parse_duration("PT").is_err());
+        assert!(parse_duration("PT30").is_err());
+        assert!(parse_duration("PTH").is_err());
+        assert!(parse_duration("P1H").is_err()); // Hours need the T
+    }
+
+    #[test]
+    fn test_format_duration_seconds_only() {
+        assert_eq!(format_duration(45), "45s");
+        assert_eq!(format_duration(1), "1s");
+    }
+
+    #[test]
+    fn test_format_duration_minutes_and_seconds() {
+        assert_eq!(format_duration(90), "1m 30s");
+        assert_eq!(format_duration(3599), "59m 59s");
+    }
+
+    #[test]
+    fn test_format_duration_hours_minutes_seconds() {
+        assert_eq!(format_duration(3600), "1h 00m 00s");
+        assert_eq!(format_duration(3661), "1h 01m 01s");
+        assert_eq!(format_duration(7200 + 1800 + 45), "2h 30m 45s");
+    }
+
+    #[test]
+    fn test_countdown_running() {
+        let countdown = Countdown::start(600, 1000);
+        assert_eq!(countdown.remaining(1100), 500);
+        assert_eq!(countdown.remaining(2000), 0);
+    }
+
+    #[test]
+    fn test_countdown_paused() {
+        // Paused at 1100: time keeps passing but the countdown is frozen
+        let mut countdown = Countdown::start(600, 1000);
+        assert!(countdown.pause(1100));
+        assert_eq!(countdown.remaining(1500), 500);
+        assert!(!countdown.pause(1200));
+    }
+
+    #[test]
+    fn test_countdown_after_resume() {
+        // 300s spent paused are not counted
+        let mut countdown = Countdown::start(600, 1000);
+        countdown.pause(1100);
+        assert!(countdown.resume(1400));
+        assert_eq!(countdown.remaining(1500), 400);
+        assert!(!countdown.resume(1450));
+    }
+
+    #[test]
+    fn test_countdown_accumulates_multiple_pauses() {
+        let mut countdown = Countdown::start(600, 0);
+        countdown.pause(100);
+        countdown.resume(1000);
+        countdown.pause(1100);
+        countdown.resume(5000);
+        assert_eq!(countdown.elapsed(5050), 250);
+        assert_eq!(countdown.remaining(5050), 350);
+    }
+
+    #[test]
+    fn test_countdown_extend() {
+        let mut countdown = Countdown::start(600, 1000);
+        countdown.extend(300);
+        assert_eq!(countdown.remaining(1500), 400);
+    }
+
+    #[test]
+    fn test_other_session_users() {
+        let sessions = ["me", "alice", "bob", "alice"].map(String::from);
+        assert_eq!(other_session_users(&sessions, "me"), ["alice", "bob"]);
+        assert!(other_session_users(&["me".to_string()], "me").is_empty());
+    }
+
+    #[test]
+    fn test_countdown_next_checkpoint() {
+        let mut countdown = Countdown::start(600, 1000);
+        // Warning first, then expiry
+        assert_eq!(countdown.next_checkpoint(1000, 60), Some(540));
+        assert_eq!(countdown.next_checkpoint(1540, 60), Some(60));
+        assert_eq!(countdown.next_checkpoint(1600, 60), Some(0));
+        assert_eq!(countdown.next_checkpoint(1700, 60), Some(0));
+
+        countdown.pause(1100);
+        assert_eq!(countdown.next_checkpoint(1200, 60), None);
+    }
+
+    #[test]
+    fn test_script_hooks_call_defined_hooks() {
+        let extended = std::rc::Rc::new(Cell::new(0));
+        let seen = extended.clone();
+        let mut engine = rhai::Engine::new();
+        engine.register_fn("extend_timer", move |secs: i64| seen.set(secs));
+
+        let hooks = ScriptHooks::compile(
+            engine,
+            "fn on_warning(remaining) { extend_timer(remaining * 5); }",
+        )
+        .unwrap();
+        assert_eq!(hooks.call("on_warning", (60_i64,)), Ok(true));
+        assert_eq!(extended.get(), 300);
+        assert_eq!(hooks.call("on_exit", ()), Ok(false));
+    }
+
+    #[test]
+    fn test_script_hooks_limit_runaway_scripts() {
+        let hooks = ScriptHooks::compile(rhai::Engine::new(), "fn on_exit() { loop {} }").unwrap();
+        assert!(hooks.call("on_exit", ()).is_err());
+
+        // Doubling a string outgrows memory long before the operation budget runs out
+        let hooks = ScriptHooks::compile(
+            rhai::Engine::new(),
+            r#"fn on_exit() { let s = "x"; loop { s += s; } }"#,
+        )
+        .unwrap();
+        let error = hooks.call("on_exit", ()).unwrap_err();
+        assert!(error.contains("Length of string"), "{}", error);
+
+        let hooks = ScriptHooks::compile(
+            rhai::Engine::new(),
+            "fn on_exit() { let a = [0]; loop { a += a; } }",
+        )
+        .unwrap();
+        assert!(hooks.call("on_exit", ()).is_err());
+    }
+
+    #[test]
+    fn test_script_hooks_reject_invalid_scripts() {
+        assert!(ScriptHooks::compile(rhai::Engine::new(), "fn on_exit( {").is_err());
+    }
+
+    #[test]
+    fn test_event_rates_count_presses_and_distinct_keys() {
+        let mut rates = EventRates::default();
+        for keycode in [0, 1, 1, 1, 127] {
+            rates.record_key(keycode);
+        }
+        assert_eq!(rates.key_presses, 5);
+        assert_eq!(rates.distinct_keys(), 3);
+
+        // Out-of-range keycodes still count as presses
+        rates.record_key(500);
+        assert_eq!(rates.key_presses, 6);
+        assert_eq!(rates.distinct_keys(), 3);
+    }
+
+    const ARM_ON_MANY_KEYS_PLUGIN: &str = r#"
+        (module
+          (func (export "sample") (param $presses i32) (param $distinct i32) (result i32)
+            (if (result i32) (i32.ge_u (local.get $distinct) (i32.const 5))
+              (then (i32.const 1))
+              (else (i32.const 0)))))
+    "#;
+
+    #[test]
+    fn test_detection_plugin_signals() {
+        let mut plugin = DetectionPlugin::load("paws", ARM_ON_MANY_KEYS_PLUGIN.as_bytes()).unwrap();
+        let mut rates = EventRates::default();
+        rates.record_key(0);
+        assert_eq!(plugin.sample(rates), Ok(PluginSignal::Nothing));
+
+        for keycode in 1..5 {
+            rates.record_key(keycode);
+        }
+        assert_eq!(plugin.sample(rates), Ok(PluginSignal::Arm));
+    }
+
+    #[test]
+    fn test_detection_plugin_rejects_unknown_signal() {
+        let wat = r#"(module (func (export "sample") (param i32 i32) (result i32) (i32.const 7)))"#;
+        let mut plugin = DetectionPlugin::load("odd", wat.as_bytes()).unwrap();
+        assert!(plugin.sample(EventRates::default()).is_err());
+    }
+
+    #[test]
+    fn test_detection_plugin_runaway_loop_runs_out_of_fuel() {
+        let wat = r#"
+            (module
+              (func (export "sample") (param i32 i32) (result i32)
+                (loop $forever (br $forever))
+                (i32.const 0)))
+        "#;
+        let mut plugin = DetectionPlugin::load("spin", wat.as_bytes()).unwrap();
+        assert!(plugin.sample(EventRates::default()).is_err());
+    }
+
+    #[test]
+    fn test_detection_plugin_memory_is_capped() {
+        // Starting above the cap fails to load
+        let wat = r#"
+            (module
+              (memory 1024)
+              (func (export "sample") (param i32 i32) (result i32) (i32.const 0)))
+        "#;
+        assert!(DetectionPlugin::load("hog", wat.as_bytes()).is_err());
+
+        // Growing past it traps, which gets the plugin unloaded
+        let wat = r#"
+            (module
+              (memory 1)
+              (func (export "sample") (param i32 i32) (result i32)
+                (drop (memory.grow (i32.const 64)))
+                (i32.const 0)))
+        "#;
+        let mut plugin = DetectionPlugin::load("greedy", wat.as_bytes()).unwrap();
+        for _ in 0..3 {
+            assert_eq!(
+                plugin.sample(EventRates::default()),
+                Ok(PluginSignal::Nothing)
+            );
+        }
+        assert!(plugin.sample(EventRates::default()).is_err());
+    }
+
+    #[test]
+    fn test_detection_plugin_gets_no_imports() {
+        let wat = r#"
+            (module
+              (import "env" "open" (func $open (param i32) (result i32)))
+              (func (export "sample") (param i32 i32) (result i32) (i32.const 0)))
+        "#;
+        assert!(DetectionPlugin::load("nosy", wat.as_bytes()).is_err());
+        assert!(DetectionPlugin::load("empty", b"(module)").is_err());
+    }
+
+    #[test]
+    fn test_shield_status_text() {
+        let status = ShieldStatus {
+            state: ShieldState::Active,
+            armed: true,
+            remaining_secs: Some(754),
+            blocked_key_presses: 42,
+            tap: TapHealth::Enabled,
+        };
+        assert_eq!(
+            status.text(),
+            "🛡️  Shield active │ 12m 34s left │ blocked: 42 │ tap ✓"
+        );
+
+        let watching = ShieldStatus {
+            armed: false,
+            remaining_secs: None,
+            tap: TapHealth::StoodDown,
+            ..status
+        };
+        assert_eq!(
+            watching.text(),
+            "👀 Watching (disarmed) │ blocked: 42 │ tap paused (screen locked)"
+        );
+
+        let paused = ShieldStatus {
+            armed: false,
+            tap: TapHealth::Paused,
+            ..status
+        };
+        assert_eq!(
+            paused.text(),
+            "⏸️  Paused │ 12m 34s left │ blocked: 42 │ tap paused (shield paused)"
+        );
+
+        let warning = ShieldStatus {
+            state: ShieldState::Warning,
+            tap: TapHealth::Disabled,
+            ..status
+        };
+        assert!(warning.text().starts_with("⚠️  Ending soon"));
+        assert!(warning.text().ends_with("tap ✗"));
+    }
+
+    #[test]
+    fn test_health_report_json() {
+        let status = ShieldStatus {
+            state: ShieldState::Active,
+            armed: true,
+            remaining_secs: None,
+            blocked_key_presses: 3,
+            tap: TapHealth::StoodDown,
+        };
+        let report = HealthReport::new(status, Some(1_700_000_000), 1_700_000_100);
+        let json: serde_json::Value = serde_json::to_value(&report).unwrap();
+        assert_eq!(json["state"], "active");
+        assert_eq!(json["tap"], "stood_down");
+        assert_eq!(json["remaining_secs"], serde_json::Value::Null);
+        assert_eq!(json["paused"], false);
+        assert_eq!(json["blocked_key_presses"], 3);
+        assert_eq!(json["last_blocked"], 1_700_000_000);
+        assert_eq!(json["updated"], 1_700_000_100);
+    }
+
+    #[test]
+    fn test_session_summary_json() {
+        let summary = SessionSummary {
+            exit_reason: Some(ExitReason::TimerExpired.name()),
+            started: 1_700_000_000,
+            ended: 1_700_003_600,
+            duration_secs: 3600,
+            blocked_key_presses: 12,
+            last_blocked: None,
+            cat_detections: 2,
+            blocked_by_source: BTreeMap::from([("Keychron K2".to_string(), 12)]),
+            tap_incidents: 1,
+            label: Some("overnight".to_string()),
+        };
+        let json = serde_json::to_string(&summary).unwrap();
+        assert!(!json.contains('\n'));
+        let json: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(json["exit_reason"], "timer_expired");
+        assert_eq!(json["duration_secs"], 3600);
+        assert_eq!(json["blocked_key_presses"], 12);
+        assert_eq!(json["last_blocked"], serde_json::Value::Null);
+        assert_eq!(json["cat_detections"], 2);
+        assert_eq!(json["blocked_by_source"]["Keychron K2"], 12);
+        assert_eq!(json["tap_incidents"], 1);
+        assert_eq!(json["label"], "overnight");
+    }
+
+    #[test]
+    fn test_shield_state_happy_path() {
+        let state = ShieldState::Idle;
+        let state = state.next(ShieldEvent::Arm).unwrap();
+        assert_eq!(state, ShieldState::Arming);
+        let state = state.next(ShieldEvent::Armed).unwrap();
+        assert_eq!(state, ShieldState::Active);
+        let state = state.next(ShieldEvent::WarningDue).unwrap();
+        assert_eq!(state, ShieldState::Warning);
+        let state = state.next(ShieldEvent::UnlockRequested).unwrap();
+        assert_eq!(state, ShieldState::Unlocking);
+        assert_eq!(state.next(ShieldEvent::Exit), Some(ShieldState::Exiting));
+    }
+
+    #[test]
+    fn test_shield_state_extending_ends_warning() {
+        assert_eq!(
+            ShieldState::Warning.next(ShieldEvent::Extended),
+            Some(ShieldState::Active)
+        );
+        assert_eq!(ShieldState::Active.next(ShieldEvent::Extended), None);
+    }
+
+    #[test]
+    fn test_shield_state_refused_unlock_resumes() {
+        assert_eq!(
+            ShieldState::Unlocking.next(ShieldEvent::Resumed),
+            Some(ShieldState::Active)
+        );
+    }
+
+    #[test]
+    fn test_shield_state_ignores_repeated_events() {
+        // A second unlock attempt while authenticating, or a warning after it fired
+        assert_eq!(
+            ShieldState::Unlocking.next(ShieldEvent::UnlockRequested),
+            None
+        );
+        assert_eq!(ShieldState::Warning.next(ShieldEvent::WarningDue), None);
+        // Nothing leaves Exiting, so cleanup runs once
+        assert_eq!(ShieldState::Exiting.next(ShieldEvent::Exit), None);
+        assert_eq!(
+            ShieldState::Exiting.next(ShieldEvent::UnlockRequested),
+            None
+        );
+        // Unlocking needs a running shield
+        assert_eq!(ShieldState::Idle.next(ShieldEvent::UnlockRequested), None);
+        assert_eq!(ShieldState::Arming.next(ShieldEvent::UnlockRequested), None);
+    }
+
+    #[test]
+    fn test_messages_are_delivered_in_order() {
+        post_message(ShieldMessage::BlockedBurst);
+        post_message(ShieldMessage::MeowHeard(0.9));
+        post_message(ShieldMessage::UnlockRequested);
+        assert_eq!(next_message(), Some(ShieldMessage::BlockedBurst));
+        assert_eq!(next_message(), Some(ShieldMessage::MeowHeard(0.9)));
+        assert_eq!(next_message(), Some(ShieldMessage::UnlockRequested));
+        assert_eq!(next_message(), None);
+    }
+
+    #[test]
+    fn test_calculate_dim_alpha_darken() {
+        assert_eq!(calculate_dim_alpha(0.0, DimMode::Darken), DIM_MIN_ALPHA);
+        assert_eq!(calculate_dim_alpha(1.0, DimMode::Darken), DIM_MAX_ALPHA);
+        let halfway = calculate_dim_alpha(0.5, DimMode::Darken);
+        assert!((halfway - (DIM_MIN_ALPHA + DIM_MAX_ALPHA) / 2.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_calculate_dim_alpha_lighten() {
+        assert_eq!(calculate_dim_alpha(0.0, DimMode::Lighten), DIM_MAX_ALPHA);
+        assert_eq!(calculate_dim_alpha(1.0, DimMode::Lighten), DIM_MIN_ALPHA);
+    }
+
+    #[test]
+    fn test_calculate_dim_alpha_clamps_progress() {
+        assert_eq!(calculate_dim_alpha(-0.5, DimMode::Darken), DIM_MIN_ALPHA);
+        assert_eq!(calculate_dim_alpha(1.5, DimMode::Darken), DIM_MAX_ALPHA);
+    }
+
+    #[test]
+    fn test_celebration_paw_layout_in_range() {
+        let layout = celebration_paw_layout(CELEBRATION_PAW_COUNT);
+        assert_eq!(layout.len(), CELEBRATION_PAW_COUNT);
+        for (x, delay) in layout {
+            assert!((0.0..1.0).contains(&x));
+            assert!((0.0..CELEBRATION_MAX_DELAY_SECS).contains(&delay));
+        }
+    }
+
+    #[test]
+    fn test_celebration_paw_layout_spreads_across_width() {
+        let layout = celebration_paw_layout(CELEBRATION_PAW_COUNT);
+        // Every tenth of the screen width gets a paw
+        for slot in 0..10 {
+            let lo = slot as f64 / 10.0;
+            assert!(layout.iter().any(|&(x, _)| x >= lo && x < lo + 0.1));
+        }
+    }
+
+    #[test]
+    fn test_stats_record_session_streak() {
+        let mut stats = Stats::default();
+        stats.record_session(600, 0);
+        stats.record_session(1200, 0);
+        assert_eq!(stats.incident_free_streak, 2);
+        assert_eq!(stats.best_streak, 2);
+
+        stats.record_session(300, 5);
+        assert_eq!(stats.incident_free_streak, 0);
+        assert_eq!(stats.best_streak, 2);
+
+        stats.record_session(60, 0);
+        assert_eq!(stats.incident_free_streak, 1);
+        assert_eq!(stats.best_streak, 2);
+    }
+
+    #[test]
+    fn test_stats_record_sources() {
+        let mut stats = Stats::default();
+        stats.record_sources(&BTreeMap::from([
+            ("Keychron K2".to_string(), 95),
+            ("Trackpad".to_string(), 2),
+        ]));
+        stats.record_sources(&BTreeMap::from([
+            ("Keychron K2".to_string(), 19),
+            ("Mouse or trackpad".to_string(), 4),
+        ]));
+        assert_eq!(
+            source_breakdown(&stats.sources),
+            vec![
+                ("Keychron K2", 114, 95),
+                ("Mouse or trackpad", 4, 3),
+                ("Trackpad", 2, 2),
+            ]
+        );
+        assert!(source_breakdown(&BTreeMap::new()).is_empty());
+    }
+
+    #[test]
+    fn test_stats_record_session_totals() {
+        let mut stats = Stats::default();
+        stats.record_session(600, 3);
+        stats.record_session(1200, 4);
+        assert_eq!(stats.sessions, 2);
+        assert_eq!(stats.total_shielded_secs, 1800);
+        assert_eq!(stats.total_blocked_events, 7);
+    }
+
+    #[test]
+    fn test_stats_record_label() {
+        let mut stats = Stats::default();
+        stats.record_label("movie night", 7200, 3);
+        stats.record_label("movie night", 3600, 0);
+        stats.record_label("coffee", 600, 1);
+        assert_eq!(
+            stats.labels["movie night"],
+            LabelStats {
+                sessions: 2,
+                total_shielded_secs: 10800,
+                total_blocked_events: 3,
+            }
+        );
+        assert_eq!(stats.labels["coffee"].sessions, 1);
+
+        let parsed: Stats = toml::from_str(&toml::to_string(&stats).unwrap()).unwrap();
+        assert_eq!(parsed, stats);
+    }
+
+    #[test]
+    fn test_stats_toml_round_trip() {
+        let mut stats = Stats::default();
+        stats.record_session(600, 0);
+        let parsed: Stats = toml::from_str(&toml::to_string(&stats).unwrap()).unwrap();
+        assert_eq!(parsed, stats);
+
+        // Missing fields (older stats files) default to zero
+        let partial: Stats = toml::from_str("sessions = 4").unwrap();
+        assert_eq!(partial.sessions, 4);
+        assert_eq!(partial.best_streak, 0);
+    }
+
+    fn cat(name: &str, active_hours: Option<&str>) -> CatProfile {
+        CatProfile {
+            name: name.to_string(),
+            active_hours: active_hours.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn test_parse_active_hours() {
+        assert_eq!(parse_active_hours("22:00-06:00"), Ok((22 * 60, 6 * 60)));
+        assert_eq!(parse_active_hours("9-17"), Ok((9 * 60, 17 * 60)));
+        assert_eq!(
+            parse_active_hours(" 7:30 - 8:15 "),
+            Ok((7 * 60 + 30, 8 * 60 + 15))
+        );
+        assert!(parse_active_hours("22:00").is_err());
+        assert!(parse_active_hours("25-3").is_err());
+        assert!(parse_active_hours("10:75-11").is_err());
+    }
+
+    #[test]
+    fn test_cat_profile_active_hours_wrap_midnight() {
+        let night_cat = cat("Mochi", Some("22:00-06:00"));
+        assert!(night_cat.is_active_at(23));
+        assert!(night_cat.is_active_at(0));
+        assert!(night_cat.is_active_at(5));
+        assert!(!night_cat.is_active_at(6));
+        assert!(!night_cat.is_active_at(12));
+
+        let day_cat = cat("Biscuit", Some("9-17"));
+        assert!(day_cat.is_active_at(9));
+        assert!(!day_cat.is_active_at(17));
+
+        assert!(!cat("Shadow", None).is_active_at(12));
+    }
+
+    #[test]
+    fn test_attribute_by_schedule() {
+        let cats = [cat("Mochi", Some("22-6")), cat("Biscuit", Some("9-17"))];
+        let mut blocked_by_hour = [0u64; 24];
+        blocked_by_hour[23] = 5;
+        blocked_by_hour[2] = 3;
+        blocked_by_hour[10] = 4;
+        blocked_by_hour[19] = 7; // Nobody's hour
+
+        let attribution = attribute_by_schedule(&cats, &blocked_by_hour);
+        assert_eq!(attribution.get("Mochi"), Some(&8));
+        assert_eq!(attribution.get("Biscuit"), Some(&4));
+        assert_eq!(attribution.len(), 2);
+    }
+
+    #[test]
+    fn test_stats_blame_last_session() {
+        let mut stats = Stats::default();
+        stats.record_session(600, 10);
+        stats.attribute_last_session(BTreeMap::from([
+            ("Mochi".to_string(), 6),
+            ("Biscuit".to_string(), 4),
+        ]));
+
+        assert_eq!(stats.blame_last_session("Biscuit"), Ok(10));
+        assert_eq!(stats.cat_incidents.get("Biscuit"), Some(&10));
+        assert_eq!(stats.cat_incidents.get("Mochi"), None);
+
+        // Blaming again moves the whole session, not just the difference
+        assert_eq!(stats.blame_last_session("Mochi"), Ok(10));
+        assert_eq!(stats.cat_incidents.get("Mochi"), Some(&10));
+        assert_eq!(stats.cat_incidents.get("Biscuit"), None);
+    }
+
+    #[test]
+    fn test_stats_blame_requires_incidents() {
+        let mut stats = Stats::default();
+        stats.record_session(600, 0);
+        assert!(stats.blame_last_session("Mochi").is_err());
+    }
+
+    #[test]
+    fn test_caps_lock_tracker_triple_press() {
+        let mut tracker = CapsLockTracker::default();
+        let start = Instant::now();
+        assert!(!tracker.record_press(start));
+        assert!(!tracker.record_press(start + Duration::from_millis(400)));
+        assert!(tracker.record_press(start + Duration::from_millis(800)));
+    }
+
+    #[test]
+    fn test_caps_lock_tracker_too_slow() {
+        let mut tracker = CapsLockTracker::default();
+        let start = Instant::now();
+        assert!(!tracker.record_press(start));
+        assert!(!tracker.record_press(start + Duration::from_millis(1500)));
+        // First press has fallen out of the window
+        assert!(!tracker.record_press(start + Duration::from_millis(2500)));
+        assert!(tracker.record_press(start + Duration::from_millis(3000)));
+    }
+
+    #[test]
+    fn test_caps_lock_tracker_resets_after_trigger() {
+        let mut tracker = CapsLockTracker::default();
+        let start = Instant::now();
+        for i in 0..3 {
+            tracker.record_press(start + Duration::from_millis(i * 100));
+        }
+        // A fourth press right after a trigger starts a new sequence
+        assert!(!tracker.record_press(start + Duration::from_millis(300)));
+    }
+
+    #[test]
+    fn test_parse_version() {
+        assert_eq!(parse_version("1.2.3"), Some((1, 2, 3)));
+        assert_eq!(parse_version("v0.4.0"), Some((0, 4, 0)));
+        assert_eq!(parse_version("v2.0"), Some((2, 0, 0)));
+        assert_eq!(parse_version("1.0.0-beta.1"), Some((1, 0, 0)));
+        assert_eq!(parse_version("latest"), None);
+        assert_eq!(parse_version("1.2.3.4"), None);
+        assert_eq!(parse_version(""), None);
+    }
+
+    #[test]
+    fn test_is_newer_version() {
+        assert!(is_newer_version("v0.2.0", "0.1.0"));
+        assert!(is_newer_version("0.1.10", "0.1.9"));
+        assert!(!is_newer_version("v0.1.0", "0.1.0"));
+        assert!(!is_newer_version("0.0.9", "0.1.0"));
+        assert!(!is_newer_version("nightly", "0.1.0"));
+    }
+
+    #[test]
+    fn test_install_target_for_bare_binary() {
+        let path = Path::new("/usr/local/bin/cat_shield");
+        assert_eq!(
+            InstallTarget::for_executable(path),
+            InstallTarget::Binary(path.to_path_buf())
+        );
+    }
+
+    #[test]
+    fn test_install_target_for_app_bundle() {
+        let path = Path::new("/Applications/Cat Shield.app/Contents/MacOS/cat_shield");
+        assert_eq!(
+            InstallTarget::for_executable(path),
+            InstallTarget::AppBundle(PathBuf::from("/Applications/Cat Shield.app"))
+        );
+        // A MacOS directory that isn't inside a bundle is just a directory
+        let path = Path::new("/Users/me/MacOS/cat_shield");
+        assert_eq!(
+            InstallTarget::for_executable(path),
+            InstallTarget::Binary(path.to_path_buf())
+        );
+    }
+
+    #[test]
+    fn test_parse_sha256_line() {
+        let digest = "a".repeat(64);
+        assert_eq!(
+            parse_sha256_line(&format!("{}  cat_shield-macos.tar.gz\n", digest)),
+            Some(digest.clone())
+        );
+        assert_eq!(parse_sha256_line(&digest.to_uppercase()), Some(digest));
+        assert_eq!(parse_sha256_line("not-a-digest  file"), None);
+        assert_eq!(parse_sha256_line(""), None);
+    }
+
+    #[test]
+    fn test_parse_designated_requirement() {
+        let signed = "designated => identifier \"com.taearls.catshield\" and anchor apple generic \
+                      and certificate leaf[subject.OU] = \"ABCDE12345\"\n";
+        assert_eq!(
+            parse_designated_requirement(signed).as_deref(),
+            Some(
+                "identifier \"com.taearls.catshield\" and anchor apple generic \
+                 and certificate leaf[subject.OU] = \"ABCDE12345\""
+            )
+        );
+        // Ad-hoc signatures (the linker's default) pin nothing but the build itself
+        let ad_hoc = "# designated => cdhash H\"0123456789abcdef0123456789abcdef01234567\"\n";
+        assert_eq!(parse_designated_requirement(ad_hoc), None);
+        assert_eq!(parse_designated_requirement(""), None);
+    }
+
+    #[test]
+    fn test_telemetry_requires_opt_in_and_endpoint() {
+        let config: Config = toml::from_str("").unwrap();
+        assert_eq!(config.telemetry.active_endpoint(), None);
+
+        let config: Config =
+            toml::from_str("[telemetry]\nendpoint = \"https://example.com\"").unwrap();
+        assert_eq!(config.telemetry.active_endpoint(), None);
+
+        let config: Config =
+            toml::from_str("[telemetry]\nenabled = true\nendpoint = \"https://example.com\"")
+                .unwrap();
+        assert_eq!(
+            config.telemetry.active_endpoint(),
+            Some("https://example.com")
+        );
+
+        let config: Config =
+            toml::from_str("[telemetry]\nenabled = true\nendpoint = \"http://example.com\"")
+                .unwrap();
+        assert_eq!(config.telemetry.active_endpoint(), None);
+    }
+
+    #[test]
+    fn test_validate_config_accepts_valid_config() {
+        let contents = r#"
+            exit_key = "Cmd+Option+U"
+            activate_when_frontmost = ["Keynote"]
+
+            [[cats]]
+            name = "Mochi"
+            active_hours = "22:00-06:00"
+
+            [telemetry]
+            enabled = true
+            endpoint = "https://example.com"
+        "#;
+        assert_eq!(validate_config(contents), Vec::<String>::new());
+        assert_eq!(validate_config(""), Vec::<String>::new());
+    }
+
+    #[test]
+    fn test_validate_config_reports_problems() {
+        let problems = validate_config("exit_kye = \"Cmd+U\"\n[telemetry]\nenabeld = true");
+        assert_eq!(
+            problems,
+            ["exit_kye: unknown key", "telemetry.enabeld: unknown key"]
+        );
+
+        let problems = validate_config(
+            "exit_key = \"Nope+U\"\n\
+             [[cats]]\nname = \"Mochi\"\nactive_hours = \"25-6\"\n\
+             [[cats]]\nname = \"mochi\"\n\
+             [telemetry]\nenabled = true",
+        );
+        assert_eq!(problems.len(), 4, "{:?}", problems);
+        assert!(problems[0].starts_with("exit_key: "));
+        assert!(problems[1].starts_with("cats[0].active_hours: "));
+        assert_eq!(problems[2], "cats[1].name: 'mochi' is defined twice");
+        assert_eq!(problems[3], "telemetry: enabled, but no endpoint is set");
+
+        let problems = validate_config("[telemetry]\nendpoint = \"http://example.com\"");
+        assert_eq!(
+            problems,
+            ["telemetry.endpoint: 'http://example.com' is not an https URL"]
+        );
+    }
+
+    #[test]
+    fn test_validate_config_reports_parse_errors_by_line() {
+        let problems = validate_config("exit_key = \"Cmd+U\"\ncats = 3");
+        assert_eq!(problems.len(), 1);
+        assert!(problems[0].starts_with("line 2: "), "{:?}", problems);
+
+        let problems = validate_config("exit_key = \"Cmd+U\"\n[telemetry\n");
+        assert_eq!(problems.len(), 1);
+        assert!(problems[0].starts_with("line 2: "), "{:?}", problems);
+    }
+
+    #[test]
+    fn test_frontmost_rules_match_name_or_bundle_id() {
+        let config: Config =
+            toml::from_str("activate_when_frontmost = [\"Final Cut Pro\", \"com.apple.Keynote\"]")
+                .unwrap();
+        let rules = config.activate_when_frontmost;
+
+        assert!(matches_frontmost_rule(
+            &rules,
+            Some("final cut pro"),
+            Some("com.apple.FinalCut")
+        ));
+        assert!(matches_frontmost_rule(
+            &rules,
+            Some("Keynote"),
+            Some("com.apple.Keynote")
+        ));
+        assert!(!matches_frontmost_rule(
+            &rules,
+            Some("Final Cut"),
+            Some("com.apple.FinalCut")
+        ));
+        assert!(!matches_frontmost_rule(&rules, None, None));
+        assert!(!matches_frontmost_rule(&[], Some("Final Cut Pro"), None));
+    }
+
+    #[test]
+    fn test_telemetry_counts_unclean_exits() {
+        let mut counters = TelemetryCounters::default();
+        counters.begin_session(&["timer", "night"]);
+        counters.end_session();
+        counters.begin_session(&["timer"]);
+        // Crashed: the next session starts without the previous one ending
+        counters.begin_session(&[]);
+        counters.end_session();
+
+        assert_eq!(counters.sessions, 3);
+        assert_eq!(counters.clean_exits, 2);
+        assert_eq!(counters.unclean_exits, 1);
+        assert_eq!(counters.features.get("timer"), Some(&2));
+        assert_eq!(counters.features.get("night"), Some(&1));
+    }
+
+    #[test]
+    fn test_telemetry_reset_keeps_open_session() {
+        let mut counters = TelemetryCounters::default();
+        counters.begin_session(&["timer"]);
+        counters.reset_reported();
+        assert_eq!(counters.sessions, 0);
+        assert!(counters.features.is_empty());
+        assert!(counters.session_open);
+    }
+
+    #[test]
+    fn test_siri_shortcuts_detected() {
+        assert!(is_siri_shortcut(SPACE_KEYCODE, CGEventFlags::MaskCommand));
+        assert!(is_siri_shortcut(SPACE_KEYCODE, CGEventFlags::MaskAlternate));
+        assert!(is_siri_shortcut(
+            SPACE_KEYCODE,
+            CGEventFlags::MaskSecondaryFn
+        ));
+        assert!(!is_siri_shortcut(SPACE_KEYCODE, CGEventFlags::empty()));
+        assert!(!is_siri_shortcut(SPACE_KEYCODE, CGEventFlags::MaskShift));
+        assert!(!is_siri_shortcut(0, CGEventFlags::MaskCommand));
+    }
+
+    #[test]
+    fn test_burst_detector_detects_burst() {
+        let mut detector = BurstDetector::default();
+        let start = Instant::now();
+        for i in 0..BURST_KEY_PRESSES - 1 {
+            assert!(!detector.record(start + Duration::from_millis(i as u64 * 50)));
+        }
+        assert!(detector.record(start + Duration::from_millis(500)));
+    }
+
+    #[test]
+    fn test_accessibility_shortcut() {
+        let cmd = CGEventFlags::MaskCommand;
+        let cmd_option = cmd.union(CGEventFlags::MaskAlternate);
+        let name = |keycode, flags| accessibility_shortcut(keycode, flags).map(|s| s.name);
+
+        assert_eq!(name(F5_KEYCODE, cmd), Some("VoiceOver"));
+        assert_eq!(
+            name(F5_KEYCODE, cmd.union(CGEventFlags::MaskSecondaryFn)),
+            Some("VoiceOver")
+        );
+        assert_eq!(
+            name(F5_KEYCODE, cmd_option),
+            Some("Accessibility Shortcuts")
+        );
+        assert_eq!(name(28, cmd_option), Some("Zoom"));
+        assert_eq!(
+            name(28, cmd_option.union(CGEventFlags::MaskControl)),
+            Some("Invert colors")
+        );
+        // Extra modifiers or a plain key make it an ordinary blocked key
+        assert_eq!(name(28, cmd_option.union(CGEventFlags::MaskShift)), None);
+        assert_eq!(name(F5_KEYCODE, CGEventFlags::empty()), None);
+        assert_eq!(name(0, cmd_option), None);
+    }
+
+    #[test]
+    fn test_gesture_mask_leaves_the_close_button_alone() {
+        // The overlay mask always includes gestures; clicks and cursor movement
+        // must still reach the close button
+        for event_type in [
+            CGEventType::LeftMouseDown,
+            CGEventType::LeftMouseUp,
+            CGEventType::LeftMouseDragged,
+            CGEventType::MouseMoved,
+            CGEventType::KeyDown,
+        ] {
+            assert_eq!(GESTURE_EVENT_MASK & (1 << event_type.0), 0);
+        }
+        assert_ne!(GESTURE_EVENT_MASK & (1 << NX_GESTURE), 0);
+        assert_eq!(GESTURE_EVENT_MASK & MOUSE_BUTTON_EVENT_MASK, 0);
+    }
+
+    #[test]
+    fn test_keys_adjacent() {
+        assert!(keys_adjacent(0, 0)); // A, A
+        assert!(keys_adjacent(0, 1)); // A, S
+        assert!(keys_adjacent(0, 12)); // A, Q
+        assert!(keys_adjacent(1, 6)); // S, Z
+        assert!(!keys_adjacent(0, 2)); // A, D
+        assert!(!keys_adjacent(18, 0)); // 1, A
+        assert!(keys_adjacent(SPACE_KEYCODE, SPACE_KEYCODE));
+        assert!(!keys_adjacent(SPACE_KEYCODE, 9)); // Space, V
+    }
+
+    #[test]
+    fn test_cat_detector_detects_kneading() {
+        let mut detector = CatDetector::default();
+        let start = Instant::now();
+        // A paw rolling back and forth over S, D, and E
+        let keys = [1, 2, 14, 2, 1, 2];
+        for (i, &key) in keys.iter().enumerate() {
+            let detected = detector.record(key, start + Duration::from_millis(100 * i as u64));
+            assert_eq!(detected, i == keys.len() - 1);
+        }
+    }
+
+    #[test]
+    fn test_cat_detector_ignores_scattered_and_slow_presses() {
+        let mut detector = CatDetector::default();
+        let start = Instant::now();
+        // Keys far apart
+        for (i, &key) in [0, 37, 6, 31, 18, 44].iter().enumerate() {
+            assert!(!detector.record(key, start + Duration::from_millis(100 * i as u64)));
+        }
+        // The same key, but slowly
+        for i in 0..CAT_RUN_PRESSES as u32 * 2 {
+            assert!(!detector.record(0, start + Duration::from_secs(1) + CAT_PRESS_GAP * 2 * i));
+        }
+    }
+
+    #[test]
+    fn test_cat_detector_cooldown() {
+        let mut detector = CatDetector::default();
+        let start = Instant::now();
+        let mut run_at = |offset: Duration| {
+            (0..CAT_RUN_PRESSES as u64)
+                .map(|i| detector.record(0, start + offset + Duration::from_millis(50 * i)))
+                .last()
+                .unwrap()
+        };
+        assert!(run_at(Duration::ZERO));
+        assert!(!run_at(Duration::from_secs(2)));
+        assert!(run_at(CAT_DETECTION_COOLDOWN + Duration::from_secs(1)));
+    }
+
+    #[test]
+    fn test_burst_detector_ignores_slow_typing() {
+        let mut detector = BurstDetector::default();
+        let start = Instant::now();
+        for i in 0..BURST_KEY_PRESSES * 2 {
+            assert!(!detector.record(start + Duration::from_millis(i as u64 * 400)));
+        }
+    }
+
+    #[test]
+    fn test_burst_detector_cooldown() {
+        let mut detector = BurstDetector::default();
+        let start = Instant::now();
+        let mut burst_at = |offset: Duration| {
+            (0..BURST_KEY_PRESSES)
+                .map(|i| detector.record(start + offset + Duration::from_millis(i as u64 * 10)))
+                .last()
+                .unwrap()
+        };
+        assert!(burst_at(Duration::ZERO));
+        assert!(!burst_at(Duration::from_secs(2)));
+        assert!(burst_at(BURST_COOLDOWN + Duration::from_secs(1)));
+    }
+
+    #[test]
+    fn test_rms_dbfs() {
+        // A full-scale square wave has an RMS of 1.0, i.e. 0 dBFS
+        let full_scale = [1.0f32, -1.0, 1.0, -1.0];
+        assert!(rms_dbfs(full_scale.into_iter()).abs() < 1e-9);
+
+        // Half amplitude is about -6 dBFS
+        let half = [0.5f32, -0.5, 0.5, -0.5];
+        assert!((rms_dbfs(half.into_iter()) + 6.0206).abs() < 0.001);
+
+        assert_eq!(rms_dbfs([0.0f32; 8].into_iter()), SILENCE_DBFS);
+        assert_eq!(rms_dbfs(std::iter::empty()), SILENCE_DBFS);
+    }
+
+    #[test]
+    fn test_loudness_tracker_requires_sustained_sound() {
+        let mut tracker = LoudnessTracker::default();
+        let start = Instant::now();
+        assert!(!tracker.record(-10.0, -30.0, start));
+        assert!(!tracker.record(-10.0, -30.0, start + Duration::from_millis(500)));
+        assert!(tracker.record(-10.0, -30.0, start + LOUDNESS_SUSTAIN));
+        // Only once per loud stretch
+        assert!(!tracker.record(-10.0, -30.0, start + LOUDNESS_SUSTAIN * 2));
+    }
+
+    #[test]
+    fn test_loudness_tracker_resets_when_quiet() {
+        let mut tracker = LoudnessTracker::default();
+        let start = Instant::now();
+        assert!(!tracker.record(-10.0, -30.0, start));
+        assert!(!tracker.record(-50.0, -30.0, start + Duration::from_millis(800)));
+        assert!(!tracker.record(-10.0, -30.0, start + Duration::from_millis(1200)));
+        assert!(tracker.record(-10.0, -30.0, start + Duration::from_millis(2200)));
+    }
+
+    #[test]
+    fn test_parse_percentage() {
+        assert_eq!(parse_percentage("20%"), Ok(0.2));
+        assert_eq!(parse_percentage("50"), Ok(0.5));
+        assert_eq!(parse_percentage(" 0 % "), Ok(0.0));
+        assert_eq!(parse_percentage("100%"), Ok(1.0));
+        assert!(parse_percentage("120%").is_err());
+        assert!(parse_percentage("-5%").is_err());
+        assert!(parse_percentage("dim").is_err());
+    }
+
+    #[test]
+    fn test_parse_loudness_threshold() {
+        assert_eq!(parse_loudness_threshold("-30"), Ok(-30.0));
+        assert_eq!(parse_loudness_threshold("-25 dB"), Ok(-25.0));
+        assert_eq!(parse_loudness_threshold("-12.5dBFS"), Ok(-12.5));
+        assert!(parse_loudness_threshold("10").is_err());
+        assert!(parse_loudness_threshold("loud").is_err());
+    }
+
+    fn circle_points(radius: f64, steps: usize, turns: f64) -> Vec<CGPoint> {
+        (0..=steps)
+            .map(|i| {
+                let angle = std::f64::consts::TAU * turns * i as f64 / steps as f64;
+                CGPoint {
+                    x: 500.0 + radius * angle.cos(),
+                    y: 400.0 + radius * angle.sin(),
+                }
+            })
+            .collect()
+    }
+
+    fn square_points(side: f64, steps_per_edge: usize) -> Vec<CGPoint> {
+        let corners = [
+            (0.0, 0.0),
+            (side, 0.0),
+            (side, side),
+            (0.0, side),
+            (0.0, 0.0),
+        ];
+        corners
+            .windows(2)
+            .flat_map(|edge| {
+                let ((x0, y0), (x1, y1)) = (edge[0], edge[1]);
+                (0..steps_per_edge).map(move |i| {
+                    let t = i as f64 / steps_per_edge as f64;
+                    CGPoint {
+                        x: 100.0 + x0 + (x1 - x0) * t,
+                        y: 100.0 + y0 + (y1 - y0) * t,
+                    }
+                })
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_recognize_gesture_circle() {
+        assert!(recognize_gesture(
+            &circle_points(150.0, 64, 1.0),
+            GestureShape::Circle
+        ));
+    }
+
+    #[test]
+    fn test_recognize_gesture_circle_too_small() {
+        assert!(!recognize_gesture(
+            &circle_points(50.0, 64, 1.0),
+            GestureShape::Circle
+        ));
+    }
+
+    #[test]
+    fn test_recognize_gesture_partial_arc() {
+        assert!(!recognize_gesture(
+            &circle_points(150.0, 64, 0.5),
+            GestureShape::Circle
+        ));
+    }
+
+    #[test]
+    fn test_recognize_gesture_too_few_points() {
+        assert!(!recognize_gesture(
+            &circle_points(150.0, 8, 1.0),
+            GestureShape::Circle
+        ));
+    }
+
+    #[test]
+    fn test_recognize_gesture_straight_line() {
+        let line: Vec<CGPoint> = (0..50)
+            .map(|i| CGPoint {
+                x: i as f64 * 10.0,
+                y: i as f64 * 10.0,
+            })
+            .collect();
+        assert!(!recognize_gesture(&line, GestureShape::Circle));
+        assert!(!recognize_gesture(&line, GestureShape::Square));
+    }
+
+    #[test]
+    fn test_recognize_gesture_square() {
+        assert!(recognize_gesture(
+            &square_points(300.0, 20),
+            GestureShape::Square
+        ));
+    }
+
+    #[test]
+    fn test_recognize_gesture_circle_is_not_square() {
+        assert!(!recognize_gesture(
+            &circle_points(150.0, 64, 1.0),
+            GestureShape::Square
+        ));
+    }
+
+    #[test]
+    fn test_parse_knock_pattern_valid() {
+        assert_eq!(parse_knock_pattern("..-.").unwrap(), "..-.");
+        assert_eq!(parse_knock_pattern(" . . - ").unwrap(), "..-");
+    }
+
+    #[test]
+    fn test_parse_knock_pattern_errors() {
+        assert!(parse_knock_pattern("").is_err());
+        assert!(parse_knock_pattern(".").is_err()); // Too short
+        assert!(parse_knock_pattern("..x.").is_err());
+        assert!(parse_knock_pattern(&".".repeat(17)).is_err()); // Too long
+    }
+
+    #[test]
+    fn test_parse_key_name() {
+        assert_eq!(parse_key_name("Left"), Ok(123));
+        assert_eq!(parse_key_name(" space "), Ok(49));
+        assert!(parse_key_name("Cmd+Left").is_err());
+        assert!(DEFAULT_FORWARD_KEYS
+            .split(',')
+            .all(|key| parse_key_name(key).is_ok()));
+    }
+
+    #[test]
+    fn test_parse_exit_key_hold() {
+        assert_eq!(parse_exit_key_hold("1"), Ok(1.0));
+        assert_eq!(parse_exit_key_hold(" 1.5s "), Ok(1.5));
+        assert_eq!(parse_exit_key_hold("0"), Ok(0.0));
+        assert!(parse_exit_key_hold("-1").is_err());
+        assert!(parse_exit_key_hold("11").is_err());
+        assert!(parse_exit_key_hold("soon").is_err());
+    }
+
+    #[test]
+    fn test_exit_chord_must_be_held() {
+        // Default exit key: Cmd+Option+U
+        let chord = UnlockInput::KeyDown {
+            keycode: 32,
+            flags: CGEventFlags::MaskCommand | CGEventFlags::MaskAlternate,
+        };
+        let start = Instant::now();
+        let at = |ms| start + Duration::from_millis(ms);
+        let mut method = ExitChord::new(Duration::from_secs(1));
+
+        // Rolled across: released before the hold time
+        assert!(!method.handle(chord, at(0)));
+        assert!(!method.handle(UnlockInput::KeyUp { keycode: 32 }, at(100)));
+        assert!(!method.handle(UnlockInput::Tick, at(1500)));
+
+        // Held through auto-repeat until the timer tick completes it
+        assert!(!method.handle(chord, at(2000)));
+        assert!(!method.handle(chord, at(2500)));
+        assert!(!method.handle(UnlockInput::Tick, at(2900)));
+        assert!(method.handle(UnlockInput::Tick, at(3000)));
+
+        // Letting go of a modifier starts over
+        assert!(!method.handle(chord, at(4000)));
+        let option_released = UnlockInput::FlagsChanged {
+            keycode: 58,
+            flags: CGEventFlags::MaskCommand,
+        };
+        assert!(!method.handle(option_released, at(4200)));
+        assert!(!method.handle(UnlockInput::Tick, at(5500)));
+
+        // No hold time: the key press itself unlocks
+        assert!(ExitChord::new(Duration::ZERO).handle(chord, at(6000)));
+    }
+
+    #[test]
+    fn test_held_key_source_ignores_callback_order() {
+        // The event tap got the exit key before the HID report: not known yet
+        assert_eq!(held_key_source(), KeySource::Unknown);
+
+        // A cat lying on the external keyboard
+        record_held_key(0x16, true, false);
+        assert_eq!(held_key_source(), KeySource::External);
+
+        // The exit key held on the laptop wins, whichever callback ran first
+        record_held_key(0x18, true, true);
+        assert_eq!(held_key_source(), KeySource::BuiltIn);
+        record_held_key(0x18, false, true);
+        assert_eq!(held_key_source(), KeySource::External);
+
+        record_held_key(0x16, false, false);
+        assert_eq!(held_key_source(), KeySource::Unknown);
+    }
+
+    #[test]
+    fn test_parse_keypad_code() {
+        assert_eq!(parse_keypad_code(" 2468 ").unwrap(), "2468");
+        assert!(parse_keypad_code("123").is_err()); // Too short
+        assert!(parse_keypad_code("12a4").is_err());
+        assert!(parse_keypad_code(&"1".repeat(13)).is_err()); // Too long
+    }
+
+    fn type_keypad(method: &mut KeypadCode, keys: &[i64], at: Instant) -> bool {
+        keys.iter().fold(false, |_, &keycode| {
+            method.handle(
+                UnlockInput::KeyDown {
+                    keycode,
+                    flags: CGEventFlags::empty(),
+                },
+                at,
+            )
+        })
+    }
+
+    #[test]
+    fn test_keypad_code() {
+        // Keypad 2, 4, 6, 8, then Enter
+        let code = [84, 86, 88, 91, KEYPAD_ENTER_KEYCODE];
+        let start = Instant::now();
+        let mut method = KeypadCode::new("2468".to_string());
+        assert!(!method.passes_key_through());
+        assert!(type_keypad(&mut method, &code, start));
+
+        // Wrong code, then the right one
+        assert!(!type_keypad(
+            &mut method,
+            &[84, KEYPAD_ENTER_KEYCODE],
+            start
+        ));
+        assert!(type_keypad(&mut method, &code, start));
+
+        // Row digits don't count (2 is keycode 19 on the number row)
+        assert!(!type_keypad(
+            &mut method,
+            &[19, 86, 88, 91, KEYPAD_ENTER_KEYCODE],
+            start
+        ));
+
+        // Clear discards what was typed
+        assert!(!type_keypad(
+            &mut method,
+            &[84, KEYPAD_CLEAR_KEYCODE, 86, 88, 91, KEYPAD_ENTER_KEYCODE],
+            start
+        ));
+
+        // A long pause starts over
+        type_keypad(&mut method, &[84, 86], start);
+        let later = start + KEYPAD_CODE_TIMEOUT + Duration::from_secs(1);
+        assert!(!type_keypad(
+            &mut method,
+            &[88, 91, KEYPAD_ENTER_KEYCODE],
+            later
+        ));
+    }
+
+    fn knock(tracker: &mut KnockTracker, start: Instant, at_ms: u64, held_ms: u64) -> bool {
+        let pressed = start + Duration::from_millis(at_ms);
+        tracker.record_click(pressed, pressed + Duration::from_millis(held_ms))
+    }
+
+    #[test]
+    fn test_knock_tracker_matches_pattern() {
+        let mut tracker = KnockTracker::new("..-".to_string());
+        let start = Instant::now();
+        assert!(!knock(&mut tracker, start, 0, 100));
+        assert!(!knock(&mut tracker, start, 300, 100));
+        assert!(knock(&mut tracker, start, 600, 500));
+    }
+
+    #[test]
+    fn test_knock_tracker_wrong_pattern() {
+        let mut tracker = KnockTracker::new("..-".to_string());
+        let start = Instant::now();
+        assert!(!knock(&mut tracker, start, 0, 500));
+        assert!(!knock(&mut tracker, start, 800, 500));
+        assert!(!knock(&mut tracker, start, 1600, 100));
+    }
+
+    #[test]
+    fn test_knock_tracker_long_gap_resets() {
+        let mut tracker = KnockTracker::new("..-".to_string());
+        let start = Instant::now();
+        assert!(!knock(&mut tracker, start, 0, 100));
+        // Pause longer than KNOCK_MAX_GAP discards the first click
+        assert!(!knock(&mut tracker, start, 2000, 100));
+        assert!(!knock(&mut tracker, start, 2300, 500));
+    }
+
+    #[test]
+    fn test_knock_tracker_long_hold_cancels() {
+        let mut tracker = KnockTracker::new("..-".to_string());
+        let start = Instant::now();
+        assert!(!knock(&mut tracker, start, 0, 100));
+        assert!(!knock(&mut tracker, start, 300, 100));
+        // Holding past KNOCK_MAX_PRESS is not a long click
+        assert!(!knock(&mut tracker, start, 600, 2500));
+    }
+
+    #[test]
+    fn test_knock_tracker_retry_without_waiting() {
+        // Extra clicks before the pattern are ignored (suffix match)
+        let mut tracker = KnockTracker::new(".-".to_string());
+        let start = Instant::now();
+        assert!(!knock(&mut tracker, start, 0, 500));
+        assert!(!knock(&mut tracker, start, 800, 100));
+        assert!(knock(&mut tracker, start, 1100, 500));
+    }
+
+    #[test]
+    fn test_emergency_exit_unlock_method_counts_caps_lock_only() {
+        let mut method = EmergencyExit::default();
+        let start = Instant::now();
+        let caps_lock = UnlockInput::FlagsChanged {
+            keycode: CAPS_LOCK_KEYCODE,
+            flags: CGEventFlags::MaskAlphaShift,
+        };
+        let shift = UnlockInput::FlagsChanged {
+            keycode: 56,
+            flags: CGEventFlags::MaskShift,
+        };
+
+        assert!(!method.handle(caps_lock, start));
+        assert!(!method.handle(shift, start + Duration::from_millis(50)));
+        assert!(!method.handle(caps_lock, start + Duration::from_millis(100)));
+        assert!(method.handle(caps_lock, start + Duration::from_millis(200)));
+    }
+
+    #[test]
+    fn test_knock_unlock_method_times_clicks() {
+        let mut method = KnockUnlock::new("-.".to_string());
+        let start = Instant::now();
+        let point = CGPoint { x: 10.0, y: 10.0 };
+        let at = |ms| start + Duration::from_millis(ms);
+
+        assert!(!method.handle(UnlockInput::MouseDown(point), at(0)));
+        assert!(!method.handle(UnlockInput::MouseUp, at(500)));
+        assert!(!method.handle(UnlockInput::MouseDown(point), at(700)));
+        assert!(method.handle(UnlockInput::MouseUp, at(800)));
+        // A release without a press is ignored
+        assert!(!method.handle(UnlockInput::MouseUp, at(900)));
+    }
+
+    // Exit key parsing tests
+    #[test]
+    fn test_keycode_from_name_letters() {
+        assert_eq!(keycode_from_name("a"), Some(0));
+        assert_eq!(keycode_from_name("u"), Some(32));
+        assert_eq!(keycode_from_name("q"), Some(12));
+        assert_eq!(keycode_from_name("U"), Some(32)); // Case insensitive
+    }
+
+    #[test]
+    fn test_keycode_from_name_special() {
+        assert_eq!(keycode_from_name("escape"), Some(53));
+        assert_eq!(keycode_from_name("Escape"), Some(53));
+        assert_eq!(keycode_from_name("esc"), Some(53));
+        assert_eq!(keycode_from_name("return"), Some(36));
+        assert_eq!(keycode_from_name("enter"), Some(36));
+        assert_eq!(keycode_from_name("space"), Some(49));
+        assert_eq!(keycode_from_name("tab"), Some(48));
+    }
+
+    #[test]
+    fn test_keycode_from_name_function_keys() {
+        assert_eq!(keycode_from_name("f1"), Some(122));
+        assert_eq!(keycode_from_name("F12"), Some(111));
+    }
+
+    #[test]
+    fn test_keycode_from_name_unknown() {
+        assert_eq!(keycode_from_name("unknown"), None);
+        assert_eq!(keycode_from_name(""), None);
+    }
+
+    #[test]
+    fn test_exit_key_parse_default() {
+        let key = ExitKey::parse("Cmd+Option+U").unwrap();
+        assert_eq!(key.keycode, 32);
+        assert!(key.requires_cmd);
+        assert!(key.requires_option);
+        assert!(!key.requires_shift);
+        assert!(!key.requires_ctrl);
+    }
+
+    #[test]
+    fn test_exit_key_parse_cmd_shift_q() {
+        let key = ExitKey::parse("Cmd+Shift+Q").unwrap();
+        assert_eq!(key.keycode, 12);
+        assert!(key.requires_cmd);
+        assert!(!key.requires_option);
+        assert!(key.requires_shift);
+        assert!(!key.requires_ctrl);
+    }
+
+    #[test]
+    fn test_exit_key_parse_ctrl_option_escape() {
+        let key = ExitKey::parse("Ctrl+Option+Escape").unwrap();
+        assert_eq!(key.keycode, 53);
+        assert!(!key.requires_cmd);
+        assert!(key.requires_option);
+        assert!(!key.requires_shift);
+        assert!(key.requires_ctrl);
+    }
+
+    #[test]
+    fn test_exit_key_parse_layout_character() {
+        assert_eq!(ExitKey::parse("Cmd+Option+U").unwrap().character, Some('u'));
+        assert_eq!(ExitKey::parse("Cmd+Shift+/").unwrap().character, Some('/'));
+        assert_eq!(ExitKey::parse("Cmd+Option+1").unwrap().character, None);
+        assert_eq!(ExitKey::parse("Ctrl+Escape").unwrap().character, None);
+        assert_eq!(ExitKey::default().character, Some('u'));
+    }
+
+    #[test]
+    fn test_keycode_typing() {
+        // Home row and a few letters of Dvorak and AZERTY, by US key position
+        let dvorak = |keycode| match keycode {
+            0 => Some('a'),
+            1 => Some('o'),
+            2 => Some('e'),
+            3 => Some('u'),
+            32 => Some('g'),
+            _ => None,
+        };
+        assert_eq!(keycode_typing('u', dvorak), Some(3));
+        assert_eq!(keycode_typing('U', dvorak), Some(3));
+        assert_eq!(keycode_typing('z', dvorak), None);
+
+        let azerty = |keycode| match keycode {
+            0 => Some('q'),
+            12 => Some('a'),
+            13 => Some('z'),
+            32 => Some('u'),
+            _ => None,
+        };
+        assert_eq!(keycode_typing('a', azerty), Some(12));
+        assert_eq!(keycode_typing('u', azerty), Some(32));
+    }
+
+    #[test]
+    fn test_exit_key_parse_case_insensitive() {
+        let key1 = ExitKey::parse("CMD+OPTION+U").unwrap();
+        let key2 = ExitKey::parse("cmd+option+u").unwrap();
+        assert_eq!(key1.keycode, key2.keycode);
+        assert_eq!(key1.requires_cmd, key2.requires_cmd);
+        assert_eq!(key1.requires_option, key2.requires_option);
+    }
+
+    #[test]
+    fn test_exit_key_parse_alternative_modifier_names() {
+        let key = ExitKey::parse("Command+Alt+U").unwrap();
+        assert!(key.requires_cmd);
+        assert!(key.requires_option);
+
+        let key2 = ExitKey::parse("Control+Opt+X").unwrap();
+        assert!(key2.requires_ctrl);
+        assert!(key2.requires_option);
+    }
+
+    #[test]
+    fn test_exit_key_parse_with_spaces() {
+        let key = ExitKey::parse(" Cmd + Option + U ").unwrap();
+        assert_eq!(key.keycode, 32);
+        assert!(key.requires_cmd);
+        assert!(key.requires_option);
+    }
+
+    #[test]
+    fn test_exit_key_parse_errors() {
+        // No modifier
+        assert!(ExitKey::parse("U").is_err());
+
+        // Unknown key
+        assert!(ExitKey::parse("Cmd+Option+Unknown").is_err());
+
+        // Empty
+        assert!(ExitKey::parse("").is_err());
+
+        // No key, only modifiers
+        assert!(ExitKey::parse("Cmd+Option").is_err());
+
+        // Multiple keys
+        assert!(ExitKey::parse("Cmd+A+B").is_err());
+    }
+
+    #[test]
+    fn test_exit_key_default() {
+        let key = ExitKey::default();
+        assert_eq!(key.keycode, 32);
+        assert!(key.requires_cmd);
+        assert!(key.requires_option);
+        assert!(!key.requires_shift);
+        assert!(!key.requires_ctrl);
+        assert_eq!(key.display_name, "Cmd+Option+U");
+    }
+
+    // Menu bar mode tests
+    #[test]
+    fn test_has_immediate_start_args_none() {
+        let args = Args {
+            timer: None,
+            hide_timer: false,
+            exit_key: None,
+            ..Default::default()
+        };
+        assert!(!has_immediate_start_args(&args));
+    }
+
+    #[test]
+    fn test_has_immediate_start_args_with_timer() {
+        let args = Args {
+            timer: Some(60),
+            hide_timer: false,
+            exit_key: None,
+            ..Default::default()
+        };
+        assert!(has_immediate_start_args(&args));
+    }
+
+    #[test]
+    fn test_has_immediate_start_args_with_exit_key() {
+        let args = Args {
+            timer: None,
+            hide_timer: false,
+            exit_key: Some(ExitKey::default()),
+            ..Default::default()
+        };
+        assert!(has_immediate_start_args(&args));
+    }
+
+    #[test]
+    fn test_has_immediate_start_args_with_both() {
+        let args = Args {
+            timer: Some(120),
+            hide_timer: true,
+            exit_key: Some(ExitKey::default()),
+            ..Default::default()
+        };
+        assert!(has_immediate_start_args(&args));
+    }
+
+    #[test]
+    fn test_has_immediate_start_args_hide_timer_alone_is_menu_mode() {
+        // hide_timer alone should NOT trigger immediate mode
+        let args = Args {
+            timer: None,
+            hide_timer: true,
+            exit_key: None,
+            ..Default::default()
+        };
+        assert!(!has_immediate_start_args(&args));
+    }
+
+    // Subcommand tests
+    #[test]
+    fn test_cli_definition() {
+        Cli::command().debug_assert();
+    }
+
+    #[test]
+    fn test_run_subcommand_takes_shield_options() {
+        let cli = Cli::try_parse_from(["cat_shield", "run", "-t", "30m"]).unwrap();
+        match cli.command {
+            Some(Command::Run(args)) => assert_eq!(args.timer, Some(30 * 60)),
+            other => panic!("expected run, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_shield_options_conflict_with_subcommands() {
+        assert!(Cli::try_parse_from(["cat_shield", "-t", "30m", "stats"]).is_err());
+    }
+
+    #[test]
+    fn test_default_shield_args_match_clap_defaults() {
+        let args = default_shield_args();
+        assert_eq!(args.fps, DEFAULT_FPS);
+        assert_eq!(args.exit_key_hold, DEFAULT_EXIT_KEY_HOLD_SECS);
+        assert_eq!(args.timer, None);
+        assert!(!args.no_overlay);
+    }
+
+    #[test]
+    fn test_shield_builder() {
+        let shield = Shield::builder()
+            .timer(Duration::from_secs(90))
+            .opacity(0.5)
+            .exit_key("Cmd+Shift+Q")
+            .build()
+            .unwrap();
+        assert_eq!(shield.args.timer, Some(90));
+        assert_eq!(shield.args.opacity, Some(0.5));
+        assert_eq!(
+            shield.args.exit_key.map(|key| key.display_name),
+            Some("Cmd+Shift+Q".to_string())
+        );
+        assert!(!shield.args.no_overlay);
+
+        assert!(Shield::builder().opacity(1.5).build().is_err());
+        assert!(Shield::builder().timer(Duration::ZERO).build().is_err());
+        assert!(Shield::builder().exit_key("Cmd+Nope").build().is_err());
+        assert!(Shield::builder().keyboard_only(true).build().is_err());
+        assert!(Shield::builder()
+            .overlay(false)
+            .keyboard_only(true)
+            .build()
+            .is_ok());
+    }
+
+    #[test]
+    fn test_shield_run_returns() {
+        // Tests don't run on the main thread: `run` must say so and return,
+        // not take the test process down
+        let shield = Shield::builder().build().unwrap();
+        assert_eq!(
+            shield.run(),
+            Err("The shield must run on the main thread".to_string())
+        );
+    }
+
+    #[test]
+    fn test_keys_subcommand_default_duration() {
+        let cli = Cli::try_parse_from(["cat_shield", "keys"]).unwrap();
+        assert!(matches!(cli.command, Some(Command::Keys { duration: 120 })));
+    }
+
+    #[test]
+    fn test_dry_run_plan() {
+        let cli = Cli::try_parse_from([
+            "cat_shield",
+            "--dry-run",
+            "-t",
+            "1h",
+            "--warn-action",
+            "sound",
+            "--allow-scroll",
+            "--knock",
+            "..-.",
+        ])
+        .unwrap();
+        let config: Config =
+            toml::from_str("[[displays]]\ndisplay = \"LG TV\"\nopacity = 0.0\npassthrough = true")
+                .unwrap();
+        let displays = [
+            PlannedDisplay {
+                identity: DisplayIdentity {
+                    name: "Built-in Retina Display".to_string(),
+                    uuid: None,
+                },
+                main: true,
+            },
+            PlannedDisplay {
+                identity: DisplayIdentity {
+                    name: "LG TV".to_string(),
+                    uuid: None,
+                },
+                main: false,
+            },
+        ];
+        let plan = dry_run_plan(
+            &cli.shield,
+            &ExitKey::default(),
+            &config,
+            &displays,
+            Some("3:45 PM"),
+            false,
+        );
+        assert_eq!(
+            plan,
+            vec![
+                "Mode: shield session",
+                "Screen \"Built-in Retina Display\" (main): overlay, 50% opacity, countdown",
+                "Screen \"LG TV\": cover window, 0% opacity, clicks pass through",
+                "Blocking: keyboard, clicks, scrolling, and trackpad gestures",
+                "Let through: scrolling and swipes",
+                "Timer: 1h 00m 00s, counting awake time, ends around 3:45 PM",
+                "Warning: 1m 00s before the end (sound)",
+                "Unlock: hold Cmd+Option+U for 1s; hold the close button for 3s; knock \"..-.\"",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_projector_reason() {
+        assert_eq!(
+            projector_reason("EPSON PJ", false),
+            Some("\"EPSON PJ\"".to_string())
+        );
+        assert!(projector_reason("Elgato Cam Link 4K", false).is_some());
+        assert!(projector_reason("Living Room", false).is_none());
+        assert_eq!(
+            projector_reason("DELL U2720Q", true),
+            Some("\"DELL U2720Q\", mirroring another display".to_string())
+        );
+
+        let cli = Cli::try_parse_from(["cat_shield", "-t", "1h", "--on-projector"]).unwrap();
+        assert_eq!(cli.shield.on_projector, Some(ProjectorAction::Pause));
+        let cli =
+            Cli::try_parse_from(["cat_shield", "-t", "1h", "--on-projector", "notify"]).unwrap();
+        assert_eq!(cli.shield.on_projector, Some(ProjectorAction::Notify));
+    }
+
+    #[test]
+    fn test_warn_action_list() {
+        let cli = Cli::try_parse_from(["cat_shield", "-t", "1h", "--warn-action", "sound,flash"])
+            .unwrap();
+        assert_eq!(
+            cli.shield.warn_action,
+            vec![WarnAction::Sound, WarnAction::Flash]
+        );
+        assert!(Cli::try_parse_from(["cat_shield", "--warn-action", "notify"]).is_err());
+    }
+
+    #[test]
+    fn test_menu_bar_presets_start_like_the_cli() {
+        let timers = DEFAULT_PRESETS
+            .iter()
+            .map(|entry| Some(Preset::parse(entry).unwrap().secs));
+        for timer in timers.chain([Some(90), None]) {
+            let args = preset_command_args(timer);
+            let cli =
+                Cli::try_parse_from(std::iter::once("cat_shield".to_string()).chain(args)).unwrap();
+            match cli.command {
+                Some(Command::Run(args)) => assert_eq!(args.timer, timer),
+                other => panic!("expected run, got {:?}", other),
+            }
+        }
+    }
+
+    #[test]
+    fn test_session_control_round_trip() {
+        for control in [
+            SessionControl::Pause(None),
+            SessionControl::Pause(Some(30)),
+            SessionControl::Resume,
+            SessionControl::Stop,
+            SessionControl::Extend(900),
+        ] {
+            assert_eq!(
+                SessionControl::decode(&control.encode(42), 42),
+                Some(control)
+            );
+            // Addressed to another session
+            assert_eq!(SessionControl::decode(&control.encode(42), 43), None);
+        }
+        assert_eq!(SessionControl::decode("42 extend", 42), None);
+        assert_eq!(SessionControl::decode("42 stop now", 42), None);
+        assert_eq!(SessionControl::decode("stop", 42), None);
+        assert_eq!(
+            SessionControl::Stop.message(),
+            ShieldMessage::UnlockRequested
+        );
+    }
+
+    #[test]
+    fn test_session_display() {
+        let idle = session_display(None, 1_000);
+        assert_eq!((idle.time.as_str(), idle.fraction), ("Ready", 0.0));
+
+        let mut report = SessionReport {
+            pid: 42,
+            state: "active".to_string(),
+            remaining_secs: Some(600),
+            duration_secs: Some(1200),
+            paused: false,
+            armed: true,
+            watching: false,
+            blocked_key_presses: 1,
+            updated: 1_000,
+        };
+        // Carried forward from when the report was written
+        let running = session_display(Some(&report), 1_060);
+        assert_eq!(running.time, "9m 00s");
+        assert_eq!(running.fraction, 0.45);
+        assert_eq!(running.status, "1 key blocked");
+
+        report.paused = true;
+        let paused = session_display(Some(&report), 1_060);
+        assert_eq!(paused.time, "10m 00s");
+        assert_eq!(paused.status, "Paused · 1 key blocked");
+
+        report.remaining_secs = None;
+        report.blocked_key_presses = 3;
+        let untimed = session_display(Some(&report), 1_060);
+        assert_eq!(untimed.fraction, 1.0);
+        assert_eq!(untimed.status, "Paused · 3 keys blocked");
+
+        report.paused = false;
+        let untimed = session_display(Some(&report), 1_060);
+        assert_eq!(untimed.status, "Until unlocked · 3 keys blocked");
+    }
+
+    #[test]
+    fn test_status_lines() {
+        let mut report: SessionReport = serde_json::from_str(
+            r#"{"pid": 42, "state": "warning", "armed": true, "tap": "enabled",
+                "remaining_secs": 50, "duration_secs": 1800, "paused": false,
+                "blocked_key_presses": 2, "last_blocked": null, "updated": 1000}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            status_lines(&report, 1_010),
+            [
+                "Running (pid 42): warning",
+                "Time left: 40s",
+                "2 keys blocked"
+            ]
+        );
+
+        report.remaining_secs = None;
+        assert_eq!(
+            status_lines(&report, 1_010),
+            [
+                "Running (pid 42): warning",
+                "Until unlocked · 2 keys blocked"
+            ]
+        );
+
+        report.watching = true;
+        report.state = "active".to_string();
+        assert_eq!(
+            status_lines(&report, 1_010),
+            ["Watching (pid 42): active", "Armed · 2 keys blocked"]
+        );
+        report.armed = false;
+        assert_eq!(status_lines(&report, 1_010)[1], "Disarmed · 2 keys blocked");
+    }
+
+    #[test]
+    fn test_lock_file_admits_one_holder() {
+        let path = std::env::temp_dir().join(format!("catshield-test-{}.lock", process::id()));
+        let first = lock_file(&path).unwrap();
+        assert!(first.is_some());
+        assert!(lock_file(&path).unwrap().is_none());
+        drop(first);
+        assert!(lock_file(&path).unwrap().is_some());
+        let _ = fs::remove_file(path);
+    }
+
+    #[test]
+    fn test_daemon_command_parse() {
+        assert_eq!(
+            DaemonCommand::parse("activate"),
+            Ok(DaemonCommand::Activate(None))
+        );
+        assert_eq!(
+            DaemonCommand::parse(" activate 30m "),
+            Ok(DaemonCommand::Activate(Some(1800)))
+        );
+        assert_eq!(
+            DaemonCommand::parse("deactivate"),
+            Ok(DaemonCommand::Deactivate)
+        );
+        assert_eq!(DaemonCommand::parse("status"), Ok(DaemonCommand::Status));
+        assert!(DaemonCommand::parse("activate soon").is_err());
+        assert!(DaemonCommand::parse("status now").is_err());
+        assert!(DaemonCommand::parse("").is_err());
+
+        assert_eq!(
+            daemon_reply("explode", 0),
+            r#"{"error":"unknown command 'explode'","ok":false}"#
+        );
+    }
+
+    #[test]
+    fn test_daemon_implies_watch_conflicts() {
+        assert!(Cli::try_parse_from(["cat_shield", "--daemon"]).is_ok());
+        assert!(Cli::try_parse_from(["cat_shield", "--daemon", "-t", "1h"]).is_err());
+    }
+
+    #[test]
+    fn test_control_subcommands() {
+        let cli = Cli::try_parse_from(["cat_shield", "extend", "30m"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Some(Command::Extend { duration: 1800 })
+        ));
+        assert!(matches!(
+            Cli::try_parse_from(["cat_shield", "stop"]).unwrap().command,
+            Some(Command::Stop)
+        ));
+        assert!(Cli::try_parse_from(["cat_shield", "extend"]).is_err());
+
+        let cli = Cli::try_parse_from(["cat_shield", "pause", "30s"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Some(Command::Pause { duration: Some(30) })
+        ));
+        assert!(matches!(
+            Cli::try_parse_from(["cat_shield", "pause"])
+                .unwrap()
+                .command,
+            Some(Command::Pause { duration: None })
+        ));
+        assert!(matches!(
+            Cli::try_parse_from(["cat_shield", "resume"])
+                .unwrap()
+                .command,
+            Some(Command::Resume)
+        ));
+    }
+
+    #[test]
+    fn test_preset_parse() {
+        let preset = Preset::parse("25m").unwrap();
+        assert_eq!((preset.name.as_str(), preset.secs), ("25m", 1500));
+        assert_eq!(preset.menu_title(), "Shield for 25m");
+
+        let preset = Preset::parse(" nap = 1h30m ").unwrap();
+        assert_eq!((preset.name.as_str(), preset.secs), ("nap", 5400));
+        assert_eq!(preset.menu_title(), "Shield for nap (1h30m)");
+
+        assert!(Preset::parse("=25m").is_err());
+        assert!(Preset::parse("nap=soon").is_err());
+    }
+
+    #[test]
+    fn test_resolve_preset() {
+        let presets: Vec<Preset> = ["25m", "nap=3h"]
+            .iter()
+            .map(|entry| Preset::parse(entry).unwrap())
+            .collect();
+        assert_eq!(resolve_preset("25m", &presets), Ok(1500));
+        assert_eq!(resolve_preset("nap", &presets), Ok(3 * 3600));
+        assert!(resolve_preset("3h", &presets)
+            .unwrap_err()
+            .contains("available: 25m, nap"));
+        assert!(resolve_preset("nap", &[]).is_err());
+    }
+
+    #[test]
+    fn test_apply_preset_profiles() {
+        let config: Config = toml::from_str(
+            r#"
+            presets = ["nap=1h"]
+
+            [profiles.movie]
+            timer = "2h30m"
+            opacity = 0.15
+            exit_key = "Cmd+Shift+M"
+            emergency_exit = true
+            "#,
+        )
+        .unwrap();
+
+        let mut args = Args::default();
+        apply_preset("movie", &config, &mut args).unwrap();
+        assert_eq!(args.timer, Some(9000));
+        assert_eq!(args.opacity, Some(0.15));
+        assert_eq!(
+            args.exit_key.map(|key| key.display_name).as_deref(),
+            Some("Cmd+Shift+M")
+        );
+        assert!(args.emergency_exit);
+        assert!(!args.require_admin_exit);
+
+        // The command line's exit key wins over the profile's
+        let mut args = Args {
+            exit_key: Some(ExitKey::parse("Ctrl+Option+X").unwrap()),
+            ..Args::default()
+        };
+        apply_preset("movie", &config, &mut args).unwrap();
+        assert_eq!(
+            args.exit_key.map(|key| key.display_name).as_deref(),
+            Some("Ctrl+Option+X")
+        );
+
+        let mut args = Args::default();
+        apply_preset("nap", &config, &mut args).unwrap();
+        assert_eq!(args.timer, Some(3600));
+        assert_eq!(args.opacity, None);
+
+        let e = apply_preset("quick", &config, &mut Args::default()).unwrap_err();
+        assert!(e.contains("available: movie, nap"), "{}", e);
+    }
+
+    #[test]
+    fn test_validate_config_checks_profiles() {
+        let problems = validate_config(
+            "presets = [\"nap=1h\"]\n\
+             [profiles.nap]\ntimer = \"2h\"\n\
+             [profiles.movie]\nopacity = 1.5\nexit_kye = \"Cmd+M\"",
+        );
+        assert_eq!(
+            problems,
+            [
+                "profiles.movie.exit_kye: unknown key",
+                "profiles.movie.opacity: 1.5 is not between 0.0 and 1.0",
+                "profiles.nap: hides the preset of the same name",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_preset_counts_as_a_duration() {
+        let cli = Cli::try_parse_from(["cat_shield", "--preset", "nap", "--warn-action", "sound"])
+            .unwrap();
+        assert_eq!(cli.shield.preset.as_deref(), Some("nap"));
+        assert!(has_immediate_start_args(&cli.shield));
+        assert!(Cli::try_parse_from(["cat_shield", "-t", "1h", "--preset", "nap"]).is_err());
+        assert!(Cli::try_parse_from(["cat_shield", "--watch", "--preset", "nap"]).is_err());
+    }
+
+    #[test]
+    fn test_away_note() {
+        assert_eq!(away_note_name("  Sam "), "Sam");
+        assert_eq!(
+            away_note_text("Tyler", "3:45 PM"),
+            "Tyler is away — back around 3:45 PM"
+        );
+
+        let cli = Cli::try_parse_from(["cat_shield", "-t", "1h", "--away-note"]).unwrap();
+        assert_eq!(cli.shield.away_note.as_deref(), Some(""));
+        let cli = Cli::try_parse_from(["cat_shield", "-t", "1h", "--away-note", "Sam"]).unwrap();
+        assert_eq!(cli.shield.away_note.as_deref(), Some("Sam"));
+        assert!(Cli::try_parse_from(["cat_shield", "--away-note"]).is_err());
+        assert!(
+            Cli::try_parse_from(["cat_shield", "-t", "1h", "--no-overlay", "--away-note"]).is_err()
+        );
+    }
+
+    #[test]
+    fn test_away_note_frame_is_centered() {
+        let screen = CGSize {
+            width: 1000.0,
+            height: 800.0,
+        };
+        let frame = away_note_frame(screen);
+        assert_eq!(frame.origin.x * 2.0 + frame.size.width, screen.width);
+        assert!(frame.origin.y + frame.size.height < screen.height - TIMER_DISPLAY_HEIGHT);
+    }
+
+    #[test]
+    fn test_anchor_frame() {
+        let screen = CGSize {
+            width: 1000.0,
+            height: 800.0,
+        };
+        let size = CGSize {
+            width: 200.0,
+            height: 50.0,
+        };
+        let origin = |anchor: Anchor| {
+            let frame = anchor.frame(size, screen, 30.0);
+            (frame.origin.x, frame.origin.y)
+        };
+        assert_eq!(origin(Anchor::TopLeft), (30.0, 720.0));
+        assert_eq!(origin(Anchor::Center), (400.0, 375.0));
+        assert_eq!(origin(Anchor::BottomRight), (770.0, 30.0));
+        assert_eq!(origin(Anchor::CenterLeft), (30.0, 375.0));
+    }
+
+    #[test]
+    fn test_display_settings() {
+        let config: Config = toml::from_str(
+            r#"
+            [[displays]]
+            display = "Built-in Retina Display"
+            opacity = 0.5
+
+            [[displays]]
+            display = "37D8832A-2D66-02CA-B9F7-8F30A301B230"
+            opacity = 0.0
+            widgets = false
+            passthrough = true
+            "#,
+        )
+        .unwrap();
+
+        let built_in = DisplayIdentity {
+            name: "built-in retina display".to_string(),
+            uuid: None,
+        };
+        let settings = display_settings(&config.displays, &built_in).unwrap();
+        assert_eq!((settings.opacity, settings.passthrough), (Some(0.5), false));
+
+        let tv = DisplayIdentity {
+            name: "LG TV".to_string(),
+            uuid: Some("37d8832a-2d66-02ca-b9f7-8f30a301b230".to_string()),
+        };
+        let settings = display_settings(&config.displays, &tv).unwrap();
+        assert_eq!(settings.widgets, Some(false));
+        assert!(settings.passthrough);
+
+        let other = DisplayIdentity {
+            name: "DELL U2720Q".to_string(),
+            uuid: Some("0E7A5B51-0000-0000-0000-000000000000".to_string()),
+        };
+        assert_eq!(display_settings(&config.displays, &other), None);
+
+        let problems = validate_config(
+            r#"
+            [[displays]]
+            display = "LG TV"
+            opacity = 1.5
+            dim = true
+
+            [[displays]]
+            display = "lg tv"
+            "#,
+        );
+        assert_eq!(
+            problems,
+            vec![
+                "displays[0].dim: unknown key",
+                "displays[0].opacity: 1.5 is not between 0.0 and 1.0",
+                "displays[1].display: 'lg tv' is defined twice",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_overlay_widgets_config() {
+        let config: Config = toml::from_str(
+            r#"
+            [[overlay.widgets]]
+            kind = "now_playing"
+            anchor = "bottom-center"
+
+            [[overlay.widgets]]
+            kind = "message"
+            anchor = "center"
+            text = "Back soon"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(
+            config.overlay.widgets,
+            vec![
+                WidgetSpec {
+                    kind: WidgetKind::NowPlaying,
+                    anchor: Anchor::BottomCenter,
+                    text: None,
+                },
+                WidgetSpec {
+                    kind: WidgetKind::Message,
+                    anchor: Anchor::Center,
+                    text: Some("Back soon".to_string()),
+                },
+            ]
+        );
+        assert!(toml::from_str::<Config>(
+            "[[overlay.widgets]]\nkind = \"clock\"\nanchor = \"middle\""
+        )
+        .is_err());
+
+        let problems = validate_config(
+            r#"
+            [[overlay.widgets]]
+            kind = "message"
+            anchor = "top-right"
+
+            [[overlay.widgets]]
+            kind = "clock"
+            anchor = "center"
+            text = "hi"
+            size = 3
+
+            [[overlay.widgets]]
+            kind = "battery"
+            anchor = "center"
+            "#,
+        );
+        assert_eq!(
+            problems,
+            vec![
+                "overlay.widgets[1].size: unknown key",
+                "overlay.widgets[0]: a message widget needs text",
+                "overlay.widgets[0].anchor: top-right is taken by the close button",
+                "overlay.widgets[1].text: only message widgets have text, not clock",
+                "overlay.widgets[2].anchor: another widget is already at center",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_remind_every_requires_timer() {
+        let cli = Cli::try_parse_from(["cat_shield", "-t", "8h", "--remind-every", "30m"]).unwrap();
+        assert_eq!(cli.shield.remind_every, Some(1800));
+        assert!(!cli.shield.remind_aloud);
+        assert!(Cli::try_parse_from(["cat_shield", "--remind-every", "30m"]).is_err());
+        assert!(Cli::try_parse_from(["cat_shield", "-t", "8h", "--remind-aloud"]).is_err());
+    }
+
+    #[test]
+    fn test_describe_remaining() {
+        assert_eq!(describe_remaining(61), "1 minute");
+        assert_eq!(describe_remaining(45 * 60 + 20), "45 minutes");
+        assert_eq!(describe_remaining(3600), "1 hour");
+        assert_eq!(describe_remaining(2 * 3600 + 29), "2 hours");
+        assert_eq!(describe_remaining(3600 + 5 * 60), "1 hour 5 minutes");
+        assert_eq!(describe_remaining(3 * 3600 - 10), "3 hours");
+    }
+
+    #[test]
+    fn test_input_origin_from_process_name() {
+        assert_eq!(
+            InputOrigin::from_process_name("UniversalControl"),
+            InputOrigin::UniversalControl
+        );
+        assert_eq!(
+            InputOrigin::from_process_name("screensharingd"),
+            InputOrigin::Remote
+        );
+        assert_eq!(
+            InputOrigin::from_process_name("WindowServer"),
+            InputOrigin::Local
+        );
+    }
+
+    #[test]
+    fn test_external_keyboard_removed_counts_down_to_zero() {
+        EXTERNAL_KEYBOARD_COUNT.store(2, Ordering::SeqCst);
+        assert_eq!(external_keyboard_removed(), 1);
+        assert_eq!(external_keyboard_removed(), 0);
+        // A stray removal never wraps below zero
+        assert_eq!(external_keyboard_removed(), 0);
+    }
+
+    #[test]
+    fn test_arm_causes_disarm_when_the_last_goes() {
+        ARM_CAUSES.with(|causes| {
+            causes
+                .borrow_mut()
+                .extend([ArmCause::FrontmostApp, ArmCause::UntilUnlocked])
+        });
+        // The app losing focus leaves the keyboard disconnect's arming alone
+        assert!(!remove_arm_cause(ArmCause::FrontmostApp));
+        // Releasing a cause that never armed doesn't disarm either
+        assert!(!remove_arm_cause(ArmCause::DaemonTimer));
+        assert!(remove_arm_cause(ArmCause::UntilUnlocked));
+    }
+
+    #[test]
+    fn test_backlight_restore_level_ends_a_running_flash() {
+        assert_eq!(take_backlight_restore_level(), None);
+
+        // Exiting mid-flash puts back the level the flash started from
+        BACKLIGHT_FLASH.with(|flash| flash.set(Some((Instant::now(), 0.4))));
+        assert_eq!(take_backlight_restore_level(), Some(0.4));
+        assert!(BACKLIGHT_FLASH.with(|flash| flash.get()).is_none());
+
+        // With --backlight-off the flash started from dark; the saved level wins
+        SAVED_BACKLIGHT_LEVEL.with(|saved| saved.set(Some(0.7)));
+        BACKLIGHT_FLASH.with(|flash| flash.set(Some((Instant::now(), 0.0))));
+        assert_eq!(take_backlight_restore_level(), Some(0.7));
+        assert_eq!(take_backlight_restore_level(), None);
+    }
+
+    #[test]
+    fn test_watch_conflict() {
+        let args = Args::default();
+        assert_eq!(watch_conflict(&args), None);
+
+        let args = Args {
+            timer: Some(60),
+            ..Default::default()
+        };
+        assert_eq!(watch_conflict(&args), Some("--timer"));
+
+        let args = Args {
+            no_overlay: true,
+            ..Default::default()
+        };
+        assert_eq!(watch_conflict(&args), Some("--no-overlay"));
+    }
+}
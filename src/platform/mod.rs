@@ -0,0 +1,6 @@
+//! Safe wrappers over the system APIs the shield otherwise reaches through raw FFI.
+//!
+//! - `runloop`: CoreFoundation run loop timers and sources that invalidate and
+//!   release themselves on drop
+
+pub(crate) mod runloop;
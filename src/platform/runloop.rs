@@ -0,0 +1,135 @@
+//! Run loop timers and sources.
+//!
+//! Each wrapper owns one CoreFoundation object. Dropping it invalidates the
+//! object, which removes it from every run loop it was added to; the
+//! `CFRetained` inside then releases it, so replacing a timer or tearing down
+//! a tap no longer leaks.
+
+use std::ffi::c_void;
+
+use objc2_core_foundation::{
+    kCFRunLoopCommonModes, CFAbsoluteTimeGetCurrent, CFMachPort, CFRetained, CFRunLoop,
+    CFRunLoopMode, CFRunLoopSource, CFRunLoopSourceContext, CFRunLoopTimer,
+};
+
+/// The common run loop modes, so timers and sources keep firing while menus
+/// are tracking and during modal sessions
+pub(crate) fn common_modes() -> Option<&'static CFRunLoopMode> {
+    unsafe { kCFRunLoopCommonModes }
+}
+
+/// The current thread's run loop
+pub(crate) fn current_run_loop() -> CFRetained<CFRunLoop> {
+    CFRunLoop::current().expect("every thread has a run loop")
+}
+
+/// Timer callback, as taken by `CFRunLoopTimerCreate`
+pub(crate) type TimerCallback = unsafe extern "C-unwind" fn(*mut CFRunLoopTimer, *mut c_void);
+
+/// Repeating or one-shot timer on the current run loop
+pub(crate) struct RunLoopTimer(CFRetained<CFRunLoopTimer>);
+
+impl RunLoopTimer {
+    /// Call `callback` every `interval` seconds, starting one interval from now
+    ///
+    /// # Returns
+    /// `None` if the timer could not be created
+    pub(crate) fn schedule(interval: f64, callback: TimerCallback) -> Option<Self> {
+        Self::create(interval, interval, callback)
+    }
+
+    /// Call `callback` once, `delay` seconds from now
+    ///
+    /// # Returns
+    /// `None` if the timer could not be created
+    pub(crate) fn once(delay: f64, callback: TimerCallback) -> Option<Self> {
+        // An interval of zero makes the timer fire once
+        Self::create(delay, 0.0, callback)
+    }
+
+    fn create(delay: f64, interval: f64, callback: TimerCallback) -> Option<Self> {
+        let timer = unsafe {
+            CFRunLoopTimer::new(
+                None,
+                CFAbsoluteTimeGetCurrent() + delay,
+                interval,
+                0,
+                0,
+                Some(callback),
+                std::ptr::null_mut(),
+            )
+        }?;
+        current_run_loop().add_timer(Some(&timer), common_modes());
+        Some(Self(timer))
+    }
+
+    /// Whether the timer will still fire (the system may invalidate it across sleep)
+    pub(crate) fn is_valid(&self) -> bool {
+        self.0.is_valid()
+    }
+}
+
+impl Drop for RunLoopTimer {
+    fn drop(&mut self) {
+        // Safe from inside the timer's own callback: the run loop holds its
+        // own reference while firing
+        self.0.invalidate();
+    }
+}
+
+/// Run loop source, either signaled by hand or fed by a mach port
+pub(crate) struct RunLoopSource(CFRetained<CFRunLoopSource>);
+
+// Run loop sources may be signaled and invalidated from any thread
+unsafe impl Send for RunLoopSource {}
+unsafe impl Sync for RunLoopSource {}
+
+impl RunLoopSource {
+    /// Source on the main run loop that calls `perform` after each `signal`
+    ///
+    /// # Returns
+    /// `None` if the source could not be created
+    pub(crate) fn signaled_on_main(
+        perform: unsafe extern "C-unwind" fn(*mut c_void),
+    ) -> Option<Self> {
+        let mut context = CFRunLoopSourceContext {
+            version: 0,
+            info: std::ptr::null_mut(),
+            retain: None,
+            release: None,
+            copyDescription: None,
+            equal: None,
+            hash: None,
+            schedule: None,
+            cancel: None,
+            perform: Some(perform),
+        };
+        let source = unsafe { CFRunLoopSource::new(None, 0, &mut context) }?;
+        CFRunLoop::main()?.add_source(Some(&source), common_modes());
+        Some(Self(source))
+    }
+
+    /// Source on the current run loop that delivers `port`'s messages
+    ///
+    /// # Returns
+    /// `None` if the source could not be created
+    pub(crate) fn for_mach_port(port: &CFMachPort) -> Option<Self> {
+        let source = CFMachPort::new_run_loop_source(None, Some(port), 0)?;
+        current_run_loop().add_source(Some(&source), common_modes());
+        Some(Self(source))
+    }
+
+    /// Mark the source ready and wake the main run loop to handle it
+    pub(crate) fn signal(&self) {
+        self.0.signal();
+        if let Some(main) = CFRunLoop::main() {
+            main.wake_up();
+        }
+    }
+}
+
+impl Drop for RunLoopSource {
+    fn drop(&mut self) {
+        self.0.invalidate();
+    }
+}
@@ -0,0 +1,19 @@
+//! Drawing and view classes for the shield overlay, and its terminal status.
+//!
+//! - `palette`: day and night colors
+//! - `widgets`: reusable drawing components (HoldButton, ProgressBar, Badge,
+//!   TextLabel) that any view can compose
+//! - `views`: the overlay's `NSView` subclasses
+//! - `terminal`: the live status line shown in the launching terminal
+
+mod palette;
+mod terminal;
+mod views;
+mod widgets;
+
+pub(crate) use palette::{overlay_background_color, palette, warning_flash_color};
+pub(crate) use terminal::{enter_status_screen, leave_status_screen, render_status_line};
+pub(crate) use views::{
+    create_blur_view, hold_haptic, AwayNoteView, CatBannerView, CloseButtonView, CountdownRingView,
+    OverlayView, TimerDisplayView, WidgetView,
+};
@@ -0,0 +1,122 @@
+//! Day and night color palettes shared by every overlay element.
+
+use objc2::rc::Retained;
+use objc2_app_kit::NSColor;
+use objc2_core_foundation::CGFloat;
+use std::sync::atomic::Ordering;
+
+use crate::NIGHT_MODE;
+
+/// RGBA color components
+pub(crate) type Rgba = (CGFloat, CGFloat, CGFloat, CGFloat);
+
+/// Colors for the overlay, timer display, and close button
+pub(crate) struct Palette {
+    pub(crate) overlay_background: Rgba,
+    pub(crate) timer_background: Rgba,
+    pub(crate) timer_background_warning: Rgba,
+    pub(crate) timer_border: Rgba,
+    pub(crate) timer_border_warning: Rgba,
+    pub(crate) timer_text: Rgba,
+    pub(crate) timer_bar_background: Rgba,
+    pub(crate) timer_bar_fill: Rgba,
+    pub(crate) timer_bar_fill_warning: Rgba,
+    pub(crate) close_button: Rgba,
+    pub(crate) close_button_pressed: Rgba,
+    pub(crate) close_button_border: Rgba,
+    pub(crate) close_button_progress: Rgba,
+    pub(crate) close_button_x: Rgba,
+}
+
+/// Default palette: high-contrast colors that are easy to spot
+const DAY_PALETTE: Palette = Palette {
+    overlay_background: (0.1, 0.1, 0.15, 1.0),
+    timer_background: (0.1, 0.1, 0.15, 0.9), // Dark semi-transparent background
+    timer_background_warning: (0.8, 0.3, 0.1, 0.9), // Red/orange warning color
+    timer_border: (0.5, 0.5, 0.5, 0.8),
+    timer_border_warning: (1.0, 0.5, 0.2, 1.0),
+    timer_text: (1.0, 1.0, 1.0, 0.95),
+    timer_bar_background: (0.2, 0.2, 0.2, 1.0),
+    timer_bar_fill: (0.2, 0.8, 0.3, 1.0),
+    timer_bar_fill_warning: (1.0, 0.3, 0.1, 1.0),
+    close_button: (0.8, 0.1, 0.1, 0.95), // Dark red normally
+    close_button_pressed: (0.9, 0.2, 0.2, 1.0), // Bright red when pressed
+    close_button_border: (1.0, 1.0, 1.0, 0.9),
+    close_button_progress: (0.2, 1.0, 0.2, 1.0),
+    close_button_x: (1.0, 1.0, 1.0, 1.0),
+};
+
+/// Night palette: dim reds only, so the shield doesn't light up a dark room
+const NIGHT_PALETTE: Palette = Palette {
+    overlay_background: (0.06, 0.0, 0.0, 1.0),
+    timer_background: (0.1, 0.0, 0.0, 0.9),
+    timer_background_warning: (0.35, 0.04, 0.0, 0.9),
+    timer_border: (0.3, 0.05, 0.05, 0.8),
+    timer_border_warning: (0.5, 0.08, 0.0, 1.0),
+    timer_text: (0.55, 0.18, 0.18, 1.0),
+    timer_bar_background: (0.12, 0.02, 0.02, 1.0),
+    timer_bar_fill: (0.4, 0.06, 0.04, 1.0),
+    timer_bar_fill_warning: (0.6, 0.1, 0.04, 1.0),
+    close_button: (0.35, 0.03, 0.03, 0.95),
+    close_button_pressed: (0.5, 0.05, 0.05, 1.0),
+    close_button_border: (0.45, 0.12, 0.12, 0.9),
+    close_button_progress: (0.65, 0.15, 0.08, 1.0),
+    close_button_x: (0.55, 0.18, 0.18, 1.0),
+};
+
+/// The active palette
+pub(crate) fn palette() -> &'static Palette {
+    if NIGHT_MODE.load(Ordering::SeqCst) {
+        &NIGHT_PALETTE
+    } else {
+        &DAY_PALETTE
+    }
+}
+
+/// Create an NSColor from RGBA components
+pub(crate) fn color((red, green, blue, alpha): Rgba) -> Retained<NSColor> {
+    NSColor::colorWithRed_green_blue_alpha(red, green, blue, alpha)
+}
+
+/// Overlay background color while the pre-expiry warning flashes it
+pub(crate) fn warning_flash_color() -> Retained<NSColor> {
+    color(palette().timer_background_warning)
+}
+
+/// Overlay background color at the given opacity
+pub(crate) fn overlay_background_color(alpha: CGFloat) -> Retained<NSColor> {
+    let (red, green, blue, _) = palette().overlay_background;
+    color((red, green, blue, alpha))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_night_palette_is_dim_red() {
+        let p = &NIGHT_PALETTE;
+        for (red, green, blue, _) in [
+            p.overlay_background,
+            p.timer_background,
+            p.timer_background_warning,
+            p.timer_border,
+            p.timer_border_warning,
+            p.timer_text,
+            p.timer_bar_background,
+            p.timer_bar_fill,
+            p.timer_bar_fill_warning,
+            p.close_button,
+            p.close_button_pressed,
+            p.close_button_border,
+            p.close_button_progress,
+            p.close_button_x,
+        ] {
+            assert!(red <= 0.7, "night colors should stay dim");
+            assert!(
+                green <= red && blue <= red,
+                "night colors should be red-tinted"
+            );
+        }
+    }
+}
@@ -0,0 +1,86 @@
+//! Live status line in the launching terminal.
+//!
+//! While the shield runs, the terminal switches to its alternate screen with
+//! the status pinned to the top row and a scroll region below it, so log lines
+//! ("Siri dismissed", "Meow detected") keep scrolling underneath without
+//! overwriting the status. Leaving restores the terminal as it was.
+
+use std::cell::{Cell, RefCell};
+use std::io::{self, IsTerminal, Write};
+
+const ENTER_ALTERNATE_SCREEN: &str = "\x1b[?1049h";
+const LEAVE_ALTERNATE_SCREEN: &str = "\x1b[?1049l";
+const CLEAR_SCREEN: &str = "\x1b[2J";
+const RESET_SCROLL_REGION: &str = "\x1b[r";
+const SAVE_CURSOR: &str = "\x1b7";
+const RESTORE_CURSOR: &str = "\x1b8";
+const CLEAR_LINE: &str = "\x1b[2K";
+const LOG_TOP_ROW: u16 = 3; // Status on row 1, a blank separator on row 2
+
+thread_local! {
+    static SHOWN: Cell<bool> = const { Cell::new(false) };
+    static LAST_STATUS: RefCell<String> = const { RefCell::new(String::new()) };
+}
+
+/// Switch the terminal to the status screen, if stdout is a terminal.
+///
+/// # Returns
+/// `true` if the status line is shown
+pub(crate) fn enter_status_screen() -> bool {
+    if SHOWN.get() {
+        return true;
+    }
+    if !io::stdout().is_terminal() {
+        return false;
+    }
+
+    let mut out = io::stdout().lock();
+    // Log output starts at the top of the scroll region
+    _ = write!(
+        out,
+        "{}{}\x1b[{};r\x1b[{};1H",
+        ENTER_ALTERNATE_SCREEN, CLEAR_SCREEN, LOG_TOP_ROW, LOG_TOP_ROW
+    );
+    _ = out.flush();
+    SHOWN.set(true);
+    true
+}
+
+/// Redraw the status line if its text changed
+pub(crate) fn render_status_line(status: &str) {
+    if !SHOWN.get() {
+        return;
+    }
+    let changed = LAST_STATUS.with(|last| {
+        let mut last = last.borrow_mut();
+        if *last == status {
+            return false;
+        }
+        status.clone_into(&mut last);
+        true
+    });
+    if !changed {
+        return;
+    }
+
+    let mut out = io::stdout().lock();
+    // Draw on row 1, then put the cursor back where log output left off
+    _ = write!(
+        out,
+        "{}\x1b[1;1H{}{}{}",
+        SAVE_CURSOR, CLEAR_LINE, status, RESTORE_CURSOR
+    );
+    _ = out.flush();
+}
+
+/// Leave the status screen, restoring what the terminal showed before
+pub(crate) fn leave_status_screen() {
+    if !SHOWN.replace(false) {
+        return;
+    }
+    LAST_STATUS.with(|last| last.borrow_mut().clear());
+
+    let mut out = io::stdout().lock();
+    _ = write!(out, "{}{}", RESET_SCROLL_REGION, LEAVE_ALTERNATE_SCREEN);
+    _ = out.flush();
+}
@@ -0,0 +1,667 @@
+//! Overlay window views.
+//!
+//! Each view handles its own mouse events and draws by composing widgets.
+//! Views also describe themselves to accessibility clients with stable
+//! identifiers, so UI automation can find and drive them.
+
+use objc2::rc::Retained;
+use objc2::runtime::AnyObject;
+use objc2::{define_class, msg_send, DefinedClass, MainThreadOnly};
+use objc2_app_kit::{
+    NSAccessibility, NSAccessibilityButtonRole, NSAccessibilityGroupRole, NSAccessibilityRole,
+    NSAccessibilityStaticTextRole, NSEvent, NSHapticFeedbackManager, NSHapticFeedbackPattern,
+    NSHapticFeedbackPerformanceTime, NSHapticFeedbackPerformer, NSView, NSVisualEffectBlendingMode,
+    NSVisualEffectMaterial, NSVisualEffectState, NSVisualEffectView,
+};
+use objc2_core_foundation::{CGPoint, CGRect, CGSize};
+use objc2_foundation::{MainThreadMarker, NSString};
+use std::cell::{Cell, RefCell};
+use std::sync::atomic::Ordering;
+use std::time::Instant;
+
+use super::palette::palette;
+use super::widgets::{Badge, CountdownRing, HoldButton, ProgressBar, TextLabel};
+use crate::{
+    calculate_hold_progress, evaluate_unlock_methods, format_duration, get_remaining_seconds,
+    UnlockInput, AUTO_EXIT_DURATION_SECS, AWAY_NOTE, AWAY_NOTE_TEXT_SIZE, AX_AWAY_NOTE,
+    AX_CAT_BANNER, AX_CLOSE_BUTTON, AX_MENU_BAR_COUNTDOWN, AX_OVERLAY_VIEW,
+    AX_OVERLAY_WIDGET_PREFIX, AX_TIMER_DISPLAY, CAT_BANNER_TEXT, CAT_BANNER_TEXT_SIZE,
+    HOLD_DURATION_SECS, IS_MOUSE_INSIDE, MENU_BAR_COUNTDOWN_TEXT_SIZE, MOUSE_DOWN_TIME, NIGHT_MODE,
+    OVERLAY_WIDGET_TEXT_SIZE, TIMER_TEXT_SIZE, WARNING_SECONDS,
+};
+
+/// Expose a view to accessibility clients under a stable identifier
+fn describe_for_accessibility(
+    view: &NSView,
+    identifier: &str,
+    role: &NSAccessibilityRole,
+    label: &str,
+) {
+    view.setAccessibilityElement(true);
+    view.setAccessibilityIdentifier(Some(&NSString::from_str(identifier)));
+    view.setAccessibilityRole(Some(role));
+    view.setAccessibilityLabel(Some(&NSString::from_str(label)));
+}
+
+/// Pulse the Force Touch trackpad. A no-op on other trackpads and mice, and
+/// when haptic feedback is turned off in System Settings.
+pub(crate) fn hold_haptic(pattern: NSHapticFeedbackPattern) {
+    NSHapticFeedbackManager::defaultPerformer()
+        .performFeedbackPattern_performanceTime(pattern, NSHapticFeedbackPerformanceTime::Now);
+}
+
+/// Create a behind-window blur view for the `--blur` backdrop
+pub(crate) fn create_blur_view(
+    mtm: MainThreadMarker,
+    frame: CGRect,
+) -> Retained<NSVisualEffectView> {
+    let view = NSVisualEffectView::initWithFrame(NSVisualEffectView::alloc(mtm), frame);
+    view.setMaterial(if NIGHT_MODE.load(Ordering::SeqCst) {
+        NSVisualEffectMaterial::HUDWindow // Darker, so night mode stays dim
+    } else {
+        NSVisualEffectMaterial::FullScreenUI
+    });
+    view.setBlendingMode(NSVisualEffectBlendingMode::BehindWindow);
+    view.setState(NSVisualEffectState::Active);
+    view
+}
+
+/// Ivars for the OverlayView
+pub(crate) struct OverlayViewIvars {}
+
+define_class!(
+    // Content view of the overlay window; receives mouse events on blank areas
+    #[unsafe(super(NSView))]
+    #[name = "OverlayView"]
+    #[ivars = OverlayViewIvars]
+    pub(crate) struct OverlayView;
+
+    impl OverlayView {
+        #[unsafe(method(acceptsFirstMouse:))]
+        fn accepts_first_mouse(&self, _event: Option<&NSEvent>) -> bool {
+            true
+        }
+
+        #[unsafe(method(mouseDown:))]
+        unsafe fn mouse_down(&self, event: &NSEvent) {
+            evaluate_unlock_methods(UnlockInput::MouseDown(event.locationInWindow()));
+        }
+
+        #[unsafe(method(mouseDragged:))]
+        unsafe fn mouse_dragged(&self, event: &NSEvent) {
+            evaluate_unlock_methods(UnlockInput::MouseDragged(event.locationInWindow()));
+        }
+
+        #[unsafe(method(mouseUp:))]
+        unsafe fn mouse_up(&self, _event: &NSEvent) {
+            evaluate_unlock_methods(UnlockInput::MouseUp);
+        }
+    }
+);
+
+impl OverlayView {
+    pub(crate) fn new(mtm: MainThreadMarker, frame: CGRect) -> Retained<Self> {
+        let this = mtm.alloc::<OverlayView>();
+        let this = this.set_ivars(OverlayViewIvars {});
+        let this: Retained<Self> = unsafe { msg_send![super(this), initWithFrame: frame] };
+        describe_for_accessibility(
+            &this,
+            AX_OVERLAY_VIEW,
+            unsafe { NSAccessibilityGroupRole },
+            "Cat Shield overlay",
+        );
+        this
+    }
+}
+
+/// Ivars for the TimerDisplayView
+pub(crate) struct TimerDisplayViewIvars {}
+
+define_class!(
+    #[unsafe(super(NSView))]
+    #[name = "TimerDisplayView"]
+    #[ivars = TimerDisplayViewIvars]
+    pub(crate) struct TimerDisplayView;
+
+    impl TimerDisplayView {
+        #[unsafe(method(drawRect:))]
+        unsafe fn draw_rect(&self, _dirty_rect: CGRect) {
+            draw_timer_display(self);
+        }
+
+        #[unsafe(method_id(accessibilityValue))]
+        fn accessibility_value(&self) -> Option<Retained<AnyObject>> {
+            Some(NSString::from_str(&format_duration(get_remaining_seconds())).into())
+        }
+    }
+);
+
+impl TimerDisplayView {
+    pub(crate) fn new(mtm: MainThreadMarker, frame: CGRect) -> Retained<Self> {
+        let this = mtm.alloc::<TimerDisplayView>();
+        let this = this.set_ivars(TimerDisplayViewIvars {});
+        let this: Retained<Self> = unsafe { msg_send![super(this), initWithFrame: frame] };
+        describe_for_accessibility(
+            &this,
+            AX_TIMER_DISPLAY,
+            unsafe { NSAccessibilityStaticTextRole },
+            "Time until the shield exits",
+        );
+        this
+    }
+}
+
+/// Draw the timer countdown display: the remaining time above a bar that
+/// empties as the auto-exit approaches
+fn draw_timer_display(view: &NSView) {
+    let bounds = view.bounds();
+    let remaining = get_remaining_seconds();
+    let duration = AUTO_EXIT_DURATION_SECS.load(Ordering::SeqCst);
+    let is_warning = remaining <= WARNING_SECONDS;
+
+    let palette = palette();
+    let pick = |normal, warning| if is_warning { warning } else { normal };
+
+    let margin = 10.0;
+    let bar_height = 8.0;
+
+    Badge {
+        background: pick(palette.timer_background, palette.timer_background_warning),
+        border: pick(palette.timer_border, palette.timer_border_warning),
+        corner_radius: 10.0,
+        border_width: 2.0,
+    }
+    .draw(CGRect {
+        origin: CGPoint { x: 0.0, y: 0.0 },
+        size: bounds.size,
+    });
+
+    TextLabel {
+        text: &format_duration(remaining),
+        font_size: TIMER_TEXT_SIZE,
+        color: palette.timer_text,
+    }
+    .draw(CGRect {
+        origin: CGPoint {
+            x: margin,
+            y: margin + bar_height,
+        },
+        size: CGSize {
+            width: bounds.size.width - margin * 2.0,
+            height: bounds.size.height - bar_height - margin * 2.0,
+        },
+    });
+
+    ProgressBar {
+        fraction: if duration > 0 {
+            remaining as f64 / duration as f64
+        } else {
+            0.0
+        },
+        track: palette.timer_bar_background,
+        fill: pick(palette.timer_bar_fill, palette.timer_bar_fill_warning),
+    }
+    .draw(CGRect {
+        origin: CGPoint {
+            x: margin,
+            y: margin,
+        },
+        size: CGSize {
+            width: bounds.size.width - margin * 2.0,
+            height: bar_height,
+        },
+    });
+}
+
+/// Ivars for the AwayNoteView
+pub(crate) struct AwayNoteViewIvars {}
+
+define_class!(
+    // The `--away-note` message for passers-by
+    #[unsafe(super(NSView))]
+    #[name = "AwayNoteView"]
+    #[ivars = AwayNoteViewIvars]
+    pub(crate) struct AwayNoteView;
+
+    impl AwayNoteView {
+        #[unsafe(method(drawRect:))]
+        unsafe fn draw_rect(&self, _dirty_rect: CGRect) {
+            draw_away_note(self);
+        }
+
+        #[unsafe(method_id(accessibilityValue))]
+        fn accessibility_value(&self) -> Option<Retained<AnyObject>> {
+            Some(NSString::from_str(&away_note_text()).into())
+        }
+    }
+);
+
+impl AwayNoteView {
+    pub(crate) fn new(mtm: MainThreadMarker, frame: CGRect) -> Retained<Self> {
+        let this = mtm.alloc::<AwayNoteView>();
+        let this = this.set_ivars(AwayNoteViewIvars {});
+        let this: Retained<Self> = unsafe { msg_send![super(this), initWithFrame: frame] };
+        describe_for_accessibility(
+            &this,
+            AX_AWAY_NOTE,
+            unsafe { NSAccessibilityStaticTextRole },
+            "Away note",
+        );
+        this
+    }
+}
+
+fn away_note_text() -> String {
+    AWAY_NOTE.with(|note| {
+        note.borrow()
+            .as_ref()
+            .map(|note| note.text.clone())
+            .unwrap_or_default()
+    })
+}
+
+/// Draw the away note: one large line of text on a badge, in the timer's colors
+fn draw_away_note(view: &NSView) {
+    let bounds = view.bounds();
+    let palette = palette();
+
+    Badge {
+        background: palette.timer_background,
+        border: palette.timer_border,
+        corner_radius: 16.0,
+        border_width: 2.0,
+    }
+    .draw(CGRect {
+        origin: CGPoint { x: 0.0, y: 0.0 },
+        size: bounds.size,
+    });
+
+    TextLabel {
+        text: &away_note_text(),
+        font_size: AWAY_NOTE_TEXT_SIZE,
+        color: palette.timer_text,
+    }
+    .draw(CGRect {
+        origin: CGPoint { x: 0.0, y: 0.0 },
+        size: bounds.size,
+    });
+}
+
+/// Ivars for the CountdownRingView
+pub(crate) struct CountdownRingViewIvars {
+    fraction: Cell<f64>,
+    text: RefCell<String>,
+}
+
+define_class!(
+    // The menu bar popover's countdown: time left inside a ring that empties
+    #[unsafe(super(NSView))]
+    #[name = "CountdownRingView"]
+    #[ivars = CountdownRingViewIvars]
+    pub(crate) struct CountdownRingView;
+
+    impl CountdownRingView {
+        #[unsafe(method(drawRect:))]
+        unsafe fn draw_rect(&self, _dirty_rect: CGRect) {
+            draw_countdown_ring(self);
+        }
+
+        #[unsafe(method_id(accessibilityValue))]
+        fn accessibility_value(&self) -> Option<Retained<AnyObject>> {
+            Some(NSString::from_str(&self.ivars().text.borrow()).into())
+        }
+    }
+);
+
+impl CountdownRingView {
+    pub(crate) fn new(mtm: MainThreadMarker, frame: CGRect) -> Retained<Self> {
+        let this = mtm.alloc::<CountdownRingView>();
+        let this = this.set_ivars(CountdownRingViewIvars {
+            fraction: Cell::new(0.0),
+            text: RefCell::new(String::new()),
+        });
+        let this: Retained<Self> = unsafe { msg_send![super(this), initWithFrame: frame] };
+        describe_for_accessibility(
+            &this,
+            AX_MENU_BAR_COUNTDOWN,
+            unsafe { NSAccessibilityStaticTextRole },
+            "Time left",
+        );
+        this
+    }
+
+    /// Show `text` inside a ring filled to `fraction`, redrawing only on change
+    pub(crate) fn set_countdown(&self, fraction: f64, text: String) {
+        let ivars = self.ivars();
+        if ivars.fraction.get() != fraction || *ivars.text.borrow() != text {
+            ivars.fraction.set(fraction);
+            ivars.text.replace(text);
+            self.setNeedsDisplay(true);
+        }
+    }
+}
+
+/// Draw the countdown ring on a dark disc, so it reads the same in light and
+/// dark menu bars
+fn draw_countdown_ring(view: &CountdownRingView) {
+    let bounds = view.bounds();
+    let palette = palette();
+    let ring_width = 10.0;
+
+    Badge {
+        background: palette.timer_background,
+        border: palette.timer_border,
+        corner_radius: bounds.size.width.min(bounds.size.height) / 2.0,
+        border_width: 1.0,
+    }
+    .draw(bounds);
+
+    CountdownRing {
+        fraction: view.ivars().fraction.get(),
+        track: palette.timer_bar_background,
+        fill: palette.timer_bar_fill,
+        line_width: ring_width,
+    }
+    .draw(CGRect {
+        origin: CGPoint {
+            x: ring_width,
+            y: ring_width,
+        },
+        size: CGSize {
+            width: bounds.size.width - ring_width * 2.0,
+            height: bounds.size.height - ring_width * 2.0,
+        },
+    });
+
+    TextLabel {
+        text: &view.ivars().text.borrow(),
+        font_size: MENU_BAR_COUNTDOWN_TEXT_SIZE,
+        color: palette.timer_text,
+    }
+    .draw(bounds);
+}
+
+/// Ivars for the CatBannerView
+pub(crate) struct CatBannerViewIvars {}
+
+define_class!(
+    // The "CAT DETECTED" banner, shown for a few seconds when a cat is detected
+    #[unsafe(super(NSView))]
+    #[name = "CatBannerView"]
+    #[ivars = CatBannerViewIvars]
+    pub(crate) struct CatBannerView;
+
+    impl CatBannerView {
+        #[unsafe(method(drawRect:))]
+        unsafe fn draw_rect(&self, _dirty_rect: CGRect) {
+            draw_cat_banner(self);
+        }
+    }
+);
+
+impl CatBannerView {
+    pub(crate) fn new(mtm: MainThreadMarker, frame: CGRect) -> Retained<Self> {
+        let this = mtm.alloc::<CatBannerView>();
+        let this = this.set_ivars(CatBannerViewIvars {});
+        let this: Retained<Self> = unsafe { msg_send![super(this), initWithFrame: frame] };
+        describe_for_accessibility(
+            &this,
+            AX_CAT_BANNER,
+            unsafe { NSAccessibilityStaticTextRole },
+            "Cat detected",
+        );
+        this
+    }
+}
+
+/// Draw the banner: one huge line of text on a badge, in the timer's warning colors
+fn draw_cat_banner(view: &NSView) {
+    let bounds = view.bounds();
+    let palette = palette();
+
+    Badge {
+        background: palette.timer_background_warning,
+        border: palette.timer_border_warning,
+        corner_radius: 24.0,
+        border_width: 4.0,
+    }
+    .draw(CGRect {
+        origin: CGPoint { x: 0.0, y: 0.0 },
+        size: bounds.size,
+    });
+
+    TextLabel {
+        text: CAT_BANNER_TEXT,
+        font_size: CAT_BANNER_TEXT_SIZE,
+        color: palette.timer_text,
+    }
+    .draw(CGRect {
+        origin: CGPoint { x: 0.0, y: 0.0 },
+        size: bounds.size,
+    });
+}
+
+/// Ivars for the WidgetView
+pub(crate) struct WidgetViewIvars {
+    text: RefCell<String>,
+}
+
+define_class!(
+    // A config-file overlay widget (clock, battery, ...): one line of text on a badge
+    #[unsafe(super(NSView))]
+    #[name = "WidgetView"]
+    #[ivars = WidgetViewIvars]
+    pub(crate) struct WidgetView;
+
+    impl WidgetView {
+        #[unsafe(method(drawRect:))]
+        unsafe fn draw_rect(&self, _dirty_rect: CGRect) {
+            draw_widget(self);
+        }
+
+        #[unsafe(method_id(accessibilityValue))]
+        fn accessibility_value(&self) -> Option<Retained<AnyObject>> {
+            Some(NSString::from_str(&self.ivars().text.borrow()).into())
+        }
+    }
+);
+
+impl WidgetView {
+    /// `kind` names the widget for accessibility clients (e.g., "clock")
+    pub(crate) fn new(mtm: MainThreadMarker, frame: CGRect, kind: &str) -> Retained<Self> {
+        let this = mtm.alloc::<WidgetView>();
+        let this = this.set_ivars(WidgetViewIvars {
+            text: RefCell::new(String::new()),
+        });
+        let this: Retained<Self> = unsafe { msg_send![super(this), initWithFrame: frame] };
+        describe_for_accessibility(
+            &this,
+            &format!("{}.{}", AX_OVERLAY_WIDGET_PREFIX, kind),
+            unsafe { NSAccessibilityStaticTextRole },
+            kind,
+        );
+        this
+    }
+
+    /// Show `text`, redrawing only if it changed. Empty text hides the widget.
+    pub(crate) fn set_text(&self, text: String) {
+        if *self.ivars().text.borrow() != text {
+            self.ivars().text.replace(text);
+            self.setNeedsDisplay(true);
+        }
+    }
+}
+
+/// Draw a widget's text on a badge, in the timer's colors
+fn draw_widget(view: &WidgetView) {
+    let text = view.ivars().text.borrow();
+    if text.is_empty() {
+        return;
+    }
+    let bounds = view.bounds();
+    let palette = palette();
+
+    Badge {
+        background: palette.timer_background,
+        border: palette.timer_border,
+        corner_radius: 10.0,
+        border_width: 2.0,
+    }
+    .draw(CGRect {
+        origin: CGPoint { x: 0.0, y: 0.0 },
+        size: bounds.size,
+    });
+
+    TextLabel {
+        text: &text,
+        font_size: OVERLAY_WIDGET_TEXT_SIZE,
+        color: palette.timer_text,
+    }
+    .draw(CGRect {
+        origin: CGPoint { x: 0.0, y: 0.0 },
+        size: bounds.size,
+    });
+}
+
+/// Ivars for the CloseButtonView
+pub(crate) struct CloseButtonViewIvars {}
+
+define_class!(
+    #[unsafe(super(NSView))]
+    #[name = "CloseButtonView"]
+    #[ivars = CloseButtonViewIvars]
+    pub(crate) struct CloseButtonView;
+
+    impl CloseButtonView {
+        // The buttons on other displays sit in windows that are never key
+        #[unsafe(method(acceptsFirstMouse:))]
+        fn accepts_first_mouse(&self, _event: Option<&NSEvent>) -> bool {
+            true
+        }
+
+        #[unsafe(method(drawRect:))]
+        unsafe fn draw_rect(&self, _dirty_rect: CGRect) {
+            draw_close_button(self);
+        }
+
+        #[unsafe(method(mouseDown:))]
+        unsafe fn mouse_down(&self, _event: &NSEvent) {
+            self.start_hold();
+        }
+
+        #[unsafe(method(mouseUp:))]
+        unsafe fn mouse_up(&self, _event: &NSEvent) {
+            self.release_hold();
+        }
+
+        // AXPress presses and holds, like the mouse: automation goes through the
+        // same hold (and admin authentication) as a person would
+        #[unsafe(method(accessibilityPerformPress))]
+        fn accessibility_perform_press(&self) -> bool {
+            self.start_hold();
+            true
+        }
+
+        #[unsafe(method(accessibilityPerformCancel))]
+        fn accessibility_perform_cancel(&self) -> bool {
+            self.release_hold();
+            true
+        }
+
+        #[unsafe(method(mouseDragged:))]
+        unsafe fn mouse_dragged(&self, event: &NSEvent) {
+            // Check if mouse is still inside the button
+            let location = event.locationInWindow();
+            let bounds = self.bounds();
+
+            // Convert to view coordinates
+            let local_point = self.convertPoint_fromView(location, None);
+
+            let is_inside = local_point.x >= 0.0
+                && local_point.x <= bounds.size.width
+                && local_point.y >= 0.0
+                && local_point.y <= bounds.size.height;
+
+            let was_inside = IS_MOUSE_INSIDE.with(|inside| inside.get());
+
+            if is_inside != was_inside {
+                IS_MOUSE_INSIDE.with(|inside| inside.set(is_inside));
+
+                // Reset timer if mouse left the button
+                if !is_inside {
+                    MOUSE_DOWN_TIME.with(|time| {
+                        time.set(None);
+                    });
+                } else {
+                    // Restart timer if mouse re-entered
+                    MOUSE_DOWN_TIME.with(|time| {
+                        time.set(Some(Instant::now()));
+                    });
+                }
+            }
+
+            self.setNeedsDisplay(true);
+        }
+    }
+);
+
+impl CloseButtonView {
+    pub(crate) fn new(mtm: MainThreadMarker, frame: CGRect) -> Retained<Self> {
+        let this = mtm.alloc::<CloseButtonView>();
+        let this = this.set_ivars(CloseButtonViewIvars {});
+        let this: Retained<Self> = unsafe { msg_send![super(this), initWithFrame: frame] };
+        describe_for_accessibility(
+            &this,
+            AX_CLOSE_BUTTON,
+            unsafe { NSAccessibilityButtonRole },
+            "Hold to exit",
+        );
+        this
+    }
+
+    /// Start timing a hold on the button
+    fn start_hold(&self) {
+        MOUSE_DOWN_TIME.with(|time| {
+            time.set(Some(Instant::now()));
+        });
+        IS_MOUSE_INSIDE.with(|inside| inside.set(true));
+        // `HoldToExit` pulses again halfway through and on completion
+        hold_haptic(NSHapticFeedbackPattern::Generic);
+        self.setNeedsDisplay(true);
+    }
+
+    /// End the hold, whether or not it completed
+    fn release_hold(&self) {
+        MOUSE_DOWN_TIME.with(|time| {
+            time.set(None);
+        });
+        self.setNeedsDisplay(true);
+    }
+}
+
+/// Draw the close button with progress indicator
+fn draw_close_button(view: &NSView) {
+    // Calculate progress (0.0 to 1.0)
+    let progress = MOUSE_DOWN_TIME.with(|time| {
+        if let Some(start) = time.get() {
+            calculate_hold_progress(start.elapsed().as_secs_f64(), HOLD_DURATION_SECS)
+        } else {
+            0.0
+        }
+    });
+    let is_held = progress > 0.0 && IS_MOUSE_INSIDE.with(|inside| inside.get());
+    let palette = palette();
+
+    HoldButton {
+        progress: if is_held { progress } else { 0.0 },
+        fill: if is_held {
+            palette.close_button_pressed
+        } else {
+            palette.close_button
+        },
+        border: palette.close_button_border,
+        ring: palette.close_button_progress,
+        glyph: palette.close_button_x,
+    }
+    .draw(view.bounds());
+}
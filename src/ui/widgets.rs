@@ -0,0 +1,248 @@
+//! Reusable drawing components.
+//!
+//! Widgets are plain values drawn into a rect from a view's `drawRect:`, so a
+//! new overlay element is a few lines of composition instead of another
+//! `NSView` subclass. Colors are passed in rather than read from the palette,
+//! which keeps widgets usable in any view, including the menu bar.
+
+use objc2::rc::Retained;
+use objc2::runtime::AnyObject;
+use objc2_app_kit::{
+    NSBezierPath, NSFont, NSFontAttributeName, NSFontWeightSemibold,
+    NSForegroundColorAttributeName, NSStringDrawing,
+};
+use objc2_core_foundation::{CGFloat, CGPoint, CGRect, CGSize};
+use objc2_foundation::{NSAttributedStringKey, NSDictionary, NSString};
+
+use super::palette::{color, Rgba};
+
+/// Square rect enclosing a circle
+fn circle_rect(center: CGPoint, radius: CGFloat) -> CGRect {
+    CGRect {
+        origin: CGPoint {
+            x: center.x - radius,
+            y: center.y - radius,
+        },
+        size: CGSize {
+            width: radius * 2.0,
+            height: radius * 2.0,
+        },
+    }
+}
+
+/// Circular press-and-hold button: a filled circle with a border, an X glyph,
+/// and a progress ring that fills clockwise from the top while held
+pub(crate) struct HoldButton {
+    /// Hold progress from 0.0 to 1.0; no ring is drawn at 0.0
+    pub(crate) progress: f64,
+    pub(crate) fill: Rgba,
+    pub(crate) border: Rgba,
+    pub(crate) ring: Rgba,
+    pub(crate) glyph: Rgba,
+}
+
+impl HoldButton {
+    const BORDER_WIDTH: CGFloat = 3.0;
+    const RING_WIDTH: CGFloat = 6.0; // Thicker progress ring
+    const RING_INSET: CGFloat = 5.0;
+    const GLYPH_WIDTH: CGFloat = 5.0; // Bold X
+    const GLYPH_SCALE: CGFloat = 0.4;
+
+    pub(crate) fn draw(&self, rect: CGRect) {
+        let center = CGPoint {
+            x: rect.origin.x + rect.size.width / 2.0,
+            y: rect.origin.y + rect.size.height / 2.0,
+        };
+        let radius = (rect.size.width.min(rect.size.height) / 2.0) - 2.0;
+
+        let circle = NSBezierPath::bezierPathWithOvalInRect(circle_rect(center, radius));
+        color(self.fill).set();
+        circle.fill();
+
+        color(self.border).set();
+        circle.setLineWidth(Self::BORDER_WIDTH);
+        circle.stroke();
+
+        if self.progress > 0.0 {
+            color(self.ring).set();
+
+            // Draw arc from top, going clockwise
+            let start_angle = 90.0;
+            let end_angle = 90.0 - (self.progress * 360.0);
+
+            let arc_path = NSBezierPath::bezierPath();
+            arc_path.setLineWidth(Self::RING_WIDTH);
+            arc_path.appendBezierPathWithArcWithCenter_radius_startAngle_endAngle_clockwise(
+                center,
+                radius - Self::RING_INSET,
+                start_angle,
+                end_angle,
+                true, // clockwise
+            );
+            arc_path.stroke();
+        }
+
+        color(self.glyph).set();
+        let x_size = radius * Self::GLYPH_SCALE;
+        let x_path = NSBezierPath::bezierPath();
+        x_path.setLineWidth(Self::GLYPH_WIDTH);
+
+        // First line of X (top-left to bottom-right)
+        x_path.moveToPoint(CGPoint {
+            x: center.x - x_size,
+            y: center.y + x_size,
+        });
+        x_path.lineToPoint(CGPoint {
+            x: center.x + x_size,
+            y: center.y - x_size,
+        });
+
+        // Second line of X (top-right to bottom-left)
+        x_path.moveToPoint(CGPoint {
+            x: center.x + x_size,
+            y: center.y + x_size,
+        });
+        x_path.lineToPoint(CGPoint {
+            x: center.x - x_size,
+            y: center.y - x_size,
+        });
+
+        x_path.stroke();
+    }
+}
+
+/// Horizontal bar filled from the left in proportion to `fraction`
+pub(crate) struct ProgressBar {
+    /// Filled fraction from 0.0 to 1.0
+    pub(crate) fraction: f64,
+    pub(crate) track: Rgba,
+    pub(crate) fill: Rgba,
+}
+
+impl ProgressBar {
+    const CORNER_RADIUS: CGFloat = 4.0;
+
+    pub(crate) fn draw(&self, rect: CGRect) {
+        color(self.track).set();
+        NSBezierPath::bezierPathWithRoundedRect_xRadius_yRadius(
+            rect,
+            Self::CORNER_RADIUS,
+            Self::CORNER_RADIUS,
+        )
+        .fill();
+
+        let fill_width = rect.size.width * self.fraction.clamp(0.0, 1.0);
+        if fill_width > 0.0 {
+            color(self.fill).set();
+            let fill_rect = CGRect {
+                origin: rect.origin,
+                size: CGSize {
+                    width: fill_width,
+                    height: rect.size.height,
+                },
+            };
+            NSBezierPath::bezierPathWithRoundedRect_xRadius_yRadius(
+                fill_rect,
+                Self::CORNER_RADIUS,
+                Self::CORNER_RADIUS,
+            )
+            .fill();
+        }
+    }
+}
+
+/// Circular countdown: a full track with an arc over it that runs clockwise
+/// from the top for `fraction` of the circle
+pub(crate) struct CountdownRing {
+    /// Remaining fraction from 0.0 to 1.0
+    pub(crate) fraction: f64,
+    pub(crate) track: Rgba,
+    pub(crate) fill: Rgba,
+    pub(crate) line_width: CGFloat,
+}
+
+impl CountdownRing {
+    pub(crate) fn draw(&self, rect: CGRect) {
+        let center = CGPoint {
+            x: rect.origin.x + rect.size.width / 2.0,
+            y: rect.origin.y + rect.size.height / 2.0,
+        };
+        let radius = (rect.size.width.min(rect.size.height) - self.line_width) / 2.0;
+
+        let track = NSBezierPath::bezierPathWithOvalInRect(circle_rect(center, radius));
+        color(self.track).set();
+        track.setLineWidth(self.line_width);
+        track.stroke();
+
+        let fraction = self.fraction.clamp(0.0, 1.0);
+        if fraction > 0.0 {
+            color(self.fill).set();
+            let arc = NSBezierPath::bezierPath();
+            arc.setLineWidth(self.line_width);
+            arc.appendBezierPathWithArcWithCenter_radius_startAngle_endAngle_clockwise(
+                center,
+                radius,
+                90.0,
+                90.0 - fraction * 360.0,
+                true,
+            );
+            arc.stroke();
+        }
+    }
+}
+
+/// Rounded, bordered background panel for grouping other widgets
+pub(crate) struct Badge {
+    pub(crate) background: Rgba,
+    pub(crate) border: Rgba,
+    pub(crate) corner_radius: CGFloat,
+    pub(crate) border_width: CGFloat,
+}
+
+impl Badge {
+    pub(crate) fn draw(&self, rect: CGRect) {
+        let path = NSBezierPath::bezierPathWithRoundedRect_xRadius_yRadius(
+            rect,
+            self.corner_radius,
+            self.corner_radius,
+        );
+        color(self.background).set();
+        path.fill();
+
+        color(self.border).set();
+        path.setLineWidth(self.border_width);
+        path.stroke();
+    }
+}
+
+/// Single line of text centered in its rect.
+///
+/// Uses the system font with monospaced digits, so a ticking countdown
+/// doesn't shift sideways as its digits change.
+pub(crate) struct TextLabel<'a> {
+    pub(crate) text: &'a str,
+    pub(crate) font_size: CGFloat,
+    pub(crate) color: Rgba,
+}
+
+impl TextLabel<'_> {
+    pub(crate) fn draw(&self, rect: CGRect) {
+        let text = NSString::from_str(self.text);
+        let font = NSFont::monospacedDigitSystemFontOfSize_weight(self.font_size, unsafe {
+            NSFontWeightSemibold
+        });
+        let text_color = color(self.color);
+        let attributes: Retained<NSDictionary<NSAttributedStringKey, AnyObject>> =
+            NSDictionary::from_slices(
+                unsafe { &[NSFontAttributeName, NSForegroundColorAttributeName] },
+                &[&*font as &AnyObject, &*text_color as &AnyObject],
+            );
+
+        let size = unsafe { text.sizeWithAttributes(Some(&attributes)) };
+        let origin = CGPoint {
+            x: rect.origin.x + (rect.size.width - size.width) / 2.0,
+            y: rect.origin.y + (rect.size.height - size.height) / 2.0,
+        };
+        unsafe { text.drawAtPoint_withAttributes(origin, Some(&attributes)) };
+    }
+}